@@ -0,0 +1,838 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use ipld_core::ipld::Ipld;
+use ipld_schema::{
+    markdown_source, AdlRegistry, BlockStore, Car, CarError, Compatibility, DirectoryStore,
+    DocsFormat, GraphFormat, IpldSchema, IpldSchemaParseError, LoadError, SchemaLoader,
+};
+use thiserror::Error;
+
+/// Tooling for IPLD schemas
+///
+/// Exit codes: 0 on success, 1 if an input is invalid, 2 if the tool could not run.
+#[derive(Debug, Parser)]
+#[command(name = "ipld-schema", version)]
+struct Cli {
+    /// Directories to look up imported schema files in
+    #[arg(short = 'I', long, global = true)]
+    include: Vec<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Parse and check schemas, printing all diagnostics
+    Check {
+        /// The schema files to check
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// The format of the schema files, inferred from the file extension by default
+        #[arg(long)]
+        format: Option<Format>,
+    },
+    /// Print schemas in their canonical DSL form
+    ///
+    /// Imports are kept, comments that are not attached to a type, field or enum member are dropped.
+    Fmt {
+        /// The schema files to format
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Do not print or write anything, but fail if a file is not formatted
+        #[arg(long, conflicts_with = "write")]
+        check: bool,
+        /// Overwrite the files with their formatted version
+        #[arg(long)]
+        write: bool,
+    },
+    /// Convert a schema between the DSL and the DMT in DAG-JSON or DAG-CBOR
+    Convert {
+        /// The schema to convert
+        input: PathBuf,
+        /// The file to write to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// The format of the input, inferred from the file extension by default
+        #[arg(long)]
+        from: Option<Format>,
+        /// The format of the output, inferred from the file extension by default
+        #[arg(long)]
+        to: Option<Format>,
+    },
+    /// Validate data files against a type of a schema
+    Validate {
+        /// The schema to validate against
+        #[arg(short, long)]
+        schema: PathBuf,
+        /// The type of the root of the data
+        #[arg(short = 't', long = "type")]
+        root: String,
+        /// The data files to validate
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// The codec of the data, inferred from the file extension by default
+        #[arg(long)]
+        codec: Option<Codec>,
+        /// A directory of blocks, named by their CIDs, to load the nodes of advanced layouts from
+        #[arg(long)]
+        store: Option<PathBuf>,
+    },
+    /// Validate the DAGs of CARv1 files against a type of a schema
+    ///
+    /// Starting at the roots of each file, typed links are followed and every block is validated
+    /// against the type of its link. Prints a report with one line per block.
+    ValidateCar {
+        /// The schema to validate against
+        #[arg(short, long)]
+        schema: PathBuf,
+        /// The type of the roots of the files
+        #[arg(short = 't', long = "type")]
+        root: String,
+        /// The CAR files to validate
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// A directory of blocks, named by their CIDs, to load blocks missing from the files from
+        #[arg(long)]
+        store: Option<PathBuf>,
+    },
+    /// Generate source code from a schema
+    Codegen {
+        /// The schema to generate code for
+        schema: PathBuf,
+        /// The language to generate
+        #[arg(short, long, default_value = "rust")]
+        lang: Language,
+        /// The file to write to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Compare two versions of a schema and report how compatible the changes are
+    ///
+    /// Fails if the changes do not meet the required compatibility.
+    Compat {
+        /// The previous version of the schema
+        old: PathBuf,
+        /// The new version of the schema
+        new: PathBuf,
+        /// The compatibility the changes must have
+        #[arg(long, default_value = "backward")]
+        require: Requirement,
+    },
+    /// Print a unified diff of the type definitions of two schemas
+    Diff {
+        /// The previous version of the schema
+        old: PathBuf,
+        /// The new version of the schema
+        new: PathBuf,
+        /// Fail if the schemas differ
+        #[arg(long)]
+        exit_code: bool,
+    },
+    /// Import a JSON Schema document or CDDL rules as a schema in the DSL
+    ///
+    /// Files ending in `.cddl` are read as CDDL. Parts without an IPLD equivalent are reported as
+    /// warnings.
+    Import {
+        /// The JSON Schema document or CDDL file
+        input: PathBuf,
+        /// The name of the type for a JSON Schema document itself
+        #[arg(short = 't', long = "type", default_value = "Root")]
+        root: String,
+        /// The file to write to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Draw the types of a schema and their references as a diagram
+    Graph {
+        /// The schema to draw
+        schema: PathBuf,
+        /// The format of the diagram
+        #[arg(short, long, default_value = "dot")]
+        format: Diagram,
+        /// Only draw this type and the types it references
+        #[arg(short = 't', long = "type")]
+        root: Option<String>,
+        /// The file to write to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate reference documentation from the doc comments of a schema
+    Docs {
+        /// The schema to document
+        schema: PathBuf,
+        /// The format of the documentation
+        #[arg(short, long, default_value = "markdown")]
+        format: DocsOutput,
+        /// The title of the documentation, defaults to the name of the schema file
+        #[arg(long)]
+        title: Option<String>,
+        /// The file to write to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Write deterministic example values of a type as DAG-JSON files
+    ///
+    /// The files are named `<Type>.<example>.json`, i.e. `Message.minimal.json`. Characters of
+    /// example names, that are not letters, digits, `_` or `-`, are replaced by `_`.
+    Examples {
+        /// The schema defining the type
+        schema: PathBuf,
+        /// The type to write examples of
+        root: String,
+        /// The directory to write the files to
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Requirement {
+    /// Data written with either version must be valid under the other version
+    Full,
+    /// Data written with the old version must be valid under the new version
+    Backward,
+    /// Data written with the new version must be valid under the old version
+    Forward,
+    /// Accept any change
+    None,
+}
+
+impl Requirement {
+    fn compatibility(&self) -> Compatibility {
+        match self {
+            Requirement::Full => Compatibility::Full,
+            Requirement::Backward => Compatibility::Backward,
+            Requirement::Forward => Compatibility::Forward,
+            Requirement::None => Compatibility::Breaking,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// The schema DSL
+    Dsl,
+    /// The schema DMT encoded as DAG-JSON
+    Json,
+    /// The schema DMT encoded as DAG-CBOR
+    Cbor,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Format::Json,
+            Some("cbor") => Format::Cbor,
+            _ => Format::Dsl,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Codec {
+    DagJson,
+    DagCbor,
+}
+
+impl Codec {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("cbor") => Codec::DagCbor,
+            _ => Codec::DagJson,
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Ipld, String> {
+        match self {
+            Codec::DagJson => serde_ipld_dagjson::from_slice(bytes).map_err(|err| err.to_string()),
+            Codec::DagCbor => serde_ipld_dagcbor::from_slice(bytes).map_err(|err| err.to_string()),
+        }
+    }
+
+    fn encode(&self, ipld: &Ipld) -> Result<Vec<u8>, String> {
+        match self {
+            Codec::DagJson => serde_ipld_dagjson::to_vec(ipld).map_err(|err| err.to_string()),
+            Codec::DagCbor => serde_ipld_dagcbor::to_vec(ipld).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Diagram {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid class diagram
+    Mermaid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DocsOutput {
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Language {
+    Rust,
+    Typescript,
+    Cddl,
+}
+
+#[derive(Debug, Error)]
+enum CliError {
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}: {message}", path.display())]
+    Invalid { path: PathBuf, message: String },
+    #[error("{0} of {1} inputs are invalid")]
+    Failed(usize, usize),
+}
+
+impl CliError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            CliError::Io { .. } => ExitCode::from(2),
+            CliError::Invalid { .. } | CliError::Failed(..) => ExitCode::from(1),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let loader = cli
+        .include
+        .into_iter()
+        .fold(SchemaLoader::new(), SchemaLoader::with_search_path);
+
+    let result = match cli.command {
+        Command::Check { files, format } => check(&loader, &files, format),
+        Command::Fmt {
+            files,
+            check,
+            write,
+        } => fmt(&files, check, write),
+        Command::Convert {
+            input,
+            output,
+            from,
+            to,
+        } => convert(&loader, &input, output.as_deref(), from, to),
+        Command::Validate {
+            schema,
+            root,
+            files,
+            codec,
+            store,
+        } => validate(&loader, &schema, &root, &files, codec, store),
+        Command::ValidateCar {
+            schema,
+            root,
+            files,
+            store,
+        } => validate_car(&loader, &schema, &root, &files, store),
+        Command::Codegen {
+            schema,
+            lang,
+            output,
+        } => codegen(&loader, &schema, lang, output.as_deref()),
+        Command::Compat { old, new, require } => compat(&loader, &old, &new, require),
+        Command::Diff {
+            old,
+            new,
+            exit_code,
+        } => diff(&loader, &old, &new, exit_code),
+        Command::Import {
+            input,
+            root,
+            output,
+        } => import(&input, &root, output.as_deref()),
+        Command::Graph {
+            schema,
+            format,
+            root,
+            output,
+        } => graph(&loader, &schema, format, root.as_deref(), output.as_deref()),
+        Command::Docs {
+            schema,
+            format,
+            title,
+            output,
+        } => docs(&loader, &schema, format, title, output.as_deref()),
+        Command::Examples {
+            schema,
+            root,
+            output,
+        } => examples(&loader, &schema, &root, &output),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            err.exit_code()
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, CliError> {
+    fs::read(path).map_err(|source| CliError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn write_output(path: Option<&Path>, bytes: &[u8]) -> Result<(), CliError> {
+    match path {
+        Some(path) => fs::write(path, bytes).map_err(|source| CliError::Io {
+            path: path.to_path_buf(),
+            source,
+        }),
+        None => io::stdout()
+            .write_all(bytes)
+            .map_err(|source| CliError::Io {
+                path: PathBuf::from("<stdout>"),
+                source,
+            }),
+    }
+}
+
+fn invalid(path: &Path, message: impl ToString) -> CliError {
+    CliError::Invalid {
+        path: path.to_path_buf(),
+        message: message.to_string(),
+    }
+}
+
+fn parse_error(path: &Path, err: IpldSchemaParseError) -> CliError {
+    invalid(path, format_parse_error(path, err))
+}
+
+fn format_parse_error(path: &Path, err: IpldSchemaParseError) -> String {
+    match err {
+        IpldSchemaParseError::Syntax(err) => {
+            format!("\n{}", err.with_path(&path.to_string_lossy()))
+        }
+        err => err.to_string(),
+    }
+}
+
+/// Reports every error in a schema file that failed to parse, not only the first one
+fn parse_errors(path: &Path, err: IpldSchemaParseError) -> CliError {
+    let Ok(mut source) = fs::read_to_string(path) else {
+        return parse_error(path, err);
+    };
+    if matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("md" | "markdown")
+    ) {
+        source = markdown_source(&source);
+    }
+
+    // Imports are resolved by the loader
+    let (_, errors) = IpldSchema::parse_recovering(source);
+    let errors = errors
+        .into_iter()
+        .filter(|err| !matches!(err, IpldSchemaParseError::Import(_)))
+        .collect::<Vec<_>>();
+    if errors.len() <= 1 {
+        return parse_error(path, err);
+    }
+
+    let count = errors.len();
+    let message = errors
+        .into_iter()
+        .map(|err| match format_parse_error(path, err) {
+            // Syntax errors are printed as a block on their own
+            message if message.starts_with('\n') => message,
+            message => format!("\n  {}", message),
+        })
+        .collect::<String>();
+    invalid(path, format!("{} errors found:{}", count, message))
+}
+
+fn load_schema(
+    loader: &SchemaLoader,
+    path: &Path,
+    format: Option<Format>,
+) -> Result<IpldSchema, CliError> {
+    match format.unwrap_or_else(|| Format::from_path(path)) {
+        Format::Dsl => loader.load(path).map_err(|err| match err {
+            LoadError::Io { path, source } => CliError::Io { path, source },
+            LoadError::Parse { path, error } => parse_errors(&path, error),
+            err => invalid(path, err),
+        }),
+        Format::Json => load_dmt(path, Codec::DagJson),
+        Format::Cbor => load_dmt(path, Codec::DagCbor),
+    }
+}
+
+fn load_dmt(path: &Path, codec: Codec) -> Result<IpldSchema, CliError> {
+    let dmt = codec
+        .decode(&read(path)?)
+        .map_err(|err| invalid(path, err))?;
+    IpldSchema::from_dmt(&dmt).map_err(|err| invalid(path, err))
+}
+
+/// Runs `f` on every file, reports the failures and fails if any of them failed
+fn for_each_file(
+    files: &[PathBuf],
+    mut f: impl FnMut(&Path) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    let mut failed = 0;
+
+    for file in files {
+        match f(file) {
+            Ok(()) => (),
+            Err(err @ CliError::Io { .. }) => return Err(err),
+            Err(err) => {
+                eprintln!("error: {}", err);
+                failed += 1;
+            }
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        failed => Err(CliError::Failed(failed, files.len())),
+    }
+}
+
+fn check(loader: &SchemaLoader, files: &[PathBuf], format: Option<Format>) -> Result<(), CliError> {
+    for_each_file(files, |file| {
+        let schema = load_schema(loader, file, format)?;
+        check_schema(file, &schema)
+    })
+}
+
+/// Fails with all semantic errors of the schema loaded from `file`
+fn check_schema(file: &Path, schema: &IpldSchema) -> Result<(), CliError> {
+    let errors = schema.check();
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let message = errors
+        .iter()
+        .map(|err| format!("\n  {}", err))
+        .collect::<String>();
+    Err(invalid(
+        file,
+        format!("{} errors found:{}", errors.len(), message),
+    ))
+}
+
+fn fmt(files: &[PathBuf], check: bool, write: bool) -> Result<(), CliError> {
+    for_each_file(files, |file| {
+        let input = String::from_utf8(read(file)?).map_err(|err| invalid(file, err))?;
+        let formatted = ipld_schema::format(&input).map_err(|err| parse_error(file, err))?;
+
+        if check {
+            match input == formatted {
+                true => Ok(()),
+                false => Err(invalid(file, "file is not formatted")),
+            }
+        } else if write {
+            write_output(Some(file), formatted.as_bytes())
+        } else {
+            write_output(None, formatted.as_bytes())
+        }
+    })
+}
+
+fn convert(
+    loader: &SchemaLoader,
+    input: &Path,
+    output: Option<&Path>,
+    from: Option<Format>,
+    to: Option<Format>,
+) -> Result<(), CliError> {
+    let schema = load_schema(loader, input, from)?;
+
+    let bytes = match to.unwrap_or_else(|| output.map(Format::from_path).unwrap_or(Format::Dsl)) {
+        Format::Dsl => schema.to_string().into_bytes(),
+        Format::Json => Codec::DagJson
+            .encode(&schema.to_dmt())
+            .map_err(|err| invalid(input, err))?,
+        Format::Cbor => Codec::DagCbor
+            .encode(&schema.to_dmt())
+            .map_err(|err| invalid(input, err))?,
+    };
+
+    write_output(output, &bytes)
+}
+
+fn validate(
+    loader: &SchemaLoader,
+    schema: &Path,
+    root: &str,
+    files: &[PathBuf],
+    codec: Option<Codec>,
+    store: Option<PathBuf>,
+) -> Result<(), CliError> {
+    let schema = load_schema(loader, schema, None)?;
+    let adls = AdlRegistry::new();
+    let store = store.map(DirectoryStore::new);
+    let store = store.as_ref().map(|store| store as &dyn BlockStore);
+
+    for_each_file(files, |file| {
+        match codec.unwrap_or_else(|| Codec::from_path(file)) {
+            // DAG-CBOR is validated while reading, without decoding the whole file first
+            Codec::DagCbor => {
+                let reader = fs::File::open(file).map_err(|source| CliError::Io {
+                    path: file.to_path_buf(),
+                    source,
+                })?;
+                schema
+                    .validate_dag_cbor_with(root, io::BufReader::new(reader), &adls, store)
+                    .map_err(|err| invalid(file, err))
+            }
+            // DAG-JSON errors are shown with a snippet of the offending value
+            Codec::DagJson => {
+                let source = String::from_utf8(read(file)?).map_err(|err| invalid(file, err))?;
+                schema
+                    .validate_dag_json_with(root, &source, &adls, store)
+                    .map_err(|err| {
+                        let rendered = err.render(&file.display().to_string(), &source);
+                        invalid(file, format!("\n{}", rendered.trim_end()))
+                    })
+            }
+        }
+    })
+}
+
+fn validate_car(
+    loader: &SchemaLoader,
+    schema: &Path,
+    root: &str,
+    files: &[PathBuf],
+    store: Option<PathBuf>,
+) -> Result<(), CliError> {
+    let schema = load_schema(loader, schema, None)?;
+    let adls = AdlRegistry::new();
+    let store = store.map(DirectoryStore::new);
+    let store = store.as_ref().map(|store| store as &dyn BlockStore);
+
+    for_each_file(files, |file| {
+        let car = Car::open(file).map_err(|err| match err {
+            CarError::Io(source) => CliError::Io {
+                path: file.to_path_buf(),
+                source,
+            },
+            err => invalid(file, err),
+        })?;
+        let report = schema
+            .validate_car_with(root, &car, &adls, store)
+            .map_err(|err| invalid(file, err))?;
+        write_output(None, report.to_string().as_bytes())?;
+
+        match report.errors().count() {
+            0 => Ok(()),
+            count => Err(invalid(
+                file,
+                format!("{} of {} blocks are invalid", count, report.blocks.len()),
+            )),
+        }
+    })
+}
+
+fn codegen(
+    loader: &SchemaLoader,
+    schema: &Path,
+    lang: Language,
+    output: Option<&Path>,
+) -> Result<(), CliError> {
+    let path = schema;
+    let schema = load_schema(loader, path, None)?;
+    // Code generated for invalid schemas, i.e. with copies of themselves, does not compile
+    check_schema(path, &schema)?;
+
+    let code = match lang {
+        Language::Rust => schema.generate_rust(),
+        Language::Typescript => schema.generate_typescript(),
+        Language::Cddl => schema.generate_cddl(),
+    };
+
+    write_output(output, code.as_bytes())
+}
+
+fn compat(
+    loader: &SchemaLoader,
+    old: &Path,
+    new: &Path,
+    require: Requirement,
+) -> Result<(), CliError> {
+    let old_schema = load_schema(loader, old, None)?;
+    let new_schema = load_schema(loader, new, None)?;
+
+    let report = old_schema.compatibility(&new_schema);
+    write_output(None, report.to_string().as_bytes())?;
+
+    let required = require.compatibility();
+    match report.violations(required).count() {
+        0 => Ok(()),
+        count => Err(invalid(
+            new,
+            format!("{} changes are not {} compatible", count, required),
+        )),
+    }
+}
+
+fn diff(loader: &SchemaLoader, old: &Path, new: &Path, exit_code: bool) -> Result<(), CliError> {
+    let old_schema = load_schema(loader, old, None)?;
+    let new_schema = load_schema(loader, new, None)?;
+
+    let diff = old_schema.diff(&new_schema);
+    write_output(None, diff.to_string().as_bytes())?;
+
+    match exit_code && !diff.is_empty() {
+        true => Err(invalid(new, format!("{} types differ", diff.types.len()))),
+        false => Ok(()),
+    }
+}
+
+fn import(input: &Path, root: &str, output: Option<&Path>) -> Result<(), CliError> {
+    let (schema, warnings) = if input.extension().is_some_and(|ext| ext == "cddl") {
+        let source = String::from_utf8(read(input)?).map_err(|err| invalid(input, err))?;
+        IpldSchema::from_cddl(&source).map_err(|err| invalid(input, err))?
+    } else {
+        let document: serde_json::Value =
+            serde_json::from_slice(&read(input)?).map_err(|err| invalid(input, err))?;
+        IpldSchema::from_json_schema(&document, root).map_err(|err| invalid(input, err))?
+    };
+
+    for warning in warnings {
+        eprintln!("warning: {}: {}", input.display(), warning);
+    }
+    write_output(output, schema.to_string().as_bytes())
+}
+
+fn graph(
+    loader: &SchemaLoader,
+    path: &Path,
+    format: Diagram,
+    root: Option<&str>,
+    output: Option<&Path>,
+) -> Result<(), CliError> {
+    let schema = load_schema(loader, path, None)?;
+
+    let format = match format {
+        Diagram::Dot => GraphFormat::Dot,
+        Diagram::Mermaid => GraphFormat::Mermaid,
+    };
+    let graph = schema
+        .graph(format, root)
+        .map_err(|err| invalid(path, err))?;
+
+    write_output(output, graph.as_bytes())
+}
+
+fn docs(
+    loader: &SchemaLoader,
+    path: &Path,
+    format: DocsOutput,
+    title: Option<String>,
+    output: Option<&Path>,
+) -> Result<(), CliError> {
+    let schema = load_schema(loader, path, None)?;
+
+    let title = title.unwrap_or_else(|| {
+        path.file_stem().map_or_else(
+            || path.display().to_string(),
+            |stem| stem.to_string_lossy().into_owned(),
+        )
+    });
+    let format = match format {
+        DocsOutput::Markdown => DocsFormat::Markdown,
+        DocsOutput::Html => DocsFormat::Html,
+    };
+
+    write_output(output, schema.generate_docs(&title, format).as_bytes())
+}
+
+fn examples(loader: &SchemaLoader, path: &Path, root: &str, output: &Path) -> Result<(), CliError> {
+    let schema = load_schema(loader, path, None)?;
+    let examples = schema.examples(root).map_err(|err| invalid(path, err))?;
+
+    let mut names = BTreeSet::new();
+    for example in examples {
+        let mut bytes = Codec::DagJson
+            .encode(&example.data)
+            .map_err(|err| invalid(path, err))?;
+        bytes.push(b'\n');
+
+        // Names of union members are discriminants, which may not be valid in file names
+        let name = file_name(&example.name);
+        let mut unique = name.clone();
+        let mut counter = 1;
+        while !names.insert(unique.clone()) {
+            counter += 1;
+            unique = format!("{}-{}", name, counter);
+        }
+        let file = output.join(format!("{}.{}.json", root, unique));
+        write_output(Some(&file), &bytes)?;
+        eprintln!("wrote {}", file.display());
+    }
+
+    Ok(())
+}
+
+/// Replaces everything but ASCII letters, digits, `_` and `-`
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names() {
+        assert_eq!(file_name("minimal"), "minimal");
+        assert_eq!(file_name("../a b/ü"), "___a_b__");
+    }
+
+    #[test]
+    fn infer_formats() {
+        assert_eq!(Format::from_path(Path::new("schema.ipldsch")), Format::Dsl);
+        assert_eq!(Format::from_path(Path::new("schema.json")), Format::Json);
+        assert_eq!(Format::from_path(Path::new("schema.cbor")), Format::Cbor);
+        assert_eq!(Codec::from_path(Path::new("data.json")), Codec::DagJson);
+        assert_eq!(Codec::from_path(Path::new("data.cbor")), Codec::DagCbor);
+
+        Cli::try_parse_from([
+            "ipld-schema",
+            "validate",
+            "-s",
+            "a.ipldsch",
+            "-t",
+            "Foo",
+            "a.json",
+        ])
+        .unwrap();
+        assert!(Cli::try_parse_from(["ipld-schema", "fmt", "--check", "--write", "a"]).is_err());
+        assert!(Cli::try_parse_from(["ipld-schema", "examples", "a.ipldsch"]).is_err());
+        Cli::try_parse_from([
+            "ipld-schema",
+            "validate-car",
+            "-s",
+            "a.ipldsch",
+            "-t",
+            "Foo",
+            "a.car",
+        ])
+        .unwrap();
+    }
+}
@@ -0,0 +1,226 @@
+use std::{ops::Range, path::PathBuf};
+
+use ipld_schema::{
+    outline, IpldSchema, IpldSchemaParseError, LoadError, Location, Outline, SchemaError,
+    SchemaLoader,
+};
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Url};
+
+/// An open schema file and the results of its last analysis
+#[derive(Debug, Clone)]
+pub(crate) struct Document {
+    pub(crate) uri: Url,
+    pub(crate) text: String,
+    /// Byte offsets of the start of every line
+    line_starts: Vec<usize>,
+    /// The outline of the last version that parsed, so that features keep working while typing
+    pub(crate) outline: Outline,
+    /// All types visible in the document, including imported ones
+    pub(crate) type_names: Vec<String>,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+}
+
+impl Document {
+    pub(crate) fn new(uri: Url, text: String, loader: &SchemaLoader) -> Self {
+        let mut document = Document {
+            uri,
+            text: String::new(),
+            line_starts: vec![],
+            outline: Outline::default(),
+            type_names: vec![],
+            diagnostics: vec![],
+        };
+        document.update(text, loader);
+        document
+    }
+
+    pub(crate) fn path(&self) -> PathBuf {
+        self.uri
+            .to_file_path()
+            .unwrap_or_else(|_| PathBuf::from(self.uri.path()))
+    }
+
+    /// Replaces the text of the document and analyzes it again
+    pub(crate) fn update(&mut self, text: String, loader: &SchemaLoader) {
+        self.line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        self.text = text;
+        self.diagnostics.clear();
+
+        match outline(&self.text) {
+            Ok(outline) => self.outline = outline,
+            Err(err) => {
+                // Report every syntax error, imports are resolved by the loader
+                let (_, mut errors) = IpldSchema::parse_recovering(&self.text);
+                errors.retain(|err| !matches!(err, IpldSchemaParseError::Import(_)));
+                if errors.is_empty() {
+                    errors.push(err);
+                }
+
+                for err in errors {
+                    let diagnostic = self.parse_diagnostic(&err);
+                    self.diagnostics.push(diagnostic);
+                }
+                return;
+            }
+        }
+
+        match loader.load_str(self.path(), &self.text) {
+            Ok(schema) => {
+                self.type_names = schema.type_names().map(str::to_string).collect();
+                let errors = schema.check();
+                for err in errors {
+                    let diagnostic = self.schema_diagnostic(&err);
+                    self.diagnostics.push(diagnostic);
+                }
+            }
+            Err(err) => {
+                let diagnostic = self.load_diagnostic(&err);
+                self.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    pub(crate) fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.line_starts[line];
+
+        Position::new(
+            line as u32,
+            self.text[start..offset].encode_utf16().count() as u32,
+        )
+    }
+
+    pub(crate) fn range(&self, span: &Range<usize>) -> lsp_types::Range {
+        lsp_types::Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    /// Converts a position with a UTF-16 column into a byte offset
+    pub(crate) fn offset(&self, position: Position) -> usize {
+        let Some(start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+
+        let mut column = 0;
+        for (idx, c) in self.text[*start..].char_indices() {
+            if column >= position.character as usize || c == '\n' {
+                return start + idx;
+            }
+            column += c.len_utf16();
+        }
+
+        self.text.len()
+    }
+
+    /// Converts a 1-based line and column in characters, as reported by the parser
+    fn line_column(&self, line: usize, column: usize) -> usize {
+        let Some(start) = self.line_starts.get(line.saturating_sub(1)) else {
+            return self.text.len();
+        };
+
+        self.text[*start..]
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(self.text.len(), |(idx, _)| start + idx)
+    }
+
+    fn diagnostic(&self, span: Range<usize>, message: String) -> Diagnostic {
+        Diagnostic {
+            range: self.range(&span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("ipld-schema".to_string()),
+            message,
+            ..Diagnostic::default()
+        }
+    }
+
+    fn parse_diagnostic(&self, err: &IpldSchemaParseError) -> Diagnostic {
+        match err {
+            IpldSchemaParseError::Syntax(err) => {
+                let span = match err.line_col {
+                    pest::error::LineColLocation::Pos((line, column)) => {
+                        let offset = self.line_column(line, column);
+                        offset..offset
+                    }
+                    pest::error::LineColLocation::Span(start, end) => {
+                        self.line_column(start.0, start.1)..self.line_column(end.0, end.1)
+                    }
+                };
+                self.diagnostic(span, err.variant.message().to_string())
+            }
+            err => self.diagnostic(0..0, err.to_string()),
+        }
+    }
+
+    fn load_diagnostic(&self, err: &LoadError) -> Diagnostic {
+        let location = match err {
+            LoadError::NotFound { location, .. } => Some(location),
+            LoadError::Conflict { second, .. } => Some(second),
+            _ => None,
+        };
+
+        let span = match location {
+            Some(location) if self.is_location_in_document(location) => {
+                let offset = self.line_column(location.line, location.column);
+                offset..offset
+            }
+            _ => 0..0,
+        };
+        self.diagnostic(span, err.to_string())
+    }
+
+    fn is_location_in_document(&self, location: &Location) -> bool {
+        let path = self.path();
+        location.path == path || path.canonicalize().is_ok_and(|path| location.path == path)
+    }
+
+    fn schema_diagnostic(&self, err: &SchemaError) -> Diagnostic {
+        let ty = match err {
+            SchemaError::UndefinedType { ty, .. }
+            | SchemaError::DuplicateField { ty, .. }
+            | SchemaError::DuplicateMember { ty, .. }
+            | SchemaError::OptionalImplicit { ty, .. }
+            | SchemaError::CopyCycle { ty } => ty,
+        };
+
+        let span = self
+            .outline
+            .definition(ty)
+            .map_or(0..0, |symbol| symbol.name_span.clone());
+        self.diagnostic(span, err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        let uri = Url::parse("file:///tmp/test.ipldsch").unwrap();
+        let document = Document::new(
+            uri,
+            "# Ünïcode 🦀\ntype Foo struct {\n  x Bar\n}\n".to_string(),
+            &SchemaLoader::new(),
+        );
+
+        let crab = document.text.find("🦀").unwrap();
+        assert_eq!(document.position(crab), Position::new(0, 10));
+        assert_eq!(document.offset(Position::new(0, 10)), crab);
+        assert_eq!(document.offset(Position::new(0, 12)), crab + 4);
+        assert_eq!(document.offset(Position::new(0, 100)), crab + 4);
+        assert_eq!(document.position(document.text.len()), Position::new(4, 0));
+
+        assert_eq!(document.diagnostics.len(), 1);
+        assert_eq!(
+            document.diagnostics[0].message,
+            "Type \"Bar\" referenced by \"Foo\" is not defined"
+        );
+        assert_eq!(
+            document.diagnostics[0].range,
+            lsp_types::Range::new(Position::new(1, 5), Position::new(1, 8))
+        );
+    }
+}
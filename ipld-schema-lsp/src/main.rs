@@ -0,0 +1,16 @@
+//! A language server for IPLD schema files, speaking LSP over stdio
+
+mod document;
+mod server;
+
+use std::error::Error;
+
+use lsp_server::Connection;
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (connection, io_threads) = Connection::stdio();
+    server::run(connection)?;
+    io_threads.join()?;
+
+    Ok(())
+}
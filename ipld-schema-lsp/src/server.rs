@@ -0,0 +1,674 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ipld_schema::{Outline, SchemaLoader, Symbol, PRELUDE};
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{
+        Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, References,
+        Request as LspRequest,
+    },
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location,
+    MarkupContent, MarkupKind, OneOf, PublishDiagnosticsParams, ReferenceParams,
+    ServerCapabilities, SymbolKind, TextDocumentPositionParams, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use serde_json::Value;
+
+use crate::document::Document;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+/// Keywords that can follow `representation`
+const REPRESENTATIONS: &[&str] = &[
+    "map",
+    "listpairs",
+    "stringpairs",
+    "advanced",
+    "int",
+    "string",
+    "null",
+    "true",
+    "false",
+    "emptymap",
+    "tuple",
+    "keyed",
+    "kinded",
+    "inline",
+    "envelope",
+];
+
+const KEYWORDS: &[&str] = &[
+    "type",
+    "import",
+    "bool",
+    "string",
+    "bytes",
+    "int",
+    "float",
+    "any",
+    "struct",
+    "enum",
+    "union",
+    "unit",
+    "list",
+    "map",
+    "link",
+    "copy",
+    "representation",
+    "optional",
+    "nullable",
+    "rename",
+    "implicit",
+];
+
+/// Serves a single client until it shuts down
+pub(crate) fn run(connection: Connection) -> Result<(), BoxError> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(response.into())?;
+            }
+            Message::Notification(notification) => {
+                let method = notification.method.clone();
+                match server.handle_notification(notification) {
+                    Ok(Some(params)) => {
+                        let notification = Notification::new(
+                            PublishDiagnostics::METHOD.to_string(),
+                            serde_json::to_value(params)?,
+                        );
+                        connection.sender.send(notification.into())?;
+                    }
+                    Ok(None) => (),
+                    // A broken notification must not end the session, stderr is the log
+                    Err(err) => eprintln!("Failed to handle {}: {}", method, err),
+                }
+            }
+            Message::Response(_) => (),
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct Server {
+    documents: HashMap<Url, Document>,
+    loader: SchemaLoader,
+}
+
+impl Server {
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            HoverRequest::METHOD => self.dispatch(request, Self::hover),
+            GotoDefinition::METHOD => self.dispatch(request, Self::definition),
+            References::METHOD => self.dispatch(request, Self::references),
+            DocumentSymbolRequest::METHOD => self.dispatch(request, Self::document_symbols),
+            Completion::METHOD => self.dispatch(request, Self::completion),
+            method => {
+                return Response::new_err(
+                    id,
+                    lsp_server::ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request {}", method),
+                )
+            }
+        };
+
+        match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err(err) => Response::new_err(
+                id,
+                lsp_server::ErrorCode::InvalidParams as i32,
+                err.to_string(),
+            ),
+        }
+    }
+
+    fn dispatch<P, R>(
+        &self,
+        request: Request,
+        handler: fn(&Self, P) -> R,
+    ) -> Result<Value, BoxError>
+    where
+        P: serde::de::DeserializeOwned,
+        R: serde::Serialize,
+    {
+        let params = serde_json::from_value(request.params)?;
+        Ok(serde_json::to_value(handler(self, params))?)
+    }
+
+    /// Updates the documents and returns the diagnostics to publish
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> Result<Option<PublishDiagnosticsParams>, BoxError> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: <DidOpenTextDocument as LspNotification>::Params =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                let document = Document::new(uri.clone(), params.text_document.text, &self.loader);
+                self.documents.insert(uri.clone(), document);
+                uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: <DidChangeTextDocument as LspNotification>::Params =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                let (Some(document), Some(change)) = (
+                    self.documents.get_mut(&uri),
+                    params.content_changes.into_iter().last(),
+                ) else {
+                    return Ok(None);
+                };
+                document.update(change.text, &self.loader);
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: <DidCloseTextDocument as LspNotification>::Params =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                return Ok(Some(PublishDiagnosticsParams::new(
+                    params.text_document.uri,
+                    vec![],
+                    None,
+                )));
+            }
+            _ => return Ok(None),
+        };
+
+        let document = &self.documents[&uri];
+        Ok(Some(PublishDiagnosticsParams::new(
+            uri,
+            document.diagnostics.clone(),
+            None,
+        )))
+    }
+
+    /// Returns the document and the type name at a position
+    fn name_at(&self, position: &TextDocumentPositionParams) -> Option<(&Document, String)> {
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = document.offset(position.position);
+        let name = document.outline.name_at(offset)?;
+        Some((document, name.to_string()))
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let (document, name) = self.name_at(&params.text_document_position_params)?;
+
+        let value = match self.find_definition(document, &name) {
+            Some((_, _, symbol)) => {
+                let mut value = format!("```ipldsch\ntype {} {}\n```", name, symbol.kind);
+                if let Some(doc) = &symbol.doc {
+                    value.push_str("\n\n");
+                    value.push_str(doc);
+                }
+                value
+            }
+            None if PRELUDE.contains(&name.as_str()) => {
+                format!("```ipldsch\ntype {}\n```\n\nA type of the prelude", name)
+            }
+            None => return None,
+        };
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: None,
+        })
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let (document, name) = self.name_at(&params.text_document_position_params)?;
+        let (uri, location, _) = self.find_definition(document, &name)?;
+
+        Some(GotoDefinitionResponse::Scalar(Location::new(uri, location)))
+    }
+
+    /// Looks up a type in the document and, transitively, in the files it imports
+    fn find_definition(
+        &self,
+        document: &Document,
+        name: &str,
+    ) -> Option<(Url, lsp_types::Range, Symbol)> {
+        if let Some(symbol) = document.outline.definition(name) {
+            return Some((
+                document.uri.clone(),
+                document.range(&symbol.name_span),
+                symbol.clone(),
+            ));
+        }
+
+        self.imported(document).into_iter().find_map(|imported| {
+            let symbol = imported.outline.definition(name)?.clone();
+            let range = imported.range(&symbol.name_span);
+            Some((imported.uri, range, symbol))
+        })
+    }
+
+    /// The files `document` imports, transitively, in the order they are reached.
+    ///
+    /// Open documents take precedence over the files on disk, unreadable files are skipped.
+    fn imported(&self, document: &Document) -> Vec<Document> {
+        let mut visited = BTreeSet::from([document.path()]);
+        let mut documents = vec![];
+        let mut queue = imports(&document.path(), &document.outline, &self.loader);
+        while let Some(path) = queue.pop() {
+            if !visited.insert(path.clone()) {
+                continue;
+            }
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+
+            let imported = match self.documents.get(&uri) {
+                Some(document) => document.clone(),
+                None => match fs::read_to_string(&path) {
+                    Ok(text) => Document::new(uri, text, &self.loader),
+                    Err(_) => continue,
+                },
+            };
+            queue.extend(imports(&path, &imported.outline, &self.loader));
+            documents.push(imported);
+        }
+
+        documents
+    }
+
+    /// Finds the uses of a type in the open documents and the files they import
+    fn references(&self, params: ReferenceParams) -> Option<Vec<Location>> {
+        let (_, name) = self.name_at(&params.text_document_position)?;
+
+        let mut documents = BTreeMap::new();
+        for document in self.documents.values() {
+            documents.insert(document.uri.clone(), document.clone());
+            for imported in self.imported(document) {
+                documents.entry(imported.uri.clone()).or_insert(imported);
+            }
+        }
+
+        let mut locations = vec![];
+        for document in documents.values() {
+            if params.context.include_declaration {
+                if let Some(symbol) = document.outline.definition(&name) {
+                    locations.push(Location::new(
+                        document.uri.clone(),
+                        document.range(&symbol.name_span),
+                    ));
+                }
+            }
+            for reference in &document.outline.references {
+                if reference.name == name {
+                    locations.push(Location::new(
+                        document.uri.clone(),
+                        document.range(&reference.span),
+                    ));
+                }
+            }
+        }
+
+        Some(locations)
+    }
+
+    fn document_symbols(&self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let document = self.documents.get(&params.text_document.uri)?;
+
+        #[allow(deprecated)]
+        fn convert(document: &Document, symbol: &Symbol) -> DocumentSymbol {
+            DocumentSymbol {
+                name: symbol.name.clone(),
+                detail: Some(symbol.kind.to_string()),
+                kind: match symbol.kind {
+                    "struct" => SymbolKind::STRUCT,
+                    "enum" => SymbolKind::ENUM,
+                    "field" => SymbolKind::FIELD,
+                    "member" => SymbolKind::ENUM_MEMBER,
+                    "import" => SymbolKind::FILE,
+                    _ => SymbolKind::CLASS,
+                },
+                tags: None,
+                deprecated: None,
+                range: document.range(&symbol.span),
+                selection_range: document.range(&symbol.name_span),
+                children: Some(
+                    symbol
+                        .children
+                        .iter()
+                        .map(|child| convert(document, child))
+                        .collect(),
+                ),
+            }
+        }
+
+        Some(DocumentSymbolResponse::Nested(
+            document
+                .outline
+                .imports
+                .iter()
+                .chain(&document.outline.definitions)
+                .map(|symbol| convert(document, symbol))
+                .collect(),
+        ))
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let document = self.documents.get(&position.text_document.uri)?;
+
+        // The word before the one that is completed decides what is expected
+        let offset = document.offset(position.position);
+        let line_start = document.text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+        let mut words = document.text[line_start..offset].split_whitespace().rev();
+        let previous = match document.text[..offset].ends_with(char::is_whitespace) {
+            true => words.next(),
+            false => words.nth(1),
+        };
+
+        let keyword = |keyword: &&str| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            ..CompletionItem::default()
+        };
+
+        if previous == Some("representation") {
+            return Some(CompletionResponse::Array(
+                REPRESENTATIONS.iter().map(keyword).collect(),
+            ));
+        }
+
+        let types = document
+            .outline
+            .definitions
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .chain(document.type_names.iter().map(String::as_str))
+            .chain(PRELUDE.iter().copied())
+            .collect::<BTreeSet<_>>();
+
+        Some(CompletionResponse::Array(
+            types
+                .into_iter()
+                .map(|name| CompletionItem {
+                    label: name.to_string(),
+                    kind: Some(CompletionItemKind::CLASS),
+                    ..CompletionItem::default()
+                })
+                .chain(KEYWORDS.iter().map(keyword))
+                .collect(),
+        ))
+    }
+}
+
+/// Resolves the imports of a file like the loader, ignoring the ones that do not exist
+fn imports(path: &Path, outline: &Outline, loader: &SchemaLoader) -> Vec<PathBuf> {
+    outline
+        .imports
+        .iter()
+        .filter_map(|import| loader.resolve_import(path, &import.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use lsp_server::RequestId;
+    use lsp_types::{
+        notification::{Exit, Initialized},
+        request::{Initialize, Shutdown},
+        DidOpenTextDocumentParams, Position, TextDocumentIdentifier, TextDocumentItem,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    const SCHEMA: &str = include_str!("../../ipld-schema/test/message.ipldsch");
+
+    /// A minimal LSP client, talking to a server running in a thread
+    struct Client {
+        connection: Connection,
+        server: Option<thread::JoinHandle<()>>,
+        next_id: i32,
+    }
+
+    impl Client {
+        fn start() -> Self {
+            let (server, connection) = Connection::memory();
+            let server = thread::spawn(move || run(server).unwrap());
+            let mut client = Client {
+                connection,
+                server: Some(server),
+                next_id: 0,
+            };
+
+            client.request::<Initialize>(json!({ "capabilities": {} }));
+            client.notify::<Initialized>(json!({}));
+            client
+        }
+
+        fn request<R: LspRequest>(&mut self, params: Value) -> Value {
+            self.next_id += 1;
+            let id = RequestId::from(self.next_id);
+            let request = Request::new(id.clone(), R::METHOD.to_string(), params);
+            self.connection.sender.send(request.into()).unwrap();
+
+            loop {
+                match self.connection.receiver.recv().unwrap() {
+                    Message::Response(response) if response.id == id => {
+                        assert!(response.error.is_none(), "{:?}", response.error);
+                        return response.result.unwrap_or(Value::Null);
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        fn notify<N: LspNotification>(&mut self, params: Value) {
+            let notification = Notification::new(N::METHOD.to_string(), params);
+            self.connection.sender.send(notification.into()).unwrap();
+        }
+
+        fn open(&mut self, uri: &Url, text: &str) -> PublishDiagnosticsParams {
+            let params = DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    "ipldsch".to_string(),
+                    1,
+                    text.to_string(),
+                ),
+            };
+            self.notify::<DidOpenTextDocument>(serde_json::to_value(params).unwrap());
+
+            loop {
+                if let Message::Notification(notification) =
+                    self.connection.receiver.recv().unwrap()
+                {
+                    assert_eq!(notification.method, PublishDiagnostics::METHOD);
+                    return serde_json::from_value(notification.params).unwrap();
+                }
+            }
+        }
+
+        fn position(uri: &Url, line: u32, character: u32) -> Value {
+            serde_json::to_value(TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri.clone()),
+                Position::new(line, character),
+            ))
+            .unwrap()
+        }
+    }
+
+    impl Drop for Client {
+        fn drop(&mut self) {
+            self.request::<Shutdown>(Value::Null);
+            self.notify::<Exit>(Value::Null);
+            if let Some(server) = self.server.take() {
+                server.join().unwrap();
+            }
+        }
+    }
+
+    fn labels(completion: &Value) -> Vec<&str> {
+        completion
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn language_features() {
+        let uri = Url::parse("file:///tmp/message.ipldsch").unwrap();
+        let mut client = Client::start();
+
+        let diagnostics = client.open(&uri, SCHEMA);
+        assert_eq!(diagnostics.diagnostics, vec![]);
+
+        let hover = client.request::<HoverRequest>(Client::position(&uri, 3, 11));
+        assert_eq!(
+            hover["contents"]["value"],
+            "```ipldsch\ntype Identity copy\n```"
+        );
+        let hover = client.request::<HoverRequest>(Client::position(&uri, 1, 7));
+        assert_eq!(
+            hover["contents"]["value"],
+            "```ipldsch\ntype Message struct\n```\n\nA message"
+        );
+
+        let definition = client.request::<GotoDefinition>(Client::position(&uri, 4, 25));
+        assert_eq!(
+            definition["range"]["start"],
+            json!({"line": 11, "character": 5})
+        );
+
+        let mut params = Client::position(&uri, 11, 6);
+        params["context"] = json!({"includeDeclaration": true});
+        let references = client.request::<References>(params);
+        assert_eq!(references.as_array().unwrap().len(), 3);
+
+        let symbols = client.request::<DocumentSymbolRequest>(json!({
+            "textDocument": {"uri": uri}
+        }));
+        assert_eq!(symbols[0]["name"], "Message");
+        assert_eq!(symbols[0]["children"][1]["name"], "to");
+        assert_eq!(symbols[2]["detail"], "enum");
+        assert_eq!(symbols[2]["children"][0]["name"], "Active");
+
+        let completion = client.request::<Completion>(Client::position(&uri, 3, 6));
+        let labels = labels(&completion);
+        assert!(labels.contains(&"Identity"));
+        assert!(labels.contains(&"String"));
+        assert!(labels.contains(&"optional"));
+    }
+
+    #[test]
+    fn imported_files() {
+        let dir = std::env::temp_dir().join(format!("ipld-schema-lsp-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let root = "\
+import \"common.ipldsch\"
+import \"broken.ipldsch\"
+
+type Message struct {
+  from Identity
+} representation map
+";
+        fs::write(dir.join("common.ipldsch"), "type Identity string\n").unwrap();
+        // Not UTF-8, so the file can not be read as a schema
+        fs::write(dir.join("broken.ipldsch"), b"\xff").unwrap();
+        fs::write(dir.join("root.ipldsch"), root).unwrap();
+
+        let uri = Url::from_file_path(dir.join("root.ipldsch")).unwrap();
+        let common = Url::from_file_path(dir.join("common.ipldsch")).unwrap();
+        let mut client = Client::start();
+        client.open(&uri, root);
+
+        let definition = client.request::<GotoDefinition>(Client::position(&uri, 4, 8));
+        assert_eq!(definition["uri"], common.as_str());
+        assert_eq!(
+            definition["range"]["start"],
+            json!({"line": 0, "character": 5})
+        );
+
+        // The declaration is only in the imported file, which is not open
+        let mut params = Client::position(&uri, 4, 8);
+        params["context"] = json!({"includeDeclaration": true});
+        let references = client.request::<References>(params);
+        let uris = references
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|location| location["uri"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(uris, vec![common.as_str(), uri.as_str()]);
+
+        let completion = client.request::<Completion>(Client::position(&uri, 5, 17));
+        let labels = labels(&completion);
+        assert!(labels.contains(&"listpairs"));
+        assert!(!labels.contains(&"Identity"));
+
+        drop(client);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn diagnostics() {
+        let uri = Url::parse("file:///tmp/broken.ipldsch").unwrap();
+        let mut client = Client::start();
+
+        // Invalid notifications are logged, the session goes on
+        client.notify::<DidOpenTextDocument>(json!({}));
+
+        let diagnostics = client.open(&uri, "type Foo struct {\n  x Int\n");
+        assert_eq!(diagnostics.diagnostics.len(), 1);
+        assert_eq!(diagnostics.diagnostics[0].range.start.line, 2);
+
+        let diagnostics = client.open(&uri, "type Foo struct {\n  x\n}\ntype Bar [\n");
+        assert_eq!(
+            diagnostics
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.range.start.line)
+                .collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+
+        let diagnostics = client.open(&uri, "type Foo struct {}\ntype Foo int\n");
+        assert_eq!(
+            diagnostics.diagnostics[0].message,
+            "Type \"Foo\" is defined at /tmp/broken.ipldsch:1:1 and at /tmp/broken.ipldsch:2:1"
+        );
+        assert_eq!(diagnostics.diagnostics[0].range.start.line, 1);
+    }
+}
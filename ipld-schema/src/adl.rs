@@ -0,0 +1,306 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, OnceLock},
+};
+
+use ipld_core::ipld::Ipld;
+
+use crate::{
+    hamt::{HashMapLayout, HASH_MAP},
+    store::BlockStore,
+    DataPath, Kind, ValidationError,
+};
+
+/// The error of an advanced data layout, that failed to read its data
+pub type AdlError = Box<dyn Error + Send + Sync>;
+
+/// Presents data in a custom layout, i.e. a map sharded over many blocks, as a plain data
+/// model value.
+///
+/// Types with `representation advanced Name` are validated and traversed through the
+/// logical value of the layout registered as `Name` in an [`AdlRegistry`]. Only the layout
+/// knows its substrate, the data as it is stored, so exported types accept any value there.
+pub trait AdvancedDataLayout: Send + Sync {
+    /// The kind of the logical value, which must match the kind of the type using the layout
+    fn kind(&self) -> Kind;
+
+    /// Reads the logical value from the data, as it is stored in the parent block.
+    ///
+    /// Without a store, layouts should read as much as possible from the substrate itself,
+    /// like the entries of a HAMT that are stored in its root.
+    fn read(&self, substrate: &Ipld, store: Option<&dyn BlockStore>) -> Result<Ipld, AdlError>;
+
+    /// Reads the entry `key` of a logical map or the element at index `key` of a logical list.
+    ///
+    /// The default reads the whole value, layouts should only load what the lookup needs.
+    fn get(
+        &self,
+        substrate: &Ipld,
+        key: &str,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<Option<Ipld>, AdlError> {
+        Ok(match self.read(substrate, store)? {
+            Ipld::Map(mut entries) => entries.remove(key),
+            Ipld::List(mut items) => key
+                .parse()
+                .ok()
+                .filter(|idx| *idx < items.len())
+                .map(|idx| items.swap_remove(idx)),
+            _ => None,
+        })
+    }
+}
+
+/// The advanced data layouts, that are available by name
+#[derive(Clone)]
+pub struct AdlRegistry(BTreeMap<String, Arc<dyn AdvancedDataLayout>>);
+
+impl Default for AdlRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for AdlRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+impl AdlRegistry {
+    /// A registry of the layouts implemented by this crate, i.e. [`HASH_MAP`]
+    pub fn new() -> Self {
+        Self::empty().with_layout(HASH_MAP, HashMapLayout)
+    }
+
+    pub fn empty() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Registers a layout, replacing any layout of the same name
+    pub fn with_layout(
+        mut self,
+        name: impl Into<String>,
+        layout: impl AdvancedDataLayout + 'static,
+    ) -> Self {
+        self.0.insert(name.into(), Arc::new(layout));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn AdvancedDataLayout> {
+        self.0.get(name).map(|layout| layout.as_ref())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+}
+
+/// The registry used by the methods, that take no registry
+pub(crate) fn builtin() -> &'static AdlRegistry {
+    static BUILTIN: OnceLock<AdlRegistry> = OnceLock::new();
+    BUILTIN.get_or_init(AdlRegistry::new)
+}
+
+/// Reads the logical value of `data` through the layout `adl`, which must present a value
+/// of the `expected` kind
+pub(crate) fn read_advanced(
+    adls: &AdlRegistry,
+    name: &str,
+    adl: &str,
+    expected: Kind,
+    data: &Ipld,
+    store: Option<&dyn BlockStore>,
+    path: &DataPath,
+) -> Result<Ipld, ValidationError> {
+    let invalid = |reason: String| ValidationError::InvalidValue {
+        path: path.clone(),
+        ty: name.to_string(),
+        reason,
+    };
+
+    let layout = adls
+        .get(adl)
+        .ok_or_else(|| invalid(format!("advanced layout {} is not registered", adl)))?;
+    if layout.kind() != expected {
+        return Err(invalid(format!(
+            "advanced layout {} is of kind {}, expected {}",
+            adl,
+            layout.kind(),
+            expected
+        )));
+    }
+
+    let logical = layout
+        .read(data, store)
+        .map_err(|err| invalid(format!("advanced layout {}: {}", adl, err)))?;
+    if Kind::of(&logical) != expected {
+        return Err(invalid(format!(
+            "advanced layout {} read a value of kind {}, expected {}",
+            adl,
+            Kind::of(&logical),
+            expected
+        )));
+    }
+    Ok(logical)
+}
+
+/// Looks up a single entry of the logical value of `data` through the layout `adl`.
+///
+/// Returns `None`, if the layout is not registered, is not of the `expected` kind, or fails.
+pub(crate) fn get_advanced(
+    adls: &AdlRegistry,
+    adl: &str,
+    expected: Kind,
+    data: &Ipld,
+    key: &str,
+    store: Option<&dyn BlockStore>,
+) -> Option<Ipld> {
+    let layout = adls.get(adl).filter(|layout| layout.kind() == expected)?;
+    layout.get(data, key, store).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use ipld_core::{cid::Cid, ipld};
+
+    use super::*;
+    use crate::{store::MemoryStore, IpldSchema, PathError};
+
+    /// Bytes split into chunks, that are stored inline or in raw blocks
+    struct ChunkedBytes;
+
+    impl AdvancedDataLayout for ChunkedBytes {
+        fn kind(&self) -> Kind {
+            Kind::Bytes
+        }
+
+        fn read(&self, substrate: &Ipld, store: Option<&dyn BlockStore>) -> Result<Ipld, AdlError> {
+            let Ipld::List(chunks) = substrate else {
+                return Err("expected a list of chunks".into());
+            };
+
+            let mut bytes = vec![];
+            for chunk in chunks {
+                match (chunk, store) {
+                    (Ipld::Bytes(chunk), _) => bytes.extend(chunk),
+                    (Ipld::Link(cid), Some(store)) => {
+                        bytes.extend(store.get(cid)?.ok_or("missing chunk")?)
+                    }
+                    (Ipld::Link(_), None) => (),
+                    _ => return Err("chunks must be bytes or links".into()),
+                }
+            }
+            Ok(Ipld::Bytes(bytes))
+        }
+    }
+
+    /// A list split into shards, which are lists themselves
+    struct ShardedList;
+
+    impl AdvancedDataLayout for ShardedList {
+        fn kind(&self) -> Kind {
+            Kind::List
+        }
+
+        fn read(&self, substrate: &Ipld, _: Option<&dyn BlockStore>) -> Result<Ipld, AdlError> {
+            match substrate {
+                Ipld::List(shards) => Ok(Ipld::List(
+                    shards
+                        .iter()
+                        .flat_map(|shard| match shard {
+                            Ipld::List(items) => items.clone(),
+                            _ => vec![],
+                        })
+                        .collect(),
+                )),
+                _ => Err("expected a list of shards".into()),
+            }
+        }
+    }
+
+    const SCHEMA: &str = "\
+type File struct {
+  content Content
+  lines Lines
+}
+
+type Content bytes representation advanced ChunkedBytes
+
+type Lines [Line] representation advanced ShardedList
+
+type Line struct {
+  text String
+}
+";
+
+    #[test]
+    fn custom_layouts() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+        assert!(schema
+            .to_string()
+            .contains("type Lines [Line] representation advanced ShardedList"));
+        assert_eq!(IpldSchema::from_dmt(&schema.to_dmt()).unwrap(), schema);
+
+        let adls = AdlRegistry::new()
+            .with_layout("ChunkedBytes", ChunkedBytes)
+            .with_layout("ShardedList", ShardedList);
+        assert_eq!(
+            adls.names().collect::<Vec<_>>(),
+            vec!["ChunkedBytes", "HashMap", "ShardedList"]
+        );
+
+        let mut store = MemoryStore::new();
+        let chunk = Cid::default();
+        store.put(chunk, b"world".to_vec()).unwrap();
+
+        let file = ipld!({
+            "content": [b"hello ".to_vec(), chunk],
+            "lines": [[{ "text": "a" }], [], [{ "text": "b" }, { "text": "c" }]],
+        });
+        assert_eq!(
+            schema.validate_with("File", &file, &adls, Some(&store)),
+            Ok(())
+        );
+
+        // The builtin registry does not know the layouts
+        assert!(matches!(
+            schema.validate("File", &file),
+            Err(ValidationError::InvalidValue { reason, .. })
+                if reason == "advanced layout ChunkedBytes is not registered"
+        ));
+
+        let invalid = ipld!({
+            "content": [],
+            "lines": [[{ "text": "a" }], [{ "text": 1 }]],
+        });
+        assert_eq!(
+            schema
+                .validate_with("File", &invalid, &adls, None)
+                .unwrap_err()
+                .to_string(),
+            "Expected string for String at lines/1/text, found int"
+        );
+
+        // Layouts must present the kind of the type
+        let swapped = AdlRegistry::empty()
+            .with_layout("ChunkedBytes", ShardedList)
+            .with_layout("ShardedList", ShardedList);
+        assert!(matches!(
+            schema.validate_with("File", &file, &swapped, None),
+            Err(ValidationError::InvalidValue { reason, .. })
+                if reason == "advanced layout ChunkedBytes is of kind list, expected bytes"
+        ));
+
+        let steps = schema
+            .resolve_path_with("File", &file, "lines/1/text", &adls, None)
+            .unwrap();
+        assert_eq!(steps[2].value.as_ref(), &ipld!("b"));
+        assert!(matches!(
+            schema.resolve_path_with("File", &file, "content/0", &adls, Some(&store)),
+            Err(PathError::NotTraversable { .. })
+        ));
+    }
+}
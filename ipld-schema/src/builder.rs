@@ -0,0 +1,923 @@
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use pest::Parser;
+use thiserror::Error;
+
+use crate::{
+    enumerate::{enum_members, EnumRepresentation, EnumType, InvalidEnum},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    parse::parse_inline_type,
+    representation::{BytesRepresentation, StringPairs},
+    structural::{StructField, StructRepresentation, StructType},
+    union::{InvalidUnion, UnionMember, UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    Doc, InlineIpldType, IpldSchema, IpldType, Rule, SchemaError, SchemaParser,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BuildError {
+    #[error("Invalid {kind} \"{value}\"")]
+    Invalid { kind: &'static str, value: String },
+    #[error("Type \"{0}\" is defined more than once")]
+    DuplicateType(String),
+    #[error("Enum \"{0}\" has no members")]
+    EmptyEnum(String),
+    #[error("Union \"{0}\" has no members")]
+    EmptyUnion(String),
+    #[error("Union member \"{0}\" has no discriminant")]
+    MissingDiscriminant(String),
+    #[error("{method} can not be applied to {target}")]
+    Misplaced {
+        method: &'static str,
+        target: String,
+    },
+    #[error(transparent)]
+    Enum(#[from] InvalidEnum),
+    #[error(transparent)]
+    Union(#[from] InvalidUnion),
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+}
+
+/// Builds a schema in code, with the same definitions the parser creates from the DSL.
+///
+/// Modifiers, like `nullable` or `representation_map`, apply to the type that was defined last.
+/// Types are given as they are written in the DSL, i.e. `[&User]`. Errors are reported by
+/// [`SchemaBuilder::build`], which also checks that all referenced types are defined.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBuilder {
+    types: Vec<(String, Definition)>,
+    error: Option<BuildError>,
+}
+
+#[derive(Debug, Clone)]
+struct Definition {
+    doc: Option<String>,
+    ty: Pending,
+}
+
+#[derive(Debug, Clone)]
+enum Pending {
+    Type(IpldType),
+    /// The tags of members can only be interpreted, once the representation is known
+    Enum {
+        members: Vec<(Option<String>, String, Option<String>)>,
+        repr: EnumRepresentation,
+    },
+    /// Whether discriminants are kinds or keys depends on the representation
+    Union {
+        members: Vec<(UnionMember, bool)>,
+        repr: UnionRepresentation,
+    },
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bool(self, name: &str) -> Self {
+        self.define(name, Ok(IpldType::Bool))
+    }
+
+    pub fn string(self, name: &str) -> Self {
+        self.define(name, Ok(IpldType::String))
+    }
+
+    pub fn bytes(self, name: &str) -> Self {
+        self.define(name, Ok(IpldType::Bytes(BytesRepresentation::Bytes)))
+    }
+
+    pub fn int(self, name: &str) -> Self {
+        self.define(name, Ok(IpldType::Int))
+    }
+
+    pub fn float(self, name: &str) -> Self {
+        self.define(name, Ok(IpldType::Float))
+    }
+
+    pub fn any(self, name: &str) -> Self {
+        self.define(name, Ok(IpldType::Any))
+    }
+
+    /// Defines a list of `item`, i.e. `[item]`
+    pub fn list(self, name: &str, item: &str) -> Self {
+        let ty = inline_type(item).map(|ty| {
+            IpldType::List(ListType {
+                ty,
+                nullable: false,
+                repr: ListRepresentation::List,
+            })
+        });
+        self.define(name, ty)
+    }
+
+    /// Defines a map from `key` to `value`, i.e. `{key:value}`
+    pub fn map(self, name: &str, key: &str, value: &str) -> Self {
+        let ty = check(Rule::type_name, "type name", key).and_then(|_| {
+            Ok(IpldType::Map(MapType {
+                key: key.to_string(),
+                value: inline_type(value)?,
+                nullable: false,
+                repr: MapRepresentation::Map,
+            }))
+        });
+        self.define(name, ty)
+    }
+
+    /// Defines a link to data of type `target`, i.e. `&target`
+    pub fn link(self, name: &str, target: &str) -> Self {
+        let ty =
+            check(Rule::type_name, "type name", target).map(|_| IpldType::Link(target.to_string()));
+        self.define(name, ty)
+    }
+
+    /// Defines a copy of the type `from`, i.e. `= from`
+    pub fn copy(self, name: &str, from: &str) -> Self {
+        let ty =
+            check(Rule::type_name, "type name", from).map(|_| IpldType::Copy(from.to_string()));
+        self.define(name, ty)
+    }
+
+    /// Defines a unit type, that is represented as `repr`, i.e. `emptymap`
+    pub fn unit(self, name: &str, repr: &str) -> Self {
+        let ty = UnitRepresentation::from_name(repr)
+            .map(IpldType::Unit)
+            .ok_or_else(|| BuildError::Invalid {
+                kind: "unit representation",
+                value: repr.to_string(),
+            });
+        self.define(name, ty)
+    }
+
+    pub fn structure(
+        self,
+        name: &str,
+        fields: impl FnOnce(StructBuilder) -> StructBuilder,
+    ) -> Self {
+        let builder = fields(StructBuilder::default());
+        let ty = match builder.error {
+            Some(err) => Err(err),
+            None => Ok(IpldType::Struct(StructType {
+                fields: builder.fields,
+                repr: StructRepresentation::Map,
+            })),
+        };
+        self.define(name, ty)
+    }
+
+    pub fn enumeration(self, name: &str, members: impl FnOnce(EnumBuilder) -> EnumBuilder) -> Self {
+        let builder = members(EnumBuilder::default());
+        let ty = match builder.error {
+            Some(err) => Err(err),
+            None => Ok(Pending::Enum {
+                members: builder.members,
+                repr: EnumRepresentation::String,
+            }),
+        };
+        self.define_pending(name, ty)
+    }
+
+    /// Defines a keyed union, the representation can be changed afterwards
+    pub fn union(self, name: &str, members: impl FnOnce(UnionBuilder) -> UnionBuilder) -> Self {
+        let builder = members(UnionBuilder::default());
+        let ty = match builder.error {
+            Some(err) => Err(err),
+            None => Ok(Pending::Union {
+                members: builder.members,
+                repr: UnionRepresentation::Keyed,
+            }),
+        };
+        self.define_pending(name, ty)
+    }
+
+    /// Sets the documentation of the last type
+    pub fn doc(mut self, doc: &str) -> Self {
+        match self.types.last_mut() {
+            Some((_, def)) => def.doc = Some(doc.to_string()),
+            None => self.misplaced("doc", "no type".to_string()),
+        }
+        self
+    }
+
+    /// Allows null values in the last list or map type
+    pub fn nullable(self) -> Self {
+        self.modify("nullable", |ty| {
+            match ty {
+                IpldType::List(list) => list.nullable = true,
+                IpldType::Map(map) => map.nullable = true,
+                _ => return false,
+            }
+            true
+        })
+    }
+
+    pub fn representation_map(self) -> Self {
+        self.modify("representation_map", |ty| {
+            match ty {
+                IpldType::Struct(stru) => stru.repr = StructRepresentation::Map,
+                IpldType::Map(map) => map.repr = MapRepresentation::Map,
+                _ => return false,
+            }
+            true
+        })
+    }
+
+    pub fn representation_listpairs(self) -> Self {
+        self.modify("representation_listpairs", |ty| {
+            match ty {
+                IpldType::Struct(stru) => stru.repr = StructRepresentation::ListPairs,
+                IpldType::Map(map) => map.repr = MapRepresentation::ListPairs,
+                _ => return false,
+            }
+            true
+        })
+    }
+
+    /// Represents the last struct as a list of its field values
+    pub fn representation_tuple(self) -> Self {
+        self.modify("representation_tuple", |ty| {
+            match ty {
+                IpldType::Struct(stru) => stru.repr = StructRepresentation::Tuple,
+                _ => return false,
+            }
+            true
+        })
+    }
+
+    pub fn representation_stringpairs(mut self, inner_delim: &str, entry_delim: &str) -> Self {
+        let delims = check(Rule::stringpairs_repr_value, "delimiter", inner_delim)
+            .and_then(|_| check(Rule::stringpairs_repr_value, "delimiter", entry_delim));
+        if let Err(err) = delims {
+            self.fail(err);
+            return self;
+        }
+
+        let pairs = StringPairs {
+            inner_delim: inner_delim.to_string(),
+            entry_delim: entry_delim.to_string(),
+        };
+        self.modify("representation_stringpairs", |ty| {
+            match ty {
+                IpldType::Struct(stru) => stru.repr = StructRepresentation::StringPairs(pairs),
+                IpldType::Map(map) => map.repr = MapRepresentation::StringPairs(pairs),
+                _ => return false,
+            }
+            true
+        })
+    }
+
+    /// Reads the last map, list or bytes type through the advanced data layout `layout`
+    pub fn representation_advanced(mut self, layout: &str) -> Self {
+        if let Err(err) = check(Rule::type_name, "layout name", layout) {
+            self.fail(err);
+            return self;
+        }
+
+        let layout = layout.to_string();
+        self.modify("representation_advanced", |ty| {
+            match ty {
+                IpldType::Bytes(repr) => *repr = BytesRepresentation::Advanced(layout),
+                IpldType::List(list) => list.repr = ListRepresentation::Advanced(layout),
+                IpldType::Map(map) => map.repr = MapRepresentation::Advanced(layout),
+                _ => return false,
+            }
+            true
+        })
+    }
+
+    pub fn representation_string(self) -> Self {
+        self.enum_representation("representation_string", EnumRepresentation::String)
+    }
+
+    /// Represents the last enum as integers, every member needs an integer tag
+    pub fn representation_int(self) -> Self {
+        self.enum_representation("representation_int", EnumRepresentation::Int)
+    }
+
+    pub fn representation_keyed(self) -> Self {
+        self.union_representation("representation_keyed", Ok(UnionRepresentation::Keyed))
+    }
+
+    /// Selects the member of the last union by the kind of the data, every member needs a
+    /// kind as discriminant
+    pub fn representation_kinded(self) -> Self {
+        self.union_representation("representation_kinded", Ok(UnionRepresentation::Kinded))
+    }
+
+    /// Stores the discriminant of the last union under `discriminant_key` next to the fields
+    /// of the member
+    pub fn representation_inline(self, discriminant_key: &str) -> Self {
+        let repr = check(Rule::union_key_value, "key", discriminant_key).map(|_| {
+            UnionRepresentation::Inline {
+                discriminant_key: discriminant_key.to_string(),
+            }
+        });
+        self.union_representation("representation_inline", repr)
+    }
+
+    /// Stores the discriminant of the last union under `discriminant_key` and the member
+    /// under `content_key`
+    pub fn representation_envelope(self, discriminant_key: &str, content_key: &str) -> Self {
+        let repr = check(Rule::union_key_value, "key", discriminant_key)
+            .and_then(|_| check(Rule::union_key_value, "key", content_key))
+            .map(|_| UnionRepresentation::Envelope {
+                discriminant_key: discriminant_key.to_string(),
+                content_key: content_key.to_string(),
+            });
+        self.union_representation("representation_envelope", repr)
+    }
+
+    /// Checks the definitions and creates the schema
+    pub fn build(self) -> Result<IpldSchema, BuildError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        let mut types = BTreeMap::new();
+        for (name, def) in self.types {
+            let ty = match def.ty {
+                Pending::Type(ty) => ty,
+                Pending::Enum { members, .. } if members.is_empty() => {
+                    return Err(BuildError::EmptyEnum(name))
+                }
+                Pending::Enum { members, repr } => IpldType::Enum(EnumType {
+                    members: enum_members(members, &repr)?,
+                    repr,
+                }),
+                Pending::Union { members, .. } if members.is_empty() => {
+                    return Err(BuildError::EmptyUnion(name))
+                }
+                Pending::Union { members, repr } => IpldType::Union(UnionType {
+                    members: union_members(members, &repr)?,
+                    repr,
+                }),
+            };
+
+            match types.entry(name) {
+                Entry::Occupied(entry) => {
+                    return Err(BuildError::DuplicateType(entry.key().clone()))
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(Doc { doc: def.doc, ty });
+                }
+            }
+        }
+
+        let schema = IpldSchema(types);
+        match schema.check().into_iter().next() {
+            Some(err) => Err(err.into()),
+            None => Ok(schema),
+        }
+    }
+
+    fn define(self, name: &str, ty: Result<IpldType, BuildError>) -> Self {
+        self.define_pending(name, ty.map(Pending::Type))
+    }
+
+    fn define_pending(mut self, name: &str, ty: Result<Pending, BuildError>) -> Self {
+        match check(Rule::type_name, "type name", name).and(ty) {
+            Ok(ty) => self
+                .types
+                .push((name.to_string(), Definition { doc: None, ty })),
+            Err(err) => self.fail(err),
+        }
+        self
+    }
+
+    /// Changes the last type, `modify` returns false if the type can not be changed that way
+    fn modify(mut self, method: &'static str, modify: impl FnOnce(&mut IpldType) -> bool) -> Self {
+        let target = match self.types.last_mut() {
+            Some((name, def)) => match &mut def.ty {
+                Pending::Type(ty) => match modify(ty) {
+                    true => return self,
+                    false => format!("{} type \"{}\"", ty.type_kind(), name),
+                },
+                Pending::Enum { .. } => format!("enum type \"{}\"", name),
+                Pending::Union { .. } => format!("union type \"{}\"", name),
+            },
+            None => "no type".to_string(),
+        };
+        self.misplaced(method, target);
+        self
+    }
+
+    fn enum_representation(mut self, method: &'static str, repr: EnumRepresentation) -> Self {
+        let target = match self.types.last_mut() {
+            Some((name, def)) => match &mut def.ty {
+                Pending::Enum { repr: current, .. } => {
+                    *current = repr;
+                    return self;
+                }
+                Pending::Type(ty) => format!("{} type \"{}\"", ty.type_kind(), name),
+                Pending::Union { .. } => format!("union type \"{}\"", name),
+            },
+            None => "no type".to_string(),
+        };
+        self.misplaced(method, target);
+        self
+    }
+
+    fn union_representation(
+        mut self,
+        method: &'static str,
+        repr: Result<UnionRepresentation, BuildError>,
+    ) -> Self {
+        let repr = match repr {
+            Ok(repr) => repr,
+            Err(err) => {
+                self.fail(err);
+                return self;
+            }
+        };
+
+        let target = match self.types.last_mut() {
+            Some((name, def)) => match &mut def.ty {
+                Pending::Union { repr: current, .. } => {
+                    *current = repr;
+                    return self;
+                }
+                Pending::Type(ty) => format!("{} type \"{}\"", ty.type_kind(), name),
+                Pending::Enum { .. } => format!("enum type \"{}\"", name),
+            },
+            None => "no type".to_string(),
+        };
+        self.misplaced(method, target);
+        self
+    }
+
+    fn misplaced(&mut self, method: &'static str, target: String) {
+        self.fail(BuildError::Misplaced { method, target })
+    }
+
+    /// Keeps the first error, which is returned by `build`
+    fn fail(&mut self, err: BuildError) {
+        self.error.get_or_insert(err);
+    }
+}
+
+/// Adds the fields of a struct, modifiers apply to the field that was added last
+#[derive(Debug, Clone, Default)]
+pub struct StructBuilder {
+    fields: Vec<StructField>,
+    error: Option<BuildError>,
+}
+
+impl StructBuilder {
+    pub fn field(mut self, name: &str, ty: &str) -> Self {
+        let field = check(Rule::struct_field_name, "field name", name).and_then(|_| {
+            Ok(StructField {
+                doc: None,
+                key: name.to_string(),
+                value: inline_type(ty)?,
+                optional: false,
+                nullable: false,
+                rename: None,
+                implicit: None,
+            })
+        });
+
+        match field {
+            Ok(field) => self.fields.push(field),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    pub fn optional(self) -> Self {
+        self.modify("optional", |field| {
+            field.optional = true;
+            Ok(())
+        })
+    }
+
+    pub fn nullable(self) -> Self {
+        self.modify("nullable", |field| {
+            field.nullable = true;
+            Ok(())
+        })
+    }
+
+    /// Stores the field under `key` in the data
+    pub fn rename(self, key: &str) -> Self {
+        let key = check(Rule::struct_field_name, "field name", key).map(|_| key.to_string());
+        self.modify("rename", |field| {
+            field.rename = Some(key?);
+            Ok(())
+        })
+    }
+
+    /// The value of the field, if it is missing in the data
+    pub fn implicit(self, value: &str) -> Self {
+        let value =
+            check(Rule::struct_field_name, "implicit value", value).map(|_| value.to_string());
+        self.modify("implicit", |field| {
+            field.implicit = Some(value?);
+            Ok(())
+        })
+    }
+
+    pub fn doc(self, doc: &str) -> Self {
+        self.modify("doc", |field| {
+            field.doc = Some(doc.to_string());
+            Ok(())
+        })
+    }
+
+    fn modify(
+        mut self,
+        method: &'static str,
+        modify: impl FnOnce(&mut StructField) -> Result<(), BuildError>,
+    ) -> Self {
+        let result = match self.fields.last_mut() {
+            Some(field) => modify(field),
+            None => Err(BuildError::Misplaced {
+                method,
+                target: "no field".to_string(),
+            }),
+        };
+        if let Err(err) = result {
+            self.error.get_or_insert(err);
+        }
+        self
+    }
+}
+
+/// Adds the members of an enum, modifiers apply to the member that was added last
+#[derive(Debug, Clone, Default)]
+pub struct EnumBuilder {
+    members: Vec<(Option<String>, String, Option<String>)>,
+    error: Option<BuildError>,
+}
+
+impl EnumBuilder {
+    pub fn member(mut self, name: &str) -> Self {
+        match check(Rule::type_name, "member name", name) {
+            Ok(()) => self.members.push((None, name.to_string(), None)),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    /// Represents the member by `tag` instead of its name, which must be an integer for
+    /// `representation int`
+    pub fn tag(self, tag: &str) -> Self {
+        let tag = check(Rule::enum_field_repr_value, "member tag", tag).map(|_| tag.to_string());
+        self.modify("tag", |(_, _, current)| {
+            *current = Some(tag?);
+            Ok(())
+        })
+    }
+
+    pub fn doc(self, doc: &str) -> Self {
+        self.modify("doc", |(current, _, _)| {
+            *current = Some(doc.to_string());
+            Ok(())
+        })
+    }
+
+    fn modify(
+        mut self,
+        method: &'static str,
+        modify: impl FnOnce(&mut (Option<String>, String, Option<String>)) -> Result<(), BuildError>,
+    ) -> Self {
+        let result = match self.members.last_mut() {
+            Some(member) => modify(member),
+            None => Err(BuildError::Misplaced {
+                method,
+                target: "no member".to_string(),
+            }),
+        };
+        if let Err(err) = result {
+            self.error.get_or_insert(err);
+        }
+        self
+    }
+}
+
+/// Adds the members of a union, modifiers apply to the member that was added last
+#[derive(Debug, Clone, Default)]
+pub struct UnionBuilder {
+    /// The members and whether they have a discriminant
+    members: Vec<(UnionMember, bool)>,
+    error: Option<BuildError>,
+}
+
+impl UnionBuilder {
+    /// Adds a member of type `ty`, which is a type name or a link, i.e. `&User`
+    pub fn member(mut self, ty: &str) -> Self {
+        let member = check(Rule::union_member_type, "member type", ty).map(|_| UnionMember {
+            doc: None,
+            ty: match ty.strip_prefix('&') {
+                Some(target) => InlineIpldType::Link(target.trim_start().to_string()),
+                None => InlineIpldType::Name(ty.to_string()),
+            },
+            discriminant: String::new(),
+        });
+
+        match member {
+            Ok(member) => self.members.push((member, false)),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    /// Selects the member by `discriminant`, which is a kind like `int` for `representation
+    /// kinded` and the key or value of the discriminant otherwise
+    pub fn discriminant(self, discriminant: &str) -> Self {
+        let discriminant = check(Rule::union_key_value, "discriminant", discriminant)
+            .map(|_| discriminant.to_string());
+        self.modify("discriminant", |(member, is_set)| {
+            member.discriminant = discriminant?;
+            *is_set = true;
+            Ok(())
+        })
+    }
+
+    pub fn doc(self, doc: &str) -> Self {
+        self.modify("doc", |(member, _)| {
+            member.doc = Some(doc.to_string());
+            Ok(())
+        })
+    }
+
+    fn modify(
+        mut self,
+        method: &'static str,
+        modify: impl FnOnce(&mut (UnionMember, bool)) -> Result<(), BuildError>,
+    ) -> Self {
+        let result = match self.members.last_mut() {
+            Some(member) => modify(member),
+            None => Err(BuildError::Misplaced {
+                method,
+                target: "no member".to_string(),
+            }),
+        };
+        if let Err(err) = result {
+            self.error.get_or_insert(err);
+        }
+        self
+    }
+}
+
+/// Checks the discriminants of union members against the representation of the union
+fn union_members(
+    members: Vec<(UnionMember, bool)>,
+    repr: &UnionRepresentation,
+) -> Result<Vec<UnionMember>, BuildError> {
+    members
+        .into_iter()
+        .map(|(member, is_set)| {
+            if !is_set {
+                return Err(BuildError::MissingDiscriminant(member.name().to_string()));
+            }
+            if *repr == UnionRepresentation::Kinded && member.kind().is_none() {
+                return Err(InvalidUnion::ExpectedKind(member.name().to_string()).into());
+            }
+            Ok(member)
+        })
+        .collect()
+}
+
+/// Checks that `value` matches `rule` of the grammar completely
+fn check(rule: Rule, kind: &'static str, value: &str) -> Result<(), BuildError> {
+    let matches = SchemaParser::parse(rule, value)
+        .is_ok_and(|mut pairs| pairs.next().is_some_and(|pair| pair.as_str() == value));
+
+    match matches {
+        true => Ok(()),
+        false => Err(BuildError::Invalid {
+            kind,
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Parses a type as it is written in the DSL, i.e. `[&User]`
+fn inline_type(ty: &str) -> Result<InlineIpldType, BuildError> {
+    let invalid = || BuildError::Invalid {
+        kind: "type",
+        value: ty.to_string(),
+    };
+
+    let mut pairs = SchemaParser::parse(Rule::inline_type_def, ty).map_err(|_| invalid())?;
+    match pairs.next() {
+        Some(pair) if pair.as_str() == ty => {
+            parse_inline_type(pair.into_inner()).map_err(|_| invalid())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_parser() {
+        let built = SchemaBuilder::new()
+            .structure("Foo", |s| s.field("x", "Int").optional().rename("a"))
+            .representation_map()
+            .build()
+            .unwrap();
+        let parsed = IpldSchema::parse(
+            "type Foo struct {\n  x optional Int (rename \"a\")\n} representation map",
+        )
+        .unwrap();
+        assert_eq!(built, parsed);
+
+        let built = SchemaBuilder::new()
+            .structure("User", |s| {
+                s.field("name", "String")
+                    .rename("n")
+                    .field("friends", "[&User]")
+                    .nullable()
+                    .doc("The friends of the user")
+                    .field("status", "Status")
+                    .implicit("Active")
+            })
+            .doc("A user")
+            .representation_listpairs()
+            .enumeration("Status", |e| {
+                e.member("Active").tag("1").member("Inactive").tag("2")
+            })
+            .representation_int()
+            .map("Labels", "String", "String")
+            .representation_stringpairs("=", ",")
+            .list("Names", "String")
+            .nullable()
+            .bytes("Blob")
+            .representation_advanced("ChunkedBytes")
+            .link("Ref", "User")
+            .copy("Id", "Int")
+            .bool("Flag")
+            .unit("Ack", "emptymap")
+            .build()
+            .unwrap();
+        let parsed = IpldSchema::parse(
+            "\
+# A user
+type User struct {
+  name String (rename \"n\")
+  # The friends of the user
+  friends nullable [&User]
+  status Status (implicit \"Active\")
+} representation listpairs
+
+type Status enum {
+  | Active (\"1\")
+  | Inactive (\"2\")
+} representation int
+
+type Labels {String:String} representation stringpairs {
+  innerDelim \"=\"
+  entryDelim \",\"
+}
+
+type Names [nullable String]
+
+type Blob bytes representation advanced ChunkedBytes
+
+type Ref &User
+
+type Id = Int
+
+type Flag bool
+
+type Ack unit representation emptymap
+",
+        )
+        .unwrap();
+        assert_eq!(built, parsed);
+
+        let built = SchemaBuilder::new()
+            .union("Shape", |u| {
+                u.member("Circle")
+                    .discriminant("circle")
+                    .doc("A circle")
+                    .member("&Square")
+                    .discriminant("square")
+            })
+            .union("Value", |u| {
+                u.member("Int")
+                    .discriminant("int")
+                    .member("Shape")
+                    .discriminant("map")
+                    .member("Point")
+                    .discriminant("list")
+            })
+            .representation_kinded()
+            .union("Tagged", |u| u.member("Circle").discriminant("c"))
+            .representation_inline("tag")
+            .union("Wrapped", |u| {
+                u.member("Circle")
+                    .discriminant("c")
+                    .member("Square")
+                    .discriminant("s")
+            })
+            .representation_envelope("tag", "content")
+            .structure("Circle", |s| s.field("radius", "Int"))
+            .structure("Square", |s| s.field("side", "Int"))
+            .structure("Point", |s| {
+                s.field("x", "Int")
+                    .field("y", "Int")
+                    .field("label", "String")
+                    .optional()
+            })
+            .representation_tuple()
+            .build()
+            .unwrap();
+        let parsed = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+        assert_eq!(built, parsed);
+    }
+
+    #[test]
+    fn errors() {
+        let build = |builder: SchemaBuilder| builder.build().unwrap_err().to_string();
+
+        assert_eq!(
+            build(SchemaBuilder::new().int("foo")),
+            "Invalid type name \"foo\""
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().structure("Foo", |s| s.field("x", "[Int"))),
+            "Invalid type \"[Int\""
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().structure("Foo", |s| s.optional())),
+            "optional can not be applied to no field"
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().int("Foo").representation_listpairs()),
+            "representation_listpairs can not be applied to int type \"Foo\""
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().int("Foo").string("Foo")),
+            "Type \"Foo\" is defined more than once"
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().list("Foo", "Bar")),
+            "Type \"Bar\" referenced by \"Foo\" is not defined"
+        );
+        assert_eq!(
+            build(
+                SchemaBuilder::new()
+                    .enumeration("Foo", |e| e.member("Bar"))
+                    .representation_int()
+            ),
+            InvalidEnum::InvalidMemberTag.to_string()
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().enumeration("Foo", |e| e)),
+            "Enum \"Foo\" has no members"
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().unit("Foo", "false ")),
+            "Invalid unit representation \"false \""
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().copy("Foo", "Bar").copy("Bar", "Foo")),
+            "Type \"Bar\" is a copy of itself"
+        );
+        assert_eq!(
+            build(
+                SchemaBuilder::new()
+                    .union("Foo", |u| u.member("Int").discriminant("int"))
+                    .int("Bar")
+                    .representation_kinded()
+            ),
+            "representation_kinded can not be applied to int type \"Bar\""
+        );
+        assert_eq!(
+            build(
+                SchemaBuilder::new()
+                    .union("Foo", |u| u.member("Int").discriminant("number"))
+                    .representation_kinded()
+            ),
+            InvalidUnion::ExpectedKind("Int".to_string()).to_string()
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().union("Foo", |u| u.member("&Int"))),
+            "Union member \"Int\" has no discriminant"
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().union("Foo", |u| u.discriminant("int"))),
+            "discriminant can not be applied to no member"
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().union("Foo", |u| u.member("[Int]"))),
+            "Invalid member type \"[Int]\""
+        );
+        assert_eq!(
+            build(SchemaBuilder::new().union("Foo", |u| u)),
+            "Union \"Foo\" has no members"
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+use thiserror::Error;
+
+use crate::{
+    adl::{builtin, AdlRegistry},
+    dag::DagReport,
+    store::{BlockStore, MemoryStore, StoreError},
+    IpldSchema, ValidationError,
+};
+
+#[derive(Debug, Error)]
+pub enum CarError {
+    #[error("Failed to read CAR file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid CAR header: {0}")]
+    Header(String),
+    #[error("Invalid CAR section at byte {offset}: {reason}")]
+    Section { offset: usize, reason: String },
+}
+
+/// A CARv1 archive, i.e. the CIDs of the roots of a DAG and its blocks.
+///
+/// The blocks are not checked against the digests of their CIDs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Car {
+    pub roots: Vec<Cid>,
+    pub blocks: MemoryStore,
+}
+
+impl Car {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CarError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    pub fn read(reader: impl Read) -> Result<Self, CarError> {
+        let mut reader = Reader { reader, offset: 0 };
+
+        let header = match reader.section()? {
+            Some(header) => header,
+            None => return Err(CarError::Header("the file is empty".to_string())),
+        };
+        let roots = parse_header(&header)?;
+
+        let mut blocks = MemoryStore::new();
+        loop {
+            let offset = reader.offset;
+            let Some(section) = reader.section()? else {
+                break;
+            };
+            let section_error = |reason: String| CarError::Section { offset, reason };
+
+            let mut block = section.as_slice();
+            let cid = Cid::read_bytes(&mut block)
+                .map_err(|err| section_error(format!("invalid CID: {}", err)))?;
+            blocks
+                .put(cid, block.to_vec())
+                .map_err(|err| section_error(err.to_string()))?;
+        }
+
+        Ok(Self { roots, blocks })
+    }
+}
+
+/// Reads the roots from a CARv1 header, i.e. `{"version": 1, "roots": [cid, ...]}`
+fn parse_header(header: &[u8]) -> Result<Vec<Cid>, CarError> {
+    let header_error = |reason: &str| CarError::Header(reason.to_string());
+
+    let header = serde_ipld_dagcbor::from_slice::<Ipld>(header)
+        .map_err(|err| CarError::Header(err.to_string()))?;
+    let Ipld::Map(header) = header else {
+        return Err(header_error("expected a map"));
+    };
+
+    match header.get("version") {
+        Some(Ipld::Integer(1)) => (),
+        Some(Ipld::Integer(version)) => {
+            return Err(CarError::Header(format!(
+                "version {} is not supported",
+                version
+            )))
+        }
+        _ => return Err(header_error("expected an integer version")),
+    }
+
+    let Some(Ipld::List(roots)) = header.get("roots") else {
+        return Err(header_error("expected a list of roots"));
+    };
+    let roots = roots
+        .iter()
+        .map(|root| match root {
+            Ipld::Link(cid) => Ok(*cid),
+            _ => Err(header_error("roots must be links")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if roots.is_empty() {
+        Err(header_error("there are no roots"))
+    } else {
+        Ok(roots)
+    }
+}
+
+/// Reads the length prefixed sections of a CAR file
+struct Reader<R> {
+    reader: R,
+    /// The number of bytes consumed so far
+    offset: usize,
+}
+
+impl<R: Read> Reader<R> {
+    /// Reads the next section, or `None` at the end of the input
+    fn section(&mut self) -> Result<Option<Vec<u8>>, CarError> {
+        let offset = self.offset;
+        let Some(len) = self.varint()? else {
+            return Ok(None);
+        };
+        let len = usize::try_from(len).map_err(|_| CarError::Section {
+            offset,
+            reason: format!("length {} is too large", len),
+        })?;
+
+        let mut section = vec![];
+        (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut section)?;
+        self.offset += section.len();
+
+        if section.len() == len {
+            Ok(Some(section))
+        } else {
+            Err(CarError::Section {
+                offset,
+                reason: "unexpected end of input".to_string(),
+            })
+        }
+    }
+
+    /// Reads an unsigned LEB128 varint, or `None` at the end of the input
+    fn varint(&mut self) -> Result<Option<u64>, CarError> {
+        let offset = self.offset;
+        let mut value = 0u64;
+
+        for idx in 0..9 {
+            let mut byte = [0];
+            if self.reader.read(&mut byte)? == 0 {
+                return if idx == 0 {
+                    Ok(None)
+                } else {
+                    Err(CarError::Section {
+                        offset,
+                        reason: "unexpected end of input".to_string(),
+                    })
+                };
+            }
+            self.offset += 1;
+
+            value |= ((byte[0] & 0x7f) as u64) << (idx * 7);
+            if byte[0] & 0x80 == 0 {
+                return Ok(Some(value));
+            }
+        }
+
+        Err(CarError::Section {
+            offset,
+            reason: "varint is longer than nine bytes".to_string(),
+        })
+    }
+}
+
+impl IpldSchema {
+    /// Validates the DAGs of all roots of `car` as type `root`, see [`IpldSchema::dag_validator`]
+    pub fn validate_car(&self, root: &str, car: &Car) -> Result<DagReport, ValidationError> {
+        self.validate_car_with(root, car, builtin(), None)
+    }
+
+    /// Like [`IpldSchema::validate_car`], but reads data in advanced layouts through the layouts
+    /// in `adls`. Blocks, that are not in `car`, are loaded from `store`.
+    pub fn validate_car_with(
+        &self,
+        root: &str,
+        car: &Car,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<DagReport, ValidationError> {
+        let blocks = Fallback {
+            blocks: &car.blocks,
+            store,
+        };
+        self.dag_validator(&blocks)
+            .with_adls(adls)
+            .validate_roots(root, car.roots.iter().copied())
+    }
+}
+
+/// The blocks of a CAR, and a store for the blocks the CAR does not contain
+struct Fallback<'a> {
+    blocks: &'a MemoryStore,
+    store: Option<&'a dyn BlockStore>,
+}
+
+impl BlockStore for Fallback<'_> {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, StoreError> {
+        match (self.blocks.get(cid)?, self.store) {
+            (None, Some(store)) => store.get(cid),
+            (block, _) => Ok(block),
+        }
+    }
+
+    fn put(&mut self, cid: Cid, _block: Vec<u8>) -> Result<(), StoreError> {
+        Err(StoreError::Io {
+            cid,
+            source: io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the blocks of a CAR are read-only",
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ipld_core::{cid::multihash::Multihash, ipld};
+
+    use super::*;
+    use crate::dag::{BlockError, DAG_CBOR};
+
+    fn varint(mut value: usize, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn encode(roots: &[Cid], blocks: &[(Cid, Ipld)]) -> Vec<u8> {
+        let mut out = vec![];
+        let roots = roots.iter().map(Ipld::from).collect::<Vec<_>>();
+        let header = serde_ipld_dagcbor::to_vec(&ipld!({ "version": 1, "roots": roots })).unwrap();
+        varint(header.len(), &mut out);
+        out.extend(header);
+
+        for (cid, data) in blocks {
+            let mut section = cid.to_bytes();
+            section.extend(serde_ipld_dagcbor::to_vec(data).unwrap());
+            varint(section.len(), &mut out);
+            out.extend(section);
+        }
+        out
+    }
+
+    fn cid(n: u8) -> Cid {
+        Cid::new_v1(DAG_CBOR, Multihash::wrap(0x12, &[n; 32]).unwrap())
+    }
+
+    #[test]
+    fn validate_car() {
+        let schema = IpldSchema::parse(
+            "\
+type Post struct {
+  text String
+  replies [&Post]
+}
+",
+        )
+        .unwrap();
+
+        // A long text to need a multi-byte varint
+        let text = "x".repeat(200);
+        let bytes = encode(
+            &[cid(0)],
+            &[
+                (cid(0), ipld!({ "text": text, "replies": [cid(1), cid(2)] })),
+                (cid(1), ipld!({ "text": "reply", "replies": [] })),
+            ],
+        );
+        let car = Car::read(bytes.as_slice()).unwrap();
+        assert_eq!(car.roots, vec![cid(0)]);
+        assert_eq!(car.blocks.len(), 2);
+
+        let report = schema.validate_car("Post", &car).unwrap();
+        assert_eq!(report.blocks.len(), 3);
+        assert_eq!(
+            report.errors().map(|block| block.cid).collect::<Vec<_>>(),
+            vec![cid(2)]
+        );
+        assert_eq!(report.blocks[2].result, Err(BlockError::Missing));
+
+        // Blocks missing from the CAR are loaded from the store
+        let mut store = MemoryStore::new();
+        store
+            .put(
+                cid(2),
+                serde_ipld_dagcbor::to_vec(&ipld!({ "text": "other", "replies": [] })).unwrap(),
+            )
+            .unwrap();
+        let report = schema
+            .validate_car_with("Post", &car, crate::adl::builtin(), Some(&store))
+            .unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.blocks.len(), 3);
+
+        let mut truncated = bytes.clone();
+        truncated.pop();
+        assert!(matches!(
+            Car::read(truncated.as_slice()),
+            Err(CarError::Section { .. })
+        ));
+
+        // Blocks shared by the roots are validated once
+        let shared = encode(
+            &[cid(0), cid(1)],
+            &[
+                (cid(0), ipld!({ "text": "post", "replies": [cid(1)] })),
+                (cid(1), ipld!({ "text": "reply", "replies": [] })),
+            ],
+        );
+        let report = schema
+            .validate_car("Post", &Car::read(shared.as_slice()).unwrap())
+            .unwrap();
+        assert_eq!(
+            report
+                .blocks
+                .iter()
+                .map(|block| block.cid)
+                .collect::<Vec<_>>(),
+            vec![cid(0), cid(1)]
+        );
+
+        let no_roots = encode(&[], &[]);
+        assert_eq!(
+            Car::read(no_roots.as_slice()).unwrap_err().to_string(),
+            "Invalid CAR header: there are no roots"
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    io::{self, Read},
+    ops::Range,
+};
+
+use ipld_core::cid::Cid;
+use thiserror::Error;
+
+use crate::{
+    adl::{builtin, AdlRegistry},
+    store::BlockStore,
+    stream::{ReadError, Seq, StreamError, Token, TokenReader},
+    DataPath, IpldSchema, ValidationError,
+};
+
+/// The CBOR tag of CIDs
+const CID_TAG: u64 = 42;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CborValidationError {
+    /// The data is well-formed, but does not match the schema
+    #[error("{error} (byte {offset})")]
+    Invalid {
+        offset: usize,
+        error: ValidationError,
+    },
+    /// The data is not valid DAG-CBOR
+    #[error("Malformed DAG-CBOR at {path} (byte {offset}): {reason}")]
+    Malformed {
+        offset: usize,
+        path: DataPath,
+        reason: String,
+    },
+}
+
+impl CborValidationError {
+    /// The offset of the value that caused the error in the input
+    pub fn offset(&self) -> usize {
+        match self {
+            CborValidationError::Invalid { offset, .. }
+            | CborValidationError::Malformed { offset, .. } => *offset,
+        }
+    }
+}
+
+impl From<StreamError> for CborValidationError {
+    fn from(err: StreamError) -> Self {
+        match err {
+            StreamError::Invalid { span, error } => CborValidationError::Invalid {
+                offset: span.start,
+                error,
+            },
+            StreamError::Malformed { span, path, reason } => CborValidationError::Malformed {
+                offset: span.start,
+                path,
+                reason,
+            },
+        }
+    }
+}
+
+impl IpldSchema {
+    /// Checks, whether the DAG-CBOR encoded `data` is a valid representation of the type `root`.
+    ///
+    /// The data is validated while it is decoded, without building an [`Ipld`](ipld_core::ipld::Ipld)
+    /// tree, so only strings, the keys of structs and values in advanced layouts, which are
+    /// validated through the layout, are held in memory. `data` is read in small pieces, so
+    /// it should be buffered. Like [`IpldSchema::validate`], the validation stops at the
+    /// first error. Typed values nested in more than 128 lists and maps are rejected as
+    /// malformed.
+    pub fn validate_dag_cbor(
+        &self,
+        root: &str,
+        data: impl Read,
+    ) -> Result<(), CborValidationError> {
+        self.validate_dag_cbor_with(root, data, builtin(), None)
+    }
+
+    /// Like [`IpldSchema::validate_dag_cbor`], but reads data in advanced layouts through the
+    /// layouts in `adls`, which may load blocks from `store`
+    pub fn validate_dag_cbor_with(
+        &self,
+        root: &str,
+        data: impl Read,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<(), CborValidationError> {
+        Ok(self.validate_stream(root, &mut Decoder::new(data), adls, store)?)
+    }
+}
+
+struct Decoder<R> {
+    reader: R,
+    /// The number of bytes consumed so far
+    offset: usize,
+    /// The offset of the last data item that was started
+    item: usize,
+    peeked: Option<u8>,
+    keep_bytes: bool,
+}
+
+impl<R: Read> Decoder<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            offset: 0,
+            item: 0,
+            peeked: None,
+            keep_bytes: false,
+        }
+    }
+
+    fn error(&self, reason: impl ToString) -> ReadError {
+        ReadError {
+            span: self.item..self.offset,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, ReadError> {
+        if self.peeked.is_none() {
+            let mut byte = [0];
+            match self.reader.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => self.peeked = Some(byte[0]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => return self.peek(),
+                Err(err) => return Err(self.error(err)),
+            }
+        }
+        Ok(self.peeked)
+    }
+
+    fn byte(&mut self) -> Result<u8, ReadError> {
+        let byte = self
+            .peek()?
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        self.peeked = None;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: u64) -> Result<Vec<u8>, ReadError> {
+        let mut bytes = vec![];
+        if len == 0 {
+            return Ok(bytes);
+        }
+        if let Some(byte) = self.peeked.take() {
+            bytes.push(byte);
+        }
+        let rest = len - bytes.len() as u64;
+
+        let result = (&mut self.reader).take(rest).read_to_end(&mut bytes);
+        self.offset += bytes.len();
+        result.map_err(|err| self.error(err))?;
+
+        if bytes.len() as u64 == len {
+            Ok(bytes)
+        } else {
+            Err(self.error("unexpected end of input"))
+        }
+    }
+
+    fn skip(&mut self, len: u64) -> Result<(), ReadError> {
+        let mut skipped = 0;
+        if len > 0 && self.peeked.take().is_some() {
+            skipped += 1;
+        }
+
+        let result = io::copy(&mut (&mut self.reader).take(len - skipped), &mut io::sink());
+        skipped += result.map_err(|err| self.error(err))?;
+        self.offset += skipped as usize;
+
+        if skipped == len {
+            Ok(())
+        } else {
+            Err(self.error("unexpected end of input"))
+        }
+    }
+
+    /// Reads the major type, additional information and argument of the next data item
+    fn header(&mut self) -> Result<(u8, u8, u64), ReadError> {
+        let initial = self.byte()?;
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+        let argument = match info {
+            info @ 0..=23 => info as u64,
+            24 => self.byte()? as u64,
+            25 => u64::from_be_bytes(pad(&self.bytes(2)?)),
+            26 => u64::from_be_bytes(pad(&self.bytes(4)?)),
+            27 => u64::from_be_bytes(pad(&self.bytes(8)?)),
+            31 => return Err(self.error("indefinite lengths are not allowed in DAG-CBOR")),
+            _ => return Err(self.error(format!("invalid initial byte 0x{:02x}", initial))),
+        };
+        Ok((major, info, argument))
+    }
+
+    fn length(&self, argument: u64) -> Result<usize, ReadError> {
+        usize::try_from(argument)
+            .map_err(|_| self.error(format!("length {} is too large", argument)))
+    }
+}
+
+impl<R: Read> TokenReader for Decoder<R> {
+    fn token(&mut self) -> Result<Token, ReadError> {
+        self.item = self.offset;
+        let (major, info, argument) = self.header()?;
+
+        Ok(match major {
+            0 => Token::Int(argument as i128),
+            1 => Token::Int(-1 - argument as i128),
+            2 if self.keep_bytes => Token::Bytes(self.bytes(argument)?),
+            2 => {
+                self.skip(argument)?;
+                Token::Bytes(vec![])
+            }
+            3 => match String::from_utf8(self.bytes(argument)?) {
+                Ok(string) => Token::String(string),
+                Err(_) => return Err(self.error("strings must be valid UTF-8")),
+            },
+            4 => Token::List(Seq::new(Some(self.length(argument)?), false)),
+            5 => Token::Map(Seq::new(Some(self.length(argument)?), true)),
+            6 if argument == CID_TAG => {
+                let (major, _, len) = self.header()?;
+                if major != 2 {
+                    return Err(self.error("CIDs must be encoded as bytes"));
+                }
+                match self.bytes(len)?.split_first() {
+                    Some((0, cid)) => match Cid::try_from(cid) {
+                        Ok(cid) => Token::Link(cid),
+                        Err(err) => return Err(self.error(format!("invalid CID: {}", err))),
+                    },
+                    _ => return Err(self.error("CIDs must be prefixed with a zero byte")),
+                }
+            }
+            6 => return Err(self.error(format!("tag {} is not allowed in DAG-CBOR", argument))),
+            // The arguments of floats are their bits, which we do not need
+            _ => match info {
+                20 => Token::Bool(false),
+                21 => Token::Bool(true),
+                22 => Token::Null,
+                25..=27 => Token::Float,
+                _ => {
+                    return Err(self.error(format!(
+                        "simple value {} is not allowed in DAG-CBOR",
+                        argument
+                    )))
+                }
+            },
+        })
+    }
+
+    fn next_entry(&mut self, seq: &mut Seq) -> Result<bool, ReadError> {
+        let has_next = seq.len.is_some_and(|len| seq.read < len);
+        if has_next {
+            seq.read += 1;
+        }
+        Ok(has_next)
+    }
+
+    fn key(&mut self) -> Result<String, ReadError> {
+        match self.token()? {
+            Token::String(key) => Ok(key),
+            _ => Err(self.error("map keys must be strings")),
+        }
+    }
+
+    fn keep_bytes(&mut self, keep: bool) {
+        self.keep_bytes = keep;
+    }
+
+    fn peek_null(&mut self) -> bool {
+        // 0xf6 is the encoding of null
+        self.peek().ok().flatten() == Some(0xf6)
+    }
+
+    fn last(&self) -> Range<usize> {
+        self.item..self.offset
+    }
+
+    fn finish(&mut self) -> Result<(), ReadError> {
+        match self.peek()? {
+            None => Ok(()),
+            Some(_) => Err(ReadError {
+                span: self.offset..self.offset,
+                reason: "trailing data after the value".to_string(),
+            }),
+        }
+    }
+}
+
+/// Pads big endian bytes to eight bytes
+fn pad(bytes: &[u8]) -> [u8; 8] {
+    let mut padded = [0; 8];
+    padded[8 - bytes.len()..].copy_from_slice(bytes);
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::Unstructured;
+    use ipld_core::{ipld, ipld::Ipld};
+
+    use super::*;
+    use crate::stream::MAX_DEPTH;
+
+    const SCHEMA: &str = "\
+type Message struct {
+  from String
+  count Int
+  tags optional [nullable Tag]
+  meta {String:Any}
+  reply optional &Message
+  pairs Pairs
+  level Level
+  ack Ack
+} representation listpairs
+
+type Tag enum {
+  | Red (\"R\")
+  | Blue
+}
+
+type Level enum {
+  | Low (\"1\")
+  | High (\"10\")
+} representation int
+
+type Pairs {String:Int} representation stringpairs {
+  innerDelim \"=\"
+  entryDelim \",\"
+}
+
+type Ack unit representation true
+
+type Point struct {
+  a Int
+  b Int
+}
+";
+
+    fn encode(data: &Ipld) -> Vec<u8> {
+        serde_ipld_dagcbor::to_vec(data).unwrap()
+    }
+
+    #[test]
+    fn agrees_with_validate() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+        let generator = schema.generator("Message").unwrap();
+
+        for seed in 0..100u8 {
+            let bytes = (0..512u32)
+                .map(|idx| (idx * 31 + seed as u32 * 97) as u8 ^ seed)
+                .collect::<Vec<_>>();
+
+            for near_miss in [false, true] {
+                let data = generator
+                    .clone()
+                    .with_near_miss(near_miss)
+                    .generate(&mut Unstructured::new(&bytes))
+                    .unwrap();
+                let expected = schema.validate("Message", &data).is_ok();
+                let streamed = schema.validate_dag_cbor("Message", encode(&data).as_slice());
+                assert_eq!(streamed.is_ok(), expected, "{:?}: {:?}", data, streamed);
+            }
+        }
+    }
+
+    #[test]
+    fn unions_and_tuples() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+
+        for (root, data) in [
+            ("Point", ipld!([1, 2])),
+            ("Point", ipld!([1, 2, "a"])),
+            ("Point", ipld!([1])),
+            ("Point", ipld!([1, 2, "a", 3, 4])),
+            ("Point", ipld!([1, "2"])),
+            ("Shape", ipld!({"circle": {"radius": 1}})),
+            ("Shape", ipld!({"square": {"side": 1}})),
+            (
+                "Shape",
+                ipld!({"circle": {"radius": 1}, "square": {"side": 1}}),
+            ),
+            ("Shape", ipld!({"triangle": {"side": 1}})),
+            ("Shape", ipld!({})),
+            ("Shape", ipld!([1])),
+            ("Value", ipld!([1, 2])),
+            ("Value", ipld!("x")),
+            ("Value", ipld!(1.5)),
+            ("Tagged", ipld!({"radius": 1, "tag": "c"})),
+            ("Wrapped", ipld!({"content": {"side": 1}, "tag": "c"})),
+        ] {
+            let expected = schema.validate(root, &data).is_ok();
+            let streamed = schema.validate_dag_cbor(root, encode(&data).as_slice());
+            assert_eq!(streamed.is_ok(), expected, "{:?}: {:?}", data, streamed);
+        }
+
+        // Keyed members are streamed, so errors point into them
+        let err = schema
+            .validate_dag_cbor(
+                "Shape",
+                encode(&ipld!({"circle": {"radius": "x"}})).as_slice(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected int for Int at circle/radius, found string (byte 16)"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+
+        let err = schema
+            .validate_dag_cbor("Point", encode(&ipld!({"a": 1, "b": "x"})).as_slice())
+            .unwrap_err();
+        assert_eq!(err.offset(), 6);
+        assert_eq!(
+            err.to_string(),
+            "Expected int for Int at b, found string (byte 6)"
+        );
+
+        let err = schema
+            .validate_dag_cbor("Point", encode(&ipld!({"a": 1})).as_slice())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CborValidationError::Invalid { offset: 0, error: ValidationError::MissingField { field, .. } } if field == "b"
+        ));
+
+        let mut truncated = encode(&ipld!({"a": 1, "b": 2}));
+        truncated.pop();
+        assert!(matches!(
+            schema.validate_dag_cbor("Point", truncated.as_slice()),
+            Err(CborValidationError::Malformed { offset: 6, .. })
+        ));
+
+        let mut trailing = encode(&ipld!({"a": 1, "b": 2}));
+        trailing.push(0);
+        assert_eq!(
+            schema
+                .validate_dag_cbor("Point", trailing.as_slice())
+                .unwrap_err()
+                .to_string(),
+            "Malformed DAG-CBOR at (root) (byte 7): trailing data after the value"
+        );
+
+        // Indefinite length lists
+        assert!(matches!(
+            schema.validate_dag_cbor("Any", [0x9f, 0xff].as_slice()),
+            Err(CborValidationError::Malformed { offset: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn nesting() {
+        let schema = IpldSchema::parse(
+            "type Ll [Ll]\n\ntype Lu union {\n  | Ll list\n} representation kinded\n",
+        )
+        .unwrap();
+        let nested = |depth: usize| [vec![0x81; depth], vec![0x80]].concat();
+
+        for root in ["Ll", "Lu"] {
+            assert_eq!(
+                schema.validate_dag_cbor(root, nested(MAX_DEPTH - 1).as_slice()),
+                Ok(()),
+                "{}",
+                root
+            );
+            let err = schema
+                .validate_dag_cbor(root, nested(2000).as_slice())
+                .unwrap_err();
+            assert!(
+                matches!(err, CborValidationError::Malformed { offset, .. } if offset == MAX_DEPTH),
+                "{}: {:?}",
+                root,
+                err
+            );
+        }
+        // Skipping does not recurse
+        assert_eq!(
+            schema.validate_dag_cbor("Any", nested(2000).as_slice()),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use std::fmt::Write;
+
+use crate::{
+    enumerate::{EnumMemberTag, EnumType},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::BytesRepresentation,
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    InlineIpldType, IpldSchema, IpldType,
+};
+
+/// The CDDL types of the prelude
+const CDDL_PRELUDE: &[(&str, &str)] = &[
+    ("Bool", "bool"),
+    ("String", "tstr"),
+    ("Bytes", "bstr"),
+    ("Int", "int"),
+    ("Float", "float"),
+    ("Map", "{ * tstr => any / null }"),
+    ("List", "[* any / null]"),
+    ("Link", "#6.42(bstr)"),
+    ("Any", "any"),
+];
+
+impl IpldSchema {
+    /// Generates CDDL (RFC 8610) rules for all types of this schema.
+    ///
+    /// The rules describe the DAG-CBOR encoding of the representation of the types,
+    /// links are CIDs with tag 42.
+    pub fn generate_cddl(&self) -> String {
+        let mut out = String::new();
+        out.push_str("; Generated by ipld-schema, do not edit.\n");
+
+        for (name, def) in &self.0 {
+            out.push('\n');
+            write_doc(&mut out, def.doc.as_deref(), "");
+
+            match &def.ty {
+                IpldType::Struct(stru) if stru.repr == StructRepresentation::Map => {
+                    self.generate_map_struct(&mut out, name, stru)
+                }
+                ty => {
+                    let _ = writeln!(out, "{} = {}", name, self.cddl_type(ty));
+                }
+            }
+        }
+
+        out
+    }
+
+    fn cddl_type(&self, ty: &IpldType) -> String {
+        match ty {
+            IpldType::Bool => "bool".to_string(),
+            IpldType::String => "tstr".to_string(),
+            IpldType::Bytes(BytesRepresentation::Bytes) => "bstr".to_string(),
+            IpldType::Bytes(BytesRepresentation::Advanced(_)) => "any".to_string(),
+            IpldType::Int => "int".to_string(),
+            IpldType::Float => "float".to_string(),
+            IpldType::Any => "any".to_string(),
+            IpldType::Link(_) => "#6.42(bstr)".to_string(),
+            IpldType::List(list) => self.cddl_list(list),
+            IpldType::Map(map) => self.cddl_map(map),
+            IpldType::Struct(stru) => self.cddl_struct(stru),
+            IpldType::Union(union) => self.cddl_union(union),
+            IpldType::Enum(enu) => cddl_enum(enu),
+            IpldType::Unit(unit) => match unit {
+                UnitRepresentation::Null => "null".to_string(),
+                UnitRepresentation::True => "true".to_string(),
+                UnitRepresentation::False => "false".to_string(),
+                UnitRepresentation::EmptyMap => "{}".to_string(),
+            },
+            IpldType::Copy(from) => self.cddl_name(from),
+        }
+    }
+
+    fn cddl_name(&self, name: &str) -> String {
+        self.target_name(name, CDDL_PRELUDE)
+    }
+
+    fn cddl_inline(&self, ty: &InlineIpldType) -> String {
+        match ty {
+            InlineIpldType::Name(name) => self.cddl_name(name),
+            InlineIpldType::List(list) => self.cddl_list(list),
+            InlineIpldType::Map(map) => self.cddl_map(map),
+            InlineIpldType::Link(_) => "#6.42(bstr)".to_string(),
+        }
+    }
+
+    fn cddl_list(&self, list: &ListType) -> String {
+        match &list.repr {
+            ListRepresentation::List => {
+                format!("[* {}]", self.cddl_nullable(&list.ty, list.nullable))
+            }
+            ListRepresentation::Advanced(_) => "any".to_string(),
+        }
+    }
+
+    fn cddl_nullable(&self, ty: &InlineIpldType, nullable: bool) -> String {
+        if nullable {
+            format!("{} / null", self.cddl_inline(ty))
+        } else {
+            self.cddl_inline(ty)
+        }
+    }
+
+    /// Map keys are strings in the data model, unless they are string enums
+    fn cddl_key(&self, key: &str) -> String {
+        match self.resolve_inline(&InlineIpldType::Name(key.to_string())) {
+            Some(resolved) if matches!(resolved.as_ref(), IpldType::Enum(enu) if enu.is_string()) => {
+                key.to_string()
+            }
+            _ => "tstr".to_string(),
+        }
+    }
+
+    fn cddl_map(&self, map: &MapType) -> String {
+        let value = self.cddl_nullable(&map.value, map.nullable);
+
+        match &map.repr {
+            MapRepresentation::Map => format!("{{ * {} => {} }}", self.cddl_key(&map.key), value),
+            MapRepresentation::ListPairs => {
+                format!("[* [{}, {}]]", self.cddl_key(&map.key), value)
+            }
+            MapRepresentation::StringPairs(_) => "tstr".to_string(),
+            MapRepresentation::Advanced(_) => "any".to_string(),
+        }
+    }
+
+    /// Structs, that are not represented as maps, are written on a single line
+    fn cddl_struct(&self, stru: &StructType) -> String {
+        match &stru.repr {
+            StructRepresentation::Map => {
+                unreachable!("Structs represented as maps are generated as their own rules")
+            }
+            StructRepresentation::ListPairs => {
+                let entries = stru
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        format!(
+                            "[{:?}, {}]",
+                            field_key(field),
+                            self.cddl_nullable(&field.value, field.nullable)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if entries.is_empty() {
+                    "[]".to_string()
+                } else {
+                    format!("[* ({})]", entries.join(" / "))
+                }
+            }
+            StructRepresentation::StringPairs(_) => "tstr".to_string(),
+            StructRepresentation::Tuple => {
+                let required = stru.required_tuple_fields();
+                let fields = stru
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, field)| {
+                        format!(
+                            "{}{}: {}",
+                            if idx < required { "" } else { "? " },
+                            field.key,
+                            self.cddl_nullable(&field.value, field.nullable)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!("[{}]", fields.join(", "))
+            }
+        }
+    }
+
+    /// Members are choices, inline members are unwrapped into the map of the discriminant
+    fn cddl_union(&self, union: &UnionType) -> String {
+        union
+            .members
+            .iter()
+            .map(|member| {
+                let ty = self.cddl_inline(&member.ty);
+                match &union.repr {
+                    UnionRepresentation::Kinded => ty,
+                    UnionRepresentation::Keyed => {
+                        format!("{{ {:?} => {} }}", member.discriminant, ty)
+                    }
+                    UnionRepresentation::Envelope {
+                        discriminant_key,
+                        content_key,
+                    } => format!(
+                        "{{ {:?} => {:?}, {:?} => {} }}",
+                        discriminant_key, member.discriminant, content_key, ty
+                    ),
+                    UnionRepresentation::Inline { discriminant_key } => format!(
+                        "{{ {:?} => {:?}, ~{} }}",
+                        discriminant_key, member.discriminant, ty
+                    ),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    fn generate_map_struct(&self, out: &mut String, name: &str, stru: &StructType) {
+        if stru.fields.is_empty() {
+            let _ = writeln!(out, "{} = {{}}", name);
+            return;
+        }
+
+        let _ = writeln!(out, "{} = {{", name);
+        for (idx, field) in stru.fields.iter().enumerate() {
+            write_doc(out, field.doc.as_deref(), "  ");
+            if let Some(implicit) = &field.implicit {
+                let _ = writeln!(out, "  ; Omitted if it is {}", implicit);
+            }
+
+            let optional = field.optional || field.implicit.is_some();
+            let _ = writeln!(
+                out,
+                "  {}{:?} => {}{}",
+                if optional { "? " } else { "" },
+                field_key(field),
+                self.cddl_nullable(&field.value, field.nullable),
+                if idx + 1 < stru.fields.len() { "," } else { "" }
+            );
+        }
+        out.push_str("}\n");
+    }
+}
+
+fn field_key(field: &StructField) -> &str {
+    field.rename.as_deref().unwrap_or(&field.key)
+}
+
+fn cddl_enum(enu: &EnumType) -> String {
+    enu.members
+        .iter()
+        .map(|member| match &member.tag {
+            EnumMemberTag::String(tag) => format!("{:?}", tag),
+            EnumMemberTag::Int(tag) => tag.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+fn write_doc(out: &mut String, doc: Option<&str>, indent: &str) {
+    if let Some(doc) = doc {
+        for line in doc.lines() {
+            let _ = writeln!(out, "{}; {}", indent, line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_cddl() {
+        let schema = IpldSchema::parse(include_str!("../test/message.ipldsch")).unwrap();
+
+        let expected = "\
+; Generated by ipld-schema, do not edit.
+
+Identity = tstr
+
+; A message
+Message = {
+  ; Who sent it
+  \"from\" => #6.42(bstr),
+  \"to\" => [* Identity / null] / null,
+  \"s\" => Status,
+  ; Omitted if it is 0
+  ? \"count\" => int,
+  ? \"labels\" => { * Status => int },
+  \"d\" => bstr
+}
+
+Status = \"Act\" / \"Gone\"
+";
+
+        assert_eq!(schema.generate_cddl(), expected);
+    }
+
+    #[test]
+    fn unions_and_tuples() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+        let cddl = schema.generate_cddl();
+
+        for rule in [
+            "Point = [x: int, y: int, ? label: tstr]",
+            "Shape = { \"circle\" => Circle } / { \"square\" => #6.42(bstr) }",
+            "Value = int / Shape / Point",
+            "Tagged = { \"tag\" => \"c\", ~Circle }",
+            "Wrapped = { \"tag\" => \"c\", \"content\" => Circle } / { \"tag\" => \"s\", \"content\" => Square }",
+        ] {
+            assert!(cddl.contains(rule), "{}", cddl);
+        }
+    }
+
+    #[test]
+    fn representations() {
+        let schema = IpldSchema::parse(
+            "\
+type Code enum {
+  | Ok (\"0\")
+  | Failed (\"1\")
+} representation int
+
+type Pairs struct {
+  name String
+  value optional Any
+  list List
+} representation listpairs
+
+type Nothing unit representation emptymap
+
+type Empty struct {}
+",
+        )
+        .unwrap();
+
+        let expected = "\
+; Generated by ipld-schema, do not edit.
+
+Code = 0 / 1
+
+Empty = {}
+
+Nothing = {}
+
+Pairs = [* ([\"name\", tstr] / [\"value\", any] / [\"list\", [* any / null]])]
+";
+
+        assert_eq!(schema.generate_cddl(), expected);
+    }
+}
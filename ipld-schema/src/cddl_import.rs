@@ -0,0 +1,1367 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    enumerate::{EnumField, EnumMemberTag, EnumRepresentation, EnumType},
+    json_import::{field_name, is_distinct, type_name, ImportError, ImportWarning, Importer},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::BytesRepresentation,
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionMember, UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    Doc, InlineIpldType, IpldSchema, IpldType, Kind,
+};
+
+/// The tag of CIDs in DAG-CBOR
+const CID_TAG: u64 = 42;
+
+/// Punctuation, longer tokens first
+const PUNCTUATION: &[&str] = &[
+    "//=", "...", "/=", "//", "=>", "..", "=", "/", "{", "}", "[", "]", "(", ")", "<", ">", ",",
+    ":", "?", "*", "+", "~", "&", "^", "#",
+];
+
+impl IpldSchema {
+    /// Imports CDDL (RFC 8610) rules on a best effort basis.
+    ///
+    /// Every rule becomes a type of its own, rules of groups are inlined where they are used.
+    /// Maps with fixed keys become structs, arrays of fixed entries tuple structs and choices of
+    /// literals enums. A choice of maps with a single key becomes a keyed union, one of distinct
+    /// kinds a kinded union. Everything that has no equivalent in an IPLD schema, like ranges,
+    /// controls or tags other than CIDs, is reported as a warning, whose pointer is the name
+    /// of the rule.
+    pub fn from_cddl(source: &str) -> Result<(IpldSchema, Vec<ImportWarning>), ImportError> {
+        let tokens = lex(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            warnings: vec![],
+        };
+        let rules = parser.rules()?;
+
+        let mut importer = CddlImporter {
+            importer: Importer::default(),
+            names: BTreeMap::new(),
+            rules: &rules,
+        };
+        importer.importer.warnings = parser.warnings;
+
+        // Groups are not types, they are inlined
+        for (name, rule) in &rules {
+            if !matches!(rule.ty, Ty::Group(_)) {
+                let ty_name = importer.importer.reserve(&type_name(name));
+                importer.names.insert(name.clone(), ty_name);
+            }
+        }
+        for (name, rule) in &rules {
+            if !matches!(rule.ty, Ty::Group(_)) {
+                importer.define(name, rule);
+            }
+        }
+
+        Ok((
+            IpldSchema(importer.importer.types),
+            importer.importer.warnings,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Text(String),
+    Int(i128),
+    Float(f64),
+    Bytes,
+    /// A control operator, i.e. `.size`
+    Control(String),
+    /// A tag with its major type and value, i.e. `#6.42`
+    Tag(u64, Option<u64>),
+    Punct(&'static str),
+}
+
+#[derive(Debug)]
+struct Lexed {
+    token: Token,
+    line: usize,
+    /// The comments on the lines right before the token
+    doc: Option<String>,
+}
+
+fn lex(source: &str) -> Result<Vec<Lexed>, ImportError> {
+    let mut tokens = vec![];
+    let mut line = 1;
+    let mut doc: Vec<&str> = vec![];
+    let mut line_start = true;
+    let mut rest = source;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            // An empty line detaches the comments above it
+            if line_start {
+                doc.clear();
+            }
+            line += 1;
+            line_start = true;
+            rest = &rest[1..];
+            continue;
+        }
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        if c == ';' {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            if line_start {
+                let comment = &rest[1..end];
+                doc.push(comment.strip_prefix(' ').unwrap_or(comment).trim_end());
+            }
+            rest = &rest[end..];
+            // The comment does not make the next line empty
+            if rest.starts_with('\n') {
+                line += 1;
+                line_start = true;
+                rest = &rest[1..];
+            }
+            continue;
+        }
+
+        let error = |message: &str| ImportError::InvalidCddl {
+            line,
+            message: message.to_string(),
+        };
+        let (token, len) = if c == '"' {
+            let mut text = String::new();
+            let mut chars = rest.char_indices().skip(1);
+            let end = loop {
+                match chars.next() {
+                    Some((idx, '"')) => break idx + 1,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, 't')) => text.push('\t'),
+                        Some((_, escaped)) => text.push(escaped),
+                        None => return Err(error("Unterminated text string")),
+                    },
+                    Some((_, '\n')) | None => return Err(error("Unterminated text string")),
+                    Some((_, c)) => text.push(c),
+                }
+            };
+            (Token::Text(text), end)
+        } else if c == '\'' || rest.starts_with("h'") || rest.starts_with("b64'") {
+            let start = rest.find('\'').unwrap_or_default() + 1;
+            let end = rest[start..]
+                .find('\'')
+                .ok_or_else(|| error("Unterminated byte string"))?;
+            (Token::Bytes, start + end + 1)
+        } else if c.is_ascii_digit()
+            || c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit())
+        {
+            lex_number(rest).ok_or_else(|| error("Invalid number"))?
+        } else if c == '#' && rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+            let major_len = digits(&rest[1..]);
+            let major = rest[1..1 + major_len]
+                .parse()
+                .map_err(|_| error("Invalid tag"))?;
+            let after = &rest[1 + major_len..];
+            match after.strip_prefix('.') {
+                Some(minor) if digits(minor) > 0 => {
+                    let minor_len = digits(minor);
+                    let value = minor[..minor_len]
+                        .parse()
+                        .map_err(|_| error("Invalid tag"))?;
+                    (Token::Tag(major, Some(value)), 2 + major_len + minor_len)
+                }
+                _ => (Token::Tag(major, None), 1 + major_len),
+            }
+        } else if c == '.' && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let len = name_len(&rest[1..]);
+            (Token::Control(rest[1..1 + len].to_string()), 1 + len)
+        } else if c.is_ascii_alphabetic() || matches!(c, '@' | '_' | '$') {
+            let len = name_len(rest);
+            (Token::Name(rest[..len].to_string()), len)
+        } else if let Some(punct) = PUNCTUATION.iter().find(|punct| rest.starts_with(**punct)) {
+            (Token::Punct(punct), punct.len())
+        } else {
+            return Err(error(&format!("Unexpected character '{}'", c)));
+        };
+
+        tokens.push(Lexed {
+            token,
+            line,
+            doc: (!doc.is_empty()).then(|| doc.join("\n")),
+        });
+        doc.clear();
+        line_start = false;
+        rest = &rest[len..];
+    }
+
+    Ok(tokens)
+}
+
+fn digits(text: &str) -> usize {
+    text.find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len())
+}
+
+/// The length of a name, which does not end with `-` or `.`
+fn name_len(text: &str) -> usize {
+    let len = text
+        .find(|c: char| !c.is_ascii_alphanumeric() && !matches!(c, '@' | '_' | '$' | '-' | '.'))
+        .unwrap_or(text.len());
+    text[..len].trim_end_matches(['-', '.']).len()
+}
+
+fn lex_number(text: &str) -> Option<(Token, usize)> {
+    let sign = usize::from(text.starts_with('-'));
+    if let Some(hex) = text[sign..].strip_prefix("0x") {
+        let len = hex
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(hex.len());
+        let value = i128::from_str_radix(&hex[..len], 16).ok()?;
+        let value = if sign == 1 { -value } else { value };
+        return Some((Token::Int(value), sign + 2 + len));
+    }
+
+    let mut len = sign + digits(&text[sign..]);
+    let mut is_float = false;
+    // A fraction, but not a range like `0..10`
+    if text[len..].starts_with('.') && digits(&text[len + 1..]) > 0 {
+        is_float = true;
+        len += 1 + digits(&text[len + 1..]);
+    }
+    if text[len..].starts_with(['e', 'E']) {
+        let exponent = &text[len + 1..];
+        let exponent_sign = usize::from(exponent.starts_with(['+', '-']));
+        if digits(&exponent[exponent_sign..]) > 0 {
+            is_float = true;
+            len += 1 + exponent_sign + digits(&exponent[exponent_sign..]);
+        }
+    }
+
+    let number = &text[..len];
+    let token = if is_float {
+        Token::Float(number.parse().ok()?)
+    } else {
+        Token::Int(number.parse().ok()?)
+    };
+    Some((token, len))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ty {
+    Name(String),
+    Text(String),
+    Int(i128),
+    Float(f64),
+    Map(Vec<Entry>),
+    Array(Vec<Entry>),
+    /// A group in parentheses, that is not a single type
+    Group(Vec<Entry>),
+    /// A tagged value, i.e. `#6.42(bstr)`
+    Tag(u64, Box<Ty>),
+    /// The entries of a map or group rule, i.e. `~Circle`
+    Unwrap(String),
+    /// The values of a group as a choice, i.e. `&(a: 1, b: 2)`
+    Enumeration(Vec<Entry>),
+    Choice(Vec<Ty>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    doc: Option<String>,
+    occurrence: Occurrence,
+    key: Option<Key>,
+    value: Ty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Occurrence {
+    One,
+    Optional,
+    /// `*`, `+` or a range of occurrences
+    Many,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    /// A bare word or a literal followed by `:`
+    Text(String),
+    /// A type followed by `=>`
+    Type(Ty),
+}
+
+#[derive(Debug)]
+struct Rule {
+    doc: Option<String>,
+    ty: Ty,
+}
+
+struct Parser {
+    tokens: Vec<Lexed>,
+    pos: usize,
+    warnings: Vec<ImportWarning>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|lexed| &lexed.token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|lexed| &lexed.token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes the punctuation, if it is next
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(next)) if *next == punct) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), ImportError> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected \"{}\"", punct)))
+        }
+    }
+
+    fn error(&self, message: &str) -> ImportError {
+        let line = self
+            .tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |lexed| lexed.line);
+        ImportError::InvalidCddl {
+            line,
+            message: message.to_string(),
+        }
+    }
+
+    fn warn(&mut self, pointer: &str, message: impl ToString) {
+        self.warnings.push(ImportWarning {
+            pointer: pointer.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    fn rules(&mut self) -> Result<Vec<(String, Rule)>, ImportError> {
+        let mut rules: Vec<(String, Rule)> = vec![];
+
+        while self.pos < self.tokens.len() {
+            let doc = self.tokens[self.pos].doc.clone();
+            let Some(Token::Name(name)) = self.next() else {
+                self.pos -= 1;
+                return Err(self.error("Expected the name of a rule"));
+            };
+
+            if self.eat("<") {
+                self.warn(&name, "Generic rules are not supported");
+                self.skip_generic()?;
+            }
+
+            let extends = if self.eat("=") {
+                false
+            } else if self.eat("/=") || self.eat("//=") {
+                true
+            } else {
+                return Err(self.error("Expected \"=\""));
+            };
+
+            let ty = self.choice(&name)?;
+            if extends {
+                self.warn(&name, "Extending rules is not supported and is ignored");
+                continue;
+            }
+            if rules.iter().any(|(other, _)| *other == name) {
+                self.warn(
+                    &name,
+                    "Rule is defined twice, only the first one is imported",
+                );
+                continue;
+            }
+            rules.push((name, Rule { doc, ty }));
+        }
+
+        Ok(rules)
+    }
+
+    /// Skips generic parameters or arguments after the opening `<`
+    fn skip_generic(&mut self) -> Result<(), ImportError> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::Punct("<")) => depth += 1,
+                Some(Token::Punct(">")) => depth -= 1,
+                Some(_) => (),
+                None => return Err(self.error("Expected \">\"")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses types separated by `/`
+    fn choice(&mut self, rule: &str) -> Result<Ty, ImportError> {
+        let mut choices = vec![self.range(rule)?];
+        while self.eat("/") {
+            choices.push(self.range(rule)?);
+        }
+
+        Ok(match choices.len() {
+            1 => choices.remove(0),
+            _ => Ty::Choice(choices),
+        })
+    }
+
+    /// Parses a type, that may be constrained by a range or a control operator
+    fn range(&mut self, rule: &str) -> Result<Ty, ImportError> {
+        let ty = self.ty(rule)?;
+
+        if self.eat("..") || self.eat("...") {
+            let end = self.ty(rule)?;
+            self.warn(rule, "Ranges have no IPLD equivalent and are ignored");
+            return Ok(match (ty, end) {
+                (Ty::Int(_), Ty::Int(_)) => Ty::Name("int".to_string()),
+                _ => Ty::Name("float".to_string()),
+            });
+        }
+        if let Some(Token::Control(control)) = self.peek().cloned() {
+            self.pos += 1;
+            self.ty(rule)?;
+            self.warn(
+                rule,
+                format!("\".{}\" has no IPLD equivalent and is ignored", control),
+            );
+        }
+
+        Ok(ty)
+    }
+
+    fn ty(&mut self, rule: &str) -> Result<Ty, ImportError> {
+        let ty = match self.next() {
+            Some(Token::Text(text)) => Ty::Text(text),
+            Some(Token::Int(value)) => Ty::Int(value),
+            Some(Token::Float(value)) => Ty::Float(value),
+            Some(Token::Bytes) => {
+                self.warn(rule, "Byte string literals are imported as bytes");
+                Ty::Name("bstr".to_string())
+            }
+            Some(Token::Name(name)) => {
+                if self.eat("<") {
+                    self.warn(rule, "Generic arguments are not supported");
+                    self.skip_generic()?;
+                }
+                Ty::Name(name)
+            }
+            Some(Token::Punct("(")) => {
+                let mut entries = self.group(rule, ")")?;
+                match entries.as_slice() {
+                    [Entry {
+                        occurrence: Occurrence::One,
+                        key: None,
+                        ..
+                    }] => entries.remove(0).value,
+                    _ => Ty::Group(entries),
+                }
+            }
+            Some(Token::Punct("{")) => Ty::Map(self.group(rule, "}")?),
+            Some(Token::Punct("[")) => Ty::Array(self.group(rule, "]")?),
+            Some(Token::Punct("~")) => match self.next() {
+                Some(Token::Name(name)) => Ty::Unwrap(name),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("Expected the name of a rule after \"~\""));
+                }
+            },
+            Some(Token::Punct("&")) => match self.next() {
+                Some(Token::Punct("(")) => Ty::Enumeration(self.group(rule, ")")?),
+                Some(Token::Name(name)) => Ty::Enumeration(vec![Entry {
+                    doc: None,
+                    occurrence: Occurrence::One,
+                    key: None,
+                    value: Ty::Unwrap(name),
+                }]),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("Expected a group after \"&\""));
+                }
+            },
+            Some(Token::Tag(6, Some(tag))) => {
+                self.expect("(")?;
+                let inner = self.choice(rule)?;
+                self.expect(")")?;
+                Ty::Tag(tag, Box::new(inner))
+            }
+            Some(Token::Tag(major, _)) => {
+                let name = match major {
+                    0 | 1 => "int",
+                    2 => "bstr",
+                    3 => "tstr",
+                    _ => "any",
+                };
+                self.warn(
+                    rule,
+                    format!("Major type {} is imported as \"{}\"", major, name),
+                );
+                Ty::Name(name.to_string())
+            }
+            Some(Token::Punct("#")) => Ty::Name("any".to_string()),
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("Expected a type"));
+            }
+        };
+
+        Ok(ty)
+    }
+
+    /// Parses the entries of a group up to `close`
+    fn group(&mut self, rule: &str, close: &str) -> Result<Vec<Entry>, ImportError> {
+        let mut entries = vec![];
+
+        loop {
+            if self.eat(close) {
+                return Ok(entries);
+            }
+            if self.eat("//") {
+                self.warn(
+                    rule,
+                    "Group choices are not supported, only the first one is imported",
+                );
+                return self.skip_group(close).map(|_| entries);
+            }
+
+            entries.push(self.entry(rule)?);
+            if !self.eat(",")
+                && !matches!(self.peek(), Some(Token::Punct(next)) if *next == close || *next == "//")
+            {
+                return Err(self.error(&format!("Expected \",\" or \"{}\"", close)));
+            }
+        }
+    }
+
+    /// Skips the rest of a group including `close`
+    fn skip_group(&mut self, close: &str) -> Result<(), ImportError> {
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Some(Token::Punct(punct)) if punct == close && depth == 0 => return Ok(()),
+                Some(Token::Punct("(" | "{" | "[")) => depth += 1,
+                Some(Token::Punct(")" | "}" | "]")) => depth -= 1,
+                Some(_) => (),
+                None => return Err(self.error(&format!("Expected \"{}\"", close))),
+            }
+        }
+    }
+
+    fn entry(&mut self, rule: &str) -> Result<Entry, ImportError> {
+        let doc = self
+            .tokens
+            .get(self.pos)
+            .and_then(|lexed| lexed.doc.clone());
+
+        let occurrence = match self.peek() {
+            Some(Token::Punct("?")) => {
+                self.pos += 1;
+                Occurrence::Optional
+            }
+            Some(Token::Punct("*" | "+")) => {
+                self.pos += 1;
+                if matches!(self.peek(), Some(Token::Int(_)))
+                    && self.peek_at(1) != Some(&Token::Punct(":"))
+                {
+                    self.pos += 1;
+                }
+                Occurrence::Many
+            }
+            Some(Token::Int(_)) if self.peek_at(1) == Some(&Token::Punct("*")) => {
+                self.pos += 2;
+                if matches!(self.peek(), Some(Token::Int(_)))
+                    && self.peek_at(1) != Some(&Token::Punct(":"))
+                {
+                    self.pos += 1;
+                }
+                Occurrence::Many
+            }
+            _ => Occurrence::One,
+        };
+
+        // Bare words and literals followed by `:` are keys
+        if self.peek_at(1) == Some(&Token::Punct(":")) {
+            let key = match self.peek() {
+                Some(Token::Name(key) | Token::Text(key)) => Some(key.clone()),
+                Some(Token::Int(key)) => Some(key.to_string()),
+                _ => None,
+            };
+            if let Some(key) = key {
+                self.pos += 2;
+                return Ok(Entry {
+                    doc,
+                    occurrence,
+                    key: Some(Key::Text(key)),
+                    value: self.choice(rule)?,
+                });
+            }
+        }
+
+        let ty = self.choice(rule)?;
+        let cut = self.eat("^");
+        if cut || self.eat("=>") {
+            if cut {
+                self.expect("=>")?;
+            }
+            return Ok(Entry {
+                doc,
+                occurrence,
+                key: Some(Key::Type(ty)),
+                value: self.choice(rule)?,
+            });
+        }
+
+        Ok(Entry {
+            doc,
+            occurrence,
+            key: None,
+            value: ty,
+        })
+    }
+}
+
+struct CddlImporter<'r> {
+    importer: Importer,
+    /// Maps the names of rules to their type names
+    names: BTreeMap<String, String>,
+    rules: &'r [(String, Rule)],
+}
+
+impl CddlImporter<'_> {
+    fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, rule)| rule)
+    }
+
+    fn define(&mut self, name: &str, rule: &Rule) {
+        let (ty, nullable) = split_nullable(&rule.ty);
+        if nullable {
+            self.importer
+                .warn(name, "Types can not be nullable, only their uses");
+        }
+
+        let ty_name = self.names[name].clone();
+        let ty = self.import_type(&ty_name, name, &ty);
+        self.importer.types.insert(
+            ty_name,
+            Doc {
+                doc: rule.doc.clone(),
+                ty,
+            },
+        );
+    }
+
+    fn import_type(&mut self, name: &str, pointer: &str, ty: &Ty) -> IpldType {
+        match ty {
+            Ty::Name(reference) => self.import_name(pointer, reference),
+            Ty::Text(_) | Ty::Int(_) => self.import_enum(pointer, std::slice::from_ref(ty), &[]),
+            Ty::Float(_) => {
+                self.importer
+                    .warn(pointer, "Float literals have no IPLD equivalent");
+                IpldType::Float
+            }
+            Ty::Map(entries) => self.import_map(name, pointer, entries),
+            Ty::Array(entries) => self.import_array(name, pointer, entries),
+            Ty::Group(_) => {
+                self.importer
+                    .warn(pointer, "Groups are only supported in maps and arrays");
+                IpldType::Any
+            }
+            Ty::Tag(CID_TAG, _) => IpldType::Link("Any".to_string()),
+            Ty::Tag(tag, inner) => {
+                self.importer.warn(
+                    pointer,
+                    format!("Tag {} has no IPLD equivalent and is ignored", tag),
+                );
+                self.import_type(name, pointer, inner)
+            }
+            Ty::Unwrap(reference) => self.import_name(pointer, reference),
+            Ty::Enumeration(entries) => {
+                let entries = self.expand(pointer, entries);
+                let values = entries
+                    .iter()
+                    .map(|entry| entry.value.clone())
+                    .collect::<Vec<_>>();
+                let keys = entries
+                    .iter()
+                    .map(|entry| match &entry.key {
+                        Some(Key::Text(key)) => Some(key.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                self.import_enum(pointer, &values, &keys)
+            }
+            Ty::Choice(choices) => self.import_choice(name, pointer, choices),
+        }
+    }
+
+    fn import_name(&mut self, pointer: &str, name: &str) -> IpldType {
+        match name {
+            "bool" => IpldType::Bool,
+            "tstr" | "text" => IpldType::String,
+            "bstr" | "bytes" => IpldType::Bytes(BytesRepresentation::Bytes),
+            "int" | "uint" | "nint" | "integer" | "unsigned" => IpldType::Int,
+            "float" | "float16" | "float32" | "float64" | "float16-32" | "float32-64" => {
+                IpldType::Float
+            }
+            "number" => {
+                self.importer
+                    .warn(pointer, "Numbers are imported as floats");
+                IpldType::Float
+            }
+            "any" => IpldType::Any,
+            "null" | "nil" => IpldType::Unit(UnitRepresentation::Null),
+            "true" => IpldType::Unit(UnitRepresentation::True),
+            "false" => IpldType::Unit(UnitRepresentation::False),
+            name => match self.names.get(name) {
+                Some(name) => IpldType::Copy(name.clone()),
+                None if self.rule(name).is_some() => {
+                    self.importer
+                        .warn(pointer, "Groups are only supported in maps and arrays");
+                    IpldType::Any
+                }
+                None => {
+                    self.importer
+                        .warn(pointer, format!("Can not resolve \"{}\"", name));
+                    IpldType::Any
+                }
+            },
+        }
+    }
+
+    /// Imports the type of a field, list item or map value
+    fn import_inline(
+        &mut self,
+        parent: &str,
+        field: &str,
+        pointer: &str,
+        doc: Option<String>,
+        ty: &Ty,
+    ) -> (InlineIpldType, bool) {
+        let (ty, nullable) = split_nullable(ty);
+        let name = format!("{}{}", parent, type_name(field));
+
+        let ty = self.import_type(&name, pointer, &ty);
+        (self.importer.inline(parent, field, doc, ty), nullable)
+    }
+
+    /// Replaces entries, that are group references or unwrapped maps, by their entries
+    fn expand(&mut self, pointer: &str, entries: &[Entry]) -> Vec<Entry> {
+        let mut expanded = vec![];
+        let mut pending = entries.iter().rev().cloned().collect::<Vec<_>>();
+        // Groups may include each other, but not endlessly
+        let mut remaining = self.rules.len() * 16;
+
+        while let Some(entry) = pending.pop() {
+            let included = match (&entry.key, &entry.value, entry.occurrence) {
+                (None, Ty::Unwrap(name), Occurrence::One) => {
+                    self.rule(name).map(|rule| (name, &rule.ty))
+                }
+                // Other references are values
+                (None, Ty::Name(name), Occurrence::One) => self
+                    .rule(name)
+                    .filter(|rule| matches!(rule.ty, Ty::Group(_)))
+                    .map(|rule| (name, &rule.ty)),
+                _ => None,
+            };
+
+            match included {
+                Some((_, Ty::Map(entries) | Ty::Group(entries) | Ty::Array(entries)))
+                    if remaining > 0 =>
+                {
+                    remaining -= 1;
+                    pending.extend(entries.iter().rev().cloned());
+                }
+                Some((name, _)) => self
+                    .importer
+                    .warn(pointer, format!("Can not include \"{}\"", name)),
+                None => expanded.push(entry),
+            }
+        }
+
+        expanded
+    }
+
+    fn import_map(&mut self, name: &str, pointer: &str, entries: &[Entry]) -> IpldType {
+        let entries = self.expand(pointer, entries);
+
+        match entries.as_slice() {
+            [] => return IpldType::Unit(UnitRepresentation::EmptyMap),
+            [Entry {
+                occurrence: Occurrence::Many,
+                key: Some(Key::Type(key)),
+                value,
+                ..
+            }] => {
+                let key = self.map_key(pointer, key);
+                let (value, nullable) =
+                    self.import_inline(name, "Value", &format!("{}/*", pointer), None, value);
+                return IpldType::Map(MapType {
+                    key,
+                    value,
+                    nullable,
+                    repr: MapRepresentation::Map,
+                });
+            }
+            _ => (),
+        }
+
+        let mut fields = vec![];
+        for entry in entries {
+            let key = match &entry.key {
+                Some(Key::Text(key) | Key::Type(Ty::Text(key))) => key.clone(),
+                _ => {
+                    self.importer.warn(
+                        pointer,
+                        "Only entries with a text key are supported in structs",
+                    );
+                    continue;
+                }
+            };
+            if entry.occurrence == Occurrence::Many {
+                self.importer.warn(
+                    &format!("{}/{}", pointer, key),
+                    "Repeated entries are not supported in structs",
+                );
+            }
+            fields.push(self.import_field(name, pointer, &key, entry));
+        }
+
+        IpldType::Struct(StructType {
+            fields,
+            repr: StructRepresentation::Map,
+        })
+    }
+
+    fn import_field(&mut self, name: &str, pointer: &str, key: &str, entry: Entry) -> StructField {
+        // The comment, that the CDDL generator writes for implicit values
+        let mut implicit = None;
+        let doc = entry.doc.and_then(|doc| {
+            let lines = doc
+                .lines()
+                .filter(|line| match line.strip_prefix("Omitted if it is ") {
+                    Some(value) => {
+                        implicit = Some(value.to_string());
+                        false
+                    }
+                    None => true,
+                })
+                .collect::<Vec<_>>();
+            (!lines.is_empty()).then(|| lines.join("\n"))
+        });
+
+        let pointer = format!("{}/{}", pointer, key);
+        let (value, nullable) = self.import_inline(name, key, &pointer, None, &entry.value);
+        let field_name = field_name(key);
+        StructField {
+            doc,
+            rename: (field_name != key).then(|| key.to_string()),
+            key: field_name,
+            value,
+            optional: entry.occurrence != Occurrence::One && implicit.is_none(),
+            nullable,
+            implicit,
+        }
+    }
+
+    fn map_key(&mut self, pointer: &str, key: &Ty) -> String {
+        match key {
+            Ty::Name(name) if matches!(name.as_str(), "tstr" | "text") => "String".to_string(),
+            Ty::Name(name)
+                if self.kind(key) == Some(Kind::String) && self.names.contains_key(name) =>
+            {
+                self.names[name].clone()
+            }
+            _ => {
+                self.importer
+                    .warn(pointer, "Map keys are imported as strings");
+                "String".to_string()
+            }
+        }
+    }
+
+    fn import_array(&mut self, name: &str, pointer: &str, entries: &[Entry]) -> IpldType {
+        let entries = self.expand(pointer, entries);
+
+        if let [Entry {
+            occurrence: Occurrence::Many,
+            value,
+            ..
+        }] = entries.as_slice()
+        {
+            let (ty, nullable) =
+                self.import_inline(name, "Item", &format!("{}/*", pointer), None, value);
+            return IpldType::List(ListType {
+                ty,
+                nullable,
+                repr: ListRepresentation::List,
+            });
+        }
+
+        let mut fields = vec![];
+        for (idx, entry) in entries.into_iter().enumerate() {
+            let key = match &entry.key {
+                Some(Key::Text(key)) => key.clone(),
+                _ => format!("field{}", idx),
+            };
+            if entry.occurrence == Occurrence::Many {
+                self.importer.warn(
+                    &format!("{}/{}", pointer, key),
+                    "Repeated entries are not supported in tuples",
+                );
+            }
+            fields.push(self.import_field(name, pointer, &key, entry));
+        }
+
+        IpldType::Struct(StructType {
+            fields,
+            repr: StructRepresentation::Tuple,
+        })
+    }
+
+    /// Imports a choice as an enum of its literals, a keyed, envelope or inline union of maps or
+    /// a kinded union
+    fn import_choice(&mut self, name: &str, pointer: &str, choices: &[Ty]) -> IpldType {
+        if choices
+            .iter()
+            .all(|choice| matches!(choice, Ty::Text(_) | Ty::Int(_)))
+        {
+            return self.import_enum(pointer, choices, &[]);
+        }
+
+        let maps = choices
+            .iter()
+            .map(|choice| match choice {
+                Ty::Map(entries) => Some(entries.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(union) = maps.and_then(|maps| self.import_map_union(name, pointer, &maps)) {
+            return union;
+        }
+
+        let Some(kinds) = choices
+            .iter()
+            .map(|choice| self.kind(choice))
+            .collect::<Option<Vec<_>>>()
+            .filter(|kinds| is_distinct(kinds))
+        else {
+            self.importer
+                .warn(pointer, "Choices of the same kind are not supported");
+            return IpldType::Any;
+        };
+
+        let members = choices
+            .iter()
+            .zip(kinds)
+            .map(|(choice, kind)| {
+                let pointer = format!("{}/{}", pointer, kind);
+                let (ty, nullable) =
+                    self.import_inline(name, kind.as_str(), &pointer, None, choice);
+                UnionMember {
+                    doc: None,
+                    ty: self
+                        .importer
+                        .member(name, kind.as_str(), &pointer, None, ty, nullable),
+                    discriminant: kind.to_string(),
+                }
+            })
+            .collect();
+        IpldType::Union(UnionType {
+            members,
+            repr: UnionRepresentation::Kinded,
+        })
+    }
+
+    /// Imports a choice of maps as a union, if they all have the same shape
+    fn import_map_union(
+        &mut self,
+        name: &str,
+        pointer: &str,
+        maps: &[Vec<Entry>],
+    ) -> Option<IpldType> {
+        let text_key = |entry: &Entry| match (&entry.key, entry.occurrence) {
+            (Some(Key::Text(key) | Key::Type(Ty::Text(key))), Occurrence::One) => Some(key.clone()),
+            _ => None,
+        };
+
+        // `{ "circle" => Circle }`
+        let keys = maps
+            .iter()
+            .map(|entries| match entries.as_slice() {
+                [entry] => text_key(entry),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|keys| is_distinct(keys));
+        if let Some(keys) = keys {
+            let members = maps
+                .iter()
+                .zip(keys)
+                .map(|(entries, key)| {
+                    let member = self.import_member(name, pointer, &key, &entries[0]);
+                    UnionMember {
+                        doc: entries[0].doc.clone(),
+                        ty: member,
+                        discriminant: key,
+                    }
+                })
+                .collect();
+            return Some(IpldType::Union(UnionType {
+                members,
+                repr: UnionRepresentation::Keyed,
+            }));
+        }
+
+        // `{ "tag" => "c", "content" => Circle }` or `{ "tag" => "c", ~Circle }`
+        let shapes = maps
+            .iter()
+            .map(|entries| match entries.as_slice() {
+                [discriminant, content] => {
+                    let discriminant_key = text_key(discriminant)?;
+                    let Ty::Text(value) = &discriminant.value else {
+                        return None;
+                    };
+                    let content_key = match (&content.value, &content.key) {
+                        (Ty::Unwrap(_), None) => None,
+                        _ => Some(text_key(content)?),
+                    };
+                    Some((discriminant_key, value.clone(), content_key))
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let (discriminant_key, _, content_key) = shapes.first()?.clone();
+        let values = shapes
+            .iter()
+            .map(|(_, value, _)| value.clone())
+            .collect::<Vec<_>>();
+        if !is_distinct(&values)
+            || shapes
+                .iter()
+                .any(|(key, _, content)| (key, content) != (&discriminant_key, &content_key))
+        {
+            return None;
+        }
+
+        let members = maps
+            .iter()
+            .zip(values)
+            .map(|(entries, value)| {
+                let ty = match &entries[1].value {
+                    Ty::Unwrap(reference) if content_key.is_none() => {
+                        match self.import_name(pointer, reference) {
+                            IpldType::Copy(name) => InlineIpldType::Name(name),
+                            _ => InlineIpldType::Name("Any".to_string()),
+                        }
+                    }
+                    _ => self.import_member(name, pointer, &value, &entries[1]),
+                };
+                UnionMember {
+                    doc: entries[0].doc.clone(),
+                    ty,
+                    discriminant: value,
+                }
+            })
+            .collect();
+        let repr = match content_key {
+            Some(content_key) => UnionRepresentation::Envelope {
+                discriminant_key,
+                content_key,
+            },
+            None => UnionRepresentation::Inline { discriminant_key },
+        };
+        Some(IpldType::Union(UnionType { members, repr }))
+    }
+
+    fn import_member(
+        &mut self,
+        name: &str,
+        pointer: &str,
+        field: &str,
+        entry: &Entry,
+    ) -> InlineIpldType {
+        let pointer = format!("{}/{}", pointer, field);
+        let (ty, nullable) = self.import_inline(name, field, &pointer, None, &entry.value);
+        self.importer
+            .member(name, field, &pointer, None, ty, nullable)
+    }
+
+    /// Imports literals as an enum, whose members are named by `keys` or their values
+    fn import_enum(&mut self, pointer: &str, values: &[Ty], keys: &[Option<&str>]) -> IpldType {
+        let is_string = values.iter().all(|value| matches!(value, Ty::Text(_)));
+        let is_int = values.iter().all(|value| matches!(value, Ty::Int(_)));
+        if !is_string && !is_int {
+            self.importer.warn(
+                pointer,
+                "Only enums of strings or of integers are supported",
+            );
+            return IpldType::Any;
+        }
+
+        let mut names = vec![];
+        let mut members = vec![];
+        for (idx, value) in values.iter().enumerate() {
+            let (name, tag) = match value {
+                Ty::Text(tag) => (type_name(tag), EnumMemberTag::String(tag.clone())),
+                Ty::Int(tag) if *tag < 0 => (
+                    format!("ValueMinus{}", tag.unsigned_abs()),
+                    EnumMemberTag::Int(*tag),
+                ),
+                Ty::Int(tag) => (format!("Value{}", tag), EnumMemberTag::Int(*tag)),
+                _ => unreachable!("enums are made of literals"),
+            };
+            let name = match keys.get(idx) {
+                Some(Some(key)) => type_name(key),
+                _ => name,
+            };
+
+            let mut unique = name.clone();
+            let mut counter = 1;
+            while names.contains(&unique) {
+                counter += 1;
+                unique = format!("{}{}", name, counter);
+            }
+            names.push(unique.clone());
+
+            members.push(EnumField {
+                doc: None,
+                name: unique,
+                tag,
+            });
+        }
+
+        IpldType::Enum(EnumType {
+            members,
+            repr: if is_string {
+                EnumRepresentation::String
+            } else {
+                EnumRepresentation::Int
+            },
+        })
+    }
+
+    /// Returns the kind of the data a type describes, if it is a single one
+    fn kind(&self, ty: &Ty) -> Option<Kind> {
+        let mut ty = ty;
+        // Rules may reference each other, but not endlessly
+        for _ in 0..=self.rules.len() {
+            return match ty {
+                Ty::Name(name) | Ty::Unwrap(name) => match name.as_str() {
+                    "bool" | "true" | "false" => Some(Kind::Bool),
+                    "tstr" | "text" => Some(Kind::String),
+                    "bstr" | "bytes" => Some(Kind::Bytes),
+                    "int" | "uint" | "nint" | "integer" | "unsigned" => Some(Kind::Int),
+                    "float" | "float16" | "float32" | "float64" | "float16-32" | "float32-64"
+                    | "number" => Some(Kind::Float),
+                    "null" | "nil" => Some(Kind::Null),
+                    name => {
+                        ty = &self.rule(name)?.ty;
+                        continue;
+                    }
+                },
+                Ty::Text(_) => Some(Kind::String),
+                Ty::Int(_) => Some(Kind::Int),
+                Ty::Float(_) => Some(Kind::Float),
+                Ty::Map(_) => Some(Kind::Map),
+                Ty::Array(_) => Some(Kind::List),
+                Ty::Group(_) => None,
+                Ty::Tag(CID_TAG, _) => Some(Kind::Link),
+                Ty::Tag(_, inner) => {
+                    ty = inner;
+                    continue;
+                }
+                Ty::Enumeration(entries) => match entries.first().map(|entry| &entry.value) {
+                    Some(Ty::Text(_)) => Some(Kind::String),
+                    Some(Ty::Int(_)) => Some(Kind::Int),
+                    _ => None,
+                },
+                Ty::Choice(choices) => {
+                    let kinds = choices
+                        .iter()
+                        .map(|choice| self.kind(choice))
+                        .collect::<Option<Vec<_>>>()?;
+                    match kinds.as_slice() {
+                        [first, rest @ ..] if rest.iter().all(|kind| kind == first) => Some(*first),
+                        _ => None,
+                    }
+                }
+            };
+        }
+        None
+    }
+}
+
+/// Strips `null` from a choice, that also allows other types
+fn split_nullable(ty: &Ty) -> (Ty, bool) {
+    let is_null = |ty: &Ty| matches!(ty, Ty::Name(name) if name == "null" || name == "nil");
+
+    match ty {
+        Ty::Choice(choices) if choices.iter().any(is_null) => {
+            let mut other = choices
+                .iter()
+                .filter(|choice| !is_null(choice))
+                .cloned()
+                .collect::<Vec<_>>();
+            match other.len() {
+                0 => (ty.clone(), false),
+                1 => (other.remove(0), true),
+                _ => (Ty::Choice(other), true),
+            }
+        }
+        ty => (ty.clone(), false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_cddl() {
+        let cddl = "\
+; A message
+message = {
+  from: identity,
+  ? to: [* tstr / nil] / nil,
+  ; The text
+  body: tstr .size (1..280),
+  ? \"sent-at\": uint,
+  attachment: #6.42(bstr),
+  shape: shape,
+  common,
+  * tstr => any,
+}
+
+common = (id: uint)
+
+identity = tstr
+
+shape = { \"circle\": int } / { \"polygon\": [* int] }
+
+value = int / tstr / shape
+
+code = &(ok: 0, failed: 1)
+
+point = [x: int, y: int, ? label: tstr]
+
+status = \"active\" / \"gone\"
+";
+
+        let (schema, warnings) = IpldSchema::from_cddl(cddl).unwrap();
+
+        let expected = "\
+type Code enum {
+  | Ok (\"0\")
+  | Failed (\"1\")
+} representation int
+
+type Identity string
+
+# A message
+type Message struct {
+  from Identity
+  to nullable optional [nullable String]
+  # The text
+  body String
+  sent_at optional Int (rename \"sent-at\")
+  attachment &Any
+  shape Shape
+  id Int
+}
+
+type Point struct {
+  x Int
+  y Int
+  label optional String
+} representation tuple
+
+type Shape union {
+  | Int \"circle\"
+  | ShapePolygon \"polygon\"
+} representation keyed
+
+type ShapePolygon [Int]
+
+type Status enum {
+  | Active (\"active\")
+  | Gone (\"gone\")
+}
+
+type Value union {
+  | Int int
+  | String string
+  | Shape map
+} representation kinded
+";
+        assert_eq!(schema.to_string(), expected);
+
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "message: Ranges have no IPLD equivalent and are ignored",
+                "message: \".size\" has no IPLD equivalent and is ignored",
+                "message: Only entries with a text key are supported in structs",
+            ]
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        for file in [
+            include_str!("../test/message.ipldsch"),
+            include_str!("../test/union.ipldsch"),
+        ] {
+            let schema = IpldSchema::parse(file).unwrap();
+            let (imported, _) = IpldSchema::from_cddl(&schema.generate_cddl()).unwrap();
+            assert_eq!(imported.check(), vec![], "{}", imported);
+        }
+
+        let schema = IpldSchema::parse(include_str!("../test/message.ipldsch")).unwrap();
+        let (imported, warnings) = IpldSchema::from_cddl(&schema.generate_cddl()).unwrap();
+        // Renames are only kept, if the key is not a valid field name
+        let expected = "\
+type Identity string
+
+# A message
+type Message struct {
+  # Who sent it
+  from &Any
+  to nullable [nullable Identity]
+  s Status
+  count Int (implicit \"0\")
+  labels optional {Status:Int}
+  d Bytes
+}
+
+type Status enum {
+  | Act
+  | Gone
+}
+";
+        assert_eq!(imported.to_string(), expected);
+        assert_eq!(warnings, vec![]);
+
+        assert!(matches!(
+            IpldSchema::from_cddl("a = {\n  b: \n"),
+            Err(ImportError::InvalidCddl { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use thiserror::Error;
+
+use crate::{
+    enumerate::EnumType,
+    structural::{StructField, StructRepresentation},
+    visit::{walk_struct_field, walk_type, Visitor},
+    InlineIpldType, IpldSchema, IpldType, PRELUDE,
+};
+
+/// A semantic error in a schema, that parsed successfully
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SchemaError {
+    #[error("Type \"{reference}\" referenced by \"{ty}\" is not defined")]
+    UndefinedType { ty: String, reference: String },
+    #[error("Field \"{field}\" of \"{ty}\" is defined more than once")]
+    DuplicateField { ty: String, field: String },
+    #[error("Member \"{member}\" of \"{ty}\" is defined more than once")]
+    DuplicateMember { ty: String, member: String },
+    #[error("Field \"{field}\" of \"{ty}\" can not be optional and have an implicit value")]
+    OptionalImplicit { ty: String, field: String },
+    #[error("Type \"{ty}\" is a copy of itself")]
+    CopyCycle { ty: String },
+}
+
+impl IpldSchema {
+    /// Checks the schema for semantic errors, which are not caught by the parser.
+    ///
+    /// Returns all errors found, an empty list means the schema is valid.
+    pub fn check(&self) -> Vec<SchemaError> {
+        let mut checker = Checker {
+            schema: self,
+            fields: BTreeSet::new(),
+            errors: vec![],
+        };
+        self.visit(&mut checker);
+        checker.errors
+    }
+
+    /// Collects the names of the types, that have values, which do not nest forever.
+    ///
+    /// `type Node struct { next Node }` has no values, as every value needs another one.
+    /// Each type is mapped to the round it was found in, its values can be built from
+    /// values of the types found in earlier rounds.
+    pub(crate) fn inhabited(&self) -> BTreeMap<String, usize> {
+        let mut inhabited: BTreeMap<String, usize> =
+            PRELUDE.iter().map(|name| (name.to_string(), 0)).collect();
+
+        // Every round finds the types, whose values can be built from the ones found before
+        for round in 1.. {
+            let found = self
+                .0
+                .iter()
+                .filter(|(name, def)| {
+                    !inhabited.contains_key(*name) && is_inhabited(&def.ty, &inhabited)
+                })
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            if found.is_empty() {
+                break;
+            }
+            inhabited.extend(found.into_iter().map(|name| (name, round)));
+        }
+        inhabited
+    }
+}
+
+/// Returns true, if values of `ty` can be built from values of the `inhabited` types
+pub(crate) fn has_value(ty: &InlineIpldType, inhabited: &BTreeMap<String, usize>) -> bool {
+    match ty {
+        InlineIpldType::Name(name) => inhabited.contains_key(name),
+        // Lists and maps can be empty, links are values of their own
+        InlineIpldType::List(_) | InlineIpldType::Map(_) | InlineIpldType::Link(_) => true,
+    }
+}
+
+fn is_inhabited(ty: &IpldType, inhabited: &BTreeMap<String, usize>) -> bool {
+    match ty {
+        IpldType::Struct(stru) => {
+            let leading = match stru.repr {
+                StructRepresentation::Tuple => stru.required_tuple_fields(),
+                _ => 0,
+            };
+            stru.fields.iter().enumerate().all(|(idx, field)| {
+                let required = idx < leading || field.is_required();
+                !required || field.nullable || has_value(&field.value, inhabited)
+            })
+        }
+        IpldType::Union(union) => union
+            .members
+            .iter()
+            .any(|member| has_value(&member.ty, inhabited)),
+        IpldType::Enum(enu) => !enu.members.is_empty(),
+        IpldType::Copy(from) => inhabited.contains_key(from),
+        _ => true,
+    }
+}
+
+/// Collects the errors of the nodes while visiting a schema
+struct Checker<'a> {
+    schema: &'a IpldSchema,
+    /// The fields of the current struct
+    fields: BTreeSet<String>,
+    errors: Vec<SchemaError>,
+}
+
+impl Visitor for Checker<'_> {
+    fn visit_type(&mut self, name: &str, doc: Option<&str>, ty: &IpldType) {
+        self.fields.clear();
+        match ty {
+            IpldType::Enum(enu) => check_enum(name, enu, &mut self.errors),
+            IpldType::Copy(_) if is_copy_cycle(self.schema, name) => {
+                self.errors.push(SchemaError::CopyCycle {
+                    ty: name.to_string(),
+                })
+            }
+            _ => (),
+        }
+        walk_type(self, name, ty);
+    }
+
+    fn visit_struct_field(&mut self, parent: &str, field: &StructField) {
+        walk_struct_field(self, parent, field);
+
+        if !self.fields.insert(field.key.clone()) {
+            self.errors.push(SchemaError::DuplicateField {
+                ty: parent.to_string(),
+                field: field.key.clone(),
+            });
+        }
+
+        if field.optional && field.implicit.is_some() {
+            self.errors.push(SchemaError::OptionalImplicit {
+                ty: parent.to_string(),
+                field: field.key.clone(),
+            });
+        }
+    }
+
+    fn visit_reference(&mut self, parent: &str, name: &str) {
+        if self.schema.resolve(name).is_none() {
+            self.errors.push(SchemaError::UndefinedType {
+                ty: parent.to_string(),
+                reference: name.to_string(),
+            });
+        }
+    }
+}
+
+fn check_enum(ty: &str, enu: &EnumType, errors: &mut Vec<SchemaError>) {
+    let mut names = BTreeSet::new();
+    let mut tags = vec![];
+
+    for member in &enu.members {
+        if !names.insert(member.name.as_str()) || tags.contains(&&member.tag) {
+            errors.push(SchemaError::DuplicateMember {
+                ty: ty.to_string(),
+                member: member.name.clone(),
+            });
+        }
+        tags.push(&member.tag);
+    }
+}
+
+/// Returns true, if following the copies starting at `name` leads back to `name`
+fn is_copy_cycle(schema: &IpldSchema, name: &str) -> bool {
+    let mut seen = BTreeSet::new();
+    let mut current = name;
+
+    while let Some(IpldType::Copy(from)) = schema.0.get(current).map(|def| &def.ty) {
+        if from == name {
+            return true;
+        }
+        // The copies run into a cycle, that does not contain `name`
+        if !seen.insert(from.as_str()) {
+            return false;
+        }
+        current = from;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check() {
+        for file in [
+            include_str!("../test/primitive.ipldsch"),
+            include_str!("../test/enums.ipldsch"),
+            include_str!("../test/maps.ipldsch"),
+            include_str!("../test/example.ipldsch"),
+        ] {
+            assert_eq!(IpldSchema::parse(file).unwrap().check(), vec![]);
+        }
+
+        let schema =
+            IpldSchema::parse("type Foo struct {\n  x Bar\n  x optional Int (implicit \"1\")\n}")
+                .unwrap();
+
+        assert_eq!(
+            schema.check(),
+            vec![
+                SchemaError::UndefinedType {
+                    ty: "Foo".to_string(),
+                    reference: "Bar".to_string()
+                },
+                SchemaError::DuplicateField {
+                    ty: "Foo".to_string(),
+                    field: "x".to_string()
+                },
+                SchemaError::OptionalImplicit {
+                    ty: "Foo".to_string(),
+                    field: "x".to_string()
+                },
+            ]
+        );
+
+        let schema =
+            IpldSchema::parse("type Aa = Bb\ntype Bb = Aa\ntype Cc = Aa\ntype Dd = Dd").unwrap();
+        assert_eq!(
+            schema.check(),
+            vec![
+                SchemaError::CopyCycle {
+                    ty: "Aa".to_string()
+                },
+                SchemaError::CopyCycle {
+                    ty: "Bb".to_string()
+                },
+                SchemaError::CopyCycle {
+                    ty: "Dd".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn inhabited() {
+        let schema = IpldSchema::parse(
+            "\
+type Node struct {
+  next Node
+}
+
+type List struct {
+  next nullable List
+}
+
+type Tree union {
+  | Node \"node\"
+  | List \"list\"
+} representation keyed
+
+type Loop union {
+  | Loop \"loop\"
+} representation keyed
+
+type Pair struct {
+  first optional Node
+  second Int
+} representation tuple
+
+type Copy = Node
+",
+        )
+        .unwrap();
+
+        let inhabited = schema.inhabited();
+        for name in ["List", "Tree", "Int"] {
+            assert!(inhabited.contains_key(name), "{}", name);
+        }
+        for name in ["Node", "Loop", "Pair", "Copy"] {
+            assert!(!inhabited.contains_key(name), "{}", name);
+        }
+    }
+}
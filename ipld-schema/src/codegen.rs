@@ -0,0 +1,331 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use crate::{
+    enumerate::{EnumMemberTag, EnumType},
+    structural::StructType,
+    union::UnionType,
+    InlineIpldType, IpldSchema, IpldType,
+};
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "yield",
+];
+
+/// The Rust types of the prelude
+const RUST_PRELUDE: &[(&str, &str)] = &[
+    ("Bool", "bool"),
+    ("String", "String"),
+    ("Bytes", "Vec<u8>"),
+    ("Int", "i64"),
+    ("Float", "f64"),
+    ("Map", "BTreeMap<String, Option<Ipld>>"),
+    ("List", "Vec<Option<Ipld>>"),
+    ("Link", "Cid"),
+    ("Any", "Ipld"),
+];
+
+/// Keywords, that can not be used as raw identifiers
+const RESERVED_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+impl IpldSchema {
+    /// Generates Rust type definitions for all types of this schema.
+    ///
+    /// The generated code models the types of the schema, not their representation.
+    /// It depends on `ipld_core` for links and `Any` values. The schema should pass
+    /// [`IpldSchema::check`], i.e. copies that form a cycle generate type aliases, that do
+    /// not compile.
+    pub fn generate_rust(&self) -> String {
+        let mut out = String::new();
+        out.push_str("// Generated by ipld-schema, do not edit.\n\n");
+        out.push_str("#![allow(dead_code)]\n\n");
+        out.push_str("use ipld_core::{cid::Cid, ipld::Ipld};\n");
+        out.push_str("use std::collections::BTreeMap;\n");
+
+        for (name, def) in &self.0 {
+            out.push('\n');
+            write_doc(&mut out, def.doc.as_deref(), "");
+
+            match &def.ty {
+                IpldType::Struct(stru) => self.generate_struct(&mut out, name, stru),
+                IpldType::Union(union) => self.generate_union(&mut out, name, union),
+                IpldType::Enum(enu) => generate_enum(&mut out, name, enu),
+                IpldType::Unit(_) => {
+                    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]\n");
+                    let _ = writeln!(out, "pub struct {};", name);
+                }
+                ty => {
+                    let _ = writeln!(out, "pub type {} = {};", name, self.rust_type(ty));
+                }
+            }
+        }
+
+        out
+    }
+
+    fn rust_type(&self, ty: &IpldType) -> String {
+        match ty {
+            IpldType::Bool => "bool".to_string(),
+            IpldType::String => "String".to_string(),
+            IpldType::Bytes(_) => "Vec<u8>".to_string(),
+            IpldType::Int => "i64".to_string(),
+            IpldType::Float => "f64".to_string(),
+            IpldType::Any => "Ipld".to_string(),
+            IpldType::Link(_) => "Cid".to_string(),
+            IpldType::List(list) => {
+                format!("Vec<{}>", self.rust_nullable(&list.ty, list.nullable))
+            }
+            IpldType::Map(map) => format!(
+                "BTreeMap<{}, {}>",
+                self.rust_name(&map.key),
+                self.rust_nullable(&map.value, map.nullable)
+            ),
+            IpldType::Copy(from) => self.rust_name(from),
+            IpldType::Struct(_) | IpldType::Union(_) | IpldType::Enum(_) | IpldType::Unit(_) => {
+                unreachable!("Structs, unions, enums and units are generated as their own items")
+            }
+        }
+    }
+
+    fn rust_name(&self, name: &str) -> String {
+        self.target_name(name, RUST_PRELUDE)
+    }
+
+    fn rust_inline(&self, ty: &InlineIpldType) -> String {
+        match ty {
+            InlineIpldType::Name(name) => self.rust_name(name),
+            InlineIpldType::List(list) => {
+                format!("Vec<{}>", self.rust_nullable(&list.ty, list.nullable))
+            }
+            InlineIpldType::Map(map) => format!(
+                "BTreeMap<{}, {}>",
+                self.rust_name(&map.key),
+                self.rust_nullable(&map.value, map.nullable)
+            ),
+            InlineIpldType::Link(_) => "Cid".to_string(),
+        }
+    }
+
+    fn rust_nullable(&self, ty: &InlineIpldType, nullable: bool) -> String {
+        if nullable {
+            format!("Option<{}>", self.rust_inline(ty))
+        } else {
+            self.rust_inline(ty)
+        }
+    }
+
+    /// Returns true, if the generated type of `name` contains the struct `target` without
+    /// an indirection, like a `Vec` or a `BTreeMap`
+    fn contains_by_value<'a>(
+        &'a self,
+        name: &'a str,
+        target: &str,
+        seen: &mut BTreeSet<&'a str>,
+    ) -> bool {
+        if name == target {
+            return true;
+        }
+        if !seen.insert(name) {
+            return false;
+        }
+
+        match self.0.get(name).map(|def| &def.ty) {
+            Some(IpldType::Struct(stru)) => stru.fields.iter().any(|field| match &field.value {
+                InlineIpldType::Name(value) => self.contains_by_value(value, target, seen),
+                _ => false,
+            }),
+            Some(IpldType::Union(union)) => union.members.iter().any(|member| match &member.ty {
+                InlineIpldType::Name(value) => self.contains_by_value(value, target, seen),
+                _ => false,
+            }),
+            Some(IpldType::Copy(from)) => self.contains_by_value(from, target, seen),
+            _ => false,
+        }
+    }
+
+    fn generate_struct(&self, out: &mut String, name: &str, stru: &StructType) {
+        out.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+        if stru.fields.is_empty() {
+            let _ = writeln!(out, "pub struct {};", name);
+            return;
+        }
+
+        let _ = writeln!(out, "pub struct {} {{", name);
+        for field in &stru.fields {
+            write_doc(out, field.doc.as_deref(), "    ");
+
+            let mut ty = self.rust_inline(&field.value);
+            // Recursion needs an indirection to have a known size
+            if matches!(&field.value, InlineIpldType::Name(value)
+                if self.contains_by_value(value, name, &mut BTreeSet::new()))
+            {
+                ty = format!("Box<{}>", ty);
+            }
+            if field.nullable {
+                ty = format!("Option<{}>", ty);
+            }
+            if field.optional {
+                ty = format!("Option<{}>", ty);
+            }
+
+            let _ = writeln!(out, "    pub {}: {},", field_name(&field.key), ty);
+        }
+        out.push_str("}\n");
+    }
+
+    /// Unions become enums with a variant for every member, named after the member type
+    fn generate_union(&self, out: &mut String, name: &str, union: &UnionType) {
+        out.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+        let _ = writeln!(out, "pub enum {} {{", name);
+        for member in &union.members {
+            write_doc(out, member.doc.as_deref(), "    ");
+
+            let mut ty = self.rust_inline(&member.ty);
+            if matches!(&member.ty, InlineIpldType::Name(value)
+                if self.contains_by_value(value, name, &mut BTreeSet::new()))
+            {
+                ty = format!("Box<{}>", ty);
+            }
+            let _ = writeln!(out, "    {}({}),", member.name(), ty);
+        }
+        out.push_str("}\n");
+    }
+}
+
+fn generate_enum(out: &mut String, name: &str, enu: &EnumType) {
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    let _ = writeln!(out, "pub enum {} {{", name);
+    for member in &enu.members {
+        write_doc(out, member.doc.as_deref(), "    ");
+        match member.tag {
+            EnumMemberTag::Int(tag) => {
+                let _ = writeln!(out, "    /// Represented as `{}`", tag);
+            }
+            EnumMemberTag::String(ref tag) if *tag != member.name => {
+                let _ = writeln!(out, "    /// Represented as `\"{}\"`", tag);
+            }
+            EnumMemberTag::String(_) => (),
+        }
+        let _ = writeln!(out, "    {},", member.name);
+    }
+    out.push_str("}\n");
+}
+
+fn write_doc(out: &mut String, doc: Option<&str>, indent: &str) {
+    if let Some(doc) = doc {
+        for line in doc.lines() {
+            let _ = writeln!(out, "{}/// {}", indent, line);
+        }
+    }
+}
+
+/// Converts a field name into a snake case Rust identifier
+fn field_name(name: &str) -> String {
+    let mut out = String::new();
+    for (idx, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if idx != 0 && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+
+    if RUST_KEYWORDS.contains(&out.as_str()) {
+        format!("r#{}", out)
+    } else if RESERVED_KEYWORDS.contains(&out.as_str()) {
+        format!("{}_", out)
+    } else if out.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", out)
+    } else {
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_rust() {
+        let file = include_str!("../test/example.ipldsch");
+        let schema = IpldSchema::parse(file).unwrap();
+
+        let expected = "\
+// Generated by ipld-schema, do not edit.
+
+#![allow(dead_code)]
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+use std::collections::BTreeMap;
+
+pub type ExampleOfAny = Ipld;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ExampleOfUnit;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExampleWithAnonDefns {
+    pub foo_field: Option<BTreeMap<String, String>>,
+    pub bar_field: Option<BTreeMap<String, String>>,
+    pub baz_field: BTreeMap<String, Option<String>>,
+    pub woz_field: BTreeMap<String, Vec<Option<String>>>,
+    pub boom_field: Cid,
+}
+
+pub type ExampleWithNullable = BTreeMap<String, Option<Cid>>;
+";
+
+        assert_eq!(schema.generate_rust(), expected);
+    }
+
+    #[test]
+    fn recursion_and_keywords() {
+        let schema = IpldSchema::parse(
+            "\
+type Aa struct {
+  b Bb
+  list [Aa]
+}
+
+type Bb struct {
+  a optional Cc
+  self Int
+  crate Int
+  type Int
+}
+
+type Cc = Aa
+",
+        )
+        .unwrap();
+        let code = schema.generate_rust();
+
+        assert!(code.contains("pub b: Box<Bb>,"), "{}", code);
+        assert!(code.contains("pub list: Vec<Aa>,"), "{}", code);
+        assert!(code.contains("pub a: Option<Box<Cc>>,"), "{}", code);
+        assert!(code.contains("pub self_: i64,"), "{}", code);
+        assert!(code.contains("pub crate_: i64,"), "{}", code);
+        assert!(code.contains("pub r#type: i64,"), "{}", code);
+    }
+
+    #[test]
+    fn unions() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+        let code = schema.generate_rust();
+
+        assert!(
+            code.contains(
+                "pub enum Shape {\n    /// A circle\n    Circle(Circle),\n    Square(Cid),\n}"
+            ),
+            "{}",
+            code
+        );
+        assert!(code.contains("    Shape(Shape),"), "{}", code);
+        assert!(code.contains("    Int(i64),"), "{}", code);
+    }
+}
@@ -0,0 +1,710 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    enumerate::{EnumMemberTag, EnumType},
+    structural::{StructField, StructType},
+    union::UnionType,
+    InlineIpldType, IpldSchema, IpldType,
+};
+
+/// How a change affects existing data and existing readers.
+///
+/// A change is backward compatible, if data written with the old schema is valid under the
+/// new schema, and forward compatible, if data written with the new schema is valid under
+/// the old schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compatibility {
+    /// Backward and forward compatible
+    Full,
+    Backward,
+    Forward,
+    /// Neither backward nor forward compatible
+    Breaking,
+}
+
+impl Compatibility {
+    /// Combines the compatibility of two changes, i.e. the compatibility of applying both
+    pub fn and(self, other: Compatibility) -> Compatibility {
+        match (self, other) {
+            (Compatibility::Full, other) | (other, Compatibility::Full) => other,
+            (a, b) if a == b => a,
+            _ => Compatibility::Breaking,
+        }
+    }
+
+    /// Returns true, if `self` is at least as compatible as `required`.
+    ///
+    /// Every compatibility satisfies `Breaking`.
+    pub fn satisfies(self, required: Compatibility) -> bool {
+        self.and(required) == required
+    }
+}
+
+impl Display for Compatibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Compatibility::Full => "full",
+            Compatibility::Backward => "backward",
+            Compatibility::Forward => "forward",
+            Compatibility::Breaking => "breaking",
+        })
+    }
+}
+
+/// A single difference between two schemas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// The type, field or member that changed, i.e. `Foo.bar`
+    pub location: String,
+    pub description: String,
+    pub compatibility: Compatibility,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {}",
+            self.compatibility, self.location, self.description
+        )
+    }
+}
+
+/// The result of comparing two versions of a schema
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompatibilityReport {
+    pub changes: Vec<Change>,
+}
+
+impl CompatibilityReport {
+    /// The compatibility of all changes combined
+    pub fn compatibility(&self) -> Compatibility {
+        self.changes
+            .iter()
+            .fold(Compatibility::Full, |acc, change| {
+                acc.and(change.compatibility)
+            })
+    }
+
+    /// Returns the changes, that violate the required compatibility
+    pub fn violations(&self, required: Compatibility) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(move |change| !change.compatibility.satisfies(required))
+    }
+}
+
+impl Display for CompatibilityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "No changes");
+        }
+
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        writeln!(
+            f,
+            "{} changes, overall compatibility: {}",
+            self.changes.len(),
+            self.compatibility()
+        )
+    }
+}
+
+impl IpldSchema {
+    /// Compares this schema with a newer version of it and classifies every change.
+    ///
+    /// Documentation is ignored, only changes that affect which data is valid are reported.
+    pub fn compatibility(&self, new: &IpldSchema) -> CompatibilityReport {
+        let mut comparator = Comparator {
+            old: self,
+            new,
+            changes: vec![],
+            compared: BTreeMap::new(),
+        };
+
+        for (name, old) in &self.0 {
+            match new.0.get(name) {
+                Some(new) => comparator.compare_type(name, &old.ty, &new.ty),
+                None => comparator.push(name, "type was removed", Compatibility::Breaking),
+            }
+        }
+        for name in new.0.keys().filter(|name| !self.0.contains_key(*name)) {
+            comparator.push(name, "type was added", Compatibility::Full);
+        }
+
+        CompatibilityReport {
+            changes: comparator.changes,
+        }
+    }
+}
+
+struct Comparator<'a> {
+    old: &'a IpldSchema,
+    new: &'a IpldSchema,
+    changes: Vec<Change>,
+    /// Pairs of differently named types, that were already compared structurally, and whether
+    /// they differ
+    compared: BTreeMap<(String, String), bool>,
+}
+
+impl Comparator<'_> {
+    fn push(&mut self, location: &str, description: impl ToString, compatibility: Compatibility) {
+        self.changes.push(Change {
+            location: location.to_string(),
+            description: description.to_string(),
+            compatibility,
+        });
+    }
+
+    /// Records a flag, that allows more values when set, i.e. `nullable`
+    fn compare_relaxation(&mut self, location: &str, flag: &str, old: bool, new: bool) {
+        match (old, new) {
+            (false, true) => self.push(
+                location,
+                format!("became {}", flag),
+                Compatibility::Backward,
+            ),
+            (true, false) => self.push(
+                location,
+                format!("is no longer {}", flag),
+                Compatibility::Forward,
+            ),
+            _ => (),
+        }
+    }
+
+    fn compare_type(&mut self, location: &str, old: &IpldType, new: &IpldType) {
+        match (old, new) {
+            (IpldType::Copy(old), IpldType::Copy(new)) => self.compare_named(location, old, new),
+            (IpldType::Copy(old), new) => match self.old.resolve_inline(&name(old)) {
+                Some(old) => self.compare_type(location, &old, new),
+                None => self.push(location, "was undefined", Compatibility::Breaking),
+            },
+            (old, IpldType::Copy(new)) => match self.new.resolve_inline(&name(new)) {
+                Some(new) => self.compare_type(location, old, &new),
+                None => self.push(location, "became undefined", Compatibility::Breaking),
+            },
+            (IpldType::Bytes(old), IpldType::Bytes(new)) if old != new => self.push(
+                location,
+                format!(
+                    "representation changed from {} to {}",
+                    old.name(),
+                    new.name()
+                ),
+                Compatibility::Breaking,
+            ),
+            (IpldType::List(old), IpldType::List(new)) => {
+                if old.repr != new.repr {
+                    self.push(
+                        location,
+                        format!(
+                            "representation changed from {} to {}",
+                            old.repr.name(),
+                            new.repr.name()
+                        ),
+                        Compatibility::Breaking,
+                    );
+                }
+                self.compare_relaxation(location, "nullable", old.nullable, new.nullable);
+                self.compare_inline(location, &old.ty, &new.ty);
+            }
+            (IpldType::Map(old), IpldType::Map(new)) => {
+                if old.repr != new.repr {
+                    self.push(
+                        location,
+                        format!(
+                            "representation changed from {} to {}",
+                            old.repr.name(),
+                            new.repr.name()
+                        ),
+                        Compatibility::Breaking,
+                    );
+                }
+                self.compare_relaxation(location, "nullable", old.nullable, new.nullable);
+                self.compare_named(location, &old.key, &new.key);
+                self.compare_inline(location, &old.value, &new.value);
+            }
+            (IpldType::Link(old), IpldType::Link(new)) => {
+                self.compare_named(&format!("{} (link target)", location), old, new)
+            }
+            (IpldType::Struct(old), IpldType::Struct(new)) => {
+                self.compare_struct(location, old, new)
+            }
+            (IpldType::Union(old), IpldType::Union(new)) => self.compare_union(location, old, new),
+            (IpldType::Enum(old), IpldType::Enum(new)) => self.compare_enum(location, old, new),
+            (IpldType::Unit(old), IpldType::Unit(new)) if old != new => {
+                self.push(location, "representation changed", Compatibility::Breaking)
+            }
+            (_, IpldType::Any) if !matches!(old, IpldType::Any) => self.push(
+                location,
+                format!("changed from {} to any", old.type_kind()),
+                Compatibility::Backward,
+            ),
+            (IpldType::Any, _) if !matches!(new, IpldType::Any) => self.push(
+                location,
+                format!("changed from any to {}", new.type_kind()),
+                Compatibility::Forward,
+            ),
+            (old, new) if old.type_kind() != new.type_kind() => self.push(
+                location,
+                format!("changed from {} to {}", old.type_kind(), new.type_kind()),
+                Compatibility::Breaking,
+            ),
+            _ => (),
+        }
+    }
+
+    /// Compares two references to types by the types they resolve to.
+    ///
+    /// Equal names are not compared, as top level types are compared on their own. A changed
+    /// name is only reported, if the types differ.
+    fn compare_named(&mut self, location: &str, old: &str, new: &str) {
+        if old == new {
+            return;
+        }
+        let reference = Change {
+            location: location.to_string(),
+            description: format!("references \"{}\" instead of \"{}\"", new, old),
+            compatibility: Compatibility::Full,
+        };
+
+        let pair = (old.to_string(), new.to_string());
+        if let Some(differs) = self.compared.get(&pair) {
+            // The differences were reported, where the pair was compared first
+            if *differs {
+                self.changes.push(reference);
+            }
+            return;
+        }
+        // Recursive types reference the pair again, while it is compared
+        self.compared.insert(pair.clone(), false);
+
+        let start = self.changes.len();
+        match (
+            self.old.resolve_inline(&name(old)),
+            self.new.resolve_inline(&name(new)),
+        ) {
+            (Some(old), Some(new)) => self.compare_type(location, &old, &new),
+            _ => self.push(
+                location,
+                "references an undefined type",
+                Compatibility::Breaking,
+            ),
+        }
+
+        let differs = self.changes.len() > start;
+        if differs {
+            self.changes.insert(start, reference);
+        }
+        self.compared.insert(pair, differs);
+    }
+
+    fn compare_inline(&mut self, location: &str, old: &InlineIpldType, new: &InlineIpldType) {
+        match (old, new) {
+            (InlineIpldType::Name(old), InlineIpldType::Name(new)) => {
+                self.compare_named(location, old, new)
+            }
+            (InlineIpldType::Link(old), InlineIpldType::Link(new)) => {
+                self.compare_named(&format!("{} (link target)", location), old, new)
+            }
+            (old, new) => {
+                let old = self.inline_type(self.old, old);
+                let new = self.inline_type(self.new, new);
+                match (old, new) {
+                    (Some(old), Some(new)) => self.compare_type(location, &old, &new),
+                    _ => self.push(
+                        location,
+                        "references an undefined type",
+                        Compatibility::Breaking,
+                    ),
+                }
+            }
+        }
+    }
+
+    fn inline_type(&self, schema: &IpldSchema, ty: &InlineIpldType) -> Option<IpldType> {
+        schema.resolve_inline(ty).map(|ty| ty.into_owned())
+    }
+
+    fn compare_struct(&mut self, location: &str, old: &StructType, new: &StructType) {
+        if old.repr != new.repr {
+            self.push(
+                location,
+                format!(
+                    "representation changed from {} to {}",
+                    old.repr.name(),
+                    new.repr.name()
+                ),
+                Compatibility::Breaking,
+            );
+        }
+
+        // Fields are matched by how they appear in the data
+        let old_fields = fields_by_key(old);
+        let new_fields = fields_by_key(new);
+        let renames = renamed_fields(old, new);
+
+        for (key, old) in &old_fields {
+            let field_location = format!("{}.{}", location, old.key);
+            match (new_fields.get(key), renames.get(key)) {
+                (Some(new), _) => self.compare_field(&field_location, old, new),
+                (None, Some(new)) => self.push(
+                    &field_location,
+                    format!(
+                        "was renamed to \"{}\", add (rename \"{}\") to keep its representation",
+                        new.key, key
+                    ),
+                    Compatibility::Breaking,
+                ),
+                (None, None) if old.optional || old.implicit.is_some() => self.push(
+                    &field_location,
+                    "optional field was removed",
+                    Compatibility::Forward,
+                ),
+                (None, None) => self.push(
+                    &field_location,
+                    "required field was removed",
+                    Compatibility::Breaking,
+                ),
+            }
+        }
+
+        for (key, new) in &new_fields {
+            let renamed = renames.values().any(|renamed| std::ptr::eq(*renamed, *new));
+            if old_fields.contains_key(key) || renamed {
+                continue;
+            }
+            let field_location = format!("{}.{}", location, new.key);
+            if new.optional || new.implicit.is_some() {
+                self.push(
+                    &field_location,
+                    "optional field was added",
+                    Compatibility::Backward,
+                );
+            } else {
+                self.push(
+                    &field_location,
+                    "required field was added",
+                    Compatibility::Breaking,
+                );
+            }
+        }
+    }
+
+    fn compare_field(&mut self, location: &str, old: &StructField, new: &StructField) {
+        if old.key != new.key {
+            self.push(
+                location,
+                format!(
+                    "was renamed to \"{}\", but keeps its representation",
+                    new.key
+                ),
+                Compatibility::Full,
+            );
+        }
+
+        self.compare_relaxation(location, "optional", old.optional, new.optional);
+        self.compare_relaxation(location, "nullable", old.nullable, new.nullable);
+
+        match (&old.implicit, &new.implicit) {
+            (Some(old), Some(new)) if old != new => self.push(
+                location,
+                format!("implicit value changed from {} to {}", old, new),
+                Compatibility::Breaking,
+            ),
+            (old, new) => {
+                self.compare_relaxation(location, "implicit", old.is_some(), new.is_some())
+            }
+        }
+
+        self.compare_inline(location, &old.value, &new.value);
+    }
+
+    /// Members are matched by their discriminant, which is what the data contains
+    fn compare_union(&mut self, location: &str, old: &UnionType, new: &UnionType) {
+        if old.repr != new.repr {
+            self.push(location, "representation changed", Compatibility::Breaking);
+            return;
+        }
+
+        for member in &old.members {
+            let member_location = format!("{}.{}", location, member.discriminant);
+            match new.member(&member.discriminant) {
+                Some(new) => self.compare_inline(&member_location, &member.ty, &new.ty),
+                None => self.push(
+                    &member_location,
+                    "member was removed",
+                    Compatibility::Forward,
+                ),
+            }
+        }
+
+        for member in &new.members {
+            if old.member(&member.discriminant).is_none() {
+                self.push(
+                    &format!("{}.{}", location, member.discriminant),
+                    "member was added",
+                    Compatibility::Backward,
+                );
+            }
+        }
+    }
+
+    fn compare_enum(&mut self, location: &str, old: &EnumType, new: &EnumType) {
+        if old.repr != new.repr {
+            self.push(location, "representation changed", Compatibility::Breaking);
+            return;
+        }
+
+        for member in &old.members {
+            let member_location = format!("{}.{}", location, member.name);
+            let renamed = new.members.iter().find(|new| new.tag == member.tag);
+            let retagged = new.members.iter().find(|new| new.name == member.name);
+
+            match (renamed, retagged) {
+                (Some(new), _) if new.name != member.name => self.push(
+                    &member_location,
+                    format!(
+                        "was renamed to \"{}\", but keeps its representation",
+                        new.name
+                    ),
+                    Compatibility::Full,
+                ),
+                (Some(_), _) => (),
+                (None, Some(new)) => self.push(
+                    &member_location,
+                    format!(
+                        "representation changed from {} to {}",
+                        tag_name(&member.tag),
+                        tag_name(&new.tag)
+                    ),
+                    Compatibility::Breaking,
+                ),
+                (None, None) => self.push(
+                    &member_location,
+                    "member was removed",
+                    Compatibility::Forward,
+                ),
+            }
+        }
+
+        for member in &new.members {
+            let known = old
+                .members
+                .iter()
+                .any(|old| old.tag == member.tag || old.name == member.name);
+            if !known {
+                self.push(
+                    &format!("{}.{}", location, member.name),
+                    "member was added",
+                    Compatibility::Backward,
+                );
+            }
+        }
+    }
+}
+
+/// Pairs the removed fields with the added fields, that were renamed from them.
+///
+/// A removed field is renamed, if the added field at the same position among the added fields
+/// has the same type and flags. The fields of `new` are keyed by the old key in the data.
+fn renamed_fields<'a>(
+    old: &'a StructType,
+    new: &'a StructType,
+) -> BTreeMap<&'a str, &'a StructField> {
+    let old_keys = fields_by_key(old);
+    let new_keys = fields_by_key(new);
+    let removed = old
+        .fields
+        .iter()
+        .filter(|field| !new_keys.contains_key(field_key(field)));
+    let added = new
+        .fields
+        .iter()
+        .filter(|field| !old_keys.contains_key(field_key(field)));
+
+    removed
+        .zip(added)
+        .filter(|(old, new)| {
+            old.value == new.value
+                && old.optional == new.optional
+                && old.nullable == new.nullable
+                && old.implicit == new.implicit
+        })
+        .map(|(old, new)| (field_key(old), new))
+        .collect()
+}
+
+fn field_key(field: &StructField) -> &str {
+    field.rename.as_deref().unwrap_or(&field.key)
+}
+
+fn name(name: &str) -> InlineIpldType {
+    InlineIpldType::Name(name.to_string())
+}
+
+fn fields_by_key(stru: &StructType) -> BTreeMap<&str, &StructField> {
+    stru.fields
+        .iter()
+        .map(|field| (field_key(field), field))
+        .collect()
+}
+
+fn tag_name(tag: &EnumMemberTag) -> String {
+    match tag {
+        EnumMemberTag::Int(tag) => tag.to_string(),
+        EnumMemberTag::String(tag) => format!("\"{}\"", tag),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare(old: &str, new: &str) -> Vec<(String, Compatibility)> {
+        let old = IpldSchema::parse(old).unwrap();
+        let new = IpldSchema::parse(new).unwrap();
+        old.compatibility(&new)
+            .changes
+            .into_iter()
+            .map(|change| (change.to_string(), change.compatibility))
+            .collect()
+    }
+
+    #[test]
+    fn compatibility() {
+        assert_eq!(
+            Compatibility::Backward.and(Compatibility::Full),
+            Compatibility::Backward
+        );
+        assert_eq!(
+            Compatibility::Backward.and(Compatibility::Forward),
+            Compatibility::Breaking
+        );
+        assert!(Compatibility::Full.satisfies(Compatibility::Forward));
+        assert!(!Compatibility::Backward.satisfies(Compatibility::Forward));
+        assert!(Compatibility::Breaking.satisfies(Compatibility::Breaking));
+
+        let file = include_str!("../test/example.ipldsch");
+        let schema = IpldSchema::parse(file).unwrap();
+        let report = schema.compatibility(&schema);
+        assert_eq!(report.changes, vec![]);
+        assert_eq!(report.to_string(), "No changes\n");
+    }
+
+    #[test]
+    fn compare_structs_and_enums() {
+        let old = "\
+type Foo struct {
+  a Int
+  b optional String
+  c Int
+  d String (rename \"dd\")
+  e nullable Int
+}
+
+type Status enum {
+  | Active (\"Act\")
+  | Inactive (\"In\")
+  | Gone
+}
+";
+        let new = "\
+type Foo struct {
+  a optional Int
+  c String
+  delta String (rename \"dd\")
+  e Int
+  f optional Bool
+}
+
+type Status enum {
+  | Running (\"Act\")
+  | Inactive (\"Off\")
+  | Paused
+}
+
+type Bar string
+";
+
+        assert_eq!(
+            compare(old, new),
+            vec![
+                ("[backward] Foo.a: became optional".to_string(), Compatibility::Backward),
+                ("[forward] Foo.b: optional field was removed".to_string(), Compatibility::Forward),
+                ("[full] Foo.c: references \"String\" instead of \"Int\"".to_string(), Compatibility::Full),
+                ("[breaking] Foo.c: changed from int to string".to_string(), Compatibility::Breaking),
+                ("[full] Foo.d: was renamed to \"delta\", but keeps its representation".to_string(), Compatibility::Full),
+                ("[forward] Foo.e: is no longer nullable".to_string(), Compatibility::Forward),
+                ("[backward] Foo.f: optional field was added".to_string(), Compatibility::Backward),
+                ("[full] Status.Active: was renamed to \"Running\", but keeps its representation".to_string(), Compatibility::Full),
+                ("[breaking] Status.Inactive: representation changed from \"In\" to \"Off\"".to_string(), Compatibility::Breaking),
+                ("[forward] Status.Gone: member was removed".to_string(), Compatibility::Forward),
+                ("[backward] Status.Paused: member was added".to_string(), Compatibility::Backward),
+                ("[full] Bar: type was added".to_string(), Compatibility::Full),
+            ]
+        );
+
+        let old = IpldSchema::parse(old).unwrap();
+        let new = IpldSchema::parse(new).unwrap();
+        let report = old.compatibility(&new);
+        assert_eq!(report.compatibility(), Compatibility::Breaking);
+        assert_eq!(report.violations(Compatibility::Backward).count(), 5);
+        assert_eq!(
+            new.compatibility(&old).compatibility(),
+            Compatibility::Breaking
+        );
+    }
+
+    #[test]
+    fn compare_renames_and_references() {
+        let old = "\
+type Foo struct {
+  name String
+  owner &User
+  id UserId
+}
+
+type User struct {
+  name String
+}
+
+type UserId = Int
+";
+        let new = "\
+type Foo struct {
+  fullName String
+  owner &Person
+  id PersonId
+}
+
+type User struct {
+  name String
+}
+
+type Person struct {
+  name String
+}
+
+type UserId = Int
+
+type PersonId = String
+";
+
+        assert_eq!(
+            compare(old, new),
+            vec![
+                ("[full] Foo.id: references \"PersonId\" instead of \"UserId\"".to_string(), Compatibility::Full),
+                ("[breaking] Foo.id: changed from int to string".to_string(), Compatibility::Breaking),
+                ("[breaking] Foo.name: was renamed to \"fullName\", add (rename \"name\") to keep its representation".to_string(), Compatibility::Breaking),
+                ("[full] Person: type was added".to_string(), Compatibility::Full),
+                ("[full] PersonId: type was added".to_string(), Compatibility::Full),
+            ]
+        );
+    }
+}
@@ -0,0 +1,639 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+use thiserror::Error;
+
+use crate::{
+    adl::{builtin, read_advanced, AdlRegistry},
+    list::ListRepresentation,
+    map::MapRepresentation,
+    store::BlockStore,
+    structural::StructRepresentation,
+    validate::list_pairs,
+    DataPath, InlineIpldType, IpldSchema, IpldType, Kind, ValidationError,
+};
+
+/// The multicodec of DAG-CBOR blocks
+pub const DAG_CBOR: u64 = 0x71;
+/// The multicodec of DAG-JSON blocks
+pub const DAG_JSON: u64 = 0x0129;
+/// The multicodec of raw blocks, which are bytes
+pub const RAW: u64 = 0x55;
+/// The multihash of CIDs that contain the block itself
+const IDENTITY: u64 = 0x00;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BlockError {
+    #[error("Block is missing from the store")]
+    Missing,
+    #[error("Failed to read block: {0}")]
+    Store(String),
+    #[error("Codec 0x{0:x} is not supported")]
+    UnsupportedCodec(u64),
+    #[error("Failed to decode block: {0}")]
+    Decode(String),
+    #[error(transparent)]
+    Invalid(#[from] ValidationError),
+    #[error("Link leads back to a block it was reached from")]
+    Cycle,
+    #[error("Block is more than {0} links away from the root")]
+    TooDeep(usize),
+    #[error("More than {0} blocks would be loaded")]
+    TooManyBlocks(usize),
+}
+
+/// The result of validating one block of a DAG
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockReport {
+    pub cid: Cid,
+    /// The type the block was expected to have
+    pub ty: String,
+    /// The block with the link to this block, or `None` for the root
+    pub parent: Option<Cid>,
+    /// The path of the link to the block inside the parent block
+    pub path: DataPath,
+    pub result: Result<(), BlockError>,
+}
+
+/// The blocks of a DAG in the order they were reached, parents before their children
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DagReport {
+    pub blocks: Vec<BlockReport>,
+}
+
+impl DagReport {
+    pub fn is_valid(&self) -> bool {
+        self.blocks.iter().all(|block| block.result.is_ok())
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &BlockReport> {
+        self.blocks.iter().filter(|block| block.result.is_err())
+    }
+
+    /// The path from the root block to the link to block `index`, through its parent blocks
+    pub fn path(&self, mut index: usize) -> DataPath {
+        let mut parts = vec![&self.blocks[index].path];
+        while let Some(parent) = self.blocks[index].parent {
+            // Parents are reported before their children, and only valid blocks are walked
+            let Some(parent) = self.blocks[..index]
+                .iter()
+                .rposition(|block| block.cid == parent && block.result.is_ok())
+            else {
+                break;
+            };
+            index = parent;
+            parts.push(&self.blocks[index].path);
+        }
+
+        let mut path = DataPath::default();
+        for segment in parts.iter().rev().flat_map(|part| part.segments()) {
+            path.push(segment);
+        }
+        path
+    }
+}
+
+impl Display for DagReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for block in &self.blocks {
+            write!(f, "{} {} at {}", block.cid, block.ty, block.path)?;
+            if let Some(parent) = &block.parent {
+                write!(f, " of {}", parent)?;
+            }
+            write!(f, ": ")?;
+            match &block.result {
+                Ok(()) => writeln!(f, "valid")?,
+                Err(err) => writeln!(f, "{}", err)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validates a DAG of blocks, following the links whose target type the schema declares.
+///
+/// Every block is decoded according to the codec of its CID and validated against the type
+/// of the link that leads to it. Blocks are validated once per type, even if they are reached
+/// multiple times. Identity CIDs are decoded from the CID itself.
+pub struct DagValidator<'a, S> {
+    schema: &'a IpldSchema,
+    store: &'a S,
+    adls: &'a AdlRegistry,
+    max_depth: Option<usize>,
+    max_blocks: Option<usize>,
+}
+
+impl IpldSchema {
+    /// Creates a validator for DAGs, whose blocks are loaded from `store`
+    pub fn dag_validator<'a, S: BlockStore>(&'a self, store: &'a S) -> DagValidator<'a, S> {
+        DagValidator {
+            schema: self,
+            store,
+            adls: builtin(),
+            max_depth: None,
+            max_blocks: None,
+        }
+    }
+}
+
+/// The state of one traversal
+#[derive(Default)]
+struct Walk {
+    report: DagReport,
+    /// The blocks and types, that were validated already
+    visited: BTreeSet<(Cid, String)>,
+    /// The blocks on the path from the root to the current block
+    ancestors: BTreeSet<Cid>,
+    /// The links, that are still to be followed
+    pending: Vec<Step>,
+    loaded: usize,
+    stopped: bool,
+}
+
+/// The next step of a traversal
+enum Step {
+    /// Follows the link to `cid` at `path` in `parent`
+    Visit {
+        cid: Cid,
+        ty: String,
+        parent: Option<Cid>,
+        path: DataPath,
+        depth: usize,
+    },
+    /// Leaves an ancestor, after all its links were followed
+    Leave(Cid),
+}
+
+impl<'a, S: BlockStore> DagValidator<'a, S> {
+    /// Does not follow links of blocks, that are `max_depth` links away from the root
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Reads data in advanced layouts through `adls` instead of the layouts of this crate
+    pub fn with_adls(mut self, adls: &'a AdlRegistry) -> Self {
+        self.adls = adls;
+        self
+    }
+
+    /// Stops after loading `max_blocks` blocks
+    pub fn with_max_blocks(mut self, max_blocks: usize) -> Self {
+        self.max_blocks = Some(max_blocks);
+        self
+    }
+
+    /// Validates the block `cid` as type `root` and all blocks reachable from it.
+    ///
+    /// Fails only if `root` is not defined, problems with blocks are part of the report.
+    pub fn validate(&self, root: &str, cid: Cid) -> Result<DagReport, ValidationError> {
+        self.validate_roots(root, [cid])
+    }
+
+    /// Validates the blocks `cids` as type `root` and all blocks reachable from them.
+    ///
+    /// Blocks shared by the DAGs are validated once, and the limits apply to all of them.
+    pub fn validate_roots(
+        &self,
+        root: &str,
+        cids: impl IntoIterator<Item = Cid>,
+    ) -> Result<DagReport, ValidationError> {
+        if self.schema.resolve(root).is_none() {
+            return Err(ValidationError::UnknownType(root.to_string()));
+        }
+
+        let mut walk = Walk::default();
+        let roots = cids.into_iter().collect::<Vec<_>>();
+        for cid in roots.into_iter().rev() {
+            walk.pending.push(Step::Visit {
+                cid,
+                ty: root.to_string(),
+                parent: None,
+                path: DataPath::default(),
+                depth: 0,
+            });
+        }
+        while let Some(step) = walk.pending.pop() {
+            if walk.stopped {
+                break;
+            }
+            match step {
+                Step::Visit {
+                    cid,
+                    ty,
+                    parent,
+                    path,
+                    depth,
+                } => self.visit(&mut walk, cid, ty, parent, path, depth),
+                Step::Leave(cid) => {
+                    walk.ancestors.remove(&cid);
+                }
+            }
+        }
+        Ok(walk.report)
+    }
+
+    fn visit(
+        &self,
+        walk: &mut Walk,
+        cid: Cid,
+        ty: String,
+        parent: Option<Cid>,
+        path: DataPath,
+        depth: usize,
+    ) {
+        let report = |ty: String, result| {
+            walk.report.blocks.push(BlockReport {
+                cid,
+                ty,
+                parent,
+                path,
+                result,
+            })
+        };
+
+        if walk.ancestors.contains(&cid) {
+            return report(ty, Err(BlockError::Cycle));
+        }
+        if walk.visited.contains(&(cid, ty.clone())) {
+            return;
+        }
+        if let Some(max_depth) = self.max_depth.filter(|max_depth| depth > *max_depth) {
+            return report(ty, Err(BlockError::TooDeep(max_depth)));
+        }
+        if let Some(max_blocks) = self
+            .max_blocks
+            .filter(|max_blocks| walk.loaded >= *max_blocks)
+        {
+            report(ty, Err(BlockError::TooManyBlocks(max_blocks)));
+            walk.stopped = true;
+            return;
+        }
+
+        walk.loaded += 1;
+        walk.visited.insert((cid, ty.clone()));
+        let data = match self.load(&cid) {
+            Ok(data) => data,
+            Err(err) => return report(ty, Err(err)),
+        };
+        if let Err(err) = self
+            .schema
+            .validate_with(&ty, &data, self.adls, Some(self.store))
+        {
+            return report(ty, Err(err.into()));
+        }
+
+        // Paths of links start at this block
+        let mut links = vec![];
+        self.schema.typed_links(
+            &InlineIpldType::Name(ty.clone()),
+            &data,
+            self.adls,
+            Some(self.store),
+            &mut DataPath::default(),
+            &mut links,
+        );
+        report(ty, Ok(()));
+
+        walk.ancestors.insert(cid);
+        walk.pending.push(Step::Leave(cid));
+        // In reverse, so the links are followed in order
+        for (path, link, target) in links.into_iter().rev() {
+            walk.pending.push(Step::Visit {
+                cid: link,
+                ty: target,
+                parent: Some(cid),
+                path,
+                depth: depth + 1,
+            });
+        }
+    }
+
+    fn load(&self, cid: &Cid) -> Result<Ipld, BlockError> {
+        load_block(self.store, cid)
+    }
+}
+
+/// Loads and decodes the block `cid` from `store`, identity CIDs contain the block itself
+pub(crate) fn load_block(store: &dyn BlockStore, cid: &Cid) -> Result<Ipld, BlockError> {
+    let block = if cid.hash().code() == IDENTITY {
+        Some(cid.hash().digest().to_vec())
+    } else {
+        store
+            .get(cid)
+            .map_err(|err| BlockError::Store(err.to_string()))?
+    };
+
+    decode_block(cid, &block.ok_or(BlockError::Missing)?)
+}
+
+/// Decodes a block according to the codec of its CID
+pub(crate) fn decode_block(cid: &Cid, block: &[u8]) -> Result<Ipld, BlockError> {
+    match cid.codec() {
+        DAG_CBOR => {
+            serde_ipld_dagcbor::from_slice(block).map_err(|err| BlockError::Decode(err.to_string()))
+        }
+        DAG_JSON => {
+            serde_ipld_dagjson::from_slice(block).map_err(|err| BlockError::Decode(err.to_string()))
+        }
+        RAW => Ok(Ipld::Bytes(block.to_vec())),
+        codec => Err(BlockError::UnsupportedCodec(codec)),
+    }
+}
+
+impl IpldSchema {
+    /// Collects the links in valid `data` of type `ty` with the types of their targets.
+    ///
+    /// Data in advanced layouts is read through `adls` and `store`.
+    pub(crate) fn typed_links(
+        &self,
+        ty: &InlineIpldType,
+        data: &Ipld,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+        path: &mut DataPath,
+        links: &mut Vec<(DataPath, Cid, String)>,
+    ) {
+        let Some(resolved) = self.resolve_inline(ty) else {
+            return;
+        };
+
+        match (resolved.as_ref(), data) {
+            (IpldType::Link(target), Ipld::Link(cid)) => {
+                links.push((path.clone(), *cid, target.clone()))
+            }
+            (IpldType::Any, data) => any_links(data, path, links),
+            (IpldType::List(list), data) => {
+                let logical;
+                let data = match &list.repr {
+                    ListRepresentation::List => data,
+                    ListRepresentation::Advanced(adl) => {
+                        logical = read_advanced(adls, "", adl, Kind::List, data, store, path)
+                            .unwrap_or(Ipld::Null);
+                        &logical
+                    }
+                };
+                let Ipld::List(items) = data else {
+                    return;
+                };
+                for (idx, item) in items.iter().enumerate() {
+                    path.push(idx);
+                    self.typed_links(&list.ty, item, adls, store, path, links);
+                    path.pop();
+                }
+            }
+            (IpldType::Map(map), data) => {
+                let logical;
+                let entries = match (&map.repr, data) {
+                    (MapRepresentation::Map, Ipld::Map(entries)) => entries.iter().collect(),
+                    (MapRepresentation::ListPairs, data) => {
+                        list_pairs("", data, path).unwrap_or_default()
+                    }
+                    (MapRepresentation::Advanced(adl), data) => {
+                        logical = read_advanced(adls, "", adl, Kind::Map, data, store, path);
+                        match &logical {
+                            Ok(Ipld::Map(entries)) => entries.iter().collect(),
+                            _ => vec![],
+                        }
+                    }
+                    _ => vec![],
+                };
+                for (key, value) in entries {
+                    path.push(key);
+                    self.typed_links(&map.value, value, adls, store, path, links);
+                    path.pop();
+                }
+            }
+            (IpldType::Struct(stru), data) => {
+                let entries = match (&stru.repr, data) {
+                    (StructRepresentation::Map, Ipld::Map(entries)) => entries.iter().collect(),
+                    (StructRepresentation::ListPairs, data) => {
+                        list_pairs("", data, path).unwrap_or_default()
+                    }
+                    (StructRepresentation::Tuple, Ipld::List(items)) => {
+                        for (idx, (field, item)) in stru.fields.iter().zip(items).enumerate() {
+                            path.push(idx);
+                            self.typed_links(&field.value, item, adls, store, path, links);
+                            path.pop();
+                        }
+                        return;
+                    }
+                    _ => vec![],
+                };
+                for field in &stru.fields {
+                    let key = field.rename.as_ref().unwrap_or(&field.key);
+                    if let Some((_, value)) = entries.iter().find(|(entry, _)| *entry == key) {
+                        path.push(key);
+                        self.typed_links(&field.value, value, adls, store, path, links);
+                        path.pop();
+                    }
+                }
+            }
+            (IpldType::Union(union), data) => match union.member_of(data) {
+                Some((member, Some(key), value)) => {
+                    path.push(key);
+                    self.typed_links(&member.ty, value, adls, store, path, links);
+                    path.pop();
+                }
+                Some((member, None, value)) => {
+                    self.typed_links(&member.ty, value, adls, store, path, links)
+                }
+                None => (),
+            },
+            _ => (),
+        }
+    }
+}
+
+/// Collects all links in untyped data, their targets can be anything
+fn any_links(data: &Ipld, path: &mut DataPath, links: &mut Vec<(DataPath, Cid, String)>) {
+    match data {
+        Ipld::Link(cid) => links.push((path.clone(), *cid, "Any".to_string())),
+        Ipld::List(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                path.push(idx);
+                any_links(item, path, links);
+                path.pop();
+            }
+        }
+        Ipld::Map(entries) => {
+            for (key, value) in entries {
+                path.push(key);
+                any_links(value, path, links);
+                path.pop();
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ipld_core::{cid::multihash::Multihash, ipld};
+
+    use super::*;
+    use crate::store::MemoryStore;
+
+    /// A CID with a made up SHA2-256 digest, the store does not check it
+    fn cid(n: u8) -> Cid {
+        Cid::new_v1(DAG_CBOR, Multihash::wrap(0x12, &[n; 32]).unwrap())
+    }
+
+    #[test]
+    fn validate_dag() {
+        let schema = IpldSchema::parse(
+            "\
+type Node struct {
+  name String
+  children [&Node]
+  leaf optional &Leaf
+}
+
+type Leaf struct {
+  value Int
+}
+",
+        )
+        .unwrap();
+
+        let mut store = MemoryStore::new();
+        let mut put = |n: u8, data: Ipld| {
+            store
+                .put(cid(n), serde_ipld_dagcbor::to_vec(&data).unwrap())
+                .unwrap()
+        };
+        put(
+            0,
+            ipld!({ "name": "root", "children": [cid(1), cid(2)], "leaf": cid(3) }),
+        );
+        put(1, ipld!({ "name": "a", "children": [cid(0), cid(4)] }));
+        put(3, ipld!({ "value": "one" }));
+        put(4, ipld!({ "name": "b", "children": [], "leaf": cid(3) }));
+
+        let mut value = DataPath::default();
+        value.push("value");
+        let invalid_leaf = Err(BlockError::Invalid(ValidationError::Kind {
+            path: value,
+            ty: "Int".to_string(),
+            expected: crate::Kind::Int,
+            found: crate::Kind::String,
+        }));
+
+        let report = schema
+            .dag_validator(&store)
+            .validate("Node", cid(0))
+            .unwrap();
+        let results = (0..report.blocks.len())
+            .map(|index| {
+                (
+                    report.path(index).to_string(),
+                    report.blocks[index].result.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                ("(root)".to_string(), Ok(())),
+                ("children/0".to_string(), Ok(())),
+                ("children/0/children/0".to_string(), Err(BlockError::Cycle)),
+                ("children/0/children/1".to_string(), Ok(())),
+                (
+                    "children/0/children/1/leaf".to_string(),
+                    invalid_leaf.clone()
+                ),
+                ("children/1".to_string(), Err(BlockError::Missing)),
+            ]
+        );
+        assert!(!report.is_valid());
+        assert_eq!(report.blocks[4].parent, Some(cid(4)));
+        assert_eq!(report.blocks[4].path.to_string(), "leaf");
+        assert_eq!(
+            report.to_string().lines().nth(4),
+            Some(
+                format!(
+                    "{} Leaf at leaf of {}: {}",
+                    cid(3),
+                    cid(4),
+                    invalid_leaf.clone().unwrap_err()
+                )
+                .as_str()
+            )
+        );
+
+        let report = schema
+            .dag_validator(&store)
+            .with_max_depth(1)
+            .validate("Node", cid(0))
+            .unwrap();
+        assert_eq!(
+            report
+                .errors()
+                .map(|block| &block.result)
+                .collect::<Vec<_>>(),
+            vec![
+                &Err(BlockError::Cycle),
+                &Err(BlockError::TooDeep(1)),
+                &Err(BlockError::Missing),
+                &invalid_leaf,
+            ]
+        );
+
+        let report = schema
+            .dag_validator(&store)
+            .with_max_blocks(2)
+            .validate("Node", cid(0))
+            .unwrap();
+        assert_eq!(report.blocks.len(), 4);
+        assert_eq!(report.blocks[3].result, Err(BlockError::TooManyBlocks(2)));
+
+        assert_eq!(
+            schema.dag_validator(&store).validate("Missing", cid(0)),
+            Err(ValidationError::UnknownType("Missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn long_chains() {
+        let schema = IpldSchema::parse(
+            "\
+type Chain struct {
+  next nullable &Chain
+}
+",
+        )
+        .unwrap();
+
+        // Long chains of links are walked without recursion
+        let length = 20_000;
+        let mut store = MemoryStore::new();
+        let cid = |n: u32| {
+            let mut digest = [0; 32];
+            digest[..4].copy_from_slice(&n.to_be_bytes());
+            Cid::new_v1(DAG_CBOR, Multihash::wrap(0x12, &digest).unwrap())
+        };
+        for n in 0..length {
+            let next = if n + 1 < length {
+                Ipld::Link(cid(n + 1))
+            } else {
+                Ipld::Null
+            };
+            let data = ipld!({ "next": next });
+            store
+                .put(cid(n), serde_ipld_dagcbor::to_vec(&data).unwrap())
+                .unwrap();
+        }
+
+        let report = schema
+            .dag_validator(&store)
+            .validate("Chain", cid(0))
+            .unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.blocks.len(), length as usize);
+        assert_eq!(report.blocks[2].parent, Some(cid(1)));
+        assert_eq!(report.path(2).to_string(), "next/next");
+    }
+}
@@ -0,0 +1,893 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
+use crate::{
+    enumerate::{EnumRepresentation, EnumType},
+    list::{ListRepresentation, ListType},
+    map::MapRepresentation,
+    print::{definition_to_string, union_member_to_string},
+    representation::{BytesRepresentation, StringPairs},
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    Doc, IpldSchema, IpldType,
+};
+
+/// The number of unchanged lines around the changes in a hunk
+const CONTEXT: usize = 3;
+
+/// The structural differences between two schemas.
+///
+/// Printing the diff produces a unified diff of both schemas in their canonical DSL form.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchemaDiff {
+    /// All types that differ, ordered by name
+    pub types: Vec<TypeDiff>,
+    /// The lines of both printed schemas, marked as unchanged, removed or added
+    lines: Vec<(char, String)>,
+}
+
+/// The differences of a single type definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDiff {
+    pub name: String,
+    pub status: DiffStatus,
+    /// The individual changes, empty for added and removed types
+    pub changes: Vec<DiffChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffStatus {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single change inside of a type definition.
+///
+/// Fields and members are printed as they are written in the DSL, without documentation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffChange {
+    /// The type changed its kind, i.e. from `struct` to `map`
+    Kind {
+        old: String,
+        new: String,
+    },
+    Representation {
+        old: String,
+        new: String,
+    },
+    Doc {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// The definition of a list, map, link or copy type changed
+    Definition {
+        old: String,
+        new: String,
+    },
+    FieldAdded {
+        field: String,
+        definition: String,
+    },
+    FieldRemoved {
+        field: String,
+        definition: String,
+    },
+    /// The type of a field changed
+    FieldRetyped {
+        field: String,
+        old: String,
+        new: String,
+    },
+    /// The modifiers of a field changed, i.e. it became optional or got renamed
+    FieldChanged {
+        field: String,
+        old: String,
+        new: String,
+    },
+    FieldDoc {
+        field: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// The fields, that are in both versions, appear in a different order
+    FieldsReordered {
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    MemberAdded {
+        member: String,
+        definition: String,
+    },
+    MemberRemoved {
+        member: String,
+        definition: String,
+    },
+    /// The representation value of an enum member or the discriminant of a union member changed
+    MemberChanged {
+        member: String,
+        old: String,
+        new: String,
+    },
+    MemberDoc {
+        member: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// The members, that are in both versions, appear in a different order
+    MembersReordered {
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+}
+
+impl IpldSchema {
+    /// Computes the structural differences between this schema and `new`
+    pub fn diff(&self, new: &IpldSchema) -> SchemaDiff {
+        let mut names = self.0.keys().chain(new.0.keys()).collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        let mut diff = SchemaDiff::default();
+        // Definitions are printed with an empty line in between
+        let (mut old_started, mut new_started) = (false, false);
+
+        for name in names {
+            let (old, new) = (self.0.get(name), new.0.get(name));
+            match (old.is_some(), new.is_some()) {
+                (true, true) if old_started && new_started => diff.push(' ', ""),
+                (true, _) if old_started => diff.push('-', ""),
+                (_, true) if new_started => diff.push('+', ""),
+                _ => (),
+            }
+            old_started |= old.is_some();
+            new_started |= new.is_some();
+
+            let old_lines = old.map(|def| definition_to_string(name, def));
+            let new_lines = new.map(|def| definition_to_string(name, def));
+            match (old, new) {
+                (Some(old), Some(new)) if old == new => {
+                    for line in old_lines.unwrap_or_default().lines() {
+                        diff.push(' ', line);
+                    }
+                    continue;
+                }
+                (old, new) => diff.types.push(TypeDiff::new(name, old, new)),
+            }
+
+            let old_lines = old_lines.unwrap_or_default();
+            let new_lines = new_lines.unwrap_or_default();
+            for (marker, line) in diff_lines(&old_lines, &new_lines) {
+                diff.push(marker, line);
+            }
+        }
+
+        diff
+    }
+}
+
+impl SchemaDiff {
+    fn push(&mut self, marker: char, line: &str) {
+        self.lines.push((marker, line.to_string()));
+    }
+
+    /// Groups the changed lines with their context into hunks.
+    ///
+    /// Returns the ranges of `lines` covered by each hunk.
+    fn hunks(&self) -> Vec<Range<usize>> {
+        let mut hunks: Vec<Range<usize>> = vec![];
+
+        for (idx, (marker, _)) in self.lines.iter().enumerate() {
+            if *marker == ' ' {
+                continue;
+            }
+
+            let start = idx.saturating_sub(CONTEXT);
+            let end = (idx + 1 + CONTEXT).min(self.lines.len());
+            match hunks.last_mut() {
+                Some(last) if last.end >= start => last.end = end,
+                _ => hunks.push(start..end),
+            }
+        }
+
+        hunks
+    }
+}
+
+impl TypeDiff {
+    fn new(name: &str, old: Option<&Doc<IpldType>>, new: Option<&Doc<IpldType>>) -> Self {
+        let (status, changes) = match (old, new) {
+            (Some(old), Some(new)) => (DiffStatus::Changed, diff_definition(old, new)),
+            (Some(_), None) => (DiffStatus::Removed, vec![]),
+            _ => (DiffStatus::Added, vec![]),
+        };
+
+        TypeDiff {
+            name: name.to_string(),
+            status,
+            changes,
+        }
+    }
+}
+
+fn diff_definition(old: &Doc<IpldType>, new: &Doc<IpldType>) -> Vec<DiffChange> {
+    let mut changes = vec![];
+
+    if old.doc != new.doc {
+        changes.push(DiffChange::Doc {
+            old: old.doc.clone(),
+            new: new.doc.clone(),
+        });
+    }
+
+    if old.ty.type_kind() != new.ty.type_kind() {
+        changes.push(DiffChange::Kind {
+            old: old.ty.type_kind().to_string(),
+            new: new.ty.type_kind().to_string(),
+        });
+        return changes;
+    }
+
+    // Lists, maps, links and copies are compared as a whole, which includes the representation
+    let redefined = match (&old.ty, &new.ty) {
+        (IpldType::Map(old), IpldType::Map(new)) => {
+            (&old.key, &old.value, old.nullable) != (&new.key, &new.value, new.nullable)
+        }
+        (IpldType::List(old), IpldType::List(new)) => {
+            (&old.ty, old.nullable) != (&new.ty, new.nullable)
+        }
+        (IpldType::Link(_), _) | (IpldType::Copy(_), _) => old.ty != new.ty,
+        _ => false,
+    };
+    if redefined {
+        changes.push(DiffChange::Definition {
+            old: old.ty.to_string(),
+            new: new.ty.to_string(),
+        });
+        return changes;
+    }
+
+    let (old_repr, new_repr) = (representation(&old.ty), representation(&new.ty));
+    if old_repr != new_repr {
+        changes.push(DiffChange::Representation {
+            old: old_repr.unwrap_or_default(),
+            new: new_repr.unwrap_or_default(),
+        });
+    }
+
+    match (&old.ty, &new.ty) {
+        (IpldType::Struct(old), IpldType::Struct(new)) => diff_struct(old, new, &mut changes),
+        (IpldType::Enum(old), IpldType::Enum(new)) => diff_enum(old, new, &mut changes),
+        (IpldType::Union(old), IpldType::Union(new)) => diff_union(old, new, &mut changes),
+        _ => (),
+    }
+
+    changes
+}
+
+fn diff_struct(old: &StructType, new: &StructType, changes: &mut Vec<DiffChange>) {
+    for old_field in &old.fields {
+        let Some(new_field) = new.fields.iter().find(|field| field.key == old_field.key) else {
+            changes.push(DiffChange::FieldRemoved {
+                field: old_field.key.clone(),
+                definition: old_field.to_string(),
+            });
+            continue;
+        };
+
+        if old_field.value != new_field.value {
+            changes.push(DiffChange::FieldRetyped {
+                field: old_field.key.clone(),
+                old: old_field.value.to_string(),
+                new: new_field.value.to_string(),
+            });
+        }
+        let modifiers = |field: &StructField| {
+            (
+                field.optional,
+                field.nullable,
+                field.rename.clone(),
+                field.implicit.clone(),
+            )
+        };
+        if modifiers(old_field) != modifiers(new_field) {
+            changes.push(DiffChange::FieldChanged {
+                field: old_field.key.clone(),
+                old: old_field.to_string(),
+                new: new_field.to_string(),
+            });
+        }
+        if old_field.doc != new_field.doc {
+            changes.push(DiffChange::FieldDoc {
+                field: old_field.key.clone(),
+                old: old_field.doc.clone(),
+                new: new_field.doc.clone(),
+            });
+        }
+    }
+
+    for new_field in &new.fields {
+        if !old.fields.iter().any(|field| field.key == new_field.key) {
+            changes.push(DiffChange::FieldAdded {
+                field: new_field.key.clone(),
+                definition: new_field.to_string(),
+            });
+        }
+    }
+
+    let old_keys = old.fields.iter().map(|field| &field.key);
+    let new_keys = new.fields.iter().map(|field| &field.key);
+    if let Some((old, new)) = reordered(old_keys, new_keys) {
+        changes.push(DiffChange::FieldsReordered { old, new });
+    }
+}
+
+fn diff_enum(old: &EnumType, new: &EnumType, changes: &mut Vec<DiffChange>) {
+    for old_member in &old.members {
+        let Some(new_member) = new.members.iter().find(|m| m.name == old_member.name) else {
+            changes.push(DiffChange::MemberRemoved {
+                member: old_member.name.clone(),
+                definition: old_member.to_string(),
+            });
+            continue;
+        };
+
+        if old_member.tag != new_member.tag {
+            changes.push(DiffChange::MemberChanged {
+                member: old_member.name.clone(),
+                old: old_member.to_string(),
+                new: new_member.to_string(),
+            });
+        }
+        if old_member.doc != new_member.doc {
+            changes.push(DiffChange::MemberDoc {
+                member: old_member.name.clone(),
+                old: old_member.doc.clone(),
+                new: new_member.doc.clone(),
+            });
+        }
+    }
+
+    for new_member in &new.members {
+        if !old.members.iter().any(|m| m.name == new_member.name) {
+            changes.push(DiffChange::MemberAdded {
+                member: new_member.name.clone(),
+                definition: new_member.to_string(),
+            });
+        }
+    }
+
+    let old_names = old.members.iter().map(|member| &member.name);
+    let new_names = new.members.iter().map(|member| &member.name);
+    if let Some((old, new)) = reordered(old_names, new_names) {
+        changes.push(DiffChange::MembersReordered { old, new });
+    }
+}
+
+/// Members of unions are matched by their type, changed discriminants are changed members
+fn diff_union(old: &UnionType, new: &UnionType, changes: &mut Vec<DiffChange>) {
+    let old_types = old
+        .members
+        .iter()
+        .map(|member| member.ty.to_string())
+        .collect::<Vec<_>>();
+    let new_types = new
+        .members
+        .iter()
+        .map(|member| member.ty.to_string())
+        .collect::<Vec<_>>();
+
+    for (old_member, ty) in old.members.iter().zip(&old_types) {
+        let old_line = union_member_to_string(old_member, &old.repr);
+        let Some(idx) = new_types.iter().position(|other| other == ty) else {
+            changes.push(DiffChange::MemberRemoved {
+                member: ty.clone(),
+                definition: old_line,
+            });
+            continue;
+        };
+        let new_member = &new.members[idx];
+
+        let new_line = union_member_to_string(new_member, &new.repr);
+        if old_line != new_line {
+            changes.push(DiffChange::MemberChanged {
+                member: ty.clone(),
+                old: old_line,
+                new: new_line,
+            });
+        }
+        if old_member.doc != new_member.doc {
+            changes.push(DiffChange::MemberDoc {
+                member: ty.clone(),
+                old: old_member.doc.clone(),
+                new: new_member.doc.clone(),
+            });
+        }
+    }
+
+    for (new_member, ty) in new.members.iter().zip(&new_types) {
+        if !old_types.contains(ty) {
+            changes.push(DiffChange::MemberAdded {
+                member: ty.clone(),
+                definition: union_member_to_string(new_member, &new.repr),
+            });
+        }
+    }
+
+    if let Some((old, new)) = reordered(old_types.iter(), new_types.iter()) {
+        changes.push(DiffChange::MembersReordered { old, new });
+    }
+}
+
+/// Returns the names, that appear in both lists, if their order differs
+fn reordered<'a>(
+    old: impl Iterator<Item = &'a String> + Clone,
+    new: impl Iterator<Item = &'a String> + Clone,
+) -> Option<(Vec<String>, Vec<String>)> {
+    let common_old = old
+        .clone()
+        .filter(|name| new.clone().any(|other| other == *name))
+        .cloned()
+        .collect::<Vec<_>>();
+    let common_new = new
+        .filter(|name| old.clone().any(|other| other == *name))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    match common_old == common_new {
+        true => None,
+        false => Some((common_old, common_new)),
+    }
+}
+
+/// Describes the representation of types that have one
+fn representation(ty: &IpldType) -> Option<String> {
+    let string_pairs = |pairs: &StringPairs| {
+        format!(
+            "stringpairs (innerDelim \"{}\", entryDelim \"{}\")",
+            pairs.inner_delim, pairs.entry_delim
+        )
+    };
+
+    let repr = match ty {
+        IpldType::Struct(stru) => match &stru.repr {
+            StructRepresentation::StringPairs(pairs) => string_pairs(pairs),
+            repr => repr.name().to_string(),
+        },
+        IpldType::Bytes(BytesRepresentation::Advanced(name)) => format!("advanced {}", name),
+        IpldType::List(ListType {
+            repr: ListRepresentation::Advanced(name),
+            ..
+        }) => format!("advanced {}", name),
+        IpldType::Map(map) => match &map.repr {
+            MapRepresentation::StringPairs(pairs) => string_pairs(pairs),
+            MapRepresentation::Advanced(name) => format!("advanced {}", name),
+            repr => repr.name().to_string(),
+        },
+        IpldType::Union(union) => match &union.repr {
+            UnionRepresentation::Inline { discriminant_key } => {
+                format!("inline (discriminantKey \"{}\")", discriminant_key)
+            }
+            UnionRepresentation::Envelope {
+                discriminant_key,
+                content_key,
+            } => format!(
+                "envelope (discriminantKey \"{}\", contentKey \"{}\")",
+                discriminant_key, content_key
+            ),
+            repr => repr.name().to_string(),
+        },
+        IpldType::Enum(enu) => match enu.repr {
+            EnumRepresentation::String => "string".to_string(),
+            EnumRepresentation::Int => "int".to_string(),
+        },
+        IpldType::Unit(unit) => match unit {
+            UnitRepresentation::Null => "null",
+            UnitRepresentation::True => "true",
+            UnitRepresentation::False => "false",
+            UnitRepresentation::EmptyMap => "emptymap",
+        }
+        .to_string(),
+        _ => return None,
+    };
+
+    Some(repr)
+}
+
+fn describe_doc(old: &Option<String>, new: &Option<String>) -> &'static str {
+    match (old, new) {
+        (None, _) => "documentation added",
+        (_, None) => "documentation removed",
+        _ => "documentation changed",
+    }
+}
+
+impl Display for DiffChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffChange::Kind { old, new } => write!(f, "kind changed from {} to {}", old, new),
+            DiffChange::Representation { old, new } => {
+                write!(f, "representation changed from {} to {}", old, new)
+            }
+            DiffChange::Doc { old, new } => f.write_str(describe_doc(old, new)),
+            DiffChange::Definition { old, new } => {
+                write!(f, "definition changed from `{}` to `{}`", old, new)
+            }
+            DiffChange::FieldAdded { definition, .. } => {
+                write!(f, "field added: `{}`", definition)
+            }
+            DiffChange::FieldRemoved { definition, .. } => {
+                write!(f, "field removed: `{}`", definition)
+            }
+            DiffChange::FieldRetyped { field, old, new } => {
+                write!(f, "field {} retyped from {} to {}", field, old, new)
+            }
+            DiffChange::FieldChanged { field, old, new } => {
+                write!(f, "field {} changed from `{}` to `{}`", field, old, new)
+            }
+            DiffChange::FieldDoc { field, old, new } => {
+                write!(f, "field {}: {}", field, describe_doc(old, new))
+            }
+            DiffChange::FieldsReordered { old, new } => write!(
+                f,
+                "fields reordered from {} to {}",
+                old.join(", "),
+                new.join(", ")
+            ),
+            DiffChange::MemberAdded { definition, .. } => {
+                write!(f, "member added: `{}`", definition)
+            }
+            DiffChange::MemberRemoved { definition, .. } => {
+                write!(f, "member removed: `{}`", definition)
+            }
+            DiffChange::MemberChanged { member, old, new } => {
+                write!(f, "member {} changed from `{}` to `{}`", member, old, new)
+            }
+            DiffChange::MemberDoc { member, old, new } => {
+                write!(f, "member {}: {}", member, describe_doc(old, new))
+            }
+            DiffChange::MembersReordered { old, new } => write!(
+                f,
+                "members reordered from {} to {}",
+                old.join(", "),
+                new.join(", ")
+            ),
+        }
+    }
+}
+
+impl Display for DiffStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DiffStatus::Added => "added",
+            DiffStatus::Removed => "removed",
+            DiffStatus::Changed => "changed",
+        })
+    }
+}
+
+/// Prints a unified diff of both schemas, in their canonical DSL form.
+///
+/// The diff is preceded by a summary of the changed types, which tools like `patch` skip.
+impl Display for SchemaDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        for ty in &self.types {
+            writeln!(f, "# type {} ({})", ty.name, ty.status)?;
+            for change in &ty.changes {
+                writeln!(f, "#   {}", change)?;
+            }
+        }
+
+        writeln!(f, "--- old")?;
+        writeln!(f, "+++ new")?;
+
+        // The line numbers of both schemas at the start of the current hunk
+        let (mut old_line, mut new_line) = (1, 1);
+        let mut printed = 0;
+        for hunk in self.hunks() {
+            for (marker, _) in &self.lines[printed..hunk.start] {
+                old_line += usize::from(*marker != '+');
+                new_line += usize::from(*marker != '-');
+            }
+
+            let lines = &self.lines[hunk.clone()];
+            let old_len = lines.iter().filter(|(marker, _)| *marker != '+').count();
+            let new_len = lines.iter().filter(|(marker, _)| *marker != '-').count();
+            // Empty ranges start at the line before them
+            writeln!(
+                f,
+                "@@ -{},{} +{},{} @@",
+                old_line - usize::from(old_len == 0),
+                old_len,
+                new_line - usize::from(new_len == 0),
+                new_len
+            )?;
+            for (marker, line) in lines {
+                writeln!(f, "{}{}", marker, line)?;
+            }
+
+            old_line += old_len;
+            new_line += new_len;
+            printed = hunk.end;
+        }
+
+        Ok(())
+    }
+}
+
+/// A line based diff using the longest common subsequence.
+///
+/// Definitions are short, so the quadratic table is fine.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // common[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = match old[i] == new[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff() {
+        let file = include_str!("../test/example.ipldsch");
+        let schema = IpldSchema::parse(file).unwrap();
+        assert!(schema.diff(&schema).is_empty());
+
+        let old = IpldSchema::parse(
+            "\
+type Foo struct {
+  a Int
+  # The b
+  b String
+  c Int
+}
+
+type Status enum {
+  | Yes
+  | No
+}
+
+type Gone string
+",
+        )
+        .unwrap();
+        let new = IpldSchema::parse(
+            "\
+# A foo
+type Foo struct {
+  a optional Int
+  b Bytes
+  d Int
+} representation listpairs
+
+type Status enum {
+  | Yes (\"Y\")
+  | Maybe
+}
+
+type New bool
+",
+        )
+        .unwrap();
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.types
+                .iter()
+                .map(|ty| (ty.name.as_str(), ty.status))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Foo", DiffStatus::Changed),
+                ("Gone", DiffStatus::Removed),
+                ("New", DiffStatus::Added),
+                ("Status", DiffStatus::Changed),
+            ]
+        );
+        assert_eq!(
+            diff.types[0].changes[3],
+            DiffChange::FieldRetyped {
+                field: "b".to_string(),
+                old: "String".to_string(),
+                new: "Bytes".to_string()
+            }
+        );
+
+        let expected = "\
+# type Foo (changed)
+#   documentation added
+#   representation changed from map to listpairs
+#   field a changed from `a Int` to `a optional Int`
+#   field b retyped from String to Bytes
+#   field b: documentation removed
+#   field removed: `c Int`
+#   field added: `d Int`
+# type Gone (removed)
+# type New (added)
+# type Status (changed)
+#   member Yes changed from `| Yes` to `| Yes (\"Y\")`
+#   member removed: `| No`
+#   member added: `| Maybe`
+--- old
++++ new
+@@ -1,13 +1,13 @@
++# A foo
+ type Foo struct {
+-  a Int
+-  # The b
+-  b String
+-  c Int
+-}
++  a optional Int
++  b Bytes
++  d Int
++} representation listpairs
+-
+-type Gone string
++
++type New bool
+ 
+ type Status enum {
+-  | Yes
+-  | No
++  | Yes (\"Y\")
++  | Maybe
+ }
+";
+        assert_eq!(diff.to_string(), expected);
+    }
+
+    #[test]
+    fn each_change_once() {
+        let old = IpldSchema::parse(
+            "\
+type Names [String]
+
+type Blob bytes
+
+type Shape union {
+  | Circle \"circle\"
+  | Square \"square\"
+  | Point \"point\"
+} representation keyed
+
+type Circle int
+
+type Square int
+
+type Point int
+
+type Line int
+",
+        )
+        .unwrap();
+        let new = IpldSchema::parse(
+            "\
+type Names [String] representation advanced Sharded
+
+type Blob bytes representation advanced Chunked
+
+type Shape union {
+  # A circle
+  | Circle \"c\"
+  | Line \"line\"
+  | Point \"point\"
+} representation keyed
+
+type Circle int
+
+type Square int
+
+type Point int
+
+type Line int
+",
+        )
+        .unwrap();
+
+        let changes = old
+            .diff(&new)
+            .types
+            .iter()
+            .map(|ty| {
+                let changes = ty.changes.iter().map(ToString::to_string);
+                (ty.name.clone(), changes.collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "Blob".to_string(),
+                    vec!["representation changed from  to advanced Chunked".to_string()]
+                ),
+                (
+                    "Names".to_string(),
+                    vec!["representation changed from  to advanced Sharded".to_string()]
+                ),
+                (
+                    "Shape".to_string(),
+                    vec![
+                        "member Circle changed from `| Circle \"circle\"` to `| Circle \"c\"`"
+                            .to_string(),
+                        "member Circle: documentation added".to_string(),
+                        "member removed: `| Square \"square\"`".to_string(),
+                        "member added: `| Line \"line\"`".to_string(),
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reordered_fields() {
+        let old = IpldSchema::parse("type Foo struct {\n  a Int\n  b Int\n}\n\ntype Aa int\n\ntype Bb int\n\ntype Cc int\n\ntype Dd int").unwrap();
+        let new = IpldSchema::parse("type Foo struct {\n  b Int\n  a Int\n}\n\ntype Aa int\n\ntype Bb int\n\ntype Cc int\n\ntype Dd int").unwrap();
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.types[0].changes,
+            vec![DiffChange::FieldsReordered {
+                old: vec!["a".to_string(), "b".to_string()],
+                new: vec!["b".to_string(), "a".to_string()],
+            }]
+        );
+
+        let expected = "\
+# type Foo (changed)
+#   fields reordered from a, b to b, a
+--- old
++++ new
+@@ -7,6 +7,6 @@
+ type Dd int
+ 
+ type Foo struct {
+-  a Int
+   b Int
++  a Int
+ }
+";
+        assert_eq!(diff.to_string(), expected);
+    }
+}
@@ -0,0 +1,849 @@
+use std::collections::BTreeMap;
+
+use ipld_core::ipld::Ipld;
+use thiserror::Error;
+
+use crate::{
+    enumerate::{EnumField, EnumMemberTag, EnumRepresentation, EnumType},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::{BytesRepresentation, StringPairs},
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionMember, UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    Doc, InlineIpldType, IpldSchema, IpldType,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DmtError {
+    #[error("Expected {expected} at \"{path}\"")]
+    Expected {
+        path: String,
+        expected: &'static str,
+    },
+    #[error("Missing field \"{field}\" at \"{path}\"")]
+    MissingField { path: String, field: &'static str },
+    #[error("Unsupported type kind or representation \"{name}\" at \"{path}\"")]
+    Unsupported { path: String, name: String },
+}
+
+impl IpldSchema {
+    /// Converts the schema into its data model tree (DMT), as described by the schema-schema.
+    ///
+    /// The DMT can be encoded with any IPLD codec, i.e. DAG-JSON or DAG-CBOR.
+    /// Note that the DMT does not carry doc comments, and since struct fields are stored in a map,
+    /// the order of fields of map represented structs is not preserved.
+    pub fn to_dmt(&self) -> Ipld {
+        let types = self
+            .0
+            .iter()
+            .map(|(name, def)| (name.clone(), type_to_dmt(&def.ty)))
+            .collect();
+
+        ipld_map([("types", Ipld::Map(types))])
+    }
+
+    /// Reads a schema from its data model tree (DMT) form.
+    pub fn from_dmt(dmt: &Ipld) -> Result<Self, DmtError> {
+        let schema = as_map(dmt, "")?;
+        let types = as_map(field(schema, "types", "")?, "types")?;
+
+        let mut definitions = BTreeMap::new();
+        for (name, defn) in types {
+            let path = format!("types/{}", name);
+            definitions.insert(
+                name.clone(),
+                Doc {
+                    doc: None,
+                    ty: type_from_dmt(defn, &path)?,
+                },
+            );
+        }
+
+        Ok(Self(definitions))
+    }
+}
+
+fn ipld_map<const N: usize>(entries: [(&str, Ipld); N]) -> Ipld {
+    Ipld::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+fn empty() -> Ipld {
+    Ipld::Map(BTreeMap::new())
+}
+
+fn type_to_dmt(ty: &IpldType) -> Ipld {
+    match ty {
+        IpldType::Bool => ipld_map([("bool", empty())]),
+        IpldType::String => ipld_map([("string", empty())]),
+        IpldType::Bytes(BytesRepresentation::Bytes) => ipld_map([("bytes", empty())]),
+        IpldType::Bytes(BytesRepresentation::Advanced(name)) => ipld_map([(
+            "bytes",
+            ipld_map([("representation", advanced_to_dmt(name))]),
+        )]),
+        IpldType::Int => ipld_map([("int", empty())]),
+        IpldType::Float => ipld_map([("float", empty())]),
+        IpldType::Any => ipld_map([("any", empty())]),
+        IpldType::List(list) => ipld_map([("list", list_to_dmt(list))]),
+        IpldType::Map(map) => ipld_map([("map", map_to_dmt(map))]),
+        IpldType::Link(link) => ipld_map([("link", link_to_dmt(link))]),
+        IpldType::Union(union) => ipld_map([("union", union_to_dmt(union))]),
+        IpldType::Struct(stru) => ipld_map([("struct", struct_to_dmt(stru))]),
+        IpldType::Enum(enu) => ipld_map([("enum", enum_to_dmt(enu))]),
+        IpldType::Unit(unit) => ipld_map([("unit", unit_to_dmt(unit))]),
+        IpldType::Copy(from) => {
+            ipld_map([("copy", ipld_map([("fromType", from.as_str().into())]))])
+        }
+    }
+}
+
+fn inline_type_to_dmt(ty: &InlineIpldType) -> Ipld {
+    match ty {
+        InlineIpldType::Name(name) => Ipld::String(name.clone()),
+        InlineIpldType::List(list) => ipld_map([("list", list_to_dmt(list))]),
+        InlineIpldType::Map(map) => ipld_map([("map", map_to_dmt(map))]),
+        InlineIpldType::Link(link) => ipld_map([("link", link_to_dmt(link))]),
+    }
+}
+
+fn list_to_dmt(list: &ListType) -> Ipld {
+    let mut dmt = BTreeMap::new();
+    dmt.insert("valueType".to_string(), inline_type_to_dmt(&list.ty));
+    if list.nullable {
+        dmt.insert("valueNullable".to_string(), Ipld::Bool(true));
+    }
+    if let ListRepresentation::Advanced(name) = &list.repr {
+        dmt.insert("representation".to_string(), advanced_to_dmt(name));
+    }
+
+    Ipld::Map(dmt)
+}
+
+fn advanced_to_dmt(name: &str) -> Ipld {
+    ipld_map([("advanced", Ipld::String(name.to_string()))])
+}
+
+fn map_to_dmt(map: &MapType) -> Ipld {
+    let mut dmt = BTreeMap::new();
+    dmt.insert("keyType".to_string(), Ipld::String(map.key.clone()));
+    dmt.insert("valueType".to_string(), inline_type_to_dmt(&map.value));
+    if map.nullable {
+        dmt.insert("valueNullable".to_string(), Ipld::Bool(true));
+    }
+
+    match &map.repr {
+        MapRepresentation::Map => (),
+        MapRepresentation::ListPairs => {
+            dmt.insert(
+                "representation".to_string(),
+                ipld_map([("listpairs", empty())]),
+            );
+        }
+        MapRepresentation::StringPairs(pairs) => {
+            dmt.insert(
+                "representation".to_string(),
+                ipld_map([("stringpairs", Ipld::Map(string_pairs_to_dmt(pairs)))]),
+            );
+        }
+        MapRepresentation::Advanced(name) => {
+            dmt.insert("representation".to_string(), advanced_to_dmt(name));
+        }
+    }
+
+    Ipld::Map(dmt)
+}
+
+fn link_to_dmt(link: &str) -> Ipld {
+    if link == "Any" {
+        empty()
+    } else {
+        ipld_map([("expectedType", link.into())])
+    }
+}
+
+fn struct_to_dmt(stru: &StructType) -> Ipld {
+    let mut fields = BTreeMap::new();
+    let mut repr_fields = BTreeMap::new();
+
+    for field in &stru.fields {
+        let mut dmt = BTreeMap::new();
+        dmt.insert("type".to_string(), inline_type_to_dmt(&field.value));
+        if field.optional {
+            dmt.insert("optional".to_string(), Ipld::Bool(true));
+        }
+        if field.nullable {
+            dmt.insert("nullable".to_string(), Ipld::Bool(true));
+        }
+        fields.insert(field.key.clone(), Ipld::Map(dmt));
+
+        let mut details = BTreeMap::new();
+        if let Some(rename) = &field.rename {
+            details.insert("rename".to_string(), Ipld::String(rename.clone()));
+        }
+        if let Some(implicit) = &field.implicit {
+            details.insert("implicit".to_string(), implicit_to_dmt(field, implicit));
+        }
+        if !details.is_empty() {
+            repr_fields.insert(field.key.clone(), Ipld::Map(details));
+        }
+    }
+
+    // Field details are an extension for listpairs and stringpairs, which are keyed as well
+    let details = |mut repr: BTreeMap<String, Ipld>| {
+        if !repr_fields.is_empty() {
+            repr.insert("fields".to_string(), Ipld::Map(repr_fields.clone()));
+        }
+        Ipld::Map(repr)
+    };
+
+    let repr = match &stru.repr {
+        StructRepresentation::Map => ipld_map([("map", details(BTreeMap::new()))]),
+        StructRepresentation::ListPairs => ipld_map([("listpairs", details(BTreeMap::new()))]),
+        // The order of the fields is lost in the map of fields
+        StructRepresentation::Tuple => {
+            let order = stru
+                .fields
+                .iter()
+                .map(|field| Ipld::String(field.key.clone()))
+                .collect();
+            ipld_map([(
+                "tuple",
+                details(BTreeMap::from([(
+                    "fieldOrder".to_string(),
+                    Ipld::List(order),
+                )])),
+            )])
+        }
+        StructRepresentation::StringPairs(pairs) => {
+            ipld_map([("stringpairs", details(string_pairs_to_dmt(pairs)))])
+        }
+    };
+
+    ipld_map([("fields", Ipld::Map(fields)), ("representation", repr)])
+}
+
+/// Implicit values are stored as scalars in the DMT, so we try to interpret them
+/// according to the type of the field.
+fn implicit_to_dmt(field: &StructField, implicit: &str) -> Ipld {
+    match &field.value {
+        InlineIpldType::Name(name) if name == "Int" => implicit
+            .parse()
+            .map(Ipld::Integer)
+            .unwrap_or_else(|_| implicit.into()),
+        InlineIpldType::Name(name) if name == "Float" => implicit
+            .parse()
+            .map(Ipld::Float)
+            .unwrap_or_else(|_| implicit.into()),
+        InlineIpldType::Name(name) if name == "Bool" => implicit
+            .parse()
+            .map(Ipld::Bool)
+            .unwrap_or_else(|_| implicit.into()),
+        _ => implicit.into(),
+    }
+}
+
+fn enum_to_dmt(enu: &EnumType) -> Ipld {
+    let members = enu
+        .members
+        .iter()
+        .map(|member| Ipld::String(member.name.clone()))
+        .collect();
+
+    let tags = enu
+        .members
+        .iter()
+        .filter_map(|member| match &member.tag {
+            EnumMemberTag::String(tag) if *tag == member.name => None,
+            EnumMemberTag::String(tag) => Some((member.name.clone(), Ipld::String(tag.clone()))),
+            EnumMemberTag::Int(tag) => Some((member.name.clone(), Ipld::Integer(*tag))),
+        })
+        .collect();
+
+    let repr = match enu.repr {
+        EnumRepresentation::String => ipld_map([("string", Ipld::Map(tags))]),
+        EnumRepresentation::Int => ipld_map([("int", Ipld::Map(tags))]),
+    };
+
+    ipld_map([("members", Ipld::List(members)), ("representation", repr)])
+}
+
+fn union_to_dmt(union: &UnionType) -> Ipld {
+    let members = union
+        .members
+        .iter()
+        .map(|member| inline_type_to_dmt(&member.ty))
+        .collect();
+    let table = union
+        .members
+        .iter()
+        .map(|member| (member.discriminant.clone(), inline_type_to_dmt(&member.ty)))
+        .collect();
+
+    let repr = match &union.repr {
+        UnionRepresentation::Keyed => ipld_map([("keyed", Ipld::Map(table))]),
+        UnionRepresentation::Kinded => ipld_map([("kinded", Ipld::Map(table))]),
+        UnionRepresentation::Inline { discriminant_key } => ipld_map([(
+            "inline",
+            ipld_map([
+                ("discriminantKey", discriminant_key.as_str().into()),
+                ("discriminantTable", Ipld::Map(table)),
+            ]),
+        )]),
+        UnionRepresentation::Envelope {
+            discriminant_key,
+            content_key,
+        } => ipld_map([(
+            "envelope",
+            ipld_map([
+                ("discriminantKey", discriminant_key.as_str().into()),
+                ("contentKey", content_key.as_str().into()),
+                ("discriminantTable", Ipld::Map(table)),
+            ]),
+        )]),
+    };
+
+    ipld_map([("members", Ipld::List(members)), ("representation", repr)])
+}
+
+fn unit_to_dmt(unit: &UnitRepresentation) -> Ipld {
+    let repr = match unit {
+        UnitRepresentation::Null => "null",
+        UnitRepresentation::True => "true",
+        UnitRepresentation::False => "false",
+        UnitRepresentation::EmptyMap => "emptymap",
+    };
+
+    ipld_map([("representation", repr.into())])
+}
+
+fn string_pairs_to_dmt(pairs: &StringPairs) -> BTreeMap<String, Ipld> {
+    BTreeMap::from([
+        ("innerDelim".to_string(), pairs.inner_delim.as_str().into()),
+        ("entryDelim".to_string(), pairs.entry_delim.as_str().into()),
+    ])
+}
+
+fn as_map<'a>(ipld: &'a Ipld, path: &str) -> Result<&'a BTreeMap<String, Ipld>, DmtError> {
+    match ipld {
+        Ipld::Map(map) => Ok(map),
+        _ => Err(DmtError::Expected {
+            path: path.to_string(),
+            expected: "map",
+        }),
+    }
+}
+
+fn as_string<'a>(ipld: &'a Ipld, path: &str) -> Result<&'a str, DmtError> {
+    match ipld {
+        Ipld::String(string) => Ok(string),
+        _ => Err(DmtError::Expected {
+            path: path.to_string(),
+            expected: "string",
+        }),
+    }
+}
+
+/// Reads a union in keyed representation, i.e. a map with exactly one entry
+fn as_keyed<'a>(ipld: &'a Ipld, path: &str) -> Result<(&'a str, &'a Ipld), DmtError> {
+    let map = as_map(ipld, path)?;
+    let mut entries = map.iter();
+    match (entries.next(), entries.next()) {
+        (Some((key, value)), None) => Ok((key, value)),
+        _ => Err(DmtError::Expected {
+            path: path.to_string(),
+            expected: "map with a single entry",
+        }),
+    }
+}
+
+fn field<'a>(
+    map: &'a BTreeMap<String, Ipld>,
+    field: &'static str,
+    path: &str,
+) -> Result<&'a Ipld, DmtError> {
+    map.get(field).ok_or_else(|| DmtError::MissingField {
+        path: path.to_string(),
+        field,
+    })
+}
+
+fn flag(map: &BTreeMap<String, Ipld>, field: &str, path: &str) -> Result<bool, DmtError> {
+    match map.get(field) {
+        None => Ok(false),
+        Some(Ipld::Bool(flag)) => Ok(*flag),
+        Some(_) => Err(DmtError::Expected {
+            path: format!("{}/{}", path, field),
+            expected: "bool",
+        }),
+    }
+}
+
+fn type_from_dmt(dmt: &Ipld, path: &str) -> Result<IpldType, DmtError> {
+    let (kind, defn) = as_keyed(dmt, path)?;
+    let path = format!("{}/{}", path, kind);
+    let defn = as_map(defn, &path)?;
+
+    match kind {
+        "bool" => Ok(IpldType::Bool),
+        "string" => Ok(IpldType::String),
+        "bytes" => Ok(IpldType::Bytes(bytes_from_dmt(defn, &path)?)),
+        "int" => Ok(IpldType::Int),
+        "float" => Ok(IpldType::Float),
+        "any" => Ok(IpldType::Any),
+        "list" => Ok(IpldType::List(list_from_dmt(defn, &path)?)),
+        "map" => Ok(IpldType::Map(map_from_dmt(defn, &path)?)),
+        "link" => Ok(IpldType::Link(link_from_dmt(defn, &path)?)),
+        "union" => Ok(IpldType::Union(union_from_dmt(defn, &path)?)),
+        "struct" => Ok(IpldType::Struct(struct_from_dmt(defn, &path)?)),
+        "enum" => Ok(IpldType::Enum(enum_from_dmt(defn, &path)?)),
+        "unit" => Ok(IpldType::Unit(unit_from_dmt(defn, &path)?)),
+        "copy" => Ok(IpldType::Copy(
+            as_string(field(defn, "fromType", &path)?, &path)?.to_string(),
+        )),
+        _ => Err(DmtError::Unsupported {
+            path,
+            name: kind.to_string(),
+        }),
+    }
+}
+
+fn inline_type_from_dmt(dmt: &Ipld, path: &str) -> Result<InlineIpldType, DmtError> {
+    if let Ipld::String(name) = dmt {
+        return Ok(InlineIpldType::Name(name.clone()));
+    }
+
+    let (kind, defn) = as_keyed(dmt, path)?;
+    let path = format!("{}/{}", path, kind);
+    let defn = as_map(defn, &path)?;
+
+    match kind {
+        "list" => Ok(InlineIpldType::List(Box::new(list_from_dmt(defn, &path)?))),
+        "map" => Ok(InlineIpldType::Map(Box::new(map_from_dmt(defn, &path)?))),
+        "link" => Ok(InlineIpldType::Link(link_from_dmt(defn, &path)?)),
+        _ => Err(DmtError::Unsupported {
+            path,
+            name: kind.to_string(),
+        }),
+    }
+}
+
+fn bytes_from_dmt(
+    defn: &BTreeMap<String, Ipld>,
+    path: &str,
+) -> Result<BytesRepresentation, DmtError> {
+    let Some(repr) = defn.get("representation") else {
+        return Ok(BytesRepresentation::Bytes);
+    };
+
+    let path = format!("{}/representation", path);
+    match as_keyed(repr, &path)? {
+        ("bytes", _) => Ok(BytesRepresentation::Bytes),
+        ("advanced", name) => Ok(BytesRepresentation::Advanced(
+            as_string(name, &path)?.to_string(),
+        )),
+        (name, _) => Err(DmtError::Unsupported {
+            path,
+            name: name.to_string(),
+        }),
+    }
+}
+
+fn list_from_dmt(defn: &BTreeMap<String, Ipld>, path: &str) -> Result<ListType, DmtError> {
+    let repr = match defn.get("representation") {
+        None => ListRepresentation::List,
+        Some(repr) => {
+            let path = format!("{}/representation", path);
+            match as_keyed(repr, &path)? {
+                ("list", _) => ListRepresentation::List,
+                ("advanced", name) => {
+                    ListRepresentation::Advanced(as_string(name, &path)?.to_string())
+                }
+                (name, _) => {
+                    return Err(DmtError::Unsupported {
+                        path,
+                        name: name.to_string(),
+                    })
+                }
+            }
+        }
+    };
+
+    Ok(ListType {
+        ty: inline_type_from_dmt(field(defn, "valueType", path)?, path)?,
+        nullable: flag(defn, "valueNullable", path)?,
+        repr,
+    })
+}
+
+fn map_from_dmt(defn: &BTreeMap<String, Ipld>, path: &str) -> Result<MapType, DmtError> {
+    let repr = match defn.get("representation") {
+        None => MapRepresentation::Map,
+        Some(repr) => {
+            let path = format!("{}/representation", path);
+            match as_keyed(repr, &path)? {
+                ("map", _) => MapRepresentation::Map,
+                ("listpairs", _) => MapRepresentation::ListPairs,
+                ("stringpairs", pairs) => {
+                    MapRepresentation::StringPairs(string_pairs_from_dmt(pairs, &path)?)
+                }
+                ("advanced", name) => {
+                    MapRepresentation::Advanced(as_string(name, &path)?.to_string())
+                }
+                (name, _) => {
+                    return Err(DmtError::Unsupported {
+                        path,
+                        name: name.to_string(),
+                    })
+                }
+            }
+        }
+    };
+
+    Ok(MapType {
+        key: as_string(field(defn, "keyType", path)?, path)?.to_string(),
+        value: inline_type_from_dmt(field(defn, "valueType", path)?, path)?,
+        nullable: flag(defn, "valueNullable", path)?,
+        repr,
+    })
+}
+
+fn link_from_dmt(defn: &BTreeMap<String, Ipld>, path: &str) -> Result<String, DmtError> {
+    match defn.get("expectedType") {
+        None => Ok("Any".to_string()),
+        Some(expected) => Ok(as_string(expected, path)?.to_string()),
+    }
+}
+
+fn struct_from_dmt(defn: &BTreeMap<String, Ipld>, path: &str) -> Result<StructType, DmtError> {
+    let repr_path = format!("{}/representation", path);
+    let (repr_name, repr_defn) = as_keyed(field(defn, "representation", path)?, &repr_path)?;
+    let repr_path = format!("{}/{}", repr_path, repr_name);
+    let repr_defn = as_map(repr_defn, &repr_path)?;
+
+    let repr = match repr_name {
+        "map" => StructRepresentation::Map,
+        "listpairs" => StructRepresentation::ListPairs,
+        "tuple" => StructRepresentation::Tuple,
+        "stringpairs" => StructRepresentation::StringPairs(string_pairs_from_dmt(
+            &Ipld::Map(repr_defn.clone()),
+            &repr_path,
+        )?),
+        _ => {
+            return Err(DmtError::Unsupported {
+                path: repr_path,
+                name: repr_name.to_string(),
+            })
+        }
+    };
+    let details = match repr_defn.get("fields") {
+        Some(fields) => as_map(fields, &repr_path)?.clone(),
+        None => BTreeMap::new(),
+    };
+
+    let fields_path = format!("{}/fields", path);
+    let fields = as_map(field(defn, "fields", path)?, &fields_path)?
+        .iter()
+        .map(|(key, dmt)| {
+            let path = format!("{}/{}", fields_path, key);
+            let dmt = as_map(dmt, &path)?;
+
+            let (rename, implicit) = match details.get(key) {
+                None => (None, None),
+                Some(details) => {
+                    let details = as_map(details, &path)?;
+                    let rename = details
+                        .get("rename")
+                        .map(|rename| as_string(rename, &path).map(str::to_string))
+                        .transpose()?;
+                    let implicit = details
+                        .get("implicit")
+                        .map(|implicit| implicit_from_dmt(implicit, &path))
+                        .transpose()?;
+                    (rename, implicit)
+                }
+            };
+
+            Ok(StructField {
+                doc: None,
+                key: key.clone(),
+                value: inline_type_from_dmt(field(dmt, "type", &path)?, &path)?,
+                optional: flag(dmt, "optional", &path)?,
+                nullable: flag(dmt, "nullable", &path)?,
+                rename,
+                implicit,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let fields = match repr_defn.get("fieldOrder") {
+        Some(Ipld::List(order)) => {
+            let position = |field: &StructField| {
+                order
+                    .iter()
+                    .position(|key| *key == Ipld::String(field.key.clone()))
+            };
+            let mut fields = fields;
+            fields.sort_by_key(|field| position(field).unwrap_or(usize::MAX));
+            fields
+        }
+        Some(_) => {
+            return Err(DmtError::Expected {
+                path: format!("{}/fieldOrder", repr_path),
+                expected: "list",
+            })
+        }
+        None => fields,
+    };
+
+    Ok(StructType { fields, repr })
+}
+
+fn implicit_from_dmt(implicit: &Ipld, path: &str) -> Result<String, DmtError> {
+    match implicit {
+        Ipld::Bool(value) => Ok(value.to_string()),
+        Ipld::Integer(value) => Ok(value.to_string()),
+        Ipld::Float(value) => Ok(value.to_string()),
+        Ipld::String(value) => Ok(value.clone()),
+        _ => Err(DmtError::Expected {
+            path: path.to_string(),
+            expected: "scalar",
+        }),
+    }
+}
+
+fn enum_from_dmt(defn: &BTreeMap<String, Ipld>, path: &str) -> Result<EnumType, DmtError> {
+    let members_path = format!("{}/members", path);
+    let members = match field(defn, "members", path)? {
+        Ipld::List(members) => members,
+        _ => {
+            return Err(DmtError::Expected {
+                path: members_path,
+                expected: "list",
+            })
+        }
+    };
+
+    let repr_path = format!("{}/representation", path);
+    let (repr_name, tags) = as_keyed(field(defn, "representation", path)?, &repr_path)?;
+    let tags = as_map(tags, &repr_path)?;
+    let repr = match repr_name {
+        "string" => EnumRepresentation::String,
+        "int" => EnumRepresentation::Int,
+        _ => {
+            return Err(DmtError::Unsupported {
+                path: repr_path,
+                name: repr_name.to_string(),
+            })
+        }
+    };
+
+    let members = members
+        .iter()
+        .map(|member| {
+            let name = as_string(member, &members_path)?.to_string();
+            let tag = match (&repr, tags.get(&name)) {
+                (EnumRepresentation::String, None) => EnumMemberTag::String(name.clone()),
+                (EnumRepresentation::String, Some(Ipld::String(tag))) => {
+                    EnumMemberTag::String(tag.clone())
+                }
+                (EnumRepresentation::Int, Some(Ipld::Integer(tag))) => EnumMemberTag::Int(*tag),
+                _ => {
+                    return Err(DmtError::Expected {
+                        path: format!("{}/{}", repr_path, name),
+                        expected: "member tag matching the representation",
+                    })
+                }
+            };
+
+            Ok(EnumField {
+                doc: None,
+                name,
+                tag,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(EnumType { members, repr })
+}
+
+fn union_from_dmt(defn: &BTreeMap<String, Ipld>, path: &str) -> Result<UnionType, DmtError> {
+    let members_path = format!("{}/members", path);
+    let order = match field(defn, "members", path)? {
+        Ipld::List(members) => members,
+        _ => {
+            return Err(DmtError::Expected {
+                path: members_path,
+                expected: "list",
+            })
+        }
+    };
+
+    let repr_path = format!("{}/representation", path);
+    let (repr_name, repr_defn) = as_keyed(field(defn, "representation", path)?, &repr_path)?;
+    let repr_path = format!("{}/{}", repr_path, repr_name);
+    let repr_defn = as_map(repr_defn, &repr_path)?;
+    let key = |name| as_string(field(repr_defn, name, &repr_path)?, &repr_path).map(str::to_string);
+
+    let (repr, table) = match repr_name {
+        "keyed" => (UnionRepresentation::Keyed, repr_defn),
+        "kinded" => (UnionRepresentation::Kinded, repr_defn),
+        "inline" => (
+            UnionRepresentation::Inline {
+                discriminant_key: key("discriminantKey")?,
+            },
+            as_map(
+                field(repr_defn, "discriminantTable", &repr_path)?,
+                &repr_path,
+            )?,
+        ),
+        "envelope" => (
+            UnionRepresentation::Envelope {
+                discriminant_key: key("discriminantKey")?,
+                content_key: key("contentKey")?,
+            },
+            as_map(
+                field(repr_defn, "discriminantTable", &repr_path)?,
+                &repr_path,
+            )?,
+        ),
+        _ => {
+            return Err(DmtError::Unsupported {
+                path: repr_path,
+                name: repr_name.to_string(),
+            })
+        }
+    };
+
+    let mut members = table
+        .iter()
+        .map(|(discriminant, member)| {
+            Ok(UnionMember {
+                doc: None,
+                ty: inline_type_from_dmt(member, &format!("{}/{}", repr_path, discriminant))?,
+                discriminant: discriminant.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, DmtError>>()?;
+    // The table is sorted by discriminant, the list of members keeps their order
+    members.sort_by_key(|member| {
+        let member = inline_type_to_dmt(&member.ty);
+        order.iter().position(|ty| *ty == member)
+    });
+
+    Ok(UnionType { members, repr })
+}
+
+fn unit_from_dmt(
+    defn: &BTreeMap<String, Ipld>,
+    path: &str,
+) -> Result<UnitRepresentation, DmtError> {
+    match as_string(field(defn, "representation", path)?, path)? {
+        "null" => Ok(UnitRepresentation::Null),
+        "true" => Ok(UnitRepresentation::True),
+        "false" => Ok(UnitRepresentation::False),
+        "emptymap" => Ok(UnitRepresentation::EmptyMap),
+        repr => Err(DmtError::Unsupported {
+            path: format!("{}/representation", path),
+            name: repr.to_string(),
+        }),
+    }
+}
+
+fn string_pairs_from_dmt(dmt: &Ipld, path: &str) -> Result<StringPairs, DmtError> {
+    let dmt = as_map(dmt, path)?;
+
+    Ok(StringPairs {
+        inner_delim: as_string(field(dmt, "innerDelim", path)?, path)?.to_string(),
+        entry_delim: as_string(field(dmt, "entryDelim", path)?, path)?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Removes the information that is not carried by the DMT from a schema
+    fn strip(mut schema: IpldSchema) -> IpldSchema {
+        for def in schema.0.values_mut() {
+            def.doc = None;
+            match &mut def.ty {
+                IpldType::Struct(stru) => {
+                    if stru.repr != StructRepresentation::Tuple {
+                        stru.fields.sort_by(|a, b| a.key.cmp(&b.key));
+                    }
+                    stru.fields.iter_mut().for_each(|field| field.doc = None);
+                }
+                IpldType::Enum(enu) => enu.members.iter_mut().for_each(|member| member.doc = None),
+                IpldType::Union(union) => union
+                    .members
+                    .iter_mut()
+                    .for_each(|member| member.doc = None),
+                _ => (),
+            }
+        }
+
+        schema
+    }
+
+    #[test]
+    fn dmt_roundtrip() {
+        let files = [
+            include_str!("../test/primitive.ipldsch"),
+            include_str!("../test/struct.ipldsch"),
+            include_str!("../test/enums.ipldsch"),
+            include_str!("../test/maps.ipldsch"),
+            include_str!("../test/list.ipldsch"),
+            include_str!("../test/example.ipldsch"),
+            include_str!("../test/union.ipldsch"),
+        ];
+
+        for file in files {
+            let parsed_schema = strip(IpldSchema::parse(file).unwrap());
+            let dmt = parsed_schema.to_dmt();
+
+            assert_eq!(IpldSchema::from_dmt(&dmt).unwrap(), parsed_schema);
+        }
+    }
+
+    #[test]
+    fn dmt_layout() {
+        let schema =
+            IpldSchema::parse("type Foo struct {\n  x optional Int (rename \"a\")\n}").unwrap();
+
+        let expected = ipld_map([(
+            "types",
+            ipld_map([(
+                "Foo",
+                ipld_map([(
+                    "struct",
+                    ipld_map([
+                        (
+                            "fields",
+                            ipld_map([(
+                                "x",
+                                ipld_map([("type", "Int".into()), ("optional", true.into())]),
+                            )]),
+                        ),
+                        (
+                            "representation",
+                            ipld_map([(
+                                "map",
+                                ipld_map([(
+                                    "fields",
+                                    ipld_map([("x", ipld_map([("rename", "a".into())]))]),
+                                )]),
+                            )]),
+                        ),
+                    ]),
+                )]),
+            )]),
+        )]);
+
+        assert_eq!(schema.to_dmt(), expected);
+    }
+}
@@ -0,0 +1,509 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{
+    enumerate::{EnumMemberTag, EnumRepresentation},
+    list::{ListRepresentation, ListType},
+    map::MapRepresentation,
+    print::definition_to_string,
+    representation::BytesRepresentation,
+    structural::StructRepresentation,
+    union::UnionRepresentation,
+    unit::UnitRepresentation,
+    Doc, InlineIpldType, IpldSchema, IpldType,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsFormat {
+    Markdown,
+    /// A standalone HTML page
+    Html,
+}
+
+/// A piece of text in the generated documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Inline {
+    Text(String),
+    Code(String),
+    /// A type name, linked to its section if it is defined in the schema
+    Type(String),
+}
+
+/// The contents of the section of a type, independent of the output format
+struct Section<'s> {
+    name: &'s str,
+    doc: Option<&'s str>,
+    summary: Vec<Inline>,
+    definition: String,
+    header: &'static [&'static str],
+    rows: Vec<Vec<Vec<Inline>>>,
+    used_by: Vec<&'s str>,
+}
+
+impl IpldSchema {
+    /// Generates reference documentation for all types of this schema.
+    ///
+    /// Every type gets a section with its description, definition and representation.
+    /// Structs and enums get a table of their fields or members. Types link to the types
+    /// they reference and list the types they are used by.
+    pub fn generate_docs(&self, title: &str, format: DocsFormat) -> String {
+        let mut used_by = BTreeMap::<&str, Vec<&str>>::new();
+        for (name, def) in &self.0 {
+            let mut edges = vec![];
+            self.collect_edges(name, &def.ty, &mut edges);
+
+            for edge in edges {
+                let users = used_by.entry(edge.to).or_default();
+                if !users.contains(&edge.from) {
+                    users.push(edge.from);
+                }
+            }
+        }
+
+        let sections = self
+            .0
+            .iter()
+            .map(|(name, def)| {
+                let mut section = self.section(name, def);
+                section.used_by = used_by.remove(name.as_str()).unwrap_or_default();
+                section
+            })
+            .collect::<Vec<_>>();
+
+        match format {
+            DocsFormat::Markdown => self.markdown(title, &sections),
+            DocsFormat::Html => self.html(title, &sections),
+        }
+    }
+
+    fn section<'s>(&self, name: &'s str, def: &'s Doc<IpldType>) -> Section<'s> {
+        let definition = definition_to_string(
+            name,
+            &Doc {
+                doc: None,
+                ty: def.ty.clone(),
+            },
+        );
+
+        let mut summary = vec![Inline::Text(format!("Kind: {}", def.ty.type_kind()))];
+        if let Some(repr) = representation(&def.ty) {
+            summary.push(Inline::Text(", representation: ".to_string()));
+            summary.extend(repr);
+        }
+
+        let mut header: &[&str] = &[];
+        let mut rows = vec![];
+        match &def.ty {
+            IpldType::Struct(stru) => {
+                header = &[
+                    "Field",
+                    "Type",
+                    "Optional",
+                    "Nullable",
+                    "Rename",
+                    "Implicit",
+                    "Description",
+                ];
+                for field in &stru.fields {
+                    rows.push(vec![
+                        vec![Inline::Code(field.key.clone())],
+                        self.inline_type(&field.value),
+                        vec![Inline::Text(yes_no(field.optional))],
+                        vec![Inline::Text(yes_no(field.nullable))],
+                        field.rename.iter().cloned().map(Inline::Code).collect(),
+                        field.implicit.iter().cloned().map(Inline::Code).collect(),
+                        field.doc.iter().cloned().map(Inline::Text).collect(),
+                    ]);
+                }
+            }
+            IpldType::Union(union) => {
+                header = &["Member", "Discriminant", "Description"];
+                for member in &union.members {
+                    let discriminant = match union.repr {
+                        UnionRepresentation::Kinded => member.discriminant.clone(),
+                        _ => format!("\"{}\"", member.discriminant),
+                    };
+                    rows.push(vec![
+                        self.inline_type(&member.ty),
+                        vec![Inline::Code(discriminant)],
+                        member.doc.iter().cloned().map(Inline::Text).collect(),
+                    ]);
+                }
+            }
+            IpldType::Enum(enu) => {
+                header = &["Member", "Value", "Description"];
+                for member in &enu.members {
+                    let tag = match &member.tag {
+                        EnumMemberTag::String(tag) => format!("\"{}\"", tag),
+                        EnumMemberTag::Int(tag) => tag.to_string(),
+                    };
+                    rows.push(vec![
+                        vec![Inline::Code(member.name.clone())],
+                        vec![Inline::Code(tag)],
+                        member.doc.iter().cloned().map(Inline::Text).collect(),
+                    ]);
+                }
+            }
+            _ => (),
+        }
+
+        Section {
+            name,
+            doc: def.doc.as_deref(),
+            summary,
+            definition,
+            header,
+            rows,
+            used_by: vec![],
+        }
+    }
+
+    fn inline_type(&self, ty: &InlineIpldType) -> Vec<Inline> {
+        let text = |text: &str| Inline::Text(text.to_string());
+        let nullable = |nullable: bool| {
+            if nullable {
+                vec![text("nullable ")]
+            } else {
+                vec![]
+            }
+        };
+
+        match ty {
+            InlineIpldType::Name(name) => vec![Inline::Type(name.clone())],
+            InlineIpldType::Link(name) => vec![text("&"), Inline::Type(name.clone())],
+            InlineIpldType::List(list) => [
+                vec![text("[")],
+                nullable(list.nullable),
+                self.inline_type(&list.ty),
+                vec![text("]")],
+            ]
+            .concat(),
+            InlineIpldType::Map(map) => [
+                vec![text("{"), Inline::Type(map.key.clone()), text(":")],
+                nullable(map.nullable),
+                self.inline_type(&map.value),
+                vec![text("}")],
+            ]
+            .concat(),
+        }
+    }
+
+    fn markdown(&self, title: &str, sections: &[Section]) -> String {
+        let inline = |inline: &[Inline]| {
+            inline
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Text(text) => text
+                        .replace('\\', "\\\\")
+                        .replace('[', "\\[")
+                        .replace(']', "\\]")
+                        .replace('|', "\\|")
+                        .replace('\n', " "),
+                    Inline::Code(code) => format!("`{}`", code),
+                    Inline::Type(name) if self.0.contains_key(name) => {
+                        format!("[{}](#{})", name, anchor(name))
+                    }
+                    Inline::Type(name) => name.clone(),
+                })
+                .collect::<String>()
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}", title);
+        if !sections.is_empty() {
+            out.push('\n');
+        }
+        for section in sections {
+            let _ = writeln!(out, "- [{}](#{})", section.name, anchor(section.name));
+        }
+
+        for section in sections {
+            let _ = writeln!(out, "\n## {}\n", section.name);
+            if let Some(doc) = section.doc {
+                let _ = writeln!(out, "{}\n", doc);
+            }
+            let _ = writeln!(out, "{}\n", inline(&section.summary));
+            let _ = writeln!(out, "```ipldsch\n{}```", section.definition);
+
+            if !section.rows.is_empty() {
+                let _ = writeln!(out, "\n| {} |", section.header.join(" | "));
+                let _ = writeln!(out, "|{}", " --- |".repeat(section.header.len()));
+                for row in &section.rows {
+                    let cells = row.iter().map(|cell| inline(cell)).collect::<Vec<_>>();
+                    let _ = writeln!(out, "| {} |", cells.join(" | "));
+                }
+            }
+
+            if !section.used_by.is_empty() {
+                let users = section
+                    .used_by
+                    .iter()
+                    .map(|user| format!("[{}](#{})", user, anchor(user)))
+                    .collect::<Vec<_>>();
+                let _ = writeln!(out, "\nUsed by: {}", users.join(", "));
+            }
+        }
+
+        out
+    }
+
+    fn html(&self, title: &str, sections: &[Section]) -> String {
+        let inline = |inline: &[Inline]| {
+            inline
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Text(text) => escape(text),
+                    Inline::Code(code) => format!("<code>{}</code>", escape(code)),
+                    Inline::Type(name) if self.0.contains_key(name) => {
+                        format!("<a href=\"#{}\">{}</a>", anchor(name), escape(name))
+                    }
+                    Inline::Type(name) => escape(name),
+                })
+                .collect::<String>()
+        };
+
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(out, "<title>{}</title>", escape(title));
+        out.push_str("</head>\n<body>\n");
+        let _ = writeln!(out, "<h1>{}</h1>", escape(title));
+
+        out.push_str("<ul>\n");
+        for section in sections {
+            let _ = writeln!(
+                out,
+                "<li><a href=\"#{}\">{}</a></li>",
+                anchor(section.name),
+                escape(section.name)
+            );
+        }
+        out.push_str("</ul>\n");
+
+        for section in sections {
+            let _ = writeln!(out, "<section id=\"{}\">", anchor(section.name));
+            let _ = writeln!(out, "<h2>{}</h2>", escape(section.name));
+            if let Some(doc) = section.doc {
+                let _ = writeln!(out, "<p>{}</p>", escape(doc).replace('\n', "<br>\n"));
+            }
+            let _ = writeln!(out, "<p>{}</p>", inline(&section.summary));
+            let _ = writeln!(
+                out,
+                "<pre><code>{}</code></pre>",
+                escape(&section.definition)
+            );
+
+            if !section.rows.is_empty() {
+                out.push_str("<table>\n<thead>\n<tr>");
+                for header in section.header {
+                    let _ = write!(out, "<th>{}</th>", header);
+                }
+                out.push_str("</tr>\n</thead>\n<tbody>\n");
+                for row in &section.rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        let _ = write!(out, "<td>{}</td>", inline(cell));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</tbody>\n</table>\n");
+            }
+
+            if !section.used_by.is_empty() {
+                let users = section
+                    .used_by
+                    .iter()
+                    .map(|user| format!("<a href=\"#{}\">{}</a>", anchor(user), escape(user)))
+                    .collect::<Vec<_>>();
+                let _ = writeln!(out, "<p>Used by: {}</p>", users.join(", "));
+            }
+            out.push_str("</section>\n");
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Describes the representation of types that have more than one
+fn representation(ty: &IpldType) -> Option<Vec<Inline>> {
+    let code = |code: &str| Inline::Code(code.to_string());
+    let pairs = |inner: &str, entry: &str| {
+        vec![
+            code("stringpairs"),
+            Inline::Text(" with inner delimiter ".to_string()),
+            Inline::Code(format!("\"{}\"", inner)),
+            Inline::Text(" and entry delimiter ".to_string()),
+            Inline::Code(format!("\"{}\"", entry)),
+        ]
+    };
+
+    Some(match ty {
+        IpldType::Struct(stru) => match &stru.repr {
+            StructRepresentation::StringPairs(repr) => pairs(&repr.inner_delim, &repr.entry_delim),
+            repr => vec![code(repr.name())],
+        },
+        IpldType::Bytes(BytesRepresentation::Advanced(name)) => {
+            vec![code(&format!("advanced {}", name))]
+        }
+        IpldType::List(ListType {
+            repr: ListRepresentation::Advanced(name),
+            ..
+        }) => vec![code(&format!("advanced {}", name))],
+        IpldType::Map(map) => match &map.repr {
+            MapRepresentation::StringPairs(repr) => pairs(&repr.inner_delim, &repr.entry_delim),
+            MapRepresentation::Advanced(name) => vec![code(&format!("advanced {}", name))],
+            repr => vec![code(repr.name())],
+        },
+        IpldType::Union(union) => match &union.repr {
+            UnionRepresentation::Inline { discriminant_key } => vec![
+                code("inline"),
+                Inline::Text(" with discriminant key ".to_string()),
+                Inline::Code(format!("\"{}\"", discriminant_key)),
+            ],
+            UnionRepresentation::Envelope {
+                discriminant_key,
+                content_key,
+            } => vec![
+                code("envelope"),
+                Inline::Text(" with discriminant key ".to_string()),
+                Inline::Code(format!("\"{}\"", discriminant_key)),
+                Inline::Text(" and content key ".to_string()),
+                Inline::Code(format!("\"{}\"", content_key)),
+            ],
+            repr => vec![code(repr.name())],
+        },
+        IpldType::Enum(enu) => match enu.repr {
+            EnumRepresentation::String => vec![code("string")],
+            EnumRepresentation::Int => vec![code("int")],
+        },
+        IpldType::Unit(unit) => vec![code(match unit {
+            UnitRepresentation::Null => "null",
+            UnitRepresentation::True => "true",
+            UnitRepresentation::False => "false",
+            UnitRepresentation::EmptyMap => "emptymap",
+        })],
+        _ => return None,
+    })
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+/// The id of the section of a type, matching the anchors Markdown renderers generate
+fn anchor(name: &str) -> String {
+    name.to_lowercase()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_docs() {
+        let schema = IpldSchema::parse(include_str!("../test/message.ipldsch")).unwrap();
+
+        let expected = "\
+# Messages
+
+- [Identity](#identity)
+- [Message](#message)
+- [Status](#status)
+
+## Identity
+
+Kind: copy
+
+```ipldsch
+type Identity = String
+```
+
+Used by: [Message](#message)
+
+## Message
+
+A message
+
+Kind: struct, representation: `map`
+
+```ipldsch
+type Message struct {
+  # Who sent it
+  from &Identity
+  to nullable [nullable Identity]
+  status Status (rename \"s\")
+  count Int (implicit \"0\")
+  labels optional {Status:Int}
+  data Bytes (rename \"d\")
+}
+```
+
+| Field | Type | Optional | Nullable | Rename | Implicit | Description |
+| --- | --- | --- | --- | --- | --- | --- |
+| `from` | &[Identity](#identity) | no | no |  |  | Who sent it |
+| `to` | \\[nullable [Identity](#identity)\\] | no | yes |  |  |  |
+| `status` | [Status](#status) | no | no | `s` |  |  |
+| `count` | Int | no | no |  | `0` |  |
+| `labels` | {[Status](#status):Int} | yes | no |  |  |  |
+| `data` | Bytes | no | no | `d` |  |  |
+
+## Status
+
+Kind: enum, representation: `string`
+
+```ipldsch
+type Status enum {
+  # Still there
+  | Active (\"Act\")
+  | Gone
+}
+```
+
+| Member | Value | Description |
+| --- | --- | --- |
+| `Active` | `\"Act\"` | Still there |
+| `Gone` | `\"Gone\"` |  |
+
+Used by: [Message](#message)
+";
+        assert_eq!(
+            schema.generate_docs("Messages", DocsFormat::Markdown),
+            expected
+        );
+
+        let html = schema.generate_docs("Messages", DocsFormat::Html);
+        assert!(html.contains("<section id=\"message\">\n<h2>Message</h2>\n<p>A message</p>"));
+        assert!(html.contains(
+            "<tr><td><code>from</code></td><td>&amp;<a href=\"#identity\">Identity</a></td>"
+        ));
+        assert!(html.contains("<p>Used by: <a href=\"#message\">Message</a></p>"));
+    }
+
+    #[test]
+    fn unions() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+        let docs = schema.generate_docs("Shapes", DocsFormat::Markdown);
+
+        for part in [
+            "\
+| Member | Discriminant | Description |
+| --- | --- | --- |
+| [Circle](#circle) | `\"circle\"` | A circle |
+| &[Square](#square) | `\"square\"` |  |
+
+Used by: [Value](#value)
+",
+            "| [Shape](#shape) | `map` |  |",
+            "Kind: union, representation: `envelope` with discriminant key `\"tag\"` and content key `\"content\"`",
+            "Kind: struct, representation: `tuple`",
+        ] {
+            assert!(docs.contains(part), "{}", docs);
+        }
+    }
+}
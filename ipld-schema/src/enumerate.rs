@@ -10,19 +10,29 @@ pub enum InvalidEnum {
     InvalidMemberTag,
 }
 
+/// An enum type with its members in the order of the source
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct EnumType {
-    members: Vec<EnumField>,
-    repr: EnumRepresentation,
+pub struct EnumType {
+    pub members: Vec<EnumField>,
+    pub repr: EnumRepresentation,
+}
+
+impl EnumType {
+    /// Returns true, if all members are represented as strings
+    pub(crate) fn is_string(&self) -> bool {
+        self.members
+            .iter()
+            .all(|member| matches!(member.tag, EnumMemberTag::String(_)))
+    }
 }
 
 pub(crate) fn parse_enum(enu: Pairs<Rule>) -> Result<EnumType, IpldSchemaParseError> {
-    let mut members = vec![];
+    let mut fields = vec![];
     let mut repr = None;
 
     for pair in enu {
         match pair.as_rule() {
-            Rule::enum_field => members.push(parse_enum_field(pair.into_inner())?),
+            Rule::enum_field => fields.push(parse_enum_field(pair.into_inner())),
             Rule::enum_repr => {
                 assert!(repr.is_none());
                 repr = Some(parse_enum_representation(pair.into_inner()));
@@ -31,20 +41,48 @@ pub(crate) fn parse_enum(enu: Pairs<Rule>) -> Result<EnumType, IpldSchemaParseEr
         }
     }
 
-    Ok(EnumType {
-        members,
-        repr: repr.unwrap_or(EnumRepresentation::String),
-    })
+    let repr = repr.unwrap_or(EnumRepresentation::String);
+    let members = enum_members(fields, &repr).map_err(IpldSchemaParseError::Enum)?;
+
+    Ok(EnumType { members, repr })
 }
 
+/// Interprets the docs, names and tags of members as written in the schema
+pub(crate) fn enum_members(
+    fields: Vec<(Option<String>, String, Option<String>)>,
+    repr: &EnumRepresentation,
+) -> Result<Vec<EnumField>, InvalidEnum> {
+    fields
+        .into_iter()
+        .map(|(doc, name, tag)| {
+            let tag = match (repr, tag) {
+                (EnumRepresentation::String, tag) => {
+                    EnumMemberTag::String(tag.unwrap_or_else(|| name.clone()))
+                }
+                (EnumRepresentation::Int, Some(tag)) => EnumMemberTag::Int(
+                    tag.parse::<i128>()
+                        .map_err(|_| InvalidEnum::InvalidMemberTag)?,
+                ),
+                (EnumRepresentation::Int, None) => return Err(InvalidEnum::InvalidMemberTag),
+            };
+
+            Ok(EnumField { doc, name, tag })
+        })
+        .collect()
+}
+
+/// A member of an enum
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct EnumField {
-    doc: Option<String>,
-    name: String,
-    tag: EnumMemberTag,
+pub struct EnumField {
+    pub doc: Option<String>,
+    pub name: String,
+    pub tag: EnumMemberTag,
 }
 
-fn parse_enum_field(mut field: Pairs<Rule>) -> Result<EnumField, IpldSchemaParseError> {
+/// Parses an enum member into its doc, its name and its tag as written in the schema.
+///
+/// The tag can only be interpreted, once the representation of the enum is known.
+fn parse_enum_field(mut field: Pairs<Rule>) -> (Option<String>, String, Option<String>) {
     let doc = if field.peek().unwrap().as_rule() == Rule::comment {
         Some(parse_comment(field.next().unwrap().into_inner()))
     } else {
@@ -54,7 +92,7 @@ fn parse_enum_field(mut field: Pairs<Rule>) -> Result<EnumField, IpldSchemaParse
     let name = field.next().unwrap();
     let name = name.as_str().to_string();
 
-    let tag = if let Some(repr) = field.next() {
+    let tag = field.next().map(|repr| {
         assert_eq!(repr.as_rule(), Rule::enum_field_repr);
 
         let mut inner = repr.into_inner();
@@ -62,25 +100,22 @@ fn parse_enum_field(mut field: Pairs<Rule>) -> Result<EnumField, IpldSchemaParse
         assert!(inner.next().is_none());
 
         assert_eq!(val.as_rule(), Rule::enum_field_repr_value);
-        match val.as_str().parse::<i128>() {
-            Ok(int_val) => EnumMemberTag::Int(int_val),
-            Err(_) => EnumMemberTag::String(val.as_str().to_string()),
-        }
-    } else {
-        EnumMemberTag::String(name.clone())
-    };
+        val.as_str().to_string()
+    });
 
-    Ok(EnumField { doc, name, tag })
+    (doc, name, tag)
 }
 
+/// The value, that represents an enum member in the data
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum EnumMemberTag {
+pub enum EnumMemberTag {
     Int(i128),
     String(String),
 }
 
+/// Whether enum members are represented as strings or ints
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) enum EnumRepresentation {
+pub enum EnumRepresentation {
     String,
     Int,
 }
@@ -0,0 +1,474 @@
+use std::collections::BTreeMap;
+
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    ipld::Ipld,
+};
+
+use crate::{
+    check::has_value,
+    enumerate::EnumMemberTag,
+    generate::{advanced_map, join_pairs, GenerateError},
+    list::ListRepresentation,
+    map::MapRepresentation,
+    structural::{StructField, StructRepresentation},
+    unit::UnitRepresentation,
+    InlineIpldType, IpldSchema, IpldType,
+};
+
+/// The link used in examples, a CIDv1 of the empty `raw` block with an `identity` hash
+fn example_link() -> Cid {
+    Cid::new_v1(0x55, Multihash::wrap(0x00, &[]).unwrap())
+}
+
+/// A named example value of a type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    /// i.e. `minimal`, `full` or the name of an enum member
+    pub name: String,
+    pub data: Ipld,
+}
+
+impl IpldSchema {
+    /// Creates a deterministic set of examples of the type `root`.
+    ///
+    /// The `minimal` example only contains required fields, empty lists and maps and null for
+    /// nullable values. The `full` example fills in every optional field, nullable value, list
+    /// and map, unless that would recurse into a type that is already being filled in.
+    /// Enums have one example per member instead, named after the member, and unions have
+    /// the full example of each member with values, named after its discriminant.
+    pub fn examples(&self, root: &str) -> Result<Vec<Example>, GenerateError> {
+        let ty = self
+            .resolve_inline(&InlineIpldType::Name(root.to_string()))
+            .ok_or_else(|| GenerateError::UnknownType(root.to_string()))?;
+        let inhabited = self.inhabited();
+        if !inhabited.contains_key(root) {
+            return Err(GenerateError::Uninhabited(root.to_string()));
+        }
+
+        if let IpldType::Enum(enu) = ty.as_ref() {
+            return Ok(enu
+                .members
+                .iter()
+                .map(|member| Example {
+                    name: member.name.clone(),
+                    data: tag_value(&member.tag),
+                })
+                .collect());
+        }
+        if let IpldType::Union(union) = ty.as_ref() {
+            let mut filler = Filler::new(self, &inhabited, true);
+            filler.stack.push(root.to_string());
+            return Ok(union
+                .members
+                .iter()
+                .filter(|member| has_value(&member.ty, &inhabited))
+                .map(|member| Example {
+                    name: member.discriminant.clone(),
+                    data: union.wrap(member, filler.inline(&member.ty, false)),
+                })
+                .collect());
+        }
+
+        let root = InlineIpldType::Name(root.to_string());
+        let minimal = Filler::new(self, &inhabited, false).inline(&root, false);
+        let full = Filler::new(self, &inhabited, true).inline(&root, false);
+
+        let mut examples = vec![Example {
+            name: "minimal".to_string(),
+            data: minimal,
+        }];
+        if examples[0].data != full {
+            examples.push(Example {
+                name: "full".to_string(),
+                data: full,
+            });
+        }
+        Ok(examples)
+    }
+}
+
+fn tag_value(tag: &EnumMemberTag) -> Ipld {
+    match tag {
+        EnumMemberTag::String(tag) => Ipld::String(tag.clone()),
+        EnumMemberTag::Int(tag) => Ipld::Integer(*tag),
+    }
+}
+
+/// Fills in values of the types with values only.
+///
+/// Outside of the outermost occurrence of a type, only required values are filled in and
+/// unions use the member found first by [`IpldSchema::inhabited`], so every value is finite.
+struct Filler<'s> {
+    schema: &'s IpldSchema,
+    /// The types with values, mapped to the round they were found in
+    inhabited: &'s BTreeMap<String, usize>,
+    full: bool,
+    /// The named types being filled in
+    stack: Vec<String>,
+}
+
+impl<'s> Filler<'s> {
+    fn new(schema: &'s IpldSchema, inhabited: &'s BTreeMap<String, usize>, full: bool) -> Self {
+        Self {
+            schema,
+            inhabited,
+            full,
+            stack: vec![],
+        }
+    }
+
+    fn has_value(&self, ty: &InlineIpldType) -> bool {
+        has_value(ty, self.inhabited)
+    }
+
+    fn inline(&mut self, ty: &InlineIpldType, nullable: bool) -> Ipld {
+        if nullable && (!self.full || !self.has_value(ty)) {
+            return Ipld::Null;
+        }
+        let resolved = self
+            .schema
+            .resolve_inline(ty)
+            .expect("only types with values are filled in");
+
+        let InlineIpldType::Name(name) = ty else {
+            return self.value(&resolved);
+        };
+
+        // Recursive types are only filled in at their outermost occurrence
+        let full = self.full;
+        self.full = full && !self.stack.contains(name);
+        self.stack.push(name.clone());
+        let value = self.value(&resolved);
+        self.stack.pop();
+        self.full = full;
+
+        value
+    }
+
+    fn value(&mut self, ty: &IpldType) -> Ipld {
+        match ty {
+            IpldType::Bool => Ipld::Bool(false),
+            IpldType::String => Ipld::String(String::new()),
+            IpldType::Bytes(_) => Ipld::Bytes(vec![]),
+            IpldType::Int => Ipld::Integer(0),
+            IpldType::Float => Ipld::Float(0.0),
+            IpldType::Link(_) => Ipld::Link(example_link()),
+            IpldType::Any => Ipld::Null,
+            IpldType::List(list) => {
+                let has_item = list.nullable || self.has_value(&list.ty);
+                if self.full && has_item && list.repr == ListRepresentation::List {
+                    Ipld::List(vec![self.inline(&list.ty, list.nullable)])
+                } else {
+                    Ipld::List(vec![])
+                }
+            }
+            IpldType::Map(map) => {
+                let key = InlineIpldType::Name(map.key.clone());
+                let entries = if self.full && (map.nullable || self.has_value(&map.value)) {
+                    vec![(
+                        self.string_value(&key),
+                        self.inline(&map.value, map.nullable),
+                    )]
+                } else {
+                    vec![]
+                };
+
+                match &map.repr {
+                    MapRepresentation::Map => Ipld::Map(entries.into_iter().collect()),
+                    MapRepresentation::ListPairs => Ipld::List(
+                        entries
+                            .into_iter()
+                            .map(|(key, value)| Ipld::List(vec![Ipld::String(key), value]))
+                            .collect(),
+                    ),
+                    MapRepresentation::StringPairs(pairs) => {
+                        let entries = entries
+                            .into_iter()
+                            .map(|(key, _)| (key, self.string_value(&map.value)))
+                            .collect();
+                        join_pairs(pairs, entries)
+                    }
+                    MapRepresentation::Advanced(adl) => advanced_map(adl, entries),
+                }
+            }
+            IpldType::Struct(stru) => {
+                let full = self.full;
+                let required = stru.required_tuple_fields();
+                let has_value =
+                    |field: &StructField| field.nullable || has_value(&field.value, self.inhabited);
+                let fields = stru
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .take_while(|(idx, field)| match stru.repr {
+                        StructRepresentation::Tuple => *idx < required || full && has_value(field),
+                        _ => true,
+                    })
+                    .filter(|(_, field)| match stru.repr {
+                        StructRepresentation::Tuple => true,
+                        _ if field.is_required() => true,
+                        _ => full && has_value(field),
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut entries = vec![];
+                for (_, field) in fields {
+                    let value = match stru.repr {
+                        StructRepresentation::StringPairs(_) => {
+                            Ipld::String(self.string_value(&field.value))
+                        }
+                        _ => self.inline(&field.value, field.nullable),
+                    };
+                    entries.push((key(field), value));
+                }
+
+                match &stru.repr {
+                    StructRepresentation::Map => Ipld::Map(entries.into_iter().collect()),
+                    StructRepresentation::ListPairs => Ipld::List(
+                        entries
+                            .into_iter()
+                            .map(|(key, value)| Ipld::List(vec![Ipld::String(key), value]))
+                            .collect(),
+                    ),
+                    StructRepresentation::StringPairs(pairs) => {
+                        let entries = entries
+                            .into_iter()
+                            .map(|(key, value)| match value {
+                                Ipld::String(value) => (key, value),
+                                _ => unreachable!("stringpairs values are strings"),
+                            })
+                            .collect();
+                        join_pairs(pairs, entries)
+                    }
+                    StructRepresentation::Tuple => {
+                        Ipld::List(entries.into_iter().map(|(_, value)| value).collect())
+                    }
+                }
+            }
+            IpldType::Union(union) => {
+                let inhabited = self.inhabited;
+                let member = union
+                    .members
+                    .iter()
+                    .min_by_key(|member| {
+                        match &member.ty {
+                            InlineIpldType::Name(name) => inhabited.get(name).copied(),
+                            _ => Some(0),
+                        }
+                        .unwrap_or(usize::MAX)
+                    })
+                    .expect("unions with values have members");
+                let value = self.inline(&member.ty, false);
+                union.wrap(member, value)
+            }
+            IpldType::Enum(enu) => enu
+                .members
+                .first()
+                .map_or(Ipld::Null, |member| tag_value(&member.tag)),
+            IpldType::Unit(unit) => match unit {
+                UnitRepresentation::Null => Ipld::Null,
+                UnitRepresentation::True => Ipld::Bool(true),
+                UnitRepresentation::False => Ipld::Bool(false),
+                UnitRepresentation::EmptyMap => Ipld::Map(BTreeMap::new()),
+            },
+            IpldType::Copy(from) => self.inline(&InlineIpldType::Name(from.clone()), false),
+        }
+    }
+
+    /// An example of a value stored as a string, i.e. a map key
+    fn string_value(&self, ty: &InlineIpldType) -> String {
+        match self.schema.resolve_inline(ty).as_deref() {
+            Some(IpldType::Int) => "0".to_string(),
+            Some(IpldType::Float) => "0.0".to_string(),
+            Some(IpldType::Bool) => "false".to_string(),
+            Some(IpldType::Enum(enu)) => match enu.members.first().map(|member| &member.tag) {
+                Some(EnumMemberTag::String(tag)) => tag.clone(),
+                Some(EnumMemberTag::Int(tag)) => tag.to_string(),
+                None => String::new(),
+            },
+            _ => "key".to_string(),
+        }
+    }
+}
+
+fn key(field: &StructField) -> String {
+    field.rename.clone().unwrap_or_else(|| field.key.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use ipld_core::ipld;
+
+    use super::*;
+
+    #[test]
+    fn examples() {
+        let schema = IpldSchema::parse(
+            "\
+type Message struct {
+  from String (rename \"f\")
+  tags optional [Tag]
+  reply nullable &Message
+  thread optional Message
+  meta optional {String:Int}
+}
+
+type Tag enum {
+  | Urgent (\"U\")
+  | Later
+}
+
+type Level enum {
+  | Low (\"1\")
+  | High (\"2\")
+} representation int
+",
+        )
+        .unwrap();
+
+        let examples = schema.examples("Message").unwrap();
+        assert_eq!(
+            examples,
+            vec![
+                Example {
+                    name: "minimal".to_string(),
+                    data: ipld!({ "f": "", "reply": null }),
+                },
+                Example {
+                    name: "full".to_string(),
+                    data: ipld!({
+                        "f": "",
+                        "tags": ["U"],
+                        "reply": example_link(),
+                        "thread": { "f": "", "reply": null },
+                        "meta": { "key": 0 },
+                    }),
+                },
+            ]
+        );
+        for example in examples {
+            schema.validate("Message", &example.data).unwrap();
+        }
+
+        let examples = schema.examples("Level").unwrap();
+        assert_eq!(
+            examples
+                .iter()
+                .map(|example| (example.name.as_str(), &example.data))
+                .collect::<Vec<_>>(),
+            vec![("Low", &Ipld::Integer(1)), ("High", &Ipld::Integer(2))]
+        );
+
+        assert_eq!(
+            schema.examples("Int").unwrap(),
+            vec![Example {
+                name: "minimal".to_string(),
+                data: Ipld::Integer(0),
+            }]
+        );
+        assert_eq!(
+            schema.examples("Missing"),
+            Err(GenerateError::UnknownType("Missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn unions() {
+        let schema = IpldSchema::parse(concat!(
+            include_str!("../test/union.ipldsch"),
+            "
+type Node struct {
+  next Node
+}
+
+type Expr union {
+  | Node \"node\"
+  | Sum \"sum\"
+  | Int \"int\"
+} representation keyed
+
+type Sum struct {
+  left Expr
+  right optional Expr
+}
+
+type Holder struct {
+  node optional Node
+  nodes [Node]
+  expr nullable Expr
+}
+
+type Pair struct {
+  nodes {String:Node}
+  node optional Node
+} representation tuple
+"
+        ))
+        .unwrap();
+
+        let examples = |name| {
+            let examples = schema.examples(name).unwrap();
+            for example in &examples {
+                schema.validate(name, &example.data).unwrap();
+            }
+            examples
+                .into_iter()
+                .map(|example| (example.name, example.data))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            examples("Shape"),
+            vec![
+                ("circle".to_string(), ipld!({ "circle": { "radius": 0 } })),
+                ("square".to_string(), ipld!({ "square": example_link() })),
+            ]
+        );
+        assert_eq!(
+            examples("Value"),
+            vec![
+                ("int".to_string(), ipld!(0)),
+                ("map".to_string(), ipld!({ "square": example_link() })),
+                ("list".to_string(), ipld!([0, 0, ""])),
+            ]
+        );
+        assert_eq!(
+            examples("Tagged"),
+            vec![("c".to_string(), ipld!({ "tag": "c", "radius": 0 }))]
+        );
+        assert_eq!(
+            examples("Wrapped")[1],
+            (
+                "s".to_string(),
+                ipld!({ "tag": "s", "content": { "side": 0 } })
+            )
+        );
+
+        // Members without values are left out, recursive ones end in the simplest member
+        assert_eq!(
+            examples("Expr"),
+            vec![
+                (
+                    "sum".to_string(),
+                    ipld!({ "sum": { "left": { "int": 0 }, "right": { "int": 0 } } })
+                ),
+                ("int".to_string(), ipld!({ "int": 0 })),
+            ]
+        );
+        assert_eq!(
+            examples("Holder"),
+            vec![
+                ("minimal".to_string(), ipld!({ "nodes": [], "expr": null })),
+                (
+                    "full".to_string(),
+                    ipld!({ "nodes": [], "expr": { "int": 0 } })
+                ),
+            ]
+        );
+        assert_eq!(examples("Pair"), vec![("minimal".to_string(), ipld!([{}]))]);
+        assert_eq!(
+            schema.examples("Node"),
+            Err(GenerateError::Uninhabited("Node".to_string()))
+        );
+    }
+}
@@ -0,0 +1,787 @@
+use std::collections::BTreeMap;
+
+use arbitrary::Unstructured;
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    ipld::Ipld,
+};
+use thiserror::Error;
+
+use crate::{
+    check::has_value,
+    enumerate::{EnumMemberTag, EnumType},
+    hamt::{self, HASH_MAP},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::{BytesRepresentation, StringPairs},
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionMember, UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    InlineIpldType, IpldSchema, IpldType, Kind,
+};
+
+/// The number of entries of generated lists and maps is at most this
+const MAX_ENTRIES: u32 = 4;
+/// The codec of generated links, `raw`
+const RAW: u64 = 0x55;
+/// The multihash of generated links, `identity`
+const IDENTITY: u64 = 0x00;
+
+const KINDS: &[Kind] = &[
+    Kind::Null,
+    Kind::Bool,
+    Kind::Int,
+    Kind::Float,
+    Kind::String,
+    Kind::Bytes,
+    Kind::Link,
+    Kind::List,
+    Kind::Map,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GenerateError {
+    #[error("Type \"{0}\" is not defined")]
+    UnknownType(String),
+    #[error("Type \"{0}\" has no values, that do not nest forever")]
+    Uninhabited(String),
+}
+
+/// Generates random data for a type of a schema.
+///
+/// The randomness is taken from an [`Unstructured`], which makes the generator usable in
+/// `arbitrary` based fuzz targets. With the `proptest` feature it also provides a proptest
+/// [`Strategy`](proptest::strategy::Strategy).
+#[derive(Debug, Clone)]
+pub struct DataGenerator {
+    schema: IpldSchema,
+    root: String,
+    /// The types, that have values, see [`IpldSchema::inhabited`]
+    inhabited: BTreeMap<String, usize>,
+    max_depth: usize,
+    near_miss: bool,
+}
+
+impl IpldSchema {
+    /// Creates a generator for data of the type `root`
+    pub fn generator(&self, root: &str) -> Result<DataGenerator, GenerateError> {
+        if self.resolve(root).is_none() {
+            return Err(GenerateError::UnknownType(root.to_string()));
+        }
+        let inhabited = self.inhabited();
+        if !inhabited.contains_key(root) {
+            return Err(GenerateError::Uninhabited(root.to_string()));
+        }
+
+        Ok(DataGenerator {
+            schema: self.clone(),
+            root: root.to_string(),
+            inhabited,
+            max_depth: 4,
+            near_miss: false,
+        })
+    }
+}
+
+impl DataGenerator {
+    /// Limits how deep lists, maps and structs are nested, defaults to 4.
+    ///
+    /// Below that depth lists and maps are empty, optional fields are omitted and nullable
+    /// values are null. Required fields of recursive types are still generated.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Generates near misses instead of valid data.
+    ///
+    /// A near miss is valid data with exactly one defect, i.e. a value of the wrong kind,
+    /// a missing field or an unknown enum member. Types that accept every value, like `Any`,
+    /// have no near misses, so the generated data can still be valid for them.
+    pub fn with_near_miss(mut self, near_miss: bool) -> Self {
+        self.near_miss = near_miss;
+        self
+    }
+
+    /// Generates a value, taking all decisions from `u`
+    pub fn generate(&self, u: &mut Unstructured) -> arbitrary::Result<Ipld> {
+        let root = InlineIpldType::Name(self.root.clone());
+        self.inline(u, &root, self.max_depth as isize, self.near_miss)
+    }
+
+    /// A strategy generating values from random bytes, which shrinks along with the bytes
+    #[cfg(feature = "proptest")]
+    pub fn strategy(&self) -> impl proptest::strategy::Strategy<Value = Ipld> {
+        use proptest::{arbitrary::any, collection::vec, strategy::Strategy};
+
+        let generator = self.clone();
+        vec(any::<u8>(), 0..1024).prop_filter_map("Generation failed", move |bytes| {
+            generator.generate(&mut Unstructured::new(&bytes)).ok()
+        })
+    }
+
+    /// `depth` can get negative for required fields, `corrupt` asks for a near miss
+    fn inline(
+        &self,
+        u: &mut Unstructured,
+        ty: &InlineIpldType,
+        depth: isize,
+        corrupt: bool,
+    ) -> arbitrary::Result<Ipld> {
+        // Values of inhabited types are finite, so this only guards against recursing forever
+        if depth < -(self.schema.0.len() as isize) - 8 {
+            return Err(arbitrary::Error::IncorrectFormat);
+        }
+
+        match self.schema.resolve_inline(ty) {
+            Some(resolved) => self.value(u, &resolved, depth, corrupt),
+            None => Err(arbitrary::Error::IncorrectFormat),
+        }
+    }
+
+    fn nullable(
+        &self,
+        u: &mut Unstructured,
+        ty: &InlineIpldType,
+        nullable: bool,
+        depth: isize,
+        corrupt: bool,
+    ) -> arbitrary::Result<Ipld> {
+        if nullable && !corrupt && (depth <= 0 || !self.has_value(ty) || u.ratio(1, 4)?) {
+            return Ok(Ipld::Null);
+        }
+        self.inline(u, ty, depth, corrupt)
+    }
+
+    fn has_value(&self, ty: &InlineIpldType) -> bool {
+        has_value(ty, &self.inhabited)
+    }
+
+    /// Returns true, if there are invalid values of `ty`, that are built like valid ones
+    fn can_corrupt(&self, ty: &InlineIpldType) -> bool {
+        self.has_value(ty)
+            && self
+                .schema
+                .resolve_inline(ty)
+                .is_some_and(|ty| *ty != IpldType::Any)
+    }
+
+    fn value(
+        &self,
+        u: &mut Unstructured,
+        ty: &IpldType,
+        depth: isize,
+        corrupt: bool,
+    ) -> arbitrary::Result<Ipld> {
+        let scalar = |kind: Kind, u: &mut Unstructured| {
+            if corrupt {
+                Ok(wrong_kind(kind))
+            } else {
+                scalar(u, kind)
+            }
+        };
+
+        match ty {
+            IpldType::Bool => scalar(Kind::Bool, u),
+            IpldType::String => scalar(Kind::String, u),
+            IpldType::Bytes(BytesRepresentation::Bytes) => scalar(Kind::Bytes, u),
+            IpldType::Bytes(BytesRepresentation::Advanced(_)) if !corrupt => {
+                Ok(Ipld::Bytes(vec![]))
+            }
+            IpldType::Bytes(BytesRepresentation::Advanced(_)) => Ok(wrong_kind(Kind::Bytes)),
+            IpldType::Int => scalar(Kind::Int, u),
+            IpldType::Float => scalar(Kind::Float, u),
+            IpldType::Link(_) => scalar(Kind::Link, u),
+            IpldType::Any => any(u, depth),
+            IpldType::List(list) => self.list(u, list, depth, corrupt),
+            IpldType::Map(map) => self.map(u, map, depth, corrupt),
+            IpldType::Struct(stru) => self.structure(u, stru, depth, corrupt),
+            IpldType::Union(union) => self.union(u, union, depth, corrupt),
+            IpldType::Enum(enu) => enumeration(u, enu, corrupt),
+            IpldType::Unit(unit) => Ok(unit_value(unit, corrupt)),
+            IpldType::Copy(from) => {
+                self.inline(u, &InlineIpldType::Name(from.clone()), depth, corrupt)
+            }
+        }
+    }
+
+    fn list(
+        &self,
+        u: &mut Unstructured,
+        list: &ListType,
+        depth: isize,
+        corrupt: bool,
+    ) -> arbitrary::Result<Ipld> {
+        // The substrate of layouts other than the HAMT is unknown, so it is left empty
+        if let ListRepresentation::Advanced(_) = list.repr {
+            return Ok(match corrupt {
+                true => wrong_kind(Kind::List),
+                false => Ipld::List(vec![]),
+            });
+        }
+
+        let corrupt_item = corrupt && self.can_corrupt(&list.ty) && u.arbitrary()?;
+        if corrupt && !corrupt_item {
+            return Ok(wrong_kind(Kind::List));
+        }
+
+        let mut len = entries(u, depth)?;
+        if !list.nullable && !self.has_value(&list.ty) {
+            len = 0;
+        }
+        if corrupt_item {
+            len = len.max(1);
+        }
+        let corrupted = u.choose_index(len.max(1))?;
+
+        let items = (0..len)
+            .map(|idx| {
+                let corrupt = corrupt_item && idx == corrupted;
+                self.nullable(u, &list.ty, list.nullable, depth - 1, corrupt)
+            })
+            .collect::<arbitrary::Result<_>>()?;
+        Ok(Ipld::List(items))
+    }
+
+    fn map(
+        &self,
+        u: &mut Unstructured,
+        map: &MapType,
+        depth: isize,
+        corrupt: bool,
+    ) -> arbitrary::Result<Ipld> {
+        let key = InlineIpldType::Name(map.key.clone());
+        let corrupt_value =
+            corrupt && map.repr == MapRepresentation::Map && self.can_corrupt(&map.value);
+        let corrupt_value = corrupt_value && u.arbitrary()?;
+
+        if corrupt && !corrupt_value {
+            let kind = match map.repr {
+                MapRepresentation::Map | MapRepresentation::Advanced(_) => Kind::Map,
+                MapRepresentation::ListPairs => Kind::List,
+                MapRepresentation::StringPairs(_) => Kind::String,
+            };
+            return Ok(wrong_kind(kind));
+        }
+
+        let mut len = entries(u, depth)?;
+        if !map.nullable && !self.has_value(&map.value) {
+            len = 0;
+        }
+        if corrupt_value {
+            len = len.max(1);
+        }
+
+        let mut keys = vec![];
+        for _ in 0..len {
+            let key = self.string_value(u, &key)?;
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        let corrupted = u.choose_index(keys.len().max(1))?;
+
+        match &map.repr {
+            MapRepresentation::Map => {
+                let mut entries = BTreeMap::new();
+                for (idx, key) in keys.into_iter().enumerate() {
+                    let corrupt = corrupt_value && idx == corrupted;
+                    let value = self.nullable(u, &map.value, map.nullable, depth - 1, corrupt)?;
+                    entries.insert(key, value);
+                }
+                Ok(Ipld::Map(entries))
+            }
+            MapRepresentation::ListPairs => {
+                let mut pairs = vec![];
+                for key in keys {
+                    let value = self.nullable(u, &map.value, map.nullable, depth - 1, false)?;
+                    pairs.push(Ipld::List(vec![Ipld::String(key), value]));
+                }
+                Ok(Ipld::List(pairs))
+            }
+            MapRepresentation::StringPairs(pairs) => {
+                let mut entries = vec![];
+                for key in keys {
+                    entries.push((key, self.string_value(u, &map.value)?));
+                }
+                Ok(join_pairs(pairs, entries))
+            }
+            MapRepresentation::Advanced(adl) => {
+                let mut entries = vec![];
+                for key in keys {
+                    let value = self.nullable(u, &map.value, map.nullable, depth - 1, false)?;
+                    entries.push((key, value));
+                }
+                Ok(advanced_map(adl, entries))
+            }
+        }
+    }
+
+    fn structure(
+        &self,
+        u: &mut Unstructured,
+        stru: &StructType,
+        depth: isize,
+        corrupt: bool,
+    ) -> arbitrary::Result<Ipld> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Defect {
+            None,
+            Kind,
+            Missing(usize),
+            Unexpected,
+            Field(usize),
+        }
+
+        let key = |field: &StructField| field.rename.clone().unwrap_or_else(|| field.key.clone());
+        let defect = if corrupt {
+            let mut defects = vec![Defect::Kind];
+            for (idx, field) in stru.fields.iter().enumerate() {
+                if field.is_required() {
+                    defects.push(Defect::Missing(idx));
+                }
+                if self.can_corrupt(&field.value) && !is_string_pairs(stru) {
+                    defects.push(Defect::Field(idx));
+                }
+            }
+            if !is_string_pairs(stru) {
+                defects.push(Defect::Unexpected);
+            }
+            *u.choose(&defects)?
+        } else {
+            Defect::None
+        };
+
+        if defect == Defect::Kind {
+            let kind = match stru.repr {
+                StructRepresentation::Map => Kind::Map,
+                StructRepresentation::ListPairs | StructRepresentation::Tuple => Kind::List,
+                StructRepresentation::StringPairs(_) => Kind::String,
+            };
+            return Ok(wrong_kind(kind));
+        }
+
+        // Tuple values keep the corrupted field, even if it could be left out
+        let kept = match defect {
+            Defect::Field(idx) => stru.required_tuple_fields().max(idx + 1),
+            _ => stru.required_tuple_fields(),
+        };
+
+        let mut entries = vec![];
+        for (idx, field) in stru.fields.iter().enumerate() {
+            let is_corrupted = defect == Defect::Field(idx);
+            let present = match (defect, &stru.repr) {
+                (Defect::Missing(missing), _) if missing == idx => false,
+                (_, StructRepresentation::Tuple) if idx < kept => true,
+                _ if is_corrupted || field.is_required() => true,
+                _ if !field.nullable && !self.has_value(&field.value) => false,
+                _ => depth > 0 && u.arbitrary()?,
+            };
+            match (present, &stru.repr) {
+                (false, StructRepresentation::Tuple) => break,
+                (false, _) => continue,
+                (true, _) => (),
+            }
+
+            let value = match stru.repr {
+                StructRepresentation::StringPairs(_) => {
+                    Ipld::String(self.string_value(u, &field.value)?)
+                }
+                _ => self.nullable(u, &field.value, field.nullable, depth - 1, is_corrupted)?,
+            };
+            entries.push((key(field), value));
+        }
+
+        if defect == Defect::Unexpected {
+            let mut unexpected = "unexpected".to_string();
+            while stru.fields.iter().any(|field| key(field) == unexpected) {
+                unexpected.push('_');
+            }
+            entries.push((unexpected, Ipld::Null));
+        }
+
+        Ok(match &stru.repr {
+            StructRepresentation::Map => Ipld::Map(entries.into_iter().collect()),
+            StructRepresentation::ListPairs => Ipld::List(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| Ipld::List(vec![Ipld::String(key), value]))
+                    .collect(),
+            ),
+            StructRepresentation::StringPairs(pairs) => join_pairs(
+                pairs,
+                entries
+                    .into_iter()
+                    .map(|(key, value)| match value {
+                        Ipld::String(value) => (key, value),
+                        _ => unreachable!("stringpairs values are generated as strings"),
+                    })
+                    .collect(),
+            ),
+            StructRepresentation::Tuple => {
+                Ipld::List(entries.into_iter().map(|(_, value)| value).collect())
+            }
+        })
+    }
+
+    /// A near miss of a kinded union is of a kind without a member, of other unions it has an
+    /// unknown discriminant or an invalid member
+    fn union(
+        &self,
+        u: &mut Unstructured,
+        union: &UnionType,
+        depth: isize,
+        corrupt: bool,
+    ) -> arbitrary::Result<Ipld> {
+        let members = union
+            .members
+            .iter()
+            .filter(|member| self.has_value(&member.ty))
+            .collect::<Vec<_>>();
+        let member = *u.choose(&members)?;
+
+        if corrupt {
+            if let UnionRepresentation::Kinded = union.repr {
+                // Null could be valid in a nullable position
+                let kinds = KINDS
+                    .iter()
+                    .filter(|kind| **kind != Kind::Null)
+                    .filter(|kind| {
+                        union
+                            .members
+                            .iter()
+                            .all(|member| member.kind() != Some(**kind))
+                    })
+                    .collect::<Vec<_>>();
+                // With a member of every kind, every value is valid
+                if !kinds.is_empty() {
+                    let kind = **u.choose(&kinds)?;
+                    return scalar(u, kind);
+                }
+            } else if self.can_corrupt(&member.ty)
+                && !matches!(union.repr, UnionRepresentation::Inline { .. })
+                && u.arbitrary()?
+            {
+                let value = self.inline(u, &member.ty, depth - 1, true)?;
+                return Ok(union.wrap(member, value));
+            } else {
+                let mut discriminant = "unknown".to_string();
+                while union.member(&discriminant).is_some() {
+                    discriminant.push('_');
+                }
+                let value = self.inline(u, &member.ty, depth - 1, false)?;
+                let unknown = UnionMember {
+                    discriminant,
+                    ..member.clone()
+                };
+                return Ok(union.wrap(&unknown, value));
+            }
+        }
+
+        let value = self.inline(u, &member.ty, depth - 1, false)?;
+        Ok(union.wrap(member, value))
+    }
+
+    /// Generates a value stored as a string, i.e. a map key or in a `stringpairs` representation
+    fn string_value(&self, u: &mut Unstructured, ty: &InlineIpldType) -> arbitrary::Result<String> {
+        let Some(resolved) = self.schema.resolve_inline(ty) else {
+            return Ok(String::new());
+        };
+
+        Ok(match resolved.as_ref() {
+            IpldType::Int => u.arbitrary::<i64>()?.to_string(),
+            IpldType::Float => format!("{:?}", finite(u.arbitrary()?)),
+            IpldType::Bool => u.arbitrary::<bool>()?.to_string(),
+            IpldType::Enum(enu) => match &u.choose(&enu.members)?.tag {
+                EnumMemberTag::String(tag) => tag.clone(),
+                EnumMemberTag::Int(tag) => tag.to_string(),
+            },
+            _ => word(u)?,
+        })
+    }
+}
+
+fn is_string_pairs(stru: &StructType) -> bool {
+    matches!(stru.repr, StructRepresentation::StringPairs(_))
+}
+
+fn entries(u: &mut Unstructured, depth: isize) -> arbitrary::Result<usize> {
+    if depth <= 0 {
+        return Ok(0);
+    }
+    Ok(u.int_in_range(0..=MAX_ENTRIES)? as usize)
+}
+
+fn finite(value: f64) -> f64 {
+    if value.is_finite() {
+        value
+    } else {
+        0.0
+    }
+}
+
+/// A short lowercase string, which contains none of the delimiters used in stringpairs
+fn word(u: &mut Unstructured) -> arbitrary::Result<String> {
+    let len = u.int_in_range(1..=8)?;
+    (0..len)
+        .map(|_| Ok(char::from(b'a' + u.int_in_range(0..=25)?)))
+        .collect()
+}
+
+fn scalar(u: &mut Unstructured, kind: Kind) -> arbitrary::Result<Ipld> {
+    Ok(match kind {
+        Kind::Null => Ipld::Null,
+        Kind::Bool => Ipld::Bool(u.arbitrary()?),
+        Kind::Int => Ipld::Integer(u.arbitrary::<i64>()?.into()),
+        Kind::Float => Ipld::Float(finite(u.arbitrary()?)),
+        Kind::String => Ipld::String(u.arbitrary()?),
+        Kind::Bytes => Ipld::Bytes(u.arbitrary()?),
+        Kind::Link => {
+            let len = u.int_in_range(0..=32)?;
+            let digest = u.bytes(len)?;
+            let hash =
+                Multihash::wrap(IDENTITY, digest).map_err(|_| arbitrary::Error::IncorrectFormat)?;
+            Ipld::Link(Cid::new_v1(RAW, hash))
+        }
+        Kind::List => Ipld::List(vec![]),
+        Kind::Map => Ipld::Map(BTreeMap::new()),
+    })
+}
+
+/// Any data model value, nested up to `depth`
+fn any(u: &mut Unstructured, depth: isize) -> arbitrary::Result<Ipld> {
+    Ok(match u.choose(KINDS)? {
+        Kind::List => Ipld::List(
+            (0..entries(u, depth)?)
+                .map(|_| any(u, depth - 1))
+                .collect::<arbitrary::Result<_>>()?,
+        ),
+        Kind::Map => Ipld::Map(
+            (0..entries(u, depth)?)
+                .map(|_| Ok((u.arbitrary()?, any(u, depth - 1)?)))
+                .collect::<arbitrary::Result<_>>()?,
+        ),
+        kind => scalar(u, *kind)?,
+    })
+}
+
+/// A value, that is not of the expected kind
+fn wrong_kind(expected: Kind) -> Ipld {
+    match expected {
+        Kind::String => Ipld::Integer(0),
+        _ => Ipld::String("near miss".to_string()),
+    }
+}
+
+fn enumeration(u: &mut Unstructured, enu: &EnumType, corrupt: bool) -> arbitrary::Result<Ipld> {
+    if !corrupt {
+        return Ok(match &u.choose(&enu.members)?.tag {
+            EnumMemberTag::String(tag) => Ipld::String(tag.clone()),
+            EnumMemberTag::Int(tag) => Ipld::Integer(*tag),
+        });
+    }
+
+    // A value of the right kind, that is not a member
+    Ok(match enu.members.first().map(|member| &member.tag) {
+        Some(EnumMemberTag::Int(_)) => {
+            let max = enu
+                .members
+                .iter()
+                .filter_map(|member| match member.tag {
+                    EnumMemberTag::Int(tag) => Some(tag),
+                    EnumMemberTag::String(_) => None,
+                })
+                .max()
+                .unwrap_or_default();
+            Ipld::Integer(max + 1)
+        }
+        _ => {
+            let mut value = "NearMiss".to_string();
+            while enu
+                .members
+                .iter()
+                .any(|member| member.tag == EnumMemberTag::String(value.clone()))
+            {
+                value.push('_');
+            }
+            Ipld::String(value)
+        }
+    })
+}
+
+fn unit_value(unit: &UnitRepresentation, corrupt: bool) -> Ipld {
+    match (unit, corrupt) {
+        (UnitRepresentation::Null, false) => Ipld::Null,
+        (UnitRepresentation::Null, true) => Ipld::Bool(false),
+        (UnitRepresentation::True, false) | (UnitRepresentation::False, true) => Ipld::Bool(true),
+        (UnitRepresentation::False, false) | (UnitRepresentation::True, true) => Ipld::Bool(false),
+        (UnitRepresentation::EmptyMap, false) => Ipld::Map(BTreeMap::new()),
+        (UnitRepresentation::EmptyMap, true) => {
+            Ipld::Map(BTreeMap::from([("near miss".to_string(), Ipld::Null)]))
+        }
+    }
+}
+
+/// The substrate of a map in an advanced layout, layouts other than the HAMT are left empty
+pub(crate) fn advanced_map(adl: &str, entries: Vec<(String, Ipld)>) -> Ipld {
+    if adl == HASH_MAP {
+        hamt::root_node(entries)
+    } else {
+        Ipld::Map(BTreeMap::new())
+    }
+}
+
+pub(crate) fn join_pairs(pairs: &StringPairs, entries: Vec<(String, String)>) -> Ipld {
+    Ipld::String(
+        entries
+            .into_iter()
+            .map(|(key, value)| format!("{}{}{}", key, pairs.inner_delim, value))
+            .collect::<Vec<_>>()
+            .join(&pairs.entry_delim),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "\
+type Root struct {
+  name String (rename \"n\")
+  tags optional [nullable Tag]
+  counts {Tag:Int}
+  children optional [Root]
+  meta nullable Meta
+  pairs Pairs
+  anything Any
+  link &Root
+  nothing Nothing
+  count Int (implicit \"0\")
+}
+
+type Tag enum {
+  | Red (\"R\")
+  | Blue
+}
+
+type Level enum {
+  | Low (\"1\")
+  | High (\"10\")
+} representation int
+
+type Meta struct {
+  level Level
+  score optional Float
+} representation listpairs
+
+type Pairs struct {
+  a Int
+  b optional Bool
+} representation stringpairs {
+  innerDelim \"=\"
+  entryDelim \",\"
+}
+
+type Nothing unit representation null
+";
+
+    /// Deterministic pseudo random bytes
+    fn bytes(seed: u64) -> Vec<u8> {
+        let mut state = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (0..512)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn generate() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+        let generator = schema.generator("Root").unwrap().with_max_depth(3);
+        let near_miss = generator.clone().with_near_miss(true);
+
+        for seed in 0..200 {
+            let bytes = bytes(seed);
+
+            let valid = generator.generate(&mut Unstructured::new(&bytes)).unwrap();
+            if let Err(err) = schema.validate("Root", &valid) {
+                panic!("{:?} is invalid: {}", valid, err);
+            }
+
+            let invalid = near_miss.generate(&mut Unstructured::new(&bytes)).unwrap();
+            assert!(
+                schema.validate("Root", &invalid).is_err(),
+                "{:?} is valid",
+                invalid
+            );
+        }
+
+        // Without any randomness, the smallest value is generated
+        let minimal = generator.generate(&mut Unstructured::new(&[])).unwrap();
+        assert!(schema.validate("Root", &minimal).is_ok());
+
+        assert_eq!(
+            schema.generator("Missing").unwrap_err(),
+            GenerateError::UnknownType("Missing".to_string())
+        );
+    }
+
+    #[test]
+    fn unions_and_tuples() {
+        let schema = IpldSchema::parse(concat!(
+            include_str!("../test/union.ipldsch"),
+            "
+type Node struct {
+  next Node
+}
+
+type Tree struct {
+  node optional Node
+  children [Tree]
+}
+"
+        ))
+        .unwrap();
+
+        for root in ["Shape", "Value", "Tagged", "Wrapped", "Point", "Tree"] {
+            let generator = schema.generator(root).unwrap();
+            let near_miss = generator.clone().with_near_miss(true);
+
+            for seed in 0..100 {
+                let bytes = bytes(seed);
+
+                let valid = generator.generate(&mut Unstructured::new(&bytes)).unwrap();
+                if let Err(err) = schema.validate(root, &valid) {
+                    panic!("{:?} is invalid for {}: {}", valid, root, err);
+                }
+
+                let invalid = near_miss.generate(&mut Unstructured::new(&bytes)).unwrap();
+                assert!(
+                    schema.validate(root, &invalid).is_err(),
+                    "{:?} is valid for {}",
+                    invalid,
+                    root
+                );
+            }
+        }
+
+        assert_eq!(
+            schema.generator("Node").unwrap_err(),
+            GenerateError::Uninhabited("Node".to_string())
+        );
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn strategy(data in IpldSchema::parse(SCHEMA).unwrap().generator("Root").unwrap().strategy()) {
+            IpldSchema::parse(SCHEMA).unwrap().validate("Root", &data).unwrap();
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use thiserror::Error;
+
+use crate::{InlineIpldType, IpldSchema, IpldType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// A Graphviz `digraph`
+    Dot,
+    /// A Mermaid class diagram
+    Mermaid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GraphError {
+    #[error("Type \"{0}\" is not defined")]
+    UnknownType(String),
+}
+
+/// A reference from one type of the schema to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Edge<'s> {
+    pub(crate) from: &'s str,
+    pub(crate) to: &'s str,
+    /// The field, union discriminant, `key` or `value` the reference is in, if any
+    pub(crate) label: Option<&'s str>,
+    pub(crate) link: bool,
+}
+
+impl IpldSchema {
+    /// Draws the types of the schema as nodes with edges to the types they reference.
+    ///
+    /// Links are drawn as dashed edges, types of the prelude are left out.
+    /// With a `root`, only the root and the types it reaches are drawn.
+    pub fn graph(&self, format: GraphFormat, root: Option<&str>) -> Result<String, GraphError> {
+        let names = match root {
+            Some(root) => {
+                if !self.0.contains_key(root) {
+                    return Err(GraphError::UnknownType(root.to_string()));
+                }
+                self.reachable(root)
+            }
+            None => self.0.keys().map(String::as_str).collect(),
+        };
+
+        let nodes = self
+            .0
+            .iter()
+            .filter(|(name, _)| names.contains(name.as_str()))
+            .map(|(name, def)| (name.as_str(), def.ty.type_kind()))
+            .collect::<Vec<_>>();
+
+        let mut edges = vec![];
+        for (name, _) in &nodes {
+            self.collect_edges(name, &self.0[*name].ty, &mut edges);
+        }
+        edges.retain(|edge| self.0.contains_key(edge.to));
+
+        Ok(match format {
+            GraphFormat::Dot => dot(&nodes, &edges),
+            GraphFormat::Mermaid => mermaid(&nodes, &edges),
+        })
+    }
+
+    /// Collects the names of `root` and all types defined in the schema, that it references
+    fn reachable<'s>(&'s self, root: &'s str) -> BTreeSet<&'s str> {
+        let mut names = BTreeSet::from([root]);
+        let mut queue = vec![root];
+
+        while let Some(name) = queue.pop() {
+            let mut edges = vec![];
+            self.collect_edges(name, &self.0[name].ty, &mut edges);
+
+            for edge in edges {
+                if self.0.contains_key(edge.to) && names.insert(edge.to) {
+                    queue.push(edge.to);
+                }
+            }
+        }
+
+        names
+    }
+
+    pub(crate) fn collect_edges<'s>(
+        &'s self,
+        name: &'s str,
+        ty: &'s IpldType,
+        edges: &mut Vec<Edge<'s>>,
+    ) {
+        match ty {
+            IpldType::List(list) => inline_edges(name, None, &list.ty, edges),
+            IpldType::Map(map) => {
+                edges.push(Edge {
+                    from: name,
+                    to: &map.key,
+                    label: Some("key"),
+                    link: false,
+                });
+                inline_edges(name, Some("value"), &map.value, edges);
+            }
+            IpldType::Link(target) => edges.push(Edge {
+                from: name,
+                to: target,
+                label: None,
+                link: true,
+            }),
+            IpldType::Copy(from) => edges.push(Edge {
+                from: name,
+                to: from,
+                label: Some("copy"),
+                link: false,
+            }),
+            IpldType::Struct(stru) => {
+                for field in &stru.fields {
+                    inline_edges(name, Some(&field.key), &field.value, edges);
+                }
+            }
+            IpldType::Union(union) => {
+                for member in &union.members {
+                    inline_edges(name, Some(&member.discriminant), &member.ty, edges);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn inline_edges<'s>(
+    from: &'s str,
+    label: Option<&'s str>,
+    ty: &'s InlineIpldType,
+    edges: &mut Vec<Edge<'s>>,
+) {
+    match ty {
+        InlineIpldType::Name(to) => edges.push(Edge {
+            from,
+            to,
+            label,
+            link: false,
+        }),
+        InlineIpldType::Link(to) => edges.push(Edge {
+            from,
+            to,
+            label,
+            link: true,
+        }),
+        InlineIpldType::List(list) => inline_edges(from, label, &list.ty, edges),
+        InlineIpldType::Map(map) => {
+            edges.push(Edge {
+                from,
+                to: &map.key,
+                label,
+                link: false,
+            });
+            inline_edges(from, label, &map.value, edges);
+        }
+    }
+}
+
+fn dot(nodes: &[(&str, &str)], edges: &[Edge]) -> String {
+    let mut out = String::new();
+    out.push_str("digraph schema {\n");
+    out.push_str("  node [shape=box];\n");
+
+    for (name, kind) in nodes {
+        let _ = writeln!(out, "  \"{}\" [label=\"{}\\n{}\"];", name, name, kind);
+    }
+    for edge in edges {
+        let mut attributes = vec![];
+        if let Some(label) = edge.label {
+            attributes.push(format!("label=\"{}\"", dot_escape(label)));
+        }
+        if edge.link {
+            attributes.push("style=dashed".to_string());
+        }
+
+        let _ = write!(out, "  \"{}\" -> \"{}\"", edge.from, edge.to);
+        if !attributes.is_empty() {
+            let _ = write!(out, " [{}]", attributes.join(", "));
+        }
+        out.push_str(";\n");
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn mermaid(nodes: &[(&str, &str)], edges: &[Edge]) -> String {
+    let mut out = String::new();
+    out.push_str("classDiagram\n");
+
+    for (name, kind) in nodes {
+        let _ = writeln!(out, "  class {} {{\n    <<{}>>\n  }}", name, kind);
+    }
+    for edge in edges {
+        let arrow = if edge.link { "..>" } else { "-->" };
+        let _ = write!(out, "  {} {} {}", edge.from, arrow, edge.to);
+        if let Some(label) = edge.label {
+            let _ = write!(out, " : {}", mermaid_escape(label));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Escapes a label for a quoted DOT string
+fn dot_escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Escapes a label, that runs to the end of the line in Mermaid, with entity codes
+fn mermaid_escape(label: &str) -> String {
+    let mut out = String::new();
+    for c in label.chars() {
+        match c {
+            '#' | ';' | '"' | '<' | '>' => {
+                let _ = write!(out, "#{};", u32::from(c));
+            }
+            '\n' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph() {
+        let schema = IpldSchema::parse(
+            "\
+type Message struct {
+  from &Identity
+  to [Identity]
+  labels {Label:String}
+}
+
+type Identity = Name
+
+type Name string
+
+type Label enum {
+  | Urgent
+}
+
+type Unrelated int
+",
+        )
+        .unwrap();
+
+        let expected = "\
+digraph schema {
+  node [shape=box];
+  \"Identity\" [label=\"Identity\\ncopy\"];
+  \"Label\" [label=\"Label\\nenum\"];
+  \"Message\" [label=\"Message\\nstruct\"];
+  \"Name\" [label=\"Name\\nstring\"];
+  \"Identity\" -> \"Name\" [label=\"copy\"];
+  \"Message\" -> \"Identity\" [label=\"from\", style=dashed];
+  \"Message\" -> \"Identity\" [label=\"to\"];
+  \"Message\" -> \"Label\" [label=\"labels\"];
+}
+";
+        assert_eq!(
+            schema.graph(GraphFormat::Dot, Some("Message")).unwrap(),
+            expected
+        );
+
+        let expected = "\
+classDiagram
+  class Identity {
+    <<copy>>
+  }
+  class Label {
+    <<enum>>
+  }
+  class Message {
+    <<struct>>
+  }
+  class Name {
+    <<string>>
+  }
+  class Unrelated {
+    <<int>>
+  }
+  Identity --> Name : copy
+  Message ..> Identity : from
+  Message --> Identity : to
+  Message --> Label : labels
+";
+        assert_eq!(schema.graph(GraphFormat::Mermaid, None).unwrap(), expected);
+
+        assert_eq!(
+            schema.graph(GraphFormat::Dot, Some("Missing")),
+            Err(GraphError::UnknownType("Missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn union_members() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+
+        let expected = "\
+classDiagram
+  class Circle {
+    <<struct>>
+  }
+  class Shape {
+    <<union>>
+  }
+  class Square {
+    <<struct>>
+  }
+  Shape --> Circle : circle
+  Shape ..> Square : square
+";
+        assert_eq!(
+            schema.graph(GraphFormat::Mermaid, Some("Shape")).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn escaped_labels() {
+        let schema = IpldSchema::parse(
+            "type Shape union {\n  | Circle \"a\\\\b<c> #1\"\n} representation keyed\n\ntype Circle int\n",
+        )
+        .unwrap();
+
+        let dot = schema.graph(GraphFormat::Dot, None).unwrap();
+        assert!(
+            dot.contains("  \"Shape\" -> \"Circle\" [label=\"a\\\\\\\\b<c> #1\"];"),
+            "{}",
+            dot
+        );
+        let mermaid = schema.graph(GraphFormat::Mermaid, None).unwrap();
+        assert!(
+            mermaid.contains("  Shape --> Circle : a\\\\b#60;c#62; #35;1\n"),
+            "{}",
+            mermaid
+        );
+    }
+}
@@ -0,0 +1,996 @@
+use std::{
+    borrow::Cow,
+    collections::{btree_map, BTreeMap},
+    io::Cursor,
+    sync::OnceLock,
+};
+
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    ipld::Ipld,
+};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{
+    adl::{AdlError, AdvancedDataLayout},
+    dag::DAG_CBOR,
+    store::{BlockStore, MemoryStore},
+    IpldSchema, Kind,
+};
+
+/// The name of the advanced layout of maps stored as a HAMT, i.e. `representation advanced HashMap`
+pub const HASH_MAP: &str = "HashMap";
+
+/// The multihash of the CIDs of HAMT nodes
+const SHA2_256: u64 = 0x12;
+
+/// The node types of the [spec](https://ipld.io/specs/advanced-data-layouts/hamt/spec/),
+/// which every node is validated against before it is read
+fn spec() -> &'static IpldSchema {
+    static SPEC: OnceLock<IpldSchema> = OnceLock::new();
+    SPEC.get_or_init(|| IpldSchema::parse(include_str!("hamt.ipldsch")).expect("the spec parses"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HamtError {
+    #[error("Failed to access block: {0}")]
+    Store(String),
+    #[error("Block {0} is missing from the store")]
+    Missing(Cid),
+    #[error("Malformed HAMT node: {0}")]
+    Malformed(String),
+    #[error("Hash algorithm \"{0}\" is not supported")]
+    UnsupportedHash(String),
+    #[error("Bit width {0} is not between 3 and 16")]
+    InvalidBitWidth(i128),
+    #[error("Bucket size {0} is not positive")]
+    InvalidBucketSize(i128),
+    #[error("The hashes of more than {0} keys are equal")]
+    HashExhausted(usize),
+}
+
+/// The hash algorithms, that place keys in a HAMT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// The first 64 bits of the 128 bit x64 variant of MurmurHash3, recommended by the spec
+    Murmur3X64_64,
+    Sha2_256,
+    /// The key itself, which is only useful for keys, that are hashes already
+    Identity,
+}
+
+impl HashAlgorithm {
+    /// The multihash name of the algorithm, which is stored in the root of a HAMT
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Murmur3X64_64 => "murmur3-x64-64",
+            HashAlgorithm::Sha2_256 => "sha2-256",
+            HashAlgorithm::Identity => "identity",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            HashAlgorithm::Murmur3X64_64,
+            HashAlgorithm::Sha2_256,
+            HashAlgorithm::Identity,
+        ]
+        .into_iter()
+        .find(|alg| alg.name() == name)
+    }
+
+    pub fn hash(&self, key: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Murmur3X64_64 => {
+                // Reading from a slice can not fail
+                let hash = murmur3::murmur3_x64_128(&mut Cursor::new(key), 0).unwrap();
+                (hash as u64).to_be_bytes().to_vec()
+            }
+            HashAlgorithm::Sha2_256 => Sha256::digest(key).to_vec(),
+            HashAlgorithm::Identity => key.to_vec(),
+        }
+    }
+}
+
+/// The parameters of a HAMT, that are stored in its root
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HamtConfig {
+    hash_alg: HashAlgorithm,
+    bit_width: u32,
+    bucket_size: usize,
+}
+
+impl Default for HamtConfig {
+    fn default() -> Self {
+        Self {
+            hash_alg: HashAlgorithm::Murmur3X64_64,
+            bit_width: 8,
+            bucket_size: 3,
+        }
+    }
+}
+
+impl HamtConfig {
+    pub fn with_hash_alg(mut self, hash_alg: HashAlgorithm) -> Self {
+        self.hash_alg = hash_alg;
+        self
+    }
+
+    /// Every node has `2^bit_width` slots, which must be between 3 and 16
+    pub fn with_bit_width(mut self, bit_width: u32) -> Self {
+        self.bit_width = bit_width;
+        self
+    }
+
+    /// Every slot holds up to `bucket_size` entries, before they are moved to a child node
+    pub fn with_bucket_size(mut self, bucket_size: usize) -> Self {
+        self.bucket_size = bucket_size;
+        self
+    }
+
+    pub fn hash_alg(&self) -> HashAlgorithm {
+        self.hash_alg
+    }
+
+    pub fn bit_width(&self) -> u32 {
+        self.bit_width
+    }
+
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    fn check(&self) -> Result<(), HamtError> {
+        if !(3..=16).contains(&self.bit_width) {
+            Err(HamtError::InvalidBitWidth(self.bit_width as i128))
+        } else if self.bucket_size == 0 {
+            Err(HamtError::InvalidBucketSize(0))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads the parameters from a `HashMapRoot`
+    fn from_root(root: &BTreeMap<String, Ipld>) -> Result<Self, HamtError> {
+        let Some(Ipld::String(hash_alg)) = root.get("hashAlg") else {
+            return Err(malformed("expected a string hashAlg"));
+        };
+        let hash_alg = HashAlgorithm::from_name(hash_alg)
+            .ok_or_else(|| HamtError::UnsupportedHash(hash_alg.clone()))?;
+
+        let int = |field: &str| match root.get(field) {
+            Some(Ipld::Integer(value)) => Ok(*value),
+            _ => Err(malformed(&format!("expected an integer {}", field))),
+        };
+        let bit_width = int("bitWidth")?;
+        let bucket_size = int("bucketSize")?;
+
+        let config = Self {
+            hash_alg,
+            bit_width: u32::try_from(bit_width)
+                .map_err(|_| HamtError::InvalidBitWidth(bit_width))?,
+            bucket_size: usize::try_from(bucket_size)
+                .map_err(|_| HamtError::InvalidBucketSize(bucket_size))?,
+        };
+        config.check()?;
+        Ok(config)
+    }
+
+    /// The number of bytes of the bitfield of a node
+    fn map_len(&self) -> usize {
+        (1 << self.bit_width) / 8
+    }
+
+    /// The slot of a hash at the given depth, i.e. the `depth`-th group of `bit_width` bits
+    fn index(&self, hash: &[u8], depth: usize) -> Option<usize> {
+        let bit_width = self.bit_width as usize;
+        let start = depth * bit_width;
+        if start + bit_width > hash.len() * 8 {
+            return None;
+        }
+
+        Some((start..start + bit_width).fold(0, |index, bit| {
+            index << 1 | ((hash[bit / 8] >> (7 - bit % 8)) & 1) as usize
+        }))
+    }
+}
+
+/// A map, that is stored as a hash array mapped trie of blocks.
+///
+/// The nodes follow the `HashMapRoot` and `HashMapNode` types of the
+/// [spec](https://ipld.io/specs/advanced-data-layouts/hamt/spec/). Changed nodes are kept in
+/// memory until [`Hamt::flush`] writes them to the store.
+#[derive(Debug, Clone)]
+pub struct Hamt<S> {
+    store: S,
+    config: HamtConfig,
+    root: Node,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Node {
+    /// The elements by the slot they occupy
+    elements: BTreeMap<usize, Element>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    /// Entries sorted by their key
+    Bucket(Vec<(Vec<u8>, Ipld)>),
+    Link(Cid),
+    /// A child, that was loaded or changed
+    Node(Box<Node>),
+}
+
+impl<S: BlockStore> Hamt<S> {
+    /// Creates an empty HAMT
+    pub fn new(store: S, config: HamtConfig) -> Result<Self, HamtError> {
+        config.check()?;
+        Ok(Self {
+            store,
+            config,
+            root: Node::default(),
+        })
+    }
+
+    /// Creates a HAMT containing `entries`
+    pub fn build(
+        store: S,
+        config: HamtConfig,
+        entries: impl IntoIterator<Item = (Vec<u8>, Ipld)>,
+    ) -> Result<Self, HamtError> {
+        let mut hamt = Self::new(store, config)?;
+        for (key, value) in entries {
+            hamt.set(key, value)?;
+        }
+        Ok(hamt)
+    }
+
+    /// Loads the HAMT, whose root node is stored under `root`
+    pub fn load(store: S, root: &Cid) -> Result<Self, HamtError> {
+        let data = load_block(&store, root)?;
+        Self::from_root(store, &data)
+    }
+
+    /// Reads the HAMT from its root node, a `HashMapRoot`
+    pub fn from_root(store: S, root: &Ipld) -> Result<Self, HamtError> {
+        let (config, root) = parse_root(root)?;
+        Ok(Self {
+            store,
+            config,
+            root,
+        })
+    }
+
+    pub fn config(&self) -> &HamtConfig {
+        &self.config
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    pub fn get(&self, key: &[u8]) -> Result<Option<Ipld>, HamtError> {
+        let hash = self.config.hash_alg.hash(key);
+        self.context().get(&self.root, &hash, 0, key)
+    }
+
+    /// Inserts an entry and returns the value it replaced
+    pub fn set(&mut self, key: Vec<u8>, value: Ipld) -> Result<Option<Ipld>, HamtError> {
+        let hash = self.config.hash_alg.hash(&key);
+        let context = Context {
+            store: &self.store,
+            config: &self.config,
+        };
+        context.set(&mut self.root, &hash, 0, key, value)
+    }
+
+    /// Removes an entry and returns its value
+    pub fn delete(&mut self, key: &[u8]) -> Result<Option<Ipld>, HamtError> {
+        let hash = self.config.hash_alg.hash(key);
+        let context = Context {
+            store: &self.store,
+            config: &self.config,
+        };
+        context.delete(&mut self.root, &hash, 0, key)
+    }
+
+    /// Iterates over the entries in the order of their hashes
+    pub fn iter(&self) -> HamtIter<'_, S> {
+        HamtIter::new(Some(&self.store), self.config, &self.root)
+    }
+
+    /// Writes all changed nodes to the store and returns the root node
+    pub fn flush(&mut self) -> Result<Ipld, HamtError> {
+        flush_node(&mut self.store, &self.config, &mut self.root)?;
+
+        let Ipld::Map(mut root) = self.root.to_ipld(&self.config) else {
+            unreachable!("nodes are maps");
+        };
+        root.insert(
+            "hashAlg".to_string(),
+            Ipld::String(self.config.hash_alg.name().to_string()),
+        );
+        root.insert(
+            "bitWidth".to_string(),
+            Ipld::Integer(self.config.bit_width.into()),
+        );
+        root.insert(
+            "bucketSize".to_string(),
+            Ipld::Integer(self.config.bucket_size as i128),
+        );
+        Ok(Ipld::Map(root))
+    }
+
+    /// Writes all changed nodes and the root node to the store and returns the CID of the root
+    pub fn save(&mut self) -> Result<Cid, HamtError> {
+        let root = self.flush()?;
+        put_block(&mut self.store, &root)
+    }
+
+    fn context(&self) -> Context<'_, S> {
+        Context {
+            store: &self.store,
+            config: &self.config,
+        }
+    }
+}
+
+/// Reads the entries of a HAMT from its root node.
+///
+/// Without a store, only the entries in the root node are read.
+pub(crate) fn read_entries(
+    root: &Ipld,
+    store: Option<&dyn BlockStore>,
+) -> Result<Vec<(Vec<u8>, Ipld)>, HamtError> {
+    let (config, root) = parse_root(root)?;
+    HamtIter::new(store, config, &root).collect()
+}
+
+/// Looks up `key` in a HAMT, only loading the nodes on the way to it.
+///
+/// Without a store, only the entries in the root node are found.
+pub(crate) fn get_entry(
+    root: &Ipld,
+    key: &[u8],
+    store: Option<&dyn BlockStore>,
+) -> Result<Option<Ipld>, HamtError> {
+    let (config, root) = parse_root(root)?;
+    let hash = config.hash_alg.hash(key);
+    match store {
+        Some(store) => Context {
+            store,
+            config: &config,
+        }
+        .get(&root, &hash, 0, key),
+        None => match (Context {
+            store: &MemoryStore::new(),
+            config: &config,
+        })
+        .get(&root, &hash, 0, key)
+        {
+            Err(HamtError::Missing(_)) => Ok(None),
+            result => result,
+        },
+    }
+}
+
+/// The layout of [`HASH_MAP`], which presents the entries of a HAMT as a map with string keys
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashMapLayout;
+
+impl AdvancedDataLayout for HashMapLayout {
+    fn kind(&self) -> Kind {
+        Kind::Map
+    }
+
+    fn read(&self, substrate: &Ipld, store: Option<&dyn BlockStore>) -> Result<Ipld, AdlError> {
+        let mut entries = BTreeMap::new();
+        for (key, value) in read_entries(substrate, store)? {
+            let key = String::from_utf8(key).map_err(|_| "keys must be valid UTF-8")?;
+            entries.insert(key, value);
+        }
+        Ok(Ipld::Map(entries))
+    }
+
+    fn get(
+        &self,
+        substrate: &Ipld,
+        key: &str,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<Option<Ipld>, AdlError> {
+        Ok(get_entry(substrate, key.as_bytes(), store)?)
+    }
+}
+
+/// The root node of a HAMT with the default configuration, whose child nodes are discarded.
+///
+/// Used to fill maps in advanced layouts with generated data.
+pub(crate) fn root_node(entries: Vec<(String, Ipld)>) -> Ipld {
+    let entries = entries
+        .into_iter()
+        .map(|(key, value)| (key.into_bytes(), value));
+    Hamt::build(MemoryStore::new(), HamtConfig::default(), entries)
+        .and_then(|mut hamt| hamt.flush())
+        .expect("the default configuration is valid and 64 bit hashes do not run out")
+}
+
+struct Context<'a, S: ?Sized> {
+    store: &'a S,
+    config: &'a HamtConfig,
+}
+
+impl<S: BlockStore + ?Sized> Context<'_, S> {
+    fn load(&self, cid: &Cid) -> Result<Node, HamtError> {
+        let node = load_block(self.store, cid)?;
+        conform(&node, "HashMapNode")?;
+        parse_node(&node, self.config)
+    }
+
+    /// Replaces a link by the node it points to, to change it
+    fn child<'e>(&self, element: &'e mut Element) -> Result<&'e mut Node, HamtError> {
+        if let Element::Link(cid) = element {
+            *element = Element::Node(Box::new(self.load(cid)?));
+        }
+        match element {
+            Element::Node(node) => Ok(node),
+            _ => unreachable!("only called for links and nodes"),
+        }
+    }
+
+    fn get(
+        &self,
+        node: &Node,
+        hash: &[u8],
+        depth: usize,
+        key: &[u8],
+    ) -> Result<Option<Ipld>, HamtError> {
+        let Some(index) = self.config.index(hash, depth) else {
+            return Ok(None);
+        };
+
+        match node.elements.get(&index) {
+            None => Ok(None),
+            Some(Element::Bucket(entries)) => Ok(entries
+                .iter()
+                .find(|(entry, _)| entry == key)
+                .map(|(_, value)| value.clone())),
+            Some(Element::Node(child)) => self.get(child, hash, depth + 1, key),
+            Some(Element::Link(cid)) => self.get(&self.load(cid)?, hash, depth + 1, key),
+        }
+    }
+
+    fn set(
+        &self,
+        node: &mut Node,
+        hash: &[u8],
+        depth: usize,
+        key: Vec<u8>,
+        value: Ipld,
+    ) -> Result<Option<Ipld>, HamtError> {
+        let index = self
+            .config
+            .index(hash, depth)
+            .ok_or(HamtError::HashExhausted(self.config.bucket_size))?;
+
+        let element = match node.elements.entry(index) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(Element::Bucket(vec![(key, value)]));
+                return Ok(None);
+            }
+            btree_map::Entry::Occupied(entry) => entry.into_mut(),
+        };
+
+        let Element::Bucket(entries) = element else {
+            let child = self.child(element)?;
+            return self.set(child, hash, depth + 1, key, value);
+        };
+
+        match entries.binary_search_by(|(entry, _)| entry.cmp(&key)) {
+            Ok(pos) => Ok(Some(std::mem::replace(&mut entries[pos].1, value))),
+            Err(pos) if entries.len() < self.config.bucket_size => {
+                entries.insert(pos, (key, value));
+                Ok(None)
+            }
+            // A full bucket is replaced by a child node, that holds its entries
+            Err(_) => {
+                let mut child = Node::default();
+                for (key, value) in entries.iter().cloned().chain([(key, value)]) {
+                    let hash = self.config.hash_alg.hash(&key);
+                    self.set(&mut child, &hash, depth + 1, key, value)?;
+                }
+                *element = Element::Node(Box::new(child));
+                Ok(None)
+            }
+        }
+    }
+
+    fn delete(
+        &self,
+        node: &mut Node,
+        hash: &[u8],
+        depth: usize,
+        key: &[u8],
+    ) -> Result<Option<Ipld>, HamtError> {
+        let Some(index) = self.config.index(hash, depth) else {
+            return Ok(None);
+        };
+        let Some(element) = node.elements.get_mut(&index) else {
+            return Ok(None);
+        };
+
+        let removed = match element {
+            Element::Bucket(entries) => entries
+                .binary_search_by(|(entry, _)| entry.as_slice().cmp(key))
+                .ok()
+                .map(|pos| entries.remove(pos).1),
+            _ => {
+                let child = self.child(element)?;
+                let removed = self.delete(child, hash, depth + 1, key)?;
+                // Children with few entries are moved back into a bucket, which keeps the
+                // HAMT independent of the order of changes
+                if let Some(entries) = child.collapse(self.config.bucket_size) {
+                    *element = Element::Bucket(entries);
+                }
+                removed
+            }
+        };
+
+        if matches!(element, Element::Bucket(entries) if entries.is_empty()) {
+            node.elements.remove(&index);
+        }
+        Ok(removed)
+    }
+}
+
+impl Node {
+    /// Returns all entries, if the node has no children and at most `bucket_size` entries
+    fn collapse(&self, bucket_size: usize) -> Option<Vec<(Vec<u8>, Ipld)>> {
+        let mut entries = vec![];
+        for element in self.elements.values() {
+            match element {
+                Element::Bucket(bucket) => entries.extend(bucket.iter().cloned()),
+                _ => return None,
+            }
+        }
+
+        if entries.len() <= bucket_size {
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Some(entries)
+        } else {
+            None
+        }
+    }
+
+    fn to_ipld(&self, config: &HamtConfig) -> Ipld {
+        let mut map = vec![0; config.map_len()];
+        for index in self.elements.keys() {
+            map[config.map_len() - 1 - index / 8] |= 1 << (index % 8);
+        }
+
+        let data = self
+            .elements
+            .values()
+            .map(|element| match element {
+                Element::Bucket(entries) => Ipld::List(
+                    entries
+                        .iter()
+                        .map(|(key, value)| match value {
+                            // The value of a BucketEntry is implicitly null
+                            Ipld::Null => Ipld::List(vec![Ipld::Bytes(key.clone())]),
+                            value => Ipld::List(vec![Ipld::Bytes(key.clone()), value.clone()]),
+                        })
+                        .collect(),
+                ),
+                Element::Link(cid) => Ipld::Link(*cid),
+                Element::Node(node) => node.to_ipld(config),
+            })
+            .collect();
+
+        Ipld::Map(BTreeMap::from([
+            ("map".to_string(), Ipld::Bytes(map)),
+            ("data".to_string(), Ipld::List(data)),
+        ]))
+    }
+}
+
+/// Writes the children of `node`, that are held in memory, and replaces them by links
+fn flush_node<S: BlockStore>(
+    store: &mut S,
+    config: &HamtConfig,
+    node: &mut Node,
+) -> Result<(), HamtError> {
+    for element in node.elements.values_mut() {
+        if let Element::Node(child) = element {
+            flush_node(store, config, child)?;
+            *element = Element::Link(put_block(store, &child.to_ipld(config))?);
+        }
+    }
+    Ok(())
+}
+
+/// Fails, if `node` is not a valid value of the spec type `ty`
+fn conform(node: &Ipld, ty: &str) -> Result<(), HamtError> {
+    spec()
+        .validate(ty, node)
+        .map_err(|err| malformed(&err.to_string()))
+}
+
+fn parse_root(root: &Ipld) -> Result<(HamtConfig, Node), HamtError> {
+    conform(root, "HashMapRoot")?;
+    let Ipld::Map(fields) = root else {
+        return Err(malformed("expected a map"));
+    };
+    let config = HamtConfig::from_root(fields)?;
+    Ok((config, parse_node(root, &config)?))
+}
+
+fn parse_node(data: &Ipld, config: &HamtConfig) -> Result<Node, HamtError> {
+    let Ipld::Map(fields) = data else {
+        return Err(malformed("expected a map"));
+    };
+    let Some(Ipld::Bytes(map)) = fields.get("map") else {
+        return Err(malformed("expected map bytes"));
+    };
+    let Some(Ipld::List(data)) = fields.get("data") else {
+        return Err(malformed("expected a data list"));
+    };
+    if map.len() != config.map_len() {
+        return Err(malformed(&format!(
+            "expected a map of {} bytes, found {}",
+            config.map_len(),
+            map.len()
+        )));
+    }
+
+    let indices = (0..1 << config.bit_width)
+        .filter(|index| map[map.len() - 1 - index / 8] & (1 << (index % 8)) != 0)
+        .collect::<Vec<usize>>();
+    if indices.len() != data.len() {
+        return Err(malformed("the map does not match the data"));
+    }
+
+    let elements = indices
+        .into_iter()
+        .zip(data)
+        .map(|(index, element)| Ok((index, parse_element(element, config)?)))
+        .collect::<Result<_, HamtError>>()?;
+    Ok(Node { elements })
+}
+
+fn parse_element(element: &Ipld, config: &HamtConfig) -> Result<Element, HamtError> {
+    match element {
+        Ipld::Link(cid) => Ok(Element::Link(*cid)),
+        Ipld::Map(_) => Ok(Element::Node(Box::new(parse_node(element, config)?))),
+        Ipld::List(entries) => entries
+            .iter()
+            .map(|entry| match entry {
+                Ipld::List(entry) => match entry.as_slice() {
+                    [Ipld::Bytes(key), value] => Ok((key.clone(), value.clone())),
+                    // The value is implicitly null
+                    [Ipld::Bytes(key)] => Ok((key.clone(), Ipld::Null)),
+                    _ => Err(malformed("bucket entries must be a key and a value")),
+                },
+                _ => Err(malformed("bucket entries must be lists")),
+            })
+            .collect::<Result<_, _>>()
+            .map(Element::Bucket),
+        _ => Err(malformed("elements must be buckets, links or nodes")),
+    }
+}
+
+fn malformed(reason: &str) -> HamtError {
+    HamtError::Malformed(reason.to_string())
+}
+
+fn load_block<S: BlockStore + ?Sized>(store: &S, cid: &Cid) -> Result<Ipld, HamtError> {
+    let block = store
+        .get(cid)
+        .map_err(|err| HamtError::Store(err.to_string()))?
+        .ok_or(HamtError::Missing(*cid))?;
+    serde_ipld_dagcbor::from_slice(&block).map_err(|err| malformed(&err.to_string()))
+}
+
+/// Stores a node as DAG-CBOR, addressed by its SHA2-256 digest
+fn put_block<S: BlockStore + ?Sized>(store: &mut S, data: &Ipld) -> Result<Cid, HamtError> {
+    let block = serde_ipld_dagcbor::to_vec(data).map_err(|err| malformed(&err.to_string()))?;
+    let hash = Multihash::wrap(SHA2_256, &Sha256::digest(&block))
+        .map_err(|err| HamtError::Store(err.to_string()))?;
+    let cid = Cid::new_v1(DAG_CBOR, hash);
+    store
+        .put(cid, block)
+        .map_err(|err| HamtError::Store(err.to_string()))?;
+    Ok(cid)
+}
+
+/// An iterator over the entries of a [`Hamt`]
+pub struct HamtIter<'a, S: ?Sized> {
+    store: Option<&'a S>,
+    config: HamtConfig,
+    stack: Vec<Frame<'a>>,
+    bucket: std::vec::IntoIter<(Vec<u8>, Ipld)>,
+}
+
+enum Frame<'a> {
+    Borrowed(btree_map::Values<'a, usize, Element>),
+    Loaded(btree_map::IntoValues<usize, Element>),
+}
+
+impl<'a, S: BlockStore + ?Sized> HamtIter<'a, S> {
+    /// Iterates over the entries below `root`, skipping links if there is no store
+    fn new(store: Option<&'a S>, config: HamtConfig, root: &'a Node) -> Self {
+        Self {
+            store,
+            config,
+            stack: vec![Frame::Borrowed(root.elements.values())],
+            bucket: vec![].into_iter(),
+        }
+    }
+}
+
+impl<S: BlockStore + ?Sized> Iterator for HamtIter<'_, S> {
+    type Item = Result<(Vec<u8>, Ipld), HamtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.bucket.next() {
+                return Some(Ok(entry));
+            }
+
+            let element = match self.stack.last_mut()? {
+                Frame::Borrowed(elements) => elements.next().map(Cow::Borrowed),
+                Frame::Loaded(elements) => elements.next().map(Cow::Owned),
+            };
+            let Some(element) = element else {
+                self.stack.pop();
+                continue;
+            };
+
+            let cid = match element {
+                Cow::Borrowed(Element::Bucket(entries)) => {
+                    self.bucket = entries.clone().into_iter();
+                    continue;
+                }
+                Cow::Owned(Element::Bucket(entries)) => {
+                    self.bucket = entries.into_iter();
+                    continue;
+                }
+                Cow::Borrowed(Element::Node(node)) => {
+                    self.stack.push(Frame::Borrowed(node.elements.values()));
+                    continue;
+                }
+                Cow::Owned(Element::Node(node)) => {
+                    self.stack.push(Frame::Loaded(node.elements.into_values()));
+                    continue;
+                }
+                Cow::Borrowed(Element::Link(cid)) => *cid,
+                Cow::Owned(Element::Link(cid)) => cid,
+            };
+
+            let Some(store) = self.store else {
+                continue;
+            };
+            let context = Context {
+                store,
+                config: &self.config,
+            };
+            match context.load(&cid) {
+                Ok(node) => self.stack.push(Frame::Loaded(node.elements.into_values())),
+                Err(err) => {
+                    self.stack.clear();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ipld_core::ipld;
+
+    use super::*;
+    use crate::{adl::builtin, IpldSchema};
+
+    fn key(n: usize) -> Vec<u8> {
+        format!("key{}", n).into_bytes()
+    }
+
+    #[test]
+    fn operations() {
+        // Small nodes and buckets to get a deep tree
+        let config = HamtConfig::default()
+            .with_hash_alg(HashAlgorithm::Sha2_256)
+            .with_bit_width(3)
+            .with_bucket_size(1);
+        let mut hamt = Hamt::build(
+            MemoryStore::new(),
+            config,
+            (0..100).map(|n| (key(n), Ipld::Integer(n as i128))),
+        )
+        .unwrap();
+
+        assert_eq!(hamt.set(key(7), ipld!("seven")).unwrap(), Some(ipld!(7)));
+        assert_eq!(hamt.get(&key(7)).unwrap(), Some(ipld!("seven")));
+        assert_eq!(hamt.get(&key(100)).unwrap(), None);
+
+        let root = hamt.save().unwrap();
+        let mut hamt = Hamt::load(hamt.into_store(), &root).unwrap();
+        assert_eq!(hamt.config(), &config);
+        assert_eq!(hamt.get(&key(99)).unwrap(), Some(ipld!(99)));
+        assert_eq!(hamt.iter().count(), 100);
+
+        for n in (0..100).step_by(2) {
+            assert!(hamt.delete(&key(n)).unwrap().is_some());
+        }
+        assert_eq!(hamt.delete(&key(0)).unwrap(), None);
+        assert_eq!(hamt.get(&key(4)).unwrap(), None);
+
+        let mut entries = hamt.iter().collect::<Result<Vec<_>, _>>().unwrap();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(entries.len(), 50);
+        assert_eq!(entries[0], (key(1), ipld!(1)));
+
+        // The structure does not depend on the order of changes
+        let mut rebuilt =
+            Hamt::build(MemoryStore::new(), config, entries.into_iter().rev()).unwrap();
+        assert_eq!(hamt.save().unwrap(), rebuilt.save().unwrap());
+
+        assert_eq!(
+            Hamt::new(MemoryStore::new(), config.with_bit_width(2)).unwrap_err(),
+            HamtError::InvalidBitWidth(2)
+        );
+
+        let mut colliding = Hamt::new(
+            MemoryStore::new(),
+            config.with_hash_alg(HashAlgorithm::Identity),
+        )
+        .unwrap();
+        colliding.set(vec![0x00], Ipld::Null).unwrap();
+        colliding.set(vec![0x20], Ipld::Null).unwrap();
+        assert_eq!(
+            colliding.set(vec![0x01], Ipld::Null),
+            Err(HamtError::HashExhausted(1))
+        );
+    }
+
+    #[test]
+    fn validate_hash_map() {
+        let schema = IpldSchema::parse(
+            "\
+type Scores {String:Int} representation advanced HashMap
+
+type Game struct {
+  scores Scores
+}
+
+type Board union {
+  | String string
+  | Scores map
+} representation kinded
+
+type Round union {
+  | Game \"game\"
+} representation envelope {
+  discriminantKey \"tag\"
+  contentKey \"content\"
+}
+",
+        )
+        .unwrap();
+        assert!(schema
+            .to_string()
+            .contains("type Scores {String:Int} representation advanced HashMap"));
+
+        let config = HamtConfig::default().with_bucket_size(1);
+        let scores = (0..20).map(|n| (key(n), ipld!(n)));
+        let mut hamt = Hamt::build(MemoryStore::new(), config, scores).unwrap();
+        let root = hamt.flush().unwrap();
+        let game = ipld!({ "scores": root.clone() });
+        schema
+            .validate_with_store("Game", &game, hamt.store())
+            .unwrap();
+
+        // Without a store, only the entries in the root node are checked
+        let inline = read_entries(&root, None).unwrap();
+        let nested = (0..20)
+            .map(key)
+            .find(|key| inline.iter().all(|(entry, _)| entry != key))
+            .unwrap();
+        hamt.set(nested.clone(), ipld!("many")).unwrap();
+        let root = hamt.flush().unwrap();
+        let game = ipld!({ "scores": root });
+        schema.validate("Game", &game).unwrap();
+        let err = schema
+            .validate_with_store("Game", &game, hamt.store())
+            .unwrap_err();
+        let cbor = serde_ipld_dagcbor::to_vec(&game).unwrap();
+        let json = String::from_utf8(serde_ipld_dagjson::to_vec(&game).unwrap()).unwrap();
+        assert_eq!(schema.validate_dag_cbor("Game", cbor.as_slice()), Ok(()));
+        assert!(schema
+            .validate_dag_cbor_with("Game", cbor.as_slice(), builtin(), Some(hamt.store()))
+            .is_err());
+        assert!(schema
+            .validate_dag_json_with("Game", &json, builtin(), Some(hamt.store()))
+            .is_err());
+        assert!(matches!(
+            err,
+            crate::ValidationError::Kind { path, .. }
+                if path.to_string() == format!("scores/{}", String::from_utf8(nested.clone()).unwrap())
+        ));
+
+        assert!(schema
+            .validate("Game", &ipld!({ "scores": { "key": 1 } }))
+            .is_err());
+
+        // Streamed data is validated through the layout as well
+        let mut single =
+            Hamt::build(MemoryStore::new(), config, [(key(0), ipld!("zero"))]).unwrap();
+        let data = ipld!({ "scores": single.flush().unwrap() });
+        let cbor = serde_ipld_dagcbor::to_vec(&data).unwrap();
+        let json = String::from_utf8(serde_ipld_dagjson::to_vec(&data).unwrap()).unwrap();
+        assert!(schema.validate("Game", &data).is_err());
+        assert!(schema.validate_dag_cbor("Game", cbor.as_slice()).is_err());
+        assert!(schema.validate_dag_json("Game", &json).is_err());
+        single.set(key(0), ipld!(0)).unwrap();
+        let scores = single.flush().unwrap();
+        let data = ipld!({ "scores": scores.clone() });
+        let cbor = serde_ipld_dagcbor::to_vec(&data).unwrap();
+        let json = String::from_utf8(serde_ipld_dagjson::to_vec(&data).unwrap()).unwrap();
+        assert_eq!(schema.validate_dag_cbor("Game", cbor.as_slice()), Ok(()));
+        assert_eq!(schema.validate_dag_json("Game", &json), Ok(()));
+
+        // Including layouts in members of unions
+        for (root, data) in [
+            ("Board", scores),
+            ("Round", ipld!({ "tag": "game", "content": data })),
+        ] {
+            let cbor = serde_ipld_dagcbor::to_vec(&data).unwrap();
+            let json = String::from_utf8(serde_ipld_dagjson::to_vec(&data).unwrap()).unwrap();
+            assert_eq!(schema.validate_dag_cbor(root, cbor.as_slice()), Ok(()));
+            assert_eq!(schema.validate_dag_json(root, &json), Ok(()));
+        }
+
+        // Paths only load the nodes on the way to the entry
+        let path = format!("scores/{}", String::from_utf8(nested).unwrap());
+        let steps = schema
+            .resolve_path_with("Game", &game, &path, builtin(), Some(hamt.store()))
+            .unwrap();
+        assert_eq!(steps.last().unwrap().value.as_ref(), &ipld!("many"));
+        assert!(matches!(
+            schema.resolve_path("Game", &game, &path),
+            Err(crate::PathError::Missing { .. })
+        ));
+    }
+
+    #[test]
+    fn spec() {
+        let mut hamt = Hamt::new(MemoryStore::new(), HamtConfig::default()).unwrap();
+        hamt.set(key(0), Ipld::Null).unwrap();
+        let root = hamt.flush().unwrap();
+        super::spec().validate("HashMapRoot", &root).unwrap();
+        let Ipld::Map(mut fields) = root.clone() else {
+            panic!("the root is a map");
+        };
+        assert_eq!(fields["data"], ipld!([[[key(0)]]]));
+        assert_eq!(
+            Hamt::from_root(MemoryStore::new(), &root)
+                .unwrap()
+                .get(&key(0))
+                .unwrap(),
+            Some(Ipld::Null)
+        );
+
+        // The value of an entry is never null, but omitted instead
+        fields.insert("data".to_string(), ipld!([[[key(0), null]]]));
+        assert!(matches!(
+            Hamt::from_root(MemoryStore::new(), &Ipld::Map(fields)),
+            Err(HamtError::Malformed(_))
+        ));
+    }
+}
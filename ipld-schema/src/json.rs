@@ -0,0 +1,668 @@
+use std::ops::Range;
+
+use codespan_reporting::{
+    diagnostic::{Diagnostic, Label},
+    files::SimpleFile,
+    term::{self, termcolor::NoColor},
+};
+use ipld_core::cid::Cid;
+use thiserror::Error;
+
+use crate::{
+    adl::{builtin, AdlRegistry},
+    store::BlockStore,
+    stream::{ReadError, Seq, StreamError, Token, TokenReader},
+    DataPath, IpldSchema, ValidationError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JsonValidationError {
+    /// The data is well-formed, but does not match the schema
+    #[error("{error} (line {line}, column {column})")]
+    Invalid {
+        span: Range<usize>,
+        line: usize,
+        column: usize,
+        error: ValidationError,
+    },
+    /// The data is not valid DAG-JSON
+    #[error("Malformed DAG-JSON at {path} (line {line}, column {column}): {reason}")]
+    Malformed {
+        span: Range<usize>,
+        line: usize,
+        column: usize,
+        path: DataPath,
+        reason: String,
+    },
+}
+
+impl JsonValidationError {
+    fn new(err: StreamError, source: &str) -> Self {
+        let position = |span: &Range<usize>| {
+            let before = &source[..span.start];
+            let line = before.matches('\n').count() + 1;
+            let column = before[before.rfind('\n').map_or(0, |idx| idx + 1)..]
+                .chars()
+                .count()
+                + 1;
+            (line, column)
+        };
+
+        match err {
+            StreamError::Invalid { span, error } => {
+                let (line, column) = position(&span);
+                JsonValidationError::Invalid {
+                    span,
+                    line,
+                    column,
+                    error,
+                }
+            }
+            StreamError::Malformed { span, path, reason } => {
+                let (line, column) = position(&span);
+                JsonValidationError::Malformed {
+                    span,
+                    line,
+                    column,
+                    path,
+                    reason,
+                }
+            }
+        }
+    }
+
+    /// The byte range of the offending value in the source
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            JsonValidationError::Invalid { span, .. }
+            | JsonValidationError::Malformed { span, .. } => span.clone(),
+        }
+    }
+
+    /// The one based line and column of the start of the offending value
+    pub fn line_col(&self) -> (usize, usize) {
+        match self {
+            JsonValidationError::Invalid { line, column, .. }
+            | JsonValidationError::Malformed { line, column, .. } => (*line, *column),
+        }
+    }
+
+    /// Creates a diagnostic, that labels the offending value in `file`
+    pub fn diagnostic<FileId>(&self, file: FileId) -> Diagnostic<FileId> {
+        let (message, label) = match self {
+            JsonValidationError::Invalid { error, .. } => {
+                let label = match error {
+                    ValidationError::Kind { expected, .. } => format!("expected {}", expected),
+                    ValidationError::MissingField { field, .. } => {
+                        format!("missing field \"{}\"", field)
+                    }
+                    ValidationError::UnexpectedField { ty, .. } => format!("not a field of {}", ty),
+                    ValidationError::InvalidValue { reason, .. } => reason.clone(),
+                    ValidationError::UnknownType(_) | ValidationError::CopyCycle(_) => {
+                        String::new()
+                    }
+                };
+                (error.to_string(), label)
+            }
+            JsonValidationError::Malformed { path, reason, .. } => {
+                (format!("Malformed DAG-JSON at {}", path), reason.clone())
+            }
+        };
+
+        Diagnostic::error()
+            .with_message(message)
+            .with_labels(vec![Label::primary(file, self.span()).with_message(label)])
+    }
+
+    /// Renders the diagnostic with a snippet of `source`, as it is printed on a terminal
+    pub fn render(&self, file_name: &str, source: &str) -> String {
+        let file = SimpleFile::new(file_name, source);
+        let mut writer = NoColor::new(Vec::new());
+
+        // Writing to a Vec does not fail and the span is always inside of the source
+        let _ = term::emit(
+            &mut writer,
+            &term::Config::default(),
+            &file,
+            &self.diagnostic(()),
+        );
+        String::from_utf8_lossy(&writer.into_inner()).into_owned()
+    }
+}
+
+impl IpldSchema {
+    /// Checks, whether the DAG-JSON text `data` is a valid representation of the type `root`.
+    ///
+    /// The data is validated while it is parsed, without building an [`Ipld`](ipld_core::ipld::Ipld)
+    /// tree. Errors point to the line and column of the offending value and can be rendered
+    /// with a snippet of the source through [`JsonValidationError::render`]. Typed values
+    /// nested in more than 128 lists and maps are rejected as malformed.
+    pub fn validate_dag_json(&self, root: &str, data: &str) -> Result<(), JsonValidationError> {
+        self.validate_dag_json_with(root, data, builtin(), None)
+    }
+
+    /// Like [`IpldSchema::validate_dag_json`], but reads data in advanced layouts through the
+    /// layouts in `adls`, which may load blocks from `store`
+    pub fn validate_dag_json_with(
+        &self,
+        root: &str,
+        data: &str,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<(), JsonValidationError> {
+        self.validate_stream(root, &mut Parser::new(data), adls, store)
+            .map_err(|err| JsonValidationError::new(err, data))
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    /// The span of the last token or key
+    last: Range<usize>,
+    /// The first key of a map, which is read ahead to tell maps from links and bytes
+    pending: Option<(Range<usize>, String)>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            pos: 0,
+            last: 0..0,
+            pending: None,
+        }
+    }
+
+    fn error(&self, start: usize, reason: impl ToString) -> ReadError {
+        // Point at least at one character, if there is one
+        let end = self.src[start..]
+            .chars()
+            .next()
+            .map_or(start, |c| start + c.len_utf8());
+        ReadError {
+            span: start..end.max(self.pos),
+            reason: reason.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ReadError> {
+        self.whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(self.pos, format!("expected '{}'", expected as char)))
+        }
+    }
+
+    fn literal(&mut self, literal: &str, token: Token) -> Result<Token, ReadError> {
+        if self.src[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(token)
+        } else {
+            Err(self.error(self.pos, "expected a value"))
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ReadError> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        let mut string = String::new();
+
+        loop {
+            let Some(c) = self.src[self.pos..].chars().next() else {
+                return Err(self.error(start, "unterminated string"));
+            };
+            self.pos += c.len_utf8();
+
+            match c {
+                '"' => return Ok(string),
+                '\\' => string.push(self.escape()?),
+                c if (c as u32) < 0x20 => {
+                    return Err(self.error(self.pos - 1, "control characters must be escaped"))
+                }
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, ReadError> {
+        let start = self.pos - 1;
+        let c = self
+            .peek()
+            .ok_or_else(|| self.error(start, "invalid escape"))?;
+        self.pos += 1;
+
+        Ok(match c {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex()?;
+                let code = if (0xd800..0xdc00).contains(&high) {
+                    // A surrogate pair
+                    if !self.src[self.pos..].starts_with("\\u") {
+                        return Err(self.error(start, "unpaired surrogate"));
+                    }
+                    self.pos += 2;
+                    let low = self.hex()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error(start, "unpaired surrogate"));
+                    }
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                char::from_u32(code).ok_or_else(|| self.error(start, "invalid unicode escape"))?
+            }
+            _ => return Err(self.error(start, "invalid escape")),
+        })
+    }
+
+    fn hex(&mut self) -> Result<u32, ReadError> {
+        let digits = self.src.get(self.pos..self.pos + 4);
+        let code = digits
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error(self.pos, "expected four hex digits"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Token, ReadError> {
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            let start = parser.pos;
+            while parser.peek().is_some_and(|b| b.is_ascii_digit()) {
+                parser.pos += 1;
+            }
+            parser.pos > start
+        };
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        let integer = self.pos;
+        if !digits(self) {
+            return Err(self.error(start, "invalid number"));
+        }
+        if self.src.as_bytes()[integer] == b'0' && self.pos - integer > 1 {
+            return Err(self.error(start, "leading zeros are not allowed"));
+        }
+
+        let mut is_float = false;
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            is_float = true;
+            if !digits(self) {
+                return Err(self.error(start, "invalid number"));
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.pos += 1;
+            is_float = true;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                return Err(self.error(start, "invalid number"));
+            }
+        }
+
+        let number = &self.src[start..self.pos];
+        if is_float {
+            Ok(Token::Float)
+        } else {
+            number
+                .parse()
+                .map(Token::Int)
+                .map_err(|_| self.error(start, "integer out of range"))
+        }
+    }
+
+    /// Reads a map, after its opening brace. Maps with the single key "/" are links or bytes.
+    fn map(&mut self, start: usize) -> Result<Token, ReadError> {
+        self.whitespace();
+        if self.peek() != Some(b'"') {
+            return Ok(Token::Map(Seq::new(None, true)));
+        }
+
+        let key_start = self.pos;
+        let key = self.string()?;
+        let key_span = key_start..self.pos;
+        self.expect(b':')?;
+        if key != "/" {
+            self.pending = Some((key_span, key));
+            return Ok(Token::Map(Seq::new(None, true)));
+        }
+
+        self.whitespace();
+        let token = match self.peek() {
+            Some(b'"') => {
+                let value_start = self.pos;
+                let cid = self.string()?;
+                Token::Link(
+                    Cid::try_from(cid.as_str())
+                        .map_err(|err| self.error(value_start, format!("invalid CID: {}", err)))?,
+                )
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                self.whitespace();
+                let key_start = self.pos;
+                if self.string()? != "bytes" {
+                    return Err(self.error(key_start, "expected \"bytes\""));
+                }
+                self.expect(b':')?;
+                self.whitespace();
+                let value_start = self.pos;
+                let Some(bytes) = decode_base64(&self.string()?) else {
+                    return Err(self.error(value_start, "bytes must be unpadded base64"));
+                };
+                self.expect(b'}')?;
+                Token::Bytes(bytes)
+            }
+            _ => return Err(self.error(key_start, "the key \"/\" is reserved for links and bytes")),
+        };
+
+        self.expect(b'}')
+            .map_err(|_| self.error(start, "links and bytes must not have other keys"))?;
+        Ok(token)
+    }
+}
+
+/// Decodes unpadded standard base64, as DAG-JSON encodes bytes
+fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    if data.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for b in data.bytes() {
+        let value = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+impl<'a> TokenReader for Parser<'a> {
+    fn token(&mut self) -> Result<Token, ReadError> {
+        self.whitespace();
+        let start = self.pos;
+
+        let token = match self.peek() {
+            Some(b'n') => self.literal("null", Token::Null),
+            Some(b't') => self.literal("true", Token::Bool(true)),
+            Some(b'f') => self.literal("false", Token::Bool(false)),
+            Some(b'"') => self.string().map(Token::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b'[') => {
+                self.pos += 1;
+                Ok(Token::List(Seq::new(None, false)))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                self.map(start)
+            }
+            Some(_) => Err(self.error(start, "expected a value")),
+            None => Err(self.error(start, "unexpected end of input")),
+        }?;
+
+        self.last = start..self.pos;
+        Ok(token)
+    }
+
+    fn next_entry(&mut self, seq: &mut Seq) -> Result<bool, ReadError> {
+        if self.pending.is_some() {
+            seq.read += 1;
+            return Ok(true);
+        }
+
+        self.whitespace();
+        let close = if seq.is_map { b'}' } else { b']' };
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            self.last.end = self.pos;
+            return Ok(false);
+        }
+
+        if seq.read > 0 {
+            self.expect(b',')?;
+        }
+        seq.read += 1;
+        Ok(true)
+    }
+
+    fn key(&mut self) -> Result<String, ReadError> {
+        if let Some((span, key)) = self.pending.take() {
+            self.last = span;
+            return Ok(key);
+        }
+
+        self.whitespace();
+        let start = self.pos;
+        if self.peek() != Some(b'"') {
+            return Err(self.error(start, "map keys must be strings"));
+        }
+        let key = self.string()?;
+        self.last = start..self.pos;
+        self.expect(b':')?;
+        Ok(key)
+    }
+
+    // The source is held in memory anyway, so bytes are always kept
+    fn keep_bytes(&mut self, _keep: bool) {}
+
+    fn peek_null(&mut self) -> bool {
+        self.whitespace();
+        self.src[self.pos..].starts_with("null")
+    }
+
+    fn last(&self) -> Range<usize> {
+        self.last.clone()
+    }
+
+    fn finish(&mut self) -> Result<(), ReadError> {
+        self.whitespace();
+        if self.pos == self.src.len() {
+            Ok(())
+        } else {
+            Err(self.error(self.pos, "trailing data after the value"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::Unstructured;
+
+    use super::*;
+    use crate::stream::MAX_DEPTH;
+
+    const SCHEMA: &str = "\
+type Message struct {
+  from String
+  to {String:Recipient}
+  body nullable Bytes
+  reply optional &Message
+}
+
+type Recipient struct {
+  name String
+  level Int
+}
+";
+
+    #[test]
+    fn validate_dag_json() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+
+        let valid = r#"{
+  "from": "alice",
+  "to": {"bob": {"name": "Bob é😀", "level": -1}},
+  "body": {"/": {"bytes": "aGVsbG8"}},
+  "reply": {"/": "bafkqaaa"}
+}"#;
+        assert_eq!(schema.validate_dag_json("Message", valid), Ok(()));
+
+        let invalid = r#"{
+  "from": "alice",
+  "to": {
+    "bob": {"name": "Bob", "level": "high"}
+  },
+  "body": null
+}"#;
+        let err = schema.validate_dag_json("Message", invalid).unwrap_err();
+        assert_eq!(err.line_col(), (4, 37));
+        assert_eq!(&invalid[err.span()], "\"high\"");
+        assert_eq!(
+            err.to_string(),
+            "Expected int for Int at to/bob/level, found string (line 4, column 37)"
+        );
+        assert_eq!(
+            err.render("message.json", invalid),
+            "\
+error: Expected int for Int at to/bob/level, found string
+  ┌─ message.json:4:37
+  │
+4 │     \"bob\": {\"name\": \"Bob\", \"level\": \"high\"}
+  │                                     ^^^^^^ expected int
+
+"
+        );
+
+        // The whole value of the wrong kind is labeled
+        let err = schema
+            .validate_dag_json(
+                "Message",
+                r#"{"from": ["a", {"b": 1}], "to": {}, "body": null}"#,
+            )
+            .unwrap_err();
+        assert_eq!(err.span(), 9..24);
+
+        let err = schema
+            .validate_dag_json("Message", r#"{"from": "a", "to": {}}"#)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            JsonValidationError::Invalid { error: ValidationError::MissingField { .. }, span, .. } if span == (0..23)
+        ));
+
+        let err = schema
+            .validate_dag_json("Message", "{\"from\": \"a\",\n  \"to\": {} \"body\": null}")
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            JsonValidationError::Malformed {
+                line: 2,
+                column: 12,
+                ..
+            }
+        ));
+
+        assert!(matches!(
+            schema.validate_dag_json("Message", r#"{"/": 1}"#),
+            Err(JsonValidationError::Malformed { .. })
+        ));
+
+        // Surrogates must be paired, and numbers are written without leading zeros
+        for (data, column, reason) in [
+            (r#""\ud83d\u0041""#, 2, "unpaired surrogate"),
+            (r#""\ud83d\ud83d""#, 2, "unpaired surrogate"),
+            (r#""\ude00""#, 2, "invalid unicode escape"),
+            ("012", 1, "leading zeros are not allowed"),
+            ("-00", 1, "leading zeros are not allowed"),
+        ] {
+            let err = schema.validate_dag_json("Message", data).unwrap_err();
+            assert!(
+                matches!(
+                    &err,
+                    JsonValidationError::Malformed { line: 1, column: c, reason: r, .. }
+                        if *c == column && r == reason
+                ),
+                "{}: {:?}",
+                data,
+                err
+            );
+        }
+        let schema = IpldSchema::parse("type Values [Any]").unwrap();
+        assert_eq!(
+            schema.validate_dag_json("Values", r#"[0, -0, 0.5, 10, "\ud83d\ude00"]"#),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn nesting() {
+        let schema = IpldSchema::parse("type Ll [Ll]").unwrap();
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth + 1), "]".repeat(depth + 1));
+
+        assert_eq!(
+            schema.validate_dag_json("Ll", &nested(MAX_DEPTH - 1)),
+            Ok(())
+        );
+        let err = schema.validate_dag_json("Ll", &nested(2000)).unwrap_err();
+        assert_eq!(err.span(), MAX_DEPTH..MAX_DEPTH + 1);
+        assert_eq!(err.line_col(), (1, MAX_DEPTH + 1));
+        let diagnostic = err.diagnostic(());
+        assert_eq!(diagnostic.labels[0].range, err.span());
+        assert_eq!(
+            diagnostic.labels[0].message,
+            "lists and maps are nested deeper than 128"
+        );
+    }
+
+    #[test]
+    fn agrees_with_validate() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+        let generator = schema.generator("Message").unwrap();
+
+        for seed in 0..100u8 {
+            let bytes = (0..512u32)
+                .map(|idx| (idx * 31 + seed as u32 * 97) as u8 ^ seed)
+                .collect::<Vec<_>>();
+
+            for near_miss in [false, true] {
+                let data = generator
+                    .clone()
+                    .with_near_miss(near_miss)
+                    .generate(&mut Unstructured::new(&bytes))
+                    .unwrap();
+                let json = String::from_utf8(serde_ipld_dagjson::to_vec(&data).unwrap()).unwrap();
+
+                let expected = schema.validate("Message", &data).is_ok();
+                let streamed = schema.validate_dag_json("Message", &json);
+                assert_eq!(streamed.is_ok(), expected, "{}: {:?}", json, streamed);
+            }
+        }
+    }
+}
@@ -0,0 +1,889 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{
+    enumerate::{EnumField, EnumMemberTag, EnumRepresentation, EnumType},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::BytesRepresentation,
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionMember, UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    Doc, InlineIpldType, IpldSchema, IpldType, Kind, PRELUDE,
+};
+
+/// Keywords, that constrain values in ways an IPLD schema can not express
+const UNSUPPORTED: &[&str] = &[
+    "pattern",
+    "format",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "contains",
+    "minProperties",
+    "maxProperties",
+    "patternProperties",
+    "dependentRequired",
+    "dependentSchemas",
+    "if",
+    "then",
+    "else",
+    "not",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ImportError {
+    #[error("Invalid JSON Schema: {0}")]
+    Invalid(String),
+    #[error("Invalid CDDL at line {line}: {message}")]
+    InvalidCddl { line: usize, message: String },
+}
+
+/// A part of a JSON Schema or of CDDL, that could not be imported exactly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportWarning {
+    /// The JSON pointer to the schema the warning is about, i.e. `#/$defs/Foo/properties/bar`,
+    /// or the name of the CDDL rule
+    pub pointer: String,
+    pub message: String,
+}
+
+impl Display for ImportWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+impl IpldSchema {
+    /// Imports a JSON Schema document on a best effort basis.
+    ///
+    /// The document itself becomes the type `root`, every entry of `$defs` or `definitions`
+    /// a type of its own. Nested object schemas are named after the type and field they are in.
+    /// A `oneOf` or `anyOf` of objects with a single property becomes a keyed union, one of
+    /// schemas of distinct kinds a kinded union. Everything that has no equivalent in an IPLD
+    /// schema, like patterns or numeric ranges, is reported as a warning.
+    pub fn from_json_schema(
+        document: &Value,
+        root: &str,
+    ) -> Result<(IpldSchema, Vec<ImportWarning>), ImportError> {
+        if !document.is_object() {
+            return Err(ImportError::Invalid(
+                "The document must be an object".to_string(),
+            ));
+        }
+
+        let mut importer = Importer::default();
+        let mut definitions = vec![];
+        for keyword in ["$defs", "definitions"] {
+            let Some(defs) = document.get(keyword).and_then(Value::as_object) else {
+                continue;
+            };
+
+            for (key, schema) in defs {
+                let pointer = format!("#/{}/{}", keyword, escape_pointer(key));
+                let name = importer.reserve(&type_name(key));
+                if name != *key {
+                    importer.warn(&pointer, format!("Renamed to \"{}\"", name));
+                }
+                importer.references.insert(pointer.clone(), name.clone());
+                importer.schemas.insert(pointer.clone(), schema.clone());
+                definitions.push((name, pointer, schema));
+            }
+        }
+
+        // A document, that only references one of its definitions, does not need a type
+        let is_reference = document.as_object().is_some_and(|document| {
+            document.contains_key("$ref")
+                && document.keys().all(|key| {
+                    matches!(
+                        key.as_str(),
+                        "$ref" | "$schema" | "$defs" | "definitions" | "$id"
+                    )
+                })
+        });
+        if !is_reference {
+            let name = importer.reserve(&type_name(root));
+            importer.define(name, "#".to_string(), document);
+        }
+        for (name, pointer, schema) in definitions {
+            importer.define(name, pointer, schema);
+        }
+
+        Ok((IpldSchema(importer.types), importer.warnings))
+    }
+}
+
+/// The state of an import, that is shared by the JSON Schema and the CDDL importer
+#[derive(Debug, Default)]
+pub(crate) struct Importer {
+    pub(crate) types: BTreeMap<String, Doc<IpldType>>,
+    /// The names of all types, including the ones reserved, but not yet defined
+    names: Vec<String>,
+    /// Maps JSON pointers of definitions to their type names
+    references: BTreeMap<String, String>,
+    /// Maps JSON pointers of definitions to their schemas
+    schemas: BTreeMap<String, Value>,
+    pub(crate) warnings: Vec<ImportWarning>,
+}
+
+impl Importer {
+    pub(crate) fn warn(&mut self, pointer: &str, message: impl ToString) {
+        self.warnings.push(ImportWarning {
+            pointer: pointer.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    /// Returns an unused type name based on `name`
+    pub(crate) fn reserve(&mut self, name: &str) -> String {
+        let mut unique = name.to_string();
+        let mut counter = 1;
+        while self.names.contains(&unique) || PRELUDE.contains(&unique.as_str()) {
+            counter += 1;
+            unique = format!("{}{}", name, counter);
+        }
+
+        self.names.push(unique.clone());
+        unique
+    }
+
+    fn define(&mut self, name: String, pointer: String, schema: &Value) {
+        let (schema, nullable) = split_nullable(schema);
+        if nullable {
+            self.warn(&pointer, "Types can not be nullable, only their uses");
+        }
+
+        let ty = self.import_type(&name, &pointer, &schema);
+        let doc = description(&schema);
+        self.types.insert(name, Doc { doc, ty });
+    }
+
+    fn import_type(&mut self, name: &str, pointer: &str, schema: &Value) -> IpldType {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            Value::Bool(true) => return IpldType::Any,
+            _ => {
+                self.warn(pointer, "Only object schemas and `true` are supported");
+                return IpldType::Any;
+            }
+        };
+
+        for keyword in UNSUPPORTED {
+            if schema.contains_key(*keyword) {
+                self.warn(
+                    pointer,
+                    format!("\"{}\" has no IPLD equivalent and is ignored", keyword),
+                );
+            }
+        }
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return match self.references.get(reference) {
+                Some(name) => IpldType::Copy(name.clone()),
+                None => {
+                    self.warn(pointer, format!("Can not resolve \"{}\"", reference));
+                    IpldType::Any
+                }
+            };
+        }
+
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            return self.import_enum(pointer, values);
+        }
+        if let Some(value) = schema.get("const") {
+            return self.import_enum(pointer, std::slice::from_ref(value));
+        }
+
+        for keyword in ["anyOf", "oneOf", "allOf"] {
+            let Some(schemas) = schema.get(keyword).and_then(Value::as_array) else {
+                continue;
+            };
+
+            if let [single] = schemas.as_slice() {
+                return self.import_type(name, &format!("{}/{}/0", pointer, keyword), single);
+            }
+            if keyword != "allOf" {
+                let pointer = format!("{}/{}", pointer, keyword);
+                if let Some(union) = self.import_union(name, &pointer, schemas) {
+                    return union;
+                }
+            }
+            self.warn(
+                pointer,
+                format!("\"{}\" of multiple schemas is not supported", keyword),
+            );
+            return IpldType::Any;
+        }
+
+        match schema.get("type") {
+            Some(Value::String(ty)) => self.import_kind(name, pointer, schema, ty),
+            Some(Value::Array(types)) => {
+                self.warn(pointer, "Multiple types are not supported");
+                match types.first().and_then(Value::as_str) {
+                    Some(ty) => self.import_kind(name, pointer, schema, ty),
+                    None => IpldType::Any,
+                }
+            }
+            _ if schema.contains_key("properties") => {
+                self.import_kind(name, pointer, schema, "object")
+            }
+            _ if schema.contains_key("items") || schema.contains_key("prefixItems") => {
+                self.import_kind(name, pointer, schema, "array")
+            }
+            _ => IpldType::Any,
+        }
+    }
+
+    fn import_kind(
+        &mut self,
+        name: &str,
+        pointer: &str,
+        schema: &Map<String, Value>,
+        ty: &str,
+    ) -> IpldType {
+        match ty {
+            "boolean" => IpldType::Bool,
+            "string" => IpldType::String,
+            "integer" => IpldType::Int,
+            "number" => IpldType::Float,
+            "null" => IpldType::Unit(UnitRepresentation::Null),
+            "array" => self.import_array(name, pointer, schema),
+            "object" => self.import_object(name, pointer, schema),
+            ty => {
+                self.warn(pointer, format!("Unknown type \"{}\"", ty));
+                IpldType::Any
+            }
+        }
+    }
+
+    fn import_array(&mut self, name: &str, pointer: &str, schema: &Map<String, Value>) -> IpldType {
+        if schema.contains_key("prefixItems") {
+            self.warn(
+                pointer,
+                "\"prefixItems\" are not supported, the items are imported as a list",
+            );
+        }
+
+        let (ty, nullable) = match schema.get("items") {
+            Some(Value::Bool(false)) | None => (InlineIpldType::Name("Any".to_string()), false),
+            Some(items) => self.import_inline(name, "Item", &format!("{}/items", pointer), items),
+        };
+
+        IpldType::List(ListType {
+            ty,
+            nullable,
+            repr: ListRepresentation::List,
+        })
+    }
+
+    fn import_object(
+        &mut self,
+        name: &str,
+        pointer: &str,
+        schema: &Map<String, Value>,
+    ) -> IpldType {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        // The DAG-JSON forms of links and bytes
+        if let Some(slash) = properties.and_then(|properties| properties.get("/")) {
+            if properties.is_some_and(|properties| properties.len() == 1) {
+                return match slash.get("type").and_then(Value::as_str) {
+                    Some("object") => IpldType::Bytes(BytesRepresentation::Bytes),
+                    _ => IpldType::Link("Any".to_string()),
+                };
+            }
+        }
+
+        let additional = schema.get("additionalProperties");
+        let Some(properties) = properties else {
+            let (value, nullable) = match additional {
+                Some(Value::Bool(false)) => {
+                    return IpldType::Unit(UnitRepresentation::EmptyMap);
+                }
+                Some(Value::Bool(true)) | None => (InlineIpldType::Name("Any".to_string()), false),
+                Some(additional) => self.import_inline(
+                    name,
+                    "Value",
+                    &format!("{}/additionalProperties", pointer),
+                    additional,
+                ),
+            };
+
+            let key = match schema
+                .get("propertyNames")
+                .and_then(|names| names.get("$ref"))
+                .and_then(Value::as_str)
+                .and_then(|reference| self.references.get(reference))
+            {
+                Some(key) => key.clone(),
+                None => {
+                    if schema.contains_key("propertyNames") {
+                        self.warn(
+                            pointer,
+                            "\"propertyNames\" is only supported as a reference",
+                        );
+                    }
+                    "String".to_string()
+                }
+            };
+
+            return IpldType::Map(MapType {
+                key,
+                value,
+                nullable,
+                repr: MapRepresentation::Map,
+            });
+        };
+
+        if !matches!(additional, Some(Value::Bool(false)) | None) {
+            self.warn(
+                pointer,
+                "\"additionalProperties\" are not supported together with \"properties\"",
+            );
+        }
+
+        let required = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| {
+                required
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let mut fields = vec![];
+        for (key, property) in properties {
+            let field_pointer = format!("{}/properties/{}", pointer, escape_pointer(key));
+            let (value, nullable) = self.import_inline(name, key, &field_pointer, property);
+
+            let field_name = field_name(key);
+            fields.push(StructField {
+                doc: description(property),
+                rename: (field_name != *key).then(|| key.clone()),
+                key: field_name,
+                value,
+                optional: !required.contains(&key.as_str()),
+                nullable,
+                implicit: None,
+            });
+        }
+
+        IpldType::Struct(StructType {
+            fields,
+            repr: StructRepresentation::Map,
+        })
+    }
+
+    /// Imports alternative schemas as a keyed union, if all of them are objects with a single
+    /// required property, or as a kinded union, if they are of distinct kinds
+    fn import_union(&mut self, name: &str, pointer: &str, schemas: &[Value]) -> Option<IpldType> {
+        let keys = schemas
+            .iter()
+            .map(single_property)
+            .collect::<Option<Vec<_>>>()
+            .filter(|keys| is_distinct(keys));
+        if let Some(keys) = keys {
+            let members = schemas
+                .iter()
+                .zip(keys)
+                .enumerate()
+                .map(|(idx, (schema, key))| {
+                    let pointer = format!("{}/{}/properties/{}", pointer, idx, escape_pointer(key));
+                    let ty = self.import_member(name, key, &pointer, &schema["properties"][key]);
+                    UnionMember {
+                        doc: description(schema),
+                        ty,
+                        discriminant: key.to_string(),
+                    }
+                })
+                .collect();
+            return Some(IpldType::Union(UnionType {
+                members,
+                repr: UnionRepresentation::Keyed,
+            }));
+        }
+
+        let kinds = schemas
+            .iter()
+            .map(|schema| self.kind(schema))
+            .collect::<Option<Vec<_>>>()
+            .filter(|kinds| is_distinct(kinds))?;
+        let members = schemas
+            .iter()
+            .zip(kinds)
+            .enumerate()
+            .map(|(idx, (schema, kind))| {
+                let pointer = format!("{}/{}", pointer, idx);
+                let ty = self.import_member(name, kind.as_str(), &pointer, schema);
+                UnionMember {
+                    doc: description(schema),
+                    ty,
+                    discriminant: kind.to_string(),
+                }
+            })
+            .collect();
+        Some(IpldType::Union(UnionType {
+            members,
+            repr: UnionRepresentation::Kinded,
+        }))
+    }
+
+    fn import_member(
+        &mut self,
+        parent: &str,
+        field: &str,
+        pointer: &str,
+        schema: &Value,
+    ) -> InlineIpldType {
+        let (ty, nullable) = self.import_inline(parent, field, pointer, schema);
+        self.member(parent, field, pointer, description(schema), ty, nullable)
+    }
+
+    /// Uses `ty` as a member of a union, which has to be named
+    pub(crate) fn member(
+        &mut self,
+        parent: &str,
+        field: &str,
+        pointer: &str,
+        doc: Option<String>,
+        ty: InlineIpldType,
+        nullable: bool,
+    ) -> InlineIpldType {
+        if nullable {
+            self.warn(pointer, "Union members can not be nullable");
+        }
+
+        match ty {
+            InlineIpldType::List(list) => self.insert(parent, field, doc, IpldType::List(*list)),
+            InlineIpldType::Map(map) => self.insert(parent, field, doc, IpldType::Map(*map)),
+            ty => ty,
+        }
+    }
+
+    /// Returns the kind of the data a schema describes, if it is a single one
+    fn kind(&self, schema: &Value) -> Option<Kind> {
+        let mut schema = schema;
+        // Definitions may reference each other, but not endlessly
+        for _ in 0..=self.schemas.len() {
+            if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+                schema = self.schemas.get(reference)?;
+                continue;
+            }
+
+            if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+                return match () {
+                    _ if values.iter().all(Value::is_string) => Some(Kind::String),
+                    _ if values.iter().all(|value| value.as_i64().is_some()) => Some(Kind::Int),
+                    _ => None,
+                };
+            }
+            for keyword in ["anyOf", "oneOf"] {
+                if let Some(schemas) = schema.get(keyword).and_then(Value::as_array) {
+                    let kinds = schemas
+                        .iter()
+                        .map(|schema| self.kind(schema))
+                        .collect::<Option<Vec<_>>>()?;
+                    return match kinds.as_slice() {
+                        [first, rest @ ..] if rest.iter().all(|kind| kind == first) => Some(*first),
+                        _ => None,
+                    };
+                }
+            }
+
+            let ty = match schema.get("type") {
+                Some(Value::String(ty)) => ty.as_str(),
+                _ if schema.get("properties").is_some() => "object",
+                _ if schema.get("items").is_some() || schema.get("prefixItems").is_some() => {
+                    "array"
+                }
+                _ => return None,
+            };
+            return match ty {
+                "boolean" => Some(Kind::Bool),
+                "string" => Some(Kind::String),
+                "integer" => Some(Kind::Int),
+                "number" => Some(Kind::Float),
+                "null" => Some(Kind::Null),
+                "array" => Some(Kind::List),
+                // The DAG-JSON forms of links and bytes
+                "object" => match schema.get("properties").and_then(Value::as_object) {
+                    Some(properties) if properties.len() == 1 && properties.contains_key("/") => {
+                        match properties["/"].get("type").and_then(Value::as_str) {
+                            Some("object") => Some(Kind::Bytes),
+                            _ => Some(Kind::Link),
+                        }
+                    }
+                    _ => Some(Kind::Map),
+                },
+                _ => None,
+            };
+        }
+        None
+    }
+
+    fn import_enum(&mut self, pointer: &str, values: &[Value]) -> IpldType {
+        let mut names = vec![];
+        let mut members = vec![];
+
+        let is_string = values.iter().all(Value::is_string);
+        let is_int = values.iter().all(|value| value.as_i64().is_some());
+        if !is_string && !is_int {
+            self.warn(
+                pointer,
+                "Only enums of strings or of integers are supported",
+            );
+            return IpldType::Any;
+        }
+
+        for value in values {
+            let (name, tag) = match value {
+                Value::String(tag) => (type_name(tag), EnumMemberTag::String(tag.clone())),
+                value => {
+                    let tag = value.as_i64().unwrap_or_default();
+                    let name = if tag < 0 {
+                        format!("ValueMinus{}", tag.unsigned_abs())
+                    } else {
+                        format!("Value{}", tag)
+                    };
+                    (name, EnumMemberTag::Int(tag.into()))
+                }
+            };
+
+            let mut unique = name.clone();
+            let mut counter = 1;
+            while names.contains(&unique) {
+                counter += 1;
+                unique = format!("{}{}", name, counter);
+            }
+            names.push(unique.clone());
+
+            members.push(EnumField {
+                doc: None,
+                name: unique,
+                tag,
+            });
+        }
+
+        IpldType::Enum(EnumType {
+            members,
+            repr: if is_string {
+                EnumRepresentation::String
+            } else {
+                EnumRepresentation::Int
+            },
+        })
+    }
+
+    /// Imports the schema of a field, list item or map value
+    fn import_inline(
+        &mut self,
+        parent: &str,
+        field: &str,
+        pointer: &str,
+        schema: &Value,
+    ) -> (InlineIpldType, bool) {
+        let (schema, nullable) = split_nullable(schema);
+        let name = format!("{}{}", parent, type_name(field));
+
+        let ty = self.import_type(&name, pointer, &schema);
+        (
+            self.inline(parent, field, description(&schema), ty),
+            nullable,
+        )
+    }
+
+    /// Uses `ty` inline.
+    ///
+    /// Structs, unions, enums and units can not be inline, they are defined as types named after
+    /// the parent type and `field`.
+    pub(crate) fn inline(
+        &mut self,
+        parent: &str,
+        field: &str,
+        doc: Option<String>,
+        ty: IpldType,
+    ) -> InlineIpldType {
+        match ty {
+            IpldType::Bool => InlineIpldType::Name("Bool".to_string()),
+            IpldType::String => InlineIpldType::Name("String".to_string()),
+            IpldType::Bytes(_) => InlineIpldType::Name("Bytes".to_string()),
+            IpldType::Int => InlineIpldType::Name("Int".to_string()),
+            IpldType::Float => InlineIpldType::Name("Float".to_string()),
+            IpldType::Any => InlineIpldType::Name("Any".to_string()),
+            IpldType::List(list) => InlineIpldType::List(Box::new(list)),
+            IpldType::Map(map) => InlineIpldType::Map(Box::new(map)),
+            IpldType::Link(target) => InlineIpldType::Link(target),
+            IpldType::Copy(name) => InlineIpldType::Name(name),
+            ty @ (IpldType::Struct(_)
+            | IpldType::Union(_)
+            | IpldType::Enum(_)
+            | IpldType::Unit(_)) => self.insert(parent, field, doc, ty),
+        }
+    }
+
+    /// Defines a type named after the parent type and `field`
+    pub(crate) fn insert(
+        &mut self,
+        parent: &str,
+        field: &str,
+        doc: Option<String>,
+        ty: IpldType,
+    ) -> InlineIpldType {
+        let name = self.reserve(&format!("{}{}", parent, type_name(field)));
+        self.types.insert(name.clone(), Doc { doc, ty });
+        InlineIpldType::Name(name)
+    }
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// The key of an object schema with a single property, that is required
+fn single_property(schema: &Value) -> Option<&str> {
+    let properties = schema.get("properties").and_then(Value::as_object)?;
+    let [key] = properties.keys().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let required = schema.get("required").and_then(Value::as_array)?;
+    required
+        .iter()
+        .any(|other| other == key)
+        .then_some(key.as_str())
+}
+
+pub(crate) fn is_distinct<T: PartialEq>(values: &[T]) -> bool {
+    values
+        .iter()
+        .enumerate()
+        .all(|(idx, value)| !values[..idx].contains(value))
+}
+
+/// Strips `null` from a schema, that also allows another type
+fn split_nullable(schema: &Value) -> (Cow<'_, Value>, bool) {
+    let is_null = |schema: &Value| schema.get("type").and_then(Value::as_str) == Some("null");
+
+    for keyword in ["anyOf", "oneOf"] {
+        let schemas = schema.get(keyword).and_then(Value::as_array);
+        if let Some([first, second]) = schemas.map(Vec::as_slice) {
+            match (is_null(first), is_null(second)) {
+                (false, true) => return (Cow::Borrowed(first), true),
+                (true, false) => return (Cow::Borrowed(second), true),
+                _ => (),
+            }
+        }
+    }
+
+    // i.e. `"type": ["string", "null"]`
+    if let Some(types) = schema.get("type").and_then(Value::as_array) {
+        let other = types
+            .iter()
+            .filter(|ty| ty.as_str() != Some("null"))
+            .collect::<Vec<_>>();
+        if let [ty] = other.as_slice() {
+            if types.len() == 2 {
+                let mut schema = schema.clone();
+                schema["type"] = (*ty).clone();
+                return (Cow::Owned(schema), true);
+            }
+        }
+    }
+
+    (Cow::Borrowed(schema), false)
+}
+
+/// Converts a name into a valid type name, i.e. `user-id` into `UserId`
+pub(crate) fn type_name(name: &str) -> String {
+    let mut out = name
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect::<String>();
+
+    if !out.starts_with(|c: char| c.is_ascii_uppercase()) {
+        out.insert(0, 'T');
+    }
+    while out.len() < 2 {
+        out.push('_');
+    }
+    out
+}
+
+/// Converts a property name into a valid field name, i.e. `user-id` into `user_id`
+pub(crate) fn field_name(name: &str) -> String {
+    let out = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+
+    if out.is_empty() {
+        "_".to_string()
+    } else {
+        out
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn from_json_schema() {
+        let document = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "description": "A message",
+            "type": "object",
+            "properties": {
+                "from": {"$ref": "#/$defs/identity"},
+                "to": {"type": ["array", "null"], "items": {"type": "string"}},
+                "body": {"type": "string", "maxLength": 280, "description": "The text"},
+                "sent-at": {"type": "integer", "minimum": 0},
+                "meta": {
+                    "type": "object",
+                    "properties": {"tags": {"anyOf": [{"$ref": "#/$defs/Tags"}, {"type": "null"}]}},
+                },
+                "attachment": {
+                    "type": "object",
+                    "properties": {"/": {"type": "string"}},
+                    "required": ["/"],
+                },
+            },
+            "required": ["from", "body"],
+            "$defs": {
+                "identity": {"type": "string", "pattern": "^[a-z]+$"},
+                "Tags": {"type": "object", "additionalProperties": {"type": "integer"}},
+                "Status": {"enum": ["Active", "gone"]},
+                "Mixed": {"oneOf": [{"type": "string"}, {"type": "integer"}]},
+                "Shape": {"oneOf": [
+                    {"properties": {"circle": {"type": "integer"}}, "required": ["circle"]},
+                    {
+                        "description": "A polygon",
+                        "properties": {"polygon": {"type": "array", "items": {"type": "integer"}}},
+                        "required": ["polygon"],
+                    },
+                ]},
+                "Either": {"anyOf": [{"type": "string"}, {"type": ["string", "null"]}]},
+            },
+        });
+
+        let (schema, warnings) = IpldSchema::from_json_schema(&document, "Message").unwrap();
+
+        let expected = "\
+type Either any
+
+type Identity string
+
+# A message
+type Message struct {
+  from Identity
+  to nullable optional [String]
+  # The text
+  body String
+  sent_at optional Int (rename \"sent-at\")
+  meta optional MessageMeta
+  attachment optional &Any
+}
+
+type MessageMeta struct {
+  tags nullable optional Tags
+}
+
+type Mixed union {
+  | String string
+  | Int int
+} representation kinded
+
+type Shape union {
+  | Int \"circle\"
+  # A polygon
+  | ShapePolygon \"polygon\"
+} representation keyed
+
+type ShapePolygon [Int]
+
+type Status enum {
+  | Active
+  | Gone (\"gone\")
+}
+
+type Tags {String:Int}
+";
+        assert_eq!(schema.to_string(), expected);
+
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "#/$defs/identity: Renamed to \"Identity\"",
+                "#/properties/body: \"maxLength\" has no IPLD equivalent and is ignored",
+                "#/properties/sent-at: \"minimum\" has no IPLD equivalent and is ignored",
+                "#/$defs/identity: \"pattern\" has no IPLD equivalent and is ignored",
+                "#/$defs/Either: \"anyOf\" of multiple schemas is not supported",
+            ]
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let schema = IpldSchema::parse(include_str!("../test/message.ipldsch")).unwrap();
+        let document = schema.to_json_schema("Message").unwrap();
+
+        let (imported, warnings) = IpldSchema::from_json_schema(&document, "Message").unwrap();
+        // Renames and implicit values are not part of the JSON Schema
+        let expected = "\
+type Identity string
+
+# A message
+type Message struct {
+  # Who sent it
+  from &Any
+  to nullable [nullable Identity]
+  s Status
+  count optional Int
+  labels optional {String:Int}
+  d Bytes
+}
+
+type Status enum {
+  | Act
+  | Gone
+}
+";
+        assert_eq!(imported.to_string(), expected);
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "#/$defs/Message/properties/labels: \"propertyNames\" is only supported as a reference"
+            ]
+        );
+    }
+}
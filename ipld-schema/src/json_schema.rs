@@ -0,0 +1,530 @@
+use std::collections::BTreeSet;
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::{
+    enumerate::{EnumMemberTag, EnumType},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::{BytesRepresentation, StringPairs},
+    structural::{StructRepresentation, StructType},
+    union::{UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    InlineIpldType, IpldSchema, IpldType,
+};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JsonSchemaError {
+    #[error("Type \"{0}\" is not defined")]
+    UnknownType(String),
+}
+
+impl IpldSchema {
+    /// Exports the type `root` and all types reachable from it as a JSON Schema (draft 2020-12).
+    ///
+    /// The JSON Schema describes the DAG-JSON encoding of the representation of the types,
+    /// i.e. links are `{"/": cid}` objects and `stringpairs` are plain strings.
+    /// Constraints that JSON Schema can not express are noted in `$comment`s.
+    pub fn to_json_schema(&self, root: &str) -> Result<Value, JsonSchemaError> {
+        if self.resolve(root).is_none() {
+            return Err(JsonSchemaError::UnknownType(root.to_string()));
+        }
+
+        let root_ty = InlineIpldType::Name(root.to_string());
+        let mut names = BTreeSet::new();
+        if self.0.contains_key(root) {
+            names.insert(root.to_string());
+        }
+        self.collect_references(&root_ty, &mut names);
+
+        let mut defs = Map::new();
+        for name in names {
+            let Some(def) = self.0.get(&name) else {
+                continue;
+            };
+
+            let mut schema = self.json_schema_type(&def.ty);
+            if let Some(doc) = &def.doc {
+                schema.insert("description".to_string(), json!(doc));
+            }
+            defs.insert(name, Value::Object(schema));
+        }
+
+        let mut schema = Map::new();
+        schema.insert("$schema".to_string(), json!(DRAFT));
+        schema.extend(self.json_schema_inline(&root_ty));
+        if !defs.is_empty() {
+            schema.insert("$defs".to_string(), Value::Object(defs));
+        }
+
+        Ok(Value::Object(schema))
+    }
+
+    fn json_schema_type(&self, ty: &IpldType) -> Map<String, Value> {
+        match ty {
+            IpldType::Bool => object(json!({"type": "boolean"})),
+            IpldType::String => object(json!({"type": "string"})),
+            IpldType::Int => object(json!({"type": "integer"})),
+            IpldType::Float => object(json!({"type": "number"})),
+            IpldType::Bytes(BytesRepresentation::Advanced(name)) => object(json!({
+                "$comment": format!("Bytes read through the advanced layout {}", name),
+            })),
+            IpldType::Bytes(BytesRepresentation::Bytes) => object(json!({
+                "type": "object",
+                "properties": {
+                    "/": {
+                        "type": "object",
+                        "properties": {"bytes": {"type": "string", "contentEncoding": "base64"}},
+                        "required": ["bytes"],
+                        "additionalProperties": false,
+                    },
+                },
+                "required": ["/"],
+                "additionalProperties": false,
+            })),
+            IpldType::Link(target) => {
+                let mut link = object(json!({
+                    "type": "object",
+                    "properties": {"/": {"type": "string"}},
+                    "required": ["/"],
+                    "additionalProperties": false,
+                }));
+                if target != "Any" {
+                    link.insert(
+                        "$comment".to_string(),
+                        json!(format!(
+                            "Links to {}, the linked data is not checked",
+                            target
+                        )),
+                    );
+                }
+                link
+            }
+            IpldType::Any => Map::new(),
+            IpldType::List(list) => self.json_schema_list(list),
+            IpldType::Map(map) => self.json_schema_map(map),
+            IpldType::Struct(stru) => self.json_schema_struct(stru),
+            IpldType::Union(union) => self.json_schema_union(union),
+            IpldType::Enum(enu) => json_schema_enum(enu),
+            IpldType::Unit(unit) => match unit {
+                UnitRepresentation::Null => object(json!({"type": "null"})),
+                UnitRepresentation::True => object(json!({"const": true})),
+                UnitRepresentation::False => object(json!({"const": false})),
+                UnitRepresentation::EmptyMap => {
+                    object(json!({"type": "object", "maxProperties": 0}))
+                }
+            },
+            IpldType::Copy(from) => self.json_schema_inline(&InlineIpldType::Name(from.clone())),
+        }
+    }
+
+    /// Types defined in the schema are referenced, prelude types are inlined
+    fn json_schema_inline(&self, ty: &InlineIpldType) -> Map<String, Value> {
+        match ty {
+            InlineIpldType::Name(name) if self.0.contains_key(name) => {
+                object(json!({"$ref": format!("#/$defs/{}", name)}))
+            }
+            InlineIpldType::Name(name) => match self.resolve(name) {
+                Some(ty) => self.json_schema_type(&ty),
+                None => Map::new(),
+            },
+            InlineIpldType::List(list) => self.json_schema_list(list),
+            InlineIpldType::Map(map) => self.json_schema_map(map),
+            InlineIpldType::Link(target) => self.json_schema_type(&IpldType::Link(target.clone())),
+        }
+    }
+
+    fn json_schema_nullable(&self, ty: &InlineIpldType, nullable: bool) -> Value {
+        let schema = Value::Object(self.json_schema_inline(ty));
+        if nullable {
+            json!({"anyOf": [schema, {"type": "null"}]})
+        } else {
+            schema
+        }
+    }
+
+    fn json_schema_list(&self, list: &ListType) -> Map<String, Value> {
+        match &list.repr {
+            ListRepresentation::List => object(json!({
+                "type": "array",
+                "items": self.json_schema_nullable(&list.ty, list.nullable),
+            })),
+            ListRepresentation::Advanced(name) => object(json!({
+                "$comment": format!("List of {} read through the advanced layout {}", list.ty, name),
+            })),
+        }
+    }
+
+    fn json_schema_map(&self, map: &MapType) -> Map<String, Value> {
+        let value = self.json_schema_nullable(&map.value, map.nullable);
+
+        match &map.repr {
+            MapRepresentation::Map => {
+                let mut schema = object(json!({"type": "object", "additionalProperties": value}));
+                match self.json_schema_key(&map.key) {
+                    // Object keys are always strings
+                    Some(key) if key == json!({"type": "string"}) => (),
+                    Some(key) => {
+                        schema.insert("propertyNames".to_string(), key);
+                    }
+                    None => {
+                        schema.insert(
+                            "$comment".to_string(),
+                            json!(format!("Keys are {} values", map.key)),
+                        );
+                    }
+                }
+                schema
+            }
+            MapRepresentation::ListPairs => {
+                let key = self
+                    .json_schema_key(&map.key)
+                    .unwrap_or_else(|| json!({"type": "string"}));
+                object(json!({
+                    "type": "array",
+                    "items": {
+                        "type": "array",
+                        "prefixItems": [key, value],
+                        "minItems": 2,
+                        "items": false,
+                    },
+                }))
+            }
+            MapRepresentation::Advanced(name) => object(json!({
+                "$comment": format!("Map of {} to {} read through the advanced layout {}", map.key, map.value, name),
+            })),
+            MapRepresentation::StringPairs(pairs) => object(json!({
+                "type": "string",
+                "$comment": format!(
+                    "Entries of {} to {}, {}",
+                    map.key,
+                    map.value,
+                    string_pairs(pairs)
+                ),
+            })),
+        }
+    }
+
+    /// Keys of string kinded types can be checked with `propertyNames`
+    fn json_schema_key(&self, key: &str) -> Option<Value> {
+        let resolved = self.resolve_inline(&InlineIpldType::Name(key.to_string()))?;
+        match resolved.as_ref() {
+            IpldType::String => Some(json!({"type": "string"})),
+            IpldType::Enum(enu) if enu.is_string() => Some(Value::Object(json_schema_enum(enu))),
+            _ => None,
+        }
+    }
+
+    fn json_schema_struct(&self, stru: &StructType) -> Map<String, Value> {
+        match &stru.repr {
+            StructRepresentation::Map => {
+                let mut properties = Map::new();
+                let mut required = vec![];
+                let mut comments = vec![];
+
+                for field in &stru.fields {
+                    let key = field.rename.as_deref().unwrap_or(&field.key);
+                    let mut schema = self.json_schema_nullable(&field.value, field.nullable);
+                    if let (Some(doc), Value::Object(schema)) = (&field.doc, &mut schema) {
+                        schema.insert("description".to_string(), json!(doc));
+                    }
+                    properties.insert(key.to_string(), schema);
+
+                    match &field.implicit {
+                        Some(implicit) => {
+                            comments.push(format!("\"{}\" is omitted if it is {}", key, implicit))
+                        }
+                        None if !field.optional => required.push(json!(key)),
+                        None => (),
+                    }
+                }
+
+                let mut schema = object(json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false,
+                }));
+                if !comments.is_empty() {
+                    schema.insert("$comment".to_string(), json!(comments.join(", ")));
+                }
+                schema
+            }
+            StructRepresentation::ListPairs => {
+                let entries = stru
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let key = field.rename.as_deref().unwrap_or(&field.key);
+                        json!({
+                            "prefixItems": [
+                                {"const": key},
+                                self.json_schema_nullable(&field.value, field.nullable),
+                            ],
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                object(json!({
+                    "type": "array",
+                    "items": {
+                        "type": "array",
+                        "minItems": 2,
+                        "items": false,
+                        "anyOf": entries,
+                    },
+                    "$comment": "Required fields and duplicate entries are not checked",
+                }))
+            }
+            StructRepresentation::StringPairs(pairs) => {
+                let fields = stru
+                    .fields
+                    .iter()
+                    .map(|field| field.rename.as_deref().unwrap_or(&field.key))
+                    .collect::<Vec<_>>();
+                object(json!({
+                    "type": "string",
+                    "$comment": format!(
+                        "Fields {}, {}",
+                        fields.join(", "),
+                        string_pairs(pairs)
+                    ),
+                }))
+            }
+            StructRepresentation::Tuple => {
+                let items = stru
+                    .fields
+                    .iter()
+                    .map(|field| self.json_schema_nullable(&field.value, field.nullable))
+                    .collect::<Vec<_>>();
+                let required = stru.required_tuple_fields();
+                object(json!({
+                    "type": "array",
+                    "prefixItems": items,
+                    "minItems": required,
+                    "items": false,
+                }))
+            }
+        }
+    }
+
+    fn json_schema_union(&self, union: &UnionType) -> Map<String, Value> {
+        let members = union
+            .members
+            .iter()
+            .map(|member| {
+                let schema = Value::Object(self.json_schema_inline(&member.ty));
+                match &union.repr {
+                    UnionRepresentation::Kinded => schema,
+                    UnionRepresentation::Keyed => json!({
+                        "type": "object",
+                        "properties": {&member.discriminant: schema},
+                        "required": [&member.discriminant],
+                        "additionalProperties": false,
+                    }),
+                    UnionRepresentation::Envelope {
+                        discriminant_key,
+                        content_key,
+                    } => json!({
+                        "type": "object",
+                        "properties": {
+                            discriminant_key: {"const": &member.discriminant},
+                            content_key: schema,
+                        },
+                        "required": [discriminant_key, content_key],
+                        "additionalProperties": false,
+                    }),
+                    UnionRepresentation::Inline { discriminant_key } => json!({
+                        "type": "object",
+                        "properties": {discriminant_key: {"const": &member.discriminant}},
+                        "required": [discriminant_key],
+                        "$comment": format!("The fields of {} are next to the discriminant", member.name()),
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+        object(json!({ "oneOf": members }))
+    }
+}
+
+fn json_schema_enum(enu: &EnumType) -> Map<String, Value> {
+    let tags = enu
+        .members
+        .iter()
+        .map(|member| match &member.tag {
+            EnumMemberTag::String(tag) => json!(tag),
+            EnumMemberTag::Int(tag) => {
+                serde_json::to_value(tag).unwrap_or_else(|_| json!(tag.to_string()))
+            }
+        })
+        .collect::<Vec<_>>();
+    object(json!({ "enum": tags }))
+}
+
+fn string_pairs(pairs: &StringPairs) -> String {
+    format!(
+        "joined by \"{}\" and \"{}\"",
+        pairs.inner_delim, pairs.entry_delim
+    )
+}
+
+fn object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!("JSON Schemas are objects"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_json_schema() {
+        let schema = IpldSchema::parse(include_str!("../test/message.ipldsch")).unwrap();
+
+        let link = json!({
+            "type": "object",
+            "properties": {"/": {"type": "string"}},
+            "required": ["/"],
+            "additionalProperties": false,
+            "$comment": "Links to Identity, the linked data is not checked",
+            "description": "Who sent it",
+        });
+        let bytes = json!({
+            "type": "object",
+            "properties": {
+                "/": {
+                    "type": "object",
+                    "properties": {"bytes": {"type": "string", "contentEncoding": "base64"}},
+                    "required": ["bytes"],
+                    "additionalProperties": false,
+                },
+            },
+            "required": ["/"],
+            "additionalProperties": false,
+        });
+        let expected = json!({
+            "$schema": DRAFT,
+            "$ref": "#/$defs/Message",
+            "$defs": {
+                "Identity": {"type": "string"},
+                "Message": {
+                    "type": "object",
+                    "properties": {
+                        "from": link,
+                        "to": {"anyOf": [
+                            {
+                                "type": "array",
+                                "items": {"anyOf": [{"$ref": "#/$defs/Identity"}, {"type": "null"}]},
+                            },
+                            {"type": "null"},
+                        ]},
+                        "s": {"$ref": "#/$defs/Status"},
+                        "count": {"type": "integer"},
+                        "labels": {
+                            "type": "object",
+                            "additionalProperties": {"type": "integer"},
+                            "propertyNames": {"enum": ["Act", "Gone"]},
+                        },
+                        "d": bytes,
+                    },
+                    "required": ["from", "to", "s", "d"],
+                    "additionalProperties": false,
+                    "$comment": "\"count\" is omitted if it is 0",
+                    "description": "A message",
+                },
+                "Status": {"enum": ["Act", "Gone"]},
+            },
+        });
+
+        assert_eq!(schema.to_json_schema("Message").unwrap(), expected);
+        assert_eq!(
+            schema.to_json_schema("Missing").unwrap_err(),
+            JsonSchemaError::UnknownType("Missing".to_string())
+        );
+    }
+
+    #[test]
+    fn unions_and_tuples() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+        let defs = |root: &str| schema.to_json_schema(root).unwrap()["$defs"].clone();
+
+        assert_eq!(
+            defs("Point")["Point"],
+            json!({
+                "type": "array",
+                "prefixItems": [{"type": "integer"}, {"type": "integer"}, {"type": "string"}],
+                "minItems": 2,
+                "items": false,
+            })
+        );
+        assert_eq!(
+            defs("Shape")["Shape"],
+            json!({"oneOf": [
+                {
+                    "type": "object",
+                    "properties": {"circle": {"$ref": "#/$defs/Circle"}},
+                    "required": ["circle"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {"square": {
+                        "type": "object",
+                        "properties": {"/": {"type": "string"}},
+                        "required": ["/"],
+                        "additionalProperties": false,
+                        "$comment": "Links to Square, the linked data is not checked",
+                    }},
+                    "required": ["square"],
+                    "additionalProperties": false,
+                },
+            ]})
+        );
+        assert_eq!(
+            defs("Value")["Value"],
+            json!({"oneOf": [
+                {"type": "integer"},
+                {"$ref": "#/$defs/Shape"},
+                {"$ref": "#/$defs/Point"},
+            ]})
+        );
+        assert_eq!(
+            defs("Wrapped")["Wrapped"]["oneOf"][1],
+            json!({
+                "type": "object",
+                "properties": {"tag": {"const": "s"}, "content": {"$ref": "#/$defs/Square"}},
+                "required": ["tag", "content"],
+                "additionalProperties": false,
+            })
+        );
+        assert_eq!(
+            defs("Tagged")["Tagged"]["oneOf"][0]["required"],
+            json!(["tag"])
+        );
+    }
+
+    #[test]
+    fn string_pairs() {
+        let schema = IpldSchema::parse(
+            "\
+type Meta {String:Int} representation stringpairs {
+  innerDelim \"=\"
+  entryDelim \",\"
+}
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            schema.to_json_schema("Meta").unwrap()["$defs"]["Meta"],
+            json!({
+                "type": "string",
+                "$comment": "Entries of String to Int, joined by \"=\" and \",\"",
+            })
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt::{self, Display, Formatter};
+
+use ipld_core::ipld::Ipld;
+
+/// The kinds of the IPLD data model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    Null,
+    Bool,
+    Int,
+    Float,
+    String,
+    Bytes,
+    List,
+    Map,
+    Link,
+}
+
+impl Kind {
+    /// Returns the kind of a data model value
+    pub fn of(ipld: &Ipld) -> Self {
+        match ipld {
+            Ipld::Null => Kind::Null,
+            Ipld::Bool(_) => Kind::Bool,
+            Ipld::Integer(_) => Kind::Int,
+            Ipld::Float(_) => Kind::Float,
+            Ipld::String(_) => Kind::String,
+            Ipld::Bytes(_) => Kind::Bytes,
+            Ipld::List(_) => Kind::List,
+            Ipld::Map(_) => Kind::Map,
+            Ipld::Link(_) => Kind::Link,
+        }
+    }
+
+    /// Parses a kind as it is written in the schema DSL, i.e. `map`
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "null" => Some(Kind::Null),
+            "bool" => Some(Kind::Bool),
+            "int" => Some(Kind::Int),
+            "float" => Some(Kind::Float),
+            "string" => Some(Kind::String),
+            "bytes" => Some(Kind::Bytes),
+            "list" => Some(Kind::List),
+            "map" => Some(Kind::Map),
+            "link" => Some(Kind::Link),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind as it is written in the schema DSL, i.e. `map`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Null => "null",
+            Kind::Bool => "bool",
+            Kind::Int => "int",
+            Kind::Float => "float",
+            Kind::String => "string",
+            Kind::Bytes => "bytes",
+            Kind::List => "list",
+            Kind::Map => "map",
+            Kind::Link => "link",
+        }
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -1,21 +1,88 @@
 #![allow(dead_code, unused_variables)]
 
+mod adl;
+mod builder;
+mod car;
+mod cbor;
+mod cddl;
+mod cddl_import;
+mod check;
+mod codegen;
 mod comment;
+mod compat;
+mod dag;
+mod diff;
+mod dmt;
+mod docs;
 mod enumerate;
+mod examples;
+mod generate;
+mod graph;
+mod hamt;
+mod json;
+mod json_import;
+mod json_schema;
+mod kind;
 mod list;
+mod loader;
 mod map;
+mod markdown;
+mod outline;
 mod parse;
+mod path;
+mod print;
 mod representation;
+mod selector;
+mod store;
+mod stream;
 mod structural;
+mod typescript;
+mod union;
 mod unit;
+mod validate;
+mod visit;
 
-use enumerate::EnumType;
-use list::ListType;
-use map::MapType;
 use pest_derive::Parser;
-use std::collections::BTreeMap;
-use structural::StructType;
-use unit::UnitRepresentation;
+use std::{borrow::Cow, collections::BTreeMap};
+
+pub use adl::{AdlError, AdlRegistry, AdvancedDataLayout};
+pub use builder::{BuildError, EnumBuilder, SchemaBuilder, StructBuilder, UnionBuilder};
+pub use car::{Car, CarError};
+pub use cbor::CborValidationError;
+pub use check::SchemaError;
+pub use compat::{Change, Compatibility, CompatibilityReport};
+pub use dag::{BlockError, BlockReport, DagReport, DagValidator, DAG_CBOR, DAG_JSON, RAW};
+pub use diff::{DiffChange, DiffStatus, SchemaDiff, TypeDiff};
+pub use dmt::DmtError;
+pub use docs::DocsFormat;
+pub use enumerate::{EnumField, EnumMemberTag, EnumRepresentation, EnumType, InvalidEnum};
+pub use examples::Example;
+pub use generate::{DataGenerator, GenerateError};
+pub use graph::{GraphError, GraphFormat};
+pub use hamt::{Hamt, HamtConfig, HamtError, HamtIter, HashAlgorithm, HashMapLayout, HASH_MAP};
+pub use json::JsonValidationError;
+pub use json_import::{ImportError, ImportWarning};
+pub use json_schema::JsonSchemaError;
+pub use kind::Kind;
+pub use list::{ListRepresentation, ListType};
+pub use loader::{LoadError, Location, SchemaLoader};
+pub use map::{MapRepresentation, MapType};
+pub use markdown::markdown_source;
+pub use outline::{outline, Outline, Reference, Symbol};
+pub use parse::IpldSchemaParseError;
+pub use path::{PathError, PathStep};
+pub use print::format;
+pub use representation::{BytesRepresentation, StringPairs};
+pub use selector::{RecursionLimit, Selector, SelectorError};
+pub use store::{BlockStore, DirectoryStore, MemoryStore, StoreError};
+pub use structural::{StructField, StructRepresentation, StructType};
+pub use union::{InvalidUnion, UnionMember, UnionRepresentation, UnionType};
+pub use unit::UnitRepresentation;
+pub use validate::{DataPath, ValidationError};
+pub use visit::{
+    fold_inline_type, fold_struct_field, fold_type, fold_union_member, walk_inline_type,
+    walk_struct_field, walk_type, walk_union_member, Fold, Representation, Visitor,
+};
 
 #[derive(Parser)]
 #[grammar = "schema.pest"]
@@ -32,17 +99,107 @@ struct Doc<T> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IpldSchema(BTreeMap<String, Doc<IpldType>>);
 
+impl IpldSchema {
+    /// Returns the names of all types defined in this schema
+    pub fn type_names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Returns true, if the schema defines a type of that name
+    pub fn contains_type(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Looks up a type by name.
+    ///
+    /// Types that are not defined by the schema itself are looked up in the prelude,
+    /// i.e. `String`, `Int` or `Any` resolve, even if they are not defined explicitly.
+    pub(crate) fn resolve(&self, name: &str) -> Option<Cow<'_, IpldType>> {
+        match self.0.get(name) {
+            Some(def) => Some(Cow::Borrowed(&def.ty)),
+            None => prelude_type(name).map(Cow::Owned),
+        }
+    }
+
+    /// Names a type in generated code.
+    ///
+    /// Names defined in the schema are used as they are, prelude names are looked up in
+    /// `prelude`, which maps them to types of the target language.
+    pub(crate) fn target_name(&self, name: &str, prelude: &[(&str, &str)]) -> String {
+        if self.0.contains_key(name) {
+            return name.to_string();
+        }
+        match prelude.iter().find(|(prelude, _)| *prelude == name) {
+            Some((_, target)) => target.to_string(),
+            None => name.to_string(),
+        }
+    }
+
+    /// Looks up the definition of an inline type, following copies.
+    ///
+    /// Returns `None` if a referenced type is not defined or the copies form a cycle.
+    pub(crate) fn resolve_inline(&self, ty: &InlineIpldType) -> Option<Cow<'_, IpldType>> {
+        let mut resolved = match ty {
+            InlineIpldType::Name(name) => self.resolve(name)?,
+            InlineIpldType::List(list) => Cow::Owned(IpldType::List((**list).clone())),
+            InlineIpldType::Map(map) => Cow::Owned(IpldType::Map((**map).clone())),
+            InlineIpldType::Link(target) => Cow::Owned(IpldType::Link(target.clone())),
+        };
+
+        for _ in 0..=self.0.len() {
+            match resolved.as_ref() {
+                IpldType::Copy(from) => resolved = self.resolve(&from.clone())?,
+                _ => return Some(resolved),
+            }
+        }
+
+        None
+    }
+}
+
+/// The types that are implicitly defined in every schema
+pub const PRELUDE: &[&str] = &[
+    "Bool", "String", "Bytes", "Int", "Float", "Map", "List", "Link", "Any",
+];
+
+fn prelude_type(name: &str) -> Option<IpldType> {
+    let any = || InlineIpldType::Name("Any".to_string());
+
+    match name {
+        "Bool" => Some(IpldType::Bool),
+        "String" => Some(IpldType::String),
+        "Bytes" => Some(IpldType::Bytes(BytesRepresentation::Bytes)),
+        "Int" => Some(IpldType::Int),
+        "Float" => Some(IpldType::Float),
+        "Map" => Some(IpldType::Map(MapType {
+            key: "String".to_string(),
+            value: any(),
+            nullable: true,
+            repr: MapRepresentation::Map,
+        })),
+        "List" => Some(IpldType::List(ListType {
+            ty: any(),
+            nullable: true,
+            repr: ListRepresentation::List,
+        })),
+        "Link" => Some(IpldType::Link("Any".to_string())),
+        "Any" => Some(IpldType::Any),
+        _ => None,
+    }
+}
+
+/// The type of a definition in a schema
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) enum IpldType {
+pub enum IpldType {
     Bool,
     String,
-    Bytes,
+    Bytes(BytesRepresentation),
     Int,
     Float,
     List(ListType),
     Map(MapType),
     Link(String),
-    // TODO: Union
+    Union(UnionType),
     Struct(StructType),
     Enum(EnumType),
     Unit(UnitRepresentation),
@@ -50,8 +207,31 @@ pub(crate) enum IpldType {
     Copy(String),
 }
 
+impl IpldType {
+    /// The kind of the type as it is written in the schema DSL, i.e. `struct`
+    pub(crate) fn type_kind(&self) -> &'static str {
+        match self {
+            IpldType::Bool => "bool",
+            IpldType::String => "string",
+            IpldType::Bytes(_) => "bytes",
+            IpldType::Int => "int",
+            IpldType::Float => "float",
+            IpldType::List(_) => "list",
+            IpldType::Map(_) => "map",
+            IpldType::Link(_) => "link",
+            IpldType::Union(_) => "union",
+            IpldType::Struct(_) => "struct",
+            IpldType::Enum(_) => "enum",
+            IpldType::Unit(_) => "unit",
+            IpldType::Any => "any",
+            IpldType::Copy(_) => "copy",
+        }
+    }
+}
+
+/// A type written in place, i.e. the type of a field or of list items
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) enum InlineIpldType {
+pub enum InlineIpldType {
     Name(String),
     List(Box<ListType>),
     Map(Box<MapType>),
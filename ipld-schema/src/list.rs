@@ -1,13 +1,33 @@
 use crate::{
     parse::{parse_inline_type, IpldSchemaParseError},
+    representation::parse_advanced,
     InlineIpldType, Rule,
 };
 use pest::iterators::Pairs;
 
+/// A list type, i.e. `[String]`
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct ListType {
-    pub(crate) ty: InlineIpldType,
-    pub(crate) nullable: bool,
+pub struct ListType {
+    pub ty: InlineIpldType,
+    pub nullable: bool,
+    pub repr: ListRepresentation,
+}
+
+/// How a list is represented in the data model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListRepresentation {
+    List,
+    /// The data is read through the advanced data layout with this name
+    Advanced(String),
+}
+
+impl ListRepresentation {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ListRepresentation::List => "list",
+            ListRepresentation::Advanced(_) => "advanced",
+        }
+    }
 }
 
 pub(crate) fn parse_list(mut list: Pairs<Rule>) -> Result<ListType, IpldSchemaParseError> {
@@ -19,11 +39,17 @@ pub(crate) fn parse_list(mut list: Pairs<Rule>) -> Result<ListType, IpldSchemaPa
     };
 
     let inner = list.next().unwrap();
-    assert!(list.next().is_none());
-
     let ty = parse_inline_type(inner.into_inner())?;
 
-    Ok(ListType { ty, nullable })
+    let repr = match list.next() {
+        Some(repr) => {
+            assert!(list.next().is_none());
+            ListRepresentation::Advanced(parse_advanced(repr.into_inner()))
+        }
+        None => ListRepresentation::List,
+    };
+
+    Ok(ListType { ty, nullable, repr })
 }
 
 #[cfg(test)]
@@ -49,6 +75,7 @@ mod tests {
                 ty: IpldType::List(ListType {
                     ty: InlineIpldType::Name("String".to_string()),
                     nullable: false,
+                    repr: ListRepresentation::List,
                 }),
             },
         );
@@ -59,6 +86,7 @@ mod tests {
                 ty: IpldType::List(ListType {
                     ty: InlineIpldType::Name("String".to_string()),
                     nullable: true,
+                    repr: ListRepresentation::List,
                 }),
             },
         );
@@ -70,8 +98,10 @@ mod tests {
                     ty: InlineIpldType::List(Box::new(ListType {
                         ty: InlineIpldType::Name("String".to_string()),
                         nullable: false,
+                        repr: ListRepresentation::List,
                     })),
                     nullable: false,
+                    repr: ListRepresentation::List,
                 }),
             },
         );
@@ -82,6 +112,7 @@ mod tests {
                 ty: IpldType::List(ListType {
                     ty: InlineIpldType::Link("String".to_string()),
                     nullable: false,
+                    repr: ListRepresentation::List,
                 }),
             },
         );
@@ -97,6 +128,19 @@ mod tests {
                         repr: MapRepresentation::Map,
                     })),
                     nullable: false,
+                    repr: ListRepresentation::List,
+                }),
+            },
+        );
+
+        expected_schema.0.insert(
+            "ShardedList".to_string(),
+            Doc {
+                doc: Some("A list that is sharded over many blocks".to_string()),
+                ty: IpldType::List(ListType {
+                    ty: InlineIpldType::Name("String".to_string()),
+                    nullable: false,
+                    repr: ListRepresentation::Advanced("ShardedList".to_string()),
                 }),
             },
         );
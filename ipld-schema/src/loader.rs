@@ -0,0 +1,283 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{
+    markdown::{is_markdown, markdown_source},
+    parse::{parse_file, IpldSchemaParseError},
+    Doc, IpldSchema, IpldType,
+};
+
+/// A position inside of a schema file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    fn new(path: &Path, source: &str, offset: usize) -> Self {
+        let (line, column) = pest::Position::new(source, offset)
+            .map(|pos| pos.line_col())
+            .unwrap_or((1, 1));
+
+        Self {
+            path: path.to_path_buf(),
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Failed to parse {}: {error}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        error: IpldSchemaParseError,
+    },
+    #[error("{location}: Import \"{import}\" was not found")]
+    NotFound { import: String, location: Location },
+    #[error("Import cycle detected: {}", .0.iter().map(|path| path.display()).join(" -> "))]
+    Cycle(Vec<PathBuf>),
+    #[error("Type \"{name}\" is defined at {first} and at {second}")]
+    Conflict {
+        name: String,
+        first: Location,
+        second: Location,
+    },
+}
+
+/// Loads schemas that are split over multiple files.
+///
+/// Files can import other files using `import "path/to/file.ipldsch"`.
+/// Imports are resolved relative to the importing file first, then relative to the search paths
+/// in the order they were added.
+/// The definitions of all files are merged into a single schema, where every type name
+/// must be unique. Markdown files (`.md`) can be loaded and imported as well, their schema
+/// is taken from the `ipldsch` code blocks.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaLoader {
+    search_paths: Vec<PathBuf>,
+}
+
+impl SchemaLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a directory to look up imports in
+    pub fn with_search_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// Loads the schema at `root` together with all of its imports
+    pub fn load(&self, root: impl AsRef<Path>) -> Result<IpldSchema, LoadError> {
+        let root = root.as_ref();
+        let root = root.canonicalize().map_err(|source| LoadError::Io {
+            path: root.to_path_buf(),
+            source,
+        })?;
+
+        let mut state = LoadState::default();
+        self.load_file(&root, &mut state)?;
+
+        Ok(state.into_schema())
+    }
+
+    /// Loads a schema from `source`, resolving its imports as if it was stored at `path`.
+    ///
+    /// This is useful for files that were not saved yet, i.e. in an editor.
+    pub fn load_str(&self, path: impl AsRef<Path>, source: &str) -> Result<IpldSchema, LoadError> {
+        let path = path.as_ref();
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        let mut state = LoadState::default();
+        self.load_source(&path, source, &mut state)?;
+
+        Ok(state.into_schema())
+    }
+
+    fn load_file(&self, path: &Path, state: &mut LoadState) -> Result<(), LoadError> {
+        if let Some(idx) = state.stack.iter().position(|file| file == path) {
+            let mut cycle = state.stack[idx..].to_vec();
+            cycle.push(path.to_path_buf());
+            return Err(LoadError::Cycle(cycle));
+        }
+        if state.loaded.contains(path) {
+            return Ok(());
+        }
+
+        let mut source = fs::read_to_string(path).map_err(|source| LoadError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if is_markdown(path) {
+            source = markdown_source(&source);
+        }
+        self.load_source(path, &source, state)
+    }
+
+    fn load_source(
+        &self,
+        path: &Path,
+        source: &str,
+        state: &mut LoadState,
+    ) -> Result<(), LoadError> {
+        let parsed = parse_file(source).map_err(|error| LoadError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        state.stack.push(path.to_path_buf());
+        for import in parsed.imports {
+            let location = Location::new(path, source, import.span.start);
+            let resolved =
+                self.resolve_import(path, &import.path)
+                    .ok_or_else(|| LoadError::NotFound {
+                        import: import.path.clone(),
+                        location,
+                    })?;
+
+            self.load_file(&resolved, state)?;
+        }
+        state.stack.pop();
+
+        for definition in parsed.definitions {
+            let location = Location::new(path, source, definition.span.start);
+            if let Some((_, first)) = state.definitions.get(&definition.name) {
+                return Err(LoadError::Conflict {
+                    name: definition.name,
+                    first: first.clone(),
+                    second: location,
+                });
+            }
+            state
+                .definitions
+                .insert(definition.name, (definition.def, location));
+        }
+
+        state.loaded.insert(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Finds the file an `import` in the file at `importer` refers to
+    pub fn resolve_import(&self, importer: &Path, import: &str) -> Option<PathBuf> {
+        importer
+            .parent()
+            .into_iter()
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(import))
+            .find(|candidate| candidate.is_file())
+            .and_then(|candidate| candidate.canonicalize().ok())
+    }
+}
+
+#[derive(Debug, Default)]
+struct LoadState {
+    definitions: BTreeMap<String, (Doc<IpldType>, Location)>,
+    loaded: BTreeSet<PathBuf>,
+    stack: Vec<PathBuf>,
+}
+
+impl LoadState {
+    fn into_schema(self) -> IpldSchema {
+        IpldSchema(
+            self.definitions
+                .into_iter()
+                .map(|(name, (def, _))| (name, def))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMPORTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test/imports");
+
+    fn loader() -> SchemaLoader {
+        SchemaLoader::new().with_search_path(Path::new(IMPORTS).join("vendor"))
+    }
+
+    #[test]
+    fn load_imports() {
+        let schema = loader()
+            .load(Path::new(IMPORTS).join("root.ipldsch"))
+            .unwrap();
+
+        assert_eq!(
+            schema.type_names().collect::<Vec<_>>(),
+            vec!["Identity", "Message", "PublicKey", "Status"]
+        );
+        assert_eq!(schema.check(), vec![]);
+
+        assert!(matches!(
+            IpldSchema::parse(include_str!("../test/imports/root.ipldsch")),
+            Err(IpldSchemaParseError::Import(import)) if import == "common.ipldsch"
+        ));
+    }
+
+    #[test]
+    fn load_errors() {
+        match SchemaLoader::new().load(Path::new(IMPORTS).join("root.ipldsch")) {
+            Err(LoadError::NotFound { import, location }) => {
+                assert_eq!(import, "shared.ipldsch");
+                assert!(location.path.ends_with("common.ipldsch"));
+                assert_eq!((location.line, location.column), (1, 1));
+            }
+            result => panic!("Expected missing import, got {:?}", result),
+        }
+
+        match loader().load(Path::new(IMPORTS).join("cycle_a.ipldsch")) {
+            Err(LoadError::Cycle(cycle)) => {
+                let names = cycle
+                    .iter()
+                    .map(|path| path.file_name().unwrap().to_str().unwrap())
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    names,
+                    vec!["cycle_a.ipldsch", "cycle_b.ipldsch", "cycle_a.ipldsch"]
+                );
+            }
+            result => panic!("Expected import cycle, got {:?}", result),
+        }
+
+        match loader().load(Path::new(IMPORTS).join("conflict.ipldsch")) {
+            Err(LoadError::Conflict {
+                name,
+                first,
+                second,
+            }) => {
+                assert_eq!(name, "Identity");
+                assert!(first.path.ends_with("common.ipldsch"));
+                assert_eq!((first.line, first.column), (4, 1));
+                assert!(second.path.ends_with("conflict.ipldsch"));
+                assert_eq!((second.line, second.column), (3, 1));
+            }
+            result => panic!("Expected conflict, got {:?}", result),
+        }
+    }
+}
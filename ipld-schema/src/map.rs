@@ -5,12 +5,13 @@ use crate::{
 };
 use pest::iterators::Pairs;
 
+/// A map type, i.e. `{String:Int}`
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct MapType {
-    pub(crate) key: String,
-    pub(crate) value: InlineIpldType,
-    pub(crate) nullable: bool,
-    pub(crate) repr: MapRepresentation,
+pub struct MapType {
+    pub key: String,
+    pub value: InlineIpldType,
+    pub nullable: bool,
+    pub repr: MapRepresentation,
 }
 
 pub(crate) fn parse_map(mut map: Pairs<Rule>) -> Result<MapType, IpldSchemaParseError> {
@@ -44,11 +45,25 @@ pub(crate) fn parse_map(mut map: Pairs<Rule>) -> Result<MapType, IpldSchemaParse
     })
 }
 
+/// How a map is represented in the data model
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) enum MapRepresentation {
+pub enum MapRepresentation {
     Map,
     StringPairs(StringPairs),
     ListPairs,
+    /// The data is read through the advanced data layout with this name
+    Advanced(String),
+}
+
+impl MapRepresentation {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            MapRepresentation::Map => "map",
+            MapRepresentation::StringPairs(_) => "stringpairs",
+            MapRepresentation::ListPairs => "listpairs",
+            MapRepresentation::Advanced(_) => "advanced",
+        }
+    }
 }
 
 fn parse_map_representation(mut repr: Pairs<Rule>) -> MapRepresentation {
@@ -61,19 +76,29 @@ fn parse_map_representation(mut repr: Pairs<Rule>) -> MapRepresentation {
         _ => (),
     }
 
-    // In this case, it can only be a stringpairs
+    // In this case, it can only be a stringpairs or advanced representation
     let mut inner = inner.into_inner();
     let rule = inner.next().unwrap();
     assert!(inner.next().is_none());
 
-    assert_eq!(rule.as_rule(), Rule::stringpairs_repr);
-    MapRepresentation::StringPairs(parse_string_pairs(rule.into_inner()))
+    match rule.as_rule() {
+        Rule::stringpairs_repr => {
+            MapRepresentation::StringPairs(parse_string_pairs(rule.into_inner()))
+        }
+        Rule::advanced_repr => {
+            MapRepresentation::Advanced(rule.into_inner().next().unwrap().as_str().to_string())
+        }
+        rule => unreachable!("unexpected map representation {:?}", rule),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{list::ListType, IpldSchema, IpldType};
+    use crate::{
+        list::{ListRepresentation, ListType},
+        IpldSchema, IpldType,
+    };
     use std::collections::BTreeMap;
 
     #[test]
@@ -123,6 +148,7 @@ mod tests {
                     value: InlineIpldType::List(Box::new(ListType {
                         ty: InlineIpldType::Name("Bool".to_string()),
                         nullable: true,
+                        repr: ListRepresentation::List,
                     })),
                     nullable: false,
                     repr: MapRepresentation::ListPairs,
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use crate::{parse::IpldSchemaParseError, IpldSchema};
+
+/// The info string of fenced code blocks that contain schemas
+const LANGUAGE: &str = "ipldsch";
+
+impl IpldSchema {
+    /// Parses the schema embedded in a Markdown document.
+    ///
+    /// All fenced code blocks tagged `ipldsch` are concatenated in the order they appear.
+    /// Lines and columns of errors refer to the Markdown document.
+    pub fn from_markdown(markdown: &str) -> Result<Self, IpldSchemaParseError> {
+        Self::parse(markdown_source(markdown))
+    }
+}
+
+/// Extracts the schema from the `ipldsch` code blocks of a Markdown document.
+///
+/// Every line outside of those blocks, including the fences, is replaced by an empty line,
+/// so that positions in the result are the same as in the Markdown document.
+pub fn markdown_source(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    // The fence of the code block we are in and whether it contains a schema
+    let mut fence: Option<(&str, bool)> = None;
+
+    for line in markdown.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let ending = &line[content.len()..];
+        let trimmed = content.trim_start_matches(' ');
+        let is_indented = content.len() - trimmed.len() > 3;
+
+        match fence {
+            Some((open, is_schema)) => {
+                let is_close = !is_indented
+                    && trimmed.starts_with(open)
+                    && trimmed.trim_end().chars().all(|c| open.starts_with(c));
+                if is_close {
+                    fence = None;
+                } else if is_schema {
+                    out.push_str(content);
+                }
+            }
+            None if !is_indented => {
+                if let Some(open) = opening_fence(trimmed) {
+                    let info = trimmed[open.len()..].trim();
+                    let language = info.split_whitespace().next().unwrap_or_default();
+                    fence = Some((open, language == LANGUAGE));
+                }
+            }
+            None => (),
+        }
+
+        out.push_str(ending);
+    }
+
+    out
+}
+
+/// Returns the fence if the line opens a fenced code block, i.e. three or more backticks
+fn opening_fence(line: &str) -> Option<&str> {
+    let c = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.len() - line.trim_start_matches(c).len();
+
+    // The info string of backtick fences can not contain backticks
+    let is_fence = len >= 3 && (c == '~' || !line[len..].contains('`'));
+    is_fence.then(|| &line[..len])
+}
+
+/// Markdown files are recognized by their extension
+pub(crate) fn is_markdown(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("md" | "markdown")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pest::error::LineColLocation;
+
+    use super::*;
+
+    #[test]
+    fn from_markdown() {
+        let markdown = "\
+# Messages
+
+A message has a sender:
+
+```ipldsch
+type Message struct {
+  from Identity
+}
+```
+
+```json
+{\"type Ignored\": 1}
+```
+
+Identities are names:
+
+~~~ ipldsch title=\"identity\"
+type Identity string
+~~~
+
+````markdown
+```ipldsch
+type Example int
+```
+````
+";
+        let schema = IpldSchema::from_markdown(markdown).unwrap();
+        assert_eq!(
+            schema.type_names().collect::<Vec<_>>(),
+            vec!["Identity", "Message"]
+        );
+        assert_eq!(
+            markdown_source(markdown).lines().count(),
+            markdown.lines().count()
+        );
+
+        let markdown = "\
+# Broken
+
+```ipldsch
+type Message struct {
+  from
+}
+```
+";
+        match IpldSchema::from_markdown(markdown) {
+            Err(IpldSchemaParseError::Syntax(err)) => {
+                assert!(matches!(err.line_col, LineColLocation::Pos((5, _))))
+            }
+            result => panic!("Expected a syntax error, got {:?}", result),
+        }
+    }
+}
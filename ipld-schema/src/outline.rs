@@ -0,0 +1,248 @@
+use std::ops::Range;
+
+use pest::{iterators::Pair, Parser};
+
+use crate::{
+    comment::parse_comment,
+    parse::{parse_file, IpldSchemaParseError},
+    Rule, SchemaParser,
+};
+
+/// The source positions of the definitions and type references in a schema file.
+///
+/// This is meant for editor tooling, all spans are byte offsets into the parsed input.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Outline {
+    pub imports: Vec<Symbol>,
+    /// The type definitions, with their fields or members as children
+    pub definitions: Vec<Symbol>,
+    /// Every use of a type name, i.e. field types, link targets or copied types
+    pub references: Vec<Reference>,
+}
+
+/// A named item in a schema file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// The kind of the symbol, i.e. `struct`, `field`, `member` or `import`
+    pub kind: &'static str,
+    pub doc: Option<String>,
+    /// The span of the whole item
+    pub span: Range<usize>,
+    /// The span of the name of the item
+    pub name_span: Range<usize>,
+    pub children: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub name: String,
+    pub span: Range<usize>,
+}
+
+impl Outline {
+    /// Finds the definition of a type by name
+    pub fn definition(&self, name: &str) -> Option<&Symbol> {
+        self.definitions.iter().find(|symbol| symbol.name == name)
+    }
+
+    /// Returns the name of the type defined or referenced at `offset`
+    pub fn name_at(&self, offset: usize) -> Option<&str> {
+        let contains = |span: &Range<usize>| span.start <= offset && offset <= span.end;
+
+        self.definitions
+            .iter()
+            .find(|symbol| contains(&symbol.name_span))
+            .map(|symbol| symbol.name.as_str())
+            .or_else(|| {
+                self.references
+                    .iter()
+                    .find(|reference| contains(&reference.span))
+                    .map(|reference| reference.name.as_str())
+            })
+    }
+}
+
+/// Parses a schema file and collects the positions of its definitions and references
+pub fn outline(input: &str) -> Result<Outline, IpldSchemaParseError> {
+    let parsed = parse_file(input)?;
+    let file = SchemaParser::parse(Rule::file, input)
+        .map_err(|err| IpldSchemaParseError::Syntax(Box::new(err)))?
+        .next()
+        .unwrap();
+
+    let mut outline = Outline::default();
+    let types = file
+        .into_inner()
+        .filter(|pair| matches!(pair.as_rule(), Rule::r#type | Rule::import));
+    let mut definitions = parsed.definitions.into_iter();
+
+    for pair in types {
+        let span = span(&pair);
+        let mut inner = pair.into_inner();
+        let name = inner.next().unwrap();
+
+        if name.as_rule() == Rule::import_path {
+            outline.imports.push(Symbol {
+                name: name.as_str().to_string(),
+                kind: "import",
+                doc: None,
+                span,
+                name_span: self::span(&name),
+                children: vec![],
+            });
+            continue;
+        }
+
+        let definition = definitions.next().unwrap();
+        let mut children = vec![];
+        for def in inner {
+            collect(def, &mut children, &mut outline.references);
+        }
+
+        outline.definitions.push(Symbol {
+            name: definition.name,
+            kind: definition.def.ty.type_kind(),
+            doc: definition.def.doc,
+            span,
+            name_span: self::span(&name),
+            children,
+        });
+    }
+
+    Ok(outline)
+}
+
+fn span(pair: &Pair<Rule>) -> Range<usize> {
+    pair.as_span().start()..pair.as_span().end()
+}
+
+fn collect(pair: Pair<Rule>, children: &mut Vec<Symbol>, references: &mut Vec<Reference>) {
+    match pair.as_rule() {
+        Rule::type_name => references.push(Reference {
+            name: pair.as_str().to_string(),
+            span: span(&pair),
+        }),
+        // The names of advanced layouts are not types
+        Rule::advanced_repr => (),
+        Rule::struct_field | Rule::enum_field => {
+            let kind = match pair.as_rule() {
+                Rule::struct_field => "field",
+                _ => "member",
+            };
+            let mut doc = None;
+            let mut name = None;
+            let mut inner = vec![];
+
+            for child in pair.clone().into_inner() {
+                match child.as_rule() {
+                    Rule::comment => doc = Some(parse_comment(child.into_inner())),
+                    Rule::struct_field_name | Rule::type_name if name.is_none() => {
+                        name = Some(child)
+                    }
+                    _ => inner.push(child),
+                }
+            }
+
+            let name = name.unwrap();
+            // The span of the whole rule includes leading whitespace and comments
+            let start = name.as_span().start();
+            children.push(Symbol {
+                name: name.as_str().to_string(),
+                kind,
+                doc,
+                span: start..pair.as_span().end(),
+                name_span: span(&name),
+                children: vec![],
+            });
+
+            for child in inner {
+                collect(child, children, references);
+            }
+        }
+        _ => {
+            for child in pair.into_inner() {
+                collect(child, children, references);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn outline() {
+        let input = include_str!("../test/message.ipldsch");
+        let outline = super::outline(input).unwrap();
+
+        let message = outline.definition("Message").unwrap();
+        assert_eq!(message.kind, "struct");
+        assert_eq!(message.doc.as_deref(), Some("A message"));
+        assert_eq!(&input[message.name_span.clone()], "Message");
+        assert_eq!(
+            message
+                .children
+                .iter()
+                .map(|field| (field.name.as_str(), field.doc.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("from", Some("Who sent it")),
+                ("to", None),
+                ("status", None),
+                ("count", None),
+                ("labels", None),
+                ("data", None)
+            ]
+        );
+        assert!(input[message.children[0].span.clone()].starts_with("from &Identity"));
+
+        let status = outline.definition("Status").unwrap();
+        assert_eq!(status.kind, "enum");
+        assert_eq!(
+            status
+                .children
+                .iter()
+                .map(|member| (member.name.as_str(), member.doc.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![("Active", Some("Still there")), ("Gone", None)]
+        );
+        assert_eq!(outline.definition("Identity").unwrap().kind, "copy");
+
+        let offset = input.find("&Identity").unwrap() + 3;
+        assert_eq!(outline.name_at(offset), Some("Identity"));
+        assert_eq!(outline.name_at(0), None);
+    }
+
+    #[test]
+    fn nested_references() {
+        let input = "\
+import \"common.ipldsch\"
+
+type Message struct {
+  to {String:[{Name:&Identity}]}
+}
+
+type Identity = Name
+";
+        let outline = super::outline(input).unwrap();
+
+        assert_eq!(outline.imports[0].name, "common.ipldsch");
+        assert_eq!(outline.imports[0].kind, "import");
+        assert_eq!(
+            outline
+                .references
+                .iter()
+                .map(|reference| (reference.name.as_str(), &input[reference.span.clone()]))
+                .collect::<Vec<_>>(),
+            vec![
+                ("String", "String"),
+                ("Name", "Name"),
+                ("Identity", "Identity"),
+                ("Name", "Name"),
+            ]
+        );
+
+        let offset = input.find("Identity =").unwrap();
+        assert_eq!(outline.name_at(offset), Some("Identity"));
+    }
+}
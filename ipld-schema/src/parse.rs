@@ -1,4 +1,7 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    ops::Range,
+};
 
 use itertools::Itertools;
 use pest::{
@@ -12,53 +15,216 @@ use crate::{
     enumerate::{parse_enum, InvalidEnum},
     list::parse_list,
     map::parse_map,
+    representation::{parse_advanced, BytesRepresentation},
     structural::parse_struct,
+    union::{parse_union, InvalidUnion},
     unit::parse_unit,
     Doc, InlineIpldType, IpldSchema, IpldType, Rule, SchemaParser,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Error, Default)]
 pub enum IpldSchemaParseError {
+    #[error("{0}")]
+    Syntax(Box<pest::error::Error<Rule>>),
+    #[error("Type \"{0}\" is defined more than once")]
+    DuplicateType(String),
+    #[error("Import of \"{0}\" can only be resolved by a SchemaLoader")]
+    Import(String),
     #[error("{0}")]
     Enum(InvalidEnum),
+    #[error("{0}")]
+    Union(InvalidUnion),
     #[default]
     #[error("Unknown error")]
     Unknown,
 }
 
 impl IpldSchema {
+    /// Parses a schema from its DSL form.
+    ///
+    /// Schemas containing `import` directives need to be loaded with a [`SchemaLoader`](crate::SchemaLoader).
     pub fn parse(input: impl AsRef<str>) -> Result<Self, IpldSchemaParseError> {
-        // TODO: Error output
-        let mut outer = SchemaParser::parse(Rule::file, input.as_ref()).unwrap();
-        let defs: Pair<_> = outer.next().unwrap();
-        assert!(outer.next().is_none());
+        let parsed = parse_file(input.as_ref())?;
+
+        if let Some(import) = parsed.imports.into_iter().next() {
+            return Err(IpldSchemaParseError::Import(import.path));
+        }
+
+        let mut definitions = BTreeMap::new();
+        for definition in parsed.definitions {
+            if definitions.contains_key(&definition.name) {
+                return Err(IpldSchemaParseError::DuplicateType(definition.name));
+            }
+            definitions.insert(definition.name, definition.def);
+        }
 
-        dbg!(&defs);
+        Ok(Self(definitions))
+    }
+}
 
+impl IpldSchema {
+    /// Parses a schema, recovering from errors.
+    ///
+    /// The input is split before every `type` definition and after every closing brace at the
+    /// start of a line, and each part is parsed on its own. Returns the definitions of all parts
+    /// that parsed, together with every error. Syntax errors point into the whole input.
+    pub fn parse_recovering(input: impl AsRef<str>) -> (Self, Vec<IpldSchemaParseError>) {
+        let input = input.as_ref();
         let mut definitions = BTreeMap::new();
+        let mut errors = vec![];
+
+        for segment in segments(input) {
+            let offset = segment.start;
+            let parsed = match parse_file(&input[segment]) {
+                Ok(parsed) => parsed,
+                Err(IpldSchemaParseError::Syntax(err)) => {
+                    errors.push(IpldSchemaParseError::Syntax(Box::new(relocate(
+                        *err, input, offset,
+                    ))));
+                    continue;
+                }
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
 
-        let mut current_comment = None;
-        for pair in defs.into_inner() {
-            match pair.as_rule() {
-                Rule::comment => current_comment = Some(parse_comment(pair.into_inner())),
-                Rule::r#type => {
-                    let (name, ty) = parse_type(pair.into_inner())?;
-
-                    definitions.insert(
-                        name,
-                        Doc {
-                            doc: current_comment.take(),
-                            ty,
-                        },
-                    );
+            for import in parsed.imports {
+                errors.push(IpldSchemaParseError::Import(import.path));
+            }
+            for definition in parsed.definitions {
+                match definitions.entry(definition.name) {
+                    Entry::Occupied(entry) => {
+                        errors.push(IpldSchemaParseError::DuplicateType(entry.key().clone()))
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(definition.def);
+                    }
                 }
-                Rule::EOI => (),
-                _ => todo!(),
             }
         }
 
-        Ok(Self(definitions))
+        (Self(definitions), errors)
+    }
+}
+
+/// Splits the input into parts, that can be parsed independently
+fn segments(input: &str) -> Vec<Range<usize>> {
+    let mut boundaries = vec![0];
+    // The start of the comment lines directly before the current line
+    let mut comment_start = None;
+    let mut offset = 0;
+
+    for line in input.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        if line.starts_with('#') {
+            comment_start.get_or_insert(start);
+        } else if line.starts_with("type ") || line.starts_with("import ") {
+            boundaries.push(comment_start.take().unwrap_or(start));
+        } else if line.starts_with('}') {
+            boundaries.push(offset);
+            comment_start = None;
+        } else if !line.trim().is_empty() {
+            comment_start = None;
+        }
+    }
+
+    boundaries.push(input.len());
+    boundaries.dedup();
+    boundaries
+        .windows(2)
+        .map(|window| window[0]..window[1])
+        .collect()
+}
+
+/// Moves an error of a part of the input to its position in the whole input
+fn relocate(err: pest::error::Error<Rule>, input: &str, offset: usize) -> pest::error::Error<Rule> {
+    let position = |pos: usize| pest::Position::new(input, offset + pos).unwrap();
+
+    match err.location {
+        pest::error::InputLocation::Pos(pos) => {
+            pest::error::Error::new_from_pos(err.variant, position(pos))
+        }
+        pest::error::InputLocation::Span((start, end)) => {
+            pest::error::Error::new_from_span(err.variant, position(start).span(&position(end)))
+        }
+    }
+}
+
+/// The contents of a single schema file, in the order they appear in the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ParsedFile {
+    pub(crate) imports: Vec<Import>,
+    pub(crate) definitions: Vec<Definition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Import {
+    pub(crate) path: String,
+    pub(crate) span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Definition {
+    pub(crate) name: String,
+    pub(crate) def: Doc<IpldType>,
+    pub(crate) span: Range<usize>,
+}
+
+pub(crate) fn parse_file(input: &str) -> Result<ParsedFile, IpldSchemaParseError> {
+    let mut outer = SchemaParser::parse(Rule::file, input)
+        .map_err(|err| IpldSchemaParseError::Syntax(Box::new(err)))?;
+    let defs: Pair<_> = outer.next().unwrap();
+    assert!(outer.next().is_none());
+
+    let mut imports = vec![];
+    let mut definitions = vec![];
+
+    let mut current_comment = None;
+    for pair in defs.into_inner() {
+        let span = pair.as_span().start()..pair.as_span().end();
+
+        match pair.as_rule() {
+            Rule::comment => current_comment = Some(parse_comment(pair.into_inner())),
+            Rule::import => {
+                current_comment = None;
+                imports.push(Import {
+                    path: parse_import(pair.into_inner()),
+                    span,
+                });
+            }
+            Rule::r#type => {
+                let (name, ty) = parse_type(pair.into_inner())?;
+                definitions.push(Definition {
+                    name,
+                    def: Doc {
+                        doc: current_comment.take(),
+                        ty,
+                    },
+                    span,
+                });
+            }
+            Rule::EOI => (),
+            rule => unreachable!(
+                "the file rule only contains imports, types and comments, not {:?}",
+                rule
+            ),
+        }
     }
+
+    Ok(ParsedFile {
+        imports,
+        definitions,
+    })
+}
+
+fn parse_import(mut import: Pairs<Rule>) -> String {
+    let inner = import.next().unwrap();
+    assert!(import.next().is_none());
+    assert_eq!(inner.as_rule(), Rule::import_path);
+    inner.as_str().to_string()
 }
 
 fn parse_type(def: Pairs<Rule>) -> Result<(String, IpldType), IpldSchemaParseError> {
@@ -76,7 +242,6 @@ fn parse_type(def: Pairs<Rule>) -> Result<(String, IpldType), IpldSchemaParseErr
         "int" => return Ok((name, IpldType::Int)),
         "float" => return Ok((name, IpldType::Float)),
         "any" => return Ok((name, IpldType::Any)),
-        "bytes" => return Ok((name, IpldType::Bytes)),
         _ => (),
     }
 
@@ -85,13 +250,28 @@ fn parse_type(def: Pairs<Rule>) -> Result<(String, IpldType), IpldSchemaParseErr
     assert!(outer.next().is_none());
 
     match def.as_rule() {
+        Rule::bytes_def => {
+            let repr = match def.into_inner().next() {
+                Some(repr) => BytesRepresentation::Advanced(parse_advanced(repr.into_inner())),
+                None => BytesRepresentation::Bytes,
+            };
+            Ok((name, IpldType::Bytes(repr)))
+        }
         Rule::list_def => Ok((name, IpldType::List(parse_list(def.into_inner())?))),
         Rule::map_def => Ok((name, IpldType::Map(parse_map(def.into_inner())?))),
         Rule::struct_def => Ok((name, IpldType::Struct(parse_struct(def.into_inner())?))),
         Rule::enum_def => Ok((name, IpldType::Enum(parse_enum(def.into_inner())?))),
-        Rule::link_def => Ok((name, IpldType::Link(parse_link(def.into_inner())?))),
+        Rule::union_def => Ok((name, IpldType::Union(parse_union(def.into_inner())?))),
+        Rule::link_def => Ok((
+            name,
+            IpldType::Link(parse_type_reference(def.into_inner())?),
+        )),
         Rule::unit_def => Ok((name, parse_unit(def.into_inner())?)),
-        _ => todo!(),
+        Rule::copy_def => Ok((
+            name,
+            IpldType::Copy(parse_type_reference(def.into_inner())?),
+        )),
+        rule => unreachable!("primitives are matched above, type_def has no {:?}", rule),
     }
 }
 
@@ -109,14 +289,18 @@ pub(crate) fn parse_inline_type(
         Rule::map_def => Ok(InlineIpldType::Map(Box::new(parse_map(
             inner.into_inner(),
         )?))),
-        Rule::link_def => Ok(InlineIpldType::Link(parse_link(inner.into_inner())?)),
-        _ => panic!(),
+        Rule::link_def => Ok(InlineIpldType::Link(parse_type_reference(
+            inner.into_inner(),
+        )?)),
+        rule => unreachable!("inline_type_def has no {:?}", rule),
     }
 }
 
-fn parse_link(mut link: Pairs<Rule>) -> Result<String, IpldSchemaParseError> {
-    let inner = link.next().unwrap();
-    assert!(link.next().is_none());
+pub(crate) fn parse_type_reference(
+    mut reference: Pairs<Rule>,
+) -> Result<String, IpldSchemaParseError> {
+    let inner = reference.next().unwrap();
+    assert!(reference.next().is_none());
     assert_eq!(inner.as_rule(), Rule::type_name);
     Ok(inner.as_str().to_string())
 }
@@ -164,4 +348,52 @@ mod tests {
 
         assert_eq!(parsed_schema, expected_schema);
     }
+
+    #[test]
+    fn recover_errors() {
+        let input = "\
+type Broken struct {
+  a optional nullable Int
+}
+
+# Still parsed
+type Fine struct {
+  a Int
+}
+garbage
+
+type Unclosed struct {
+  a Int
+
+type Fine bool
+type Last [String]
+";
+        let (schema, errors) = IpldSchema::parse_recovering(input);
+
+        assert_eq!(
+            schema.type_names().collect::<Vec<_>>(),
+            vec!["Fine", "Last"]
+        );
+        assert_eq!(schema.0["Fine"].doc.as_deref(), Some("Still parsed"));
+
+        let positions = errors
+            .iter()
+            .map(|err| match err {
+                IpldSchemaParseError::Syntax(err) => match err.line_col {
+                    pest::error::LineColLocation::Pos(pos) => format!("{:?}", pos),
+                    _ => panic!("Expected a position, got {:?}", err),
+                },
+                err => err.to_string(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            vec![
+                "(2, 14)",
+                "(9, 1)",
+                "(14, 1)",
+                "Type \"Fine\" is defined more than once"
+            ]
+        );
+    }
 }
@@ -0,0 +1,223 @@
+use std::borrow::Cow;
+
+use ipld_core::ipld::Ipld;
+use thiserror::Error;
+
+use crate::{
+    adl::{builtin, AdlRegistry},
+    store::BlockStore,
+    DataPath, InlineIpldType, IpldSchema, IpldType,
+};
+
+/// A value reached while resolving a path
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathStep<'d> {
+    /// The segment of the path, as written by the caller
+    pub segment: String,
+    /// The name of the type of the value, inline types are printed, i.e. `[String]`
+    pub ty: String,
+    pub value: Cow<'d, Ipld>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PathError {
+    #[error("Type \"{0}\" is not defined")]
+    UnknownType(String),
+    #[error("{ty} at {path} has no field \"{segment}\"")]
+    UnknownSegment {
+        path: DataPath,
+        ty: String,
+        segment: String,
+    },
+    #[error("\"{segment}\" of {ty} at {path} is not present in the data")]
+    Missing {
+        path: DataPath,
+        ty: String,
+        segment: String,
+    },
+    #[error("Can not resolve \"{segment}\" in {ty} at {path}")]
+    NotTraversable {
+        path: DataPath,
+        ty: String,
+        segment: String,
+    },
+}
+
+impl IpldSchema {
+    /// Walks `data` of type `root` along a `/` separated path of schema field names.
+    ///
+    /// Segments are struct field names, map keys or list indices. They are mapped to the
+    /// representation of each type, i.e. renamed fields or `stringpairs` entries.
+    /// Returns every value on the way, the last one is the value at the end of the path.
+    /// Only the layouts of this crate are known, see [`IpldSchema::resolve_path_with`].
+    pub fn resolve_path<'d>(
+        &self,
+        root: &str,
+        data: &'d Ipld,
+        path: &str,
+    ) -> Result<Vec<PathStep<'d>>, PathError> {
+        self.resolve_path_with(root, data, path, builtin(), None)
+    }
+
+    /// Like [`IpldSchema::resolve_path`], but walks data in advanced layouts through the
+    /// logical view of the layouts in `adls`, which may load blocks from `store`
+    pub fn resolve_path_with<'d>(
+        &self,
+        root: &str,
+        data: &'d Ipld,
+        path: &str,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<Vec<PathStep<'d>>, PathError> {
+        if self.resolve(root).is_none() {
+            return Err(PathError::UnknownType(root.to_string()));
+        }
+
+        let mut ty = InlineIpldType::Name(root.to_string());
+        let mut value = Cow::Borrowed(data);
+        let mut resolved = DataPath::default();
+        let mut steps = vec![];
+
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            let (child_ty, child) =
+                self.resolve_segment(&ty, &value, segment, &resolved, adls, store)?;
+
+            steps.push(PathStep {
+                segment: segment.to_string(),
+                ty: child_ty.to_string(),
+                value: child.clone(),
+            });
+            resolved.push(segment);
+            ty = child_ty;
+            value = child;
+        }
+
+        Ok(steps)
+    }
+
+    fn resolve_segment<'d>(
+        &self,
+        ty: &InlineIpldType,
+        data: &Cow<'d, Ipld>,
+        segment: &str,
+        path: &DataPath,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<(InlineIpldType, Cow<'d, Ipld>), PathError> {
+        let resolved = self
+            .resolve_inline(ty)
+            .ok_or_else(|| PathError::UnknownType(ty.to_string()))?;
+
+        // Only segments the type can have are looked up in the data
+        let defined = match resolved.as_ref() {
+            IpldType::Struct(stru) => stru.fields.iter().any(|field| field.key == segment),
+            IpldType::List(_) => segment.parse::<usize>().is_ok(),
+            // Unions are transparent, their members decide
+            IpldType::Map(_) | IpldType::Union(_) | IpldType::Any => true,
+            _ => {
+                return Err(PathError::NotTraversable {
+                    path: path.clone(),
+                    ty: ty.to_string(),
+                    segment: segment.to_string(),
+                })
+            }
+        };
+        if !defined {
+            return Err(PathError::UnknownSegment {
+                path: path.clone(),
+                ty: ty.to_string(),
+                segment: segment.to_string(),
+            });
+        }
+
+        let child = match data {
+            Cow::Borrowed(data) => self.child(ty, data, segment, adls, store),
+            // Values of stringpairs entries are owned strings, which have no children
+            Cow::Owned(data) => self
+                .child(ty, data, segment, adls, store)
+                .map(|(ty, value)| (ty, Cow::Owned(value.into_owned()))),
+        };
+
+        child.ok_or_else(|| PathError::Missing {
+            path: path.clone(),
+            ty: ty.to_string(),
+            segment: segment.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ipld_core::ipld;
+
+    use super::*;
+
+    #[test]
+    fn resolve_path() {
+        let schema = IpldSchema::parse(
+            "\
+type Root struct {
+  items [Item] (rename \"i\")
+  labels {String:String} representation stringpairs {
+    innerDelim \"=\"
+    entryDelim \",\"
+  }
+}
+
+type Item struct {
+  name optional String (rename \"n\")
+  extra Any
+}
+",
+        )
+        .unwrap();
+        let data = ipld!({
+            "i": [{"n": "first", "extra": {"deep": [1, 2]}}, {"extra": null}],
+            "labels": "a=1,b=2",
+        });
+
+        let steps = schema
+            .resolve_path("Root", &data, "items/0/extra/deep/1")
+            .unwrap();
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| (step.segment.as_str(), step.ty.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("items", "[Item]"),
+                ("0", "Item"),
+                ("extra", "Any"),
+                ("deep", "Any"),
+                ("1", "Any")
+            ]
+        );
+        assert_eq!(steps.last().unwrap().value.as_ref(), &ipld!(2));
+
+        let steps = schema.resolve_path("Root", &data, "/labels/b").unwrap();
+        assert_eq!(steps[1].value.as_ref(), &ipld!("2"));
+        assert_eq!(steps[1].ty, "String");
+
+        assert_eq!(
+            schema
+                .resolve_path("Root", &data, "items/0/nme")
+                .unwrap_err()
+                .to_string(),
+            "Item at items/0 has no field \"nme\""
+        );
+        assert_eq!(
+            schema
+                .resolve_path("Root", &data, "items/1/name")
+                .unwrap_err()
+                .to_string(),
+            "\"name\" of Item at items/1 is not present in the data"
+        );
+        assert_eq!(
+            schema
+                .resolve_path("Root", &data, "items/0/name/x")
+                .unwrap_err()
+                .to_string(),
+            "Can not resolve \"x\" in String at items/0/name"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter, Write},
+};
+
+use crate::{
+    enumerate::{EnumField, EnumMemberTag, EnumRepresentation, EnumType},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    parse::{parse_file, IpldSchemaParseError},
+    representation::{BytesRepresentation, StringPairs},
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionMember, UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    Doc, InlineIpldType, IpldSchema, IpldType,
+};
+
+const INDENT: &str = "  ";
+
+/// Prints the schema in its canonical DSL form.
+///
+/// Types are printed in alphabetical order and default representations are omitted.
+/// The output can be parsed again using [`IpldSchema::parse`].
+impl Display for IpldSchema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (idx, (name, def)) in self.0.iter().enumerate() {
+            if idx != 0 {
+                writeln!(f)?;
+            }
+
+            print_definition(f, name, def)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints a single type definition including its documentation
+pub(crate) fn definition_to_string(name: &str, def: &Doc<IpldType>) -> String {
+    struct Definition<'a>(&'a str, &'a Doc<IpldType>);
+
+    impl Display for Definition<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            print_definition(f, self.0, self.1)
+        }
+    }
+
+    Definition(name, def).to_string()
+}
+
+/// Formats a single schema file into its canonical DSL form.
+///
+/// Unlike printing an [`IpldSchema`], this keeps the `import` directives of the file,
+/// which are printed before all type definitions.
+pub fn format(input: &str) -> Result<String, IpldSchemaParseError> {
+    let parsed = parse_file(input)?;
+
+    let mut out = String::new();
+    for import in &parsed.imports {
+        let _ = writeln!(out, "import \"{}\"", import.path);
+    }
+
+    let mut definitions = BTreeMap::new();
+    for definition in parsed.definitions {
+        if definitions.contains_key(&definition.name) {
+            return Err(IpldSchemaParseError::DuplicateType(definition.name));
+        }
+        definitions.insert(definition.name, definition.def);
+    }
+
+    if !out.is_empty() && !definitions.is_empty() {
+        out.push('\n');
+    }
+    let _ = write!(out, "{}", IpldSchema(definitions));
+
+    Ok(out)
+}
+
+impl Display for IpldType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        print_type(f, self)
+    }
+}
+
+impl Display for InlineIpldType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        print_inline_type(f, self)
+    }
+}
+
+fn print_doc(f: &mut Formatter<'_>, doc: Option<&str>, indent: &str) -> fmt::Result {
+    if let Some(doc) = doc {
+        for line in doc.lines() {
+            writeln!(f, "{}# {}", indent, line)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_definition(f: &mut Formatter<'_>, name: &str, def: &Doc<IpldType>) -> fmt::Result {
+    print_doc(f, def.doc.as_deref(), "")?;
+    write!(f, "type {} ", name)?;
+    print_type(f, &def.ty)?;
+    writeln!(f)
+}
+
+fn print_type(f: &mut Formatter<'_>, ty: &IpldType) -> fmt::Result {
+    match ty {
+        IpldType::Bool => f.write_str("bool"),
+        IpldType::String => f.write_str("string"),
+        IpldType::Bytes(BytesRepresentation::Bytes) => f.write_str("bytes"),
+        IpldType::Bytes(BytesRepresentation::Advanced(name)) => {
+            write!(f, "bytes representation advanced {}", name)
+        }
+        IpldType::Int => f.write_str("int"),
+        IpldType::Float => f.write_str("float"),
+        IpldType::Any => f.write_str("any"),
+        IpldType::List(list) => print_list(f, list),
+        IpldType::Map(map) => print_map(f, map),
+        IpldType::Link(link) => write!(f, "&{}", link),
+        IpldType::Union(union) => print_union(f, union),
+        IpldType::Struct(stru) => print_struct(f, stru),
+        IpldType::Enum(enu) => print_enum(f, enu),
+        IpldType::Unit(unit) => print_unit(f, unit),
+        IpldType::Copy(from) => write!(f, "= {}", from),
+    }
+}
+
+fn print_inline_type(f: &mut Formatter<'_>, ty: &InlineIpldType) -> fmt::Result {
+    match ty {
+        InlineIpldType::Name(name) => f.write_str(name),
+        InlineIpldType::List(list) => print_list(f, list),
+        InlineIpldType::Map(map) => print_map(f, map),
+        InlineIpldType::Link(link) => write!(f, "&{}", link),
+    }
+}
+
+fn print_list(f: &mut Formatter<'_>, list: &ListType) -> fmt::Result {
+    f.write_char('[')?;
+    if list.nullable {
+        f.write_str("nullable ")?;
+    }
+    print_inline_type(f, &list.ty)?;
+    f.write_char(']')?;
+
+    match &list.repr {
+        ListRepresentation::List => Ok(()),
+        ListRepresentation::Advanced(name) => write!(f, " representation advanced {}", name),
+    }
+}
+
+fn print_map(f: &mut Formatter<'_>, map: &MapType) -> fmt::Result {
+    write!(f, "{{{}:", map.key)?;
+    if map.nullable {
+        f.write_str("nullable ")?;
+    }
+    print_inline_type(f, &map.value)?;
+    f.write_char('}')?;
+
+    match &map.repr {
+        MapRepresentation::Map => Ok(()),
+        MapRepresentation::ListPairs => f.write_str(" representation listpairs"),
+        MapRepresentation::Advanced(name) => write!(f, " representation advanced {}", name),
+        MapRepresentation::StringPairs(pairs) => {
+            f.write_str(" representation ")?;
+            print_string_pairs(f, pairs)
+        }
+    }
+}
+
+fn print_struct(f: &mut Formatter<'_>, stru: &StructType) -> fmt::Result {
+    f.write_str("struct {")?;
+    if !stru.fields.is_empty() {
+        writeln!(f)?;
+    }
+
+    for field in &stru.fields {
+        print_doc(f, field.doc.as_deref(), INDENT)?;
+        writeln!(f, "{}{}", INDENT, field)?;
+    }
+    f.write_char('}')?;
+
+    match &stru.repr {
+        StructRepresentation::Map => Ok(()),
+        StructRepresentation::ListPairs => f.write_str(" representation listpairs"),
+        StructRepresentation::Tuple => f.write_str(" representation tuple"),
+        StructRepresentation::StringPairs(pairs) => {
+            f.write_str(" representation ")?;
+            print_string_pairs(f, pairs)
+        }
+    }
+}
+
+/// Prints a field without its documentation, i.e. `foo optional Int (rename "f")`
+impl Display for StructField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.key)?;
+        if self.nullable {
+            f.write_str("nullable ")?;
+        }
+        if self.optional {
+            f.write_str("optional ")?;
+        }
+        print_inline_type(f, &self.value)?;
+
+        match (&self.rename, &self.implicit) {
+            (None, None) => Ok(()),
+            (Some(rename), None) => write!(f, " (rename \"{}\")", rename),
+            (None, Some(implicit)) => write!(f, " (implicit \"{}\")", implicit),
+            (Some(rename), Some(implicit)) => {
+                write!(f, " (rename \"{}\" implicit \"{}\")", rename, implicit)
+            }
+        }
+    }
+}
+
+fn print_enum(f: &mut Formatter<'_>, enu: &EnumType) -> fmt::Result {
+    writeln!(f, "enum {{")?;
+    for member in &enu.members {
+        print_doc(f, member.doc.as_deref(), INDENT)?;
+        writeln!(f, "{}{}", INDENT, member)?;
+    }
+    f.write_char('}')?;
+
+    match enu.repr {
+        EnumRepresentation::String => Ok(()),
+        EnumRepresentation::Int => f.write_str(" representation int"),
+    }
+}
+
+/// Prints a member without its documentation, i.e. `| Foo ("f")`
+impl Display for EnumField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "| {}", self.name)?;
+        match &self.tag {
+            EnumMemberTag::String(tag) if *tag == self.name => Ok(()),
+            EnumMemberTag::String(tag) => write!(f, " (\"{}\")", tag),
+            EnumMemberTag::Int(tag) => write!(f, " (\"{}\")", tag),
+        }
+    }
+}
+
+fn print_union(f: &mut Formatter<'_>, union: &UnionType) -> fmt::Result {
+    writeln!(f, "union {{")?;
+    for member in &union.members {
+        print_doc(f, member.doc.as_deref(), INDENT)?;
+        writeln!(
+            f,
+            "{}{}",
+            INDENT,
+            union_member_to_string(member, &union.repr)
+        )?;
+    }
+    f.write_str("} representation ")?;
+
+    match &union.repr {
+        UnionRepresentation::Keyed => f.write_str("keyed"),
+        UnionRepresentation::Kinded => f.write_str("kinded"),
+        UnionRepresentation::Inline { discriminant_key } => {
+            writeln!(f, "inline {{")?;
+            writeln!(f, "{}discriminantKey \"{}\"", INDENT, discriminant_key)?;
+            f.write_char('}')
+        }
+        UnionRepresentation::Envelope {
+            discriminant_key,
+            content_key,
+        } => {
+            writeln!(f, "envelope {{")?;
+            writeln!(f, "{}discriminantKey \"{}\"", INDENT, discriminant_key)?;
+            writeln!(f, "{}contentKey \"{}\"", INDENT, content_key)?;
+            f.write_char('}')
+        }
+    }
+}
+
+/// Prints a member without its documentation, i.e. `| &Foo "foo"` or `| Foo map`
+pub(crate) fn union_member_to_string(member: &UnionMember, repr: &UnionRepresentation) -> String {
+    match repr {
+        UnionRepresentation::Kinded => format!("| {} {}", member.ty, member.discriminant),
+        _ => format!("| {} \"{}\"", member.ty, member.discriminant),
+    }
+}
+
+fn print_unit(f: &mut Formatter<'_>, unit: &UnitRepresentation) -> fmt::Result {
+    let repr = match unit {
+        UnitRepresentation::Null => "null",
+        UnitRepresentation::True => "true",
+        UnitRepresentation::False => "false",
+        UnitRepresentation::EmptyMap => "emptymap",
+    };
+
+    write!(f, "unit representation {}", repr)
+}
+
+fn print_string_pairs(f: &mut Formatter<'_>, pairs: &StringPairs) -> fmt::Result {
+    writeln!(f, "stringpairs {{")?;
+    writeln!(f, "{}innerDelim \"{}\"", INDENT, pairs.inner_delim)?;
+    writeln!(f, "{}entryDelim \"{}\"", INDENT, pairs.entry_delim)?;
+    f.write_char('}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format;
+    use crate::IpldSchema;
+
+    #[test]
+    fn print_roundtrip() {
+        let files = [
+            include_str!("../test/primitive.ipldsch"),
+            include_str!("../test/struct.ipldsch"),
+            include_str!("../test/enums.ipldsch"),
+            include_str!("../test/maps.ipldsch"),
+            include_str!("../test/list.ipldsch"),
+            include_str!("../test/example.ipldsch"),
+            include_str!("../test/union.ipldsch"),
+        ];
+
+        for file in files {
+            let parsed_schema = IpldSchema::parse(file).unwrap();
+            let printed = parsed_schema.to_string();
+            let reparsed_schema = IpldSchema::parse(&printed).unwrap();
+
+            assert_eq!(parsed_schema, reparsed_schema);
+            assert_eq!(printed, reparsed_schema.to_string());
+        }
+
+        let file = include_str!("../test/imports/common.ipldsch");
+        assert_eq!(
+            format(file).unwrap(),
+            "import \"shared.ipldsch\"\n\n# The identity of a participant\ntype Identity struct {\n  name String\n  key PublicKey\n}\n"
+        );
+    }
+}
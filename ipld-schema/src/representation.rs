@@ -24,21 +24,40 @@ pub(crate) enum StructRepresentation {
     Advanced(String),
 }
 
-// TODO: Move to list file
+/// How bytes are represented in the data model
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) enum ListRepresentation {
-    List,
+pub enum BytesRepresentation {
+    Bytes,
+    /// The data is read through the advanced data layout with this name
     Advanced(String),
 }
 
+impl BytesRepresentation {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            BytesRepresentation::Bytes => "bytes",
+            BytesRepresentation::Advanced(_) => "advanced",
+        }
+    }
+}
+
 // TODO: Parser
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct StringJoin(String);
 
+/// The delimiters of the `stringpairs` representation
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct StringPairs {
-    pub(crate) inner_delim: String,
-    pub(crate) entry_delim: String,
+pub struct StringPairs {
+    pub inner_delim: String,
+    pub entry_delim: String,
+}
+
+/// Parses the `representation advanced Name` of lists and bytes to the name of the layout
+pub(crate) fn parse_advanced(mut repr: Pairs<Rule>) -> String {
+    let advanced = repr.next().unwrap();
+    assert!(repr.next().is_none());
+    assert_eq!(advanced.as_rule(), Rule::advanced_repr);
+    advanced.into_inner().next().unwrap().as_str().to_string()
 }
 
 pub(crate) fn parse_string_pairs(mut pair: Pairs<Rule>) -> StringPairs {
@@ -0,0 +1,1074 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use ipld_core::ipld::Ipld;
+use thiserror::Error;
+
+use crate::{
+    adl::{builtin, get_advanced, read_advanced, AdlRegistry},
+    dag::load_block,
+    list::ListRepresentation,
+    map::MapRepresentation,
+    store::BlockStore,
+    structural::StructRepresentation,
+    union::{UnionRepresentation, UnionType},
+    validate::{list_pairs, string_pairs},
+    DataPath, InlineIpldType, IpldSchema, IpldType, Kind,
+};
+
+/// An IPLD selector, which describes a traversal of a data model tree.
+///
+/// Fields are selected by their names in the schema, not by their representation in the data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    /// Selects the current node
+    Matcher,
+    /// Applies the selector to every child of a list, map or struct
+    ExploreAll(Box<Selector>),
+    /// Applies a selector to the given fields of a struct or keys of a map
+    ExploreFields(BTreeMap<String, Selector>),
+    /// Applies the selector to a single element of a list
+    ExploreIndex(usize, Box<Selector>),
+    /// Applies the selector to the elements of a list in `start..end`
+    ExploreRange {
+        start: usize,
+        end: usize,
+        next: Box<Selector>,
+    },
+    /// Applies `sequence`, which continues with itself at every [`Selector::ExploreRecursiveEdge`]
+    ExploreRecursive {
+        limit: RecursionLimit,
+        sequence: Box<Selector>,
+    },
+    /// Marks where the sequence of the innermost [`Selector::ExploreRecursive`] starts again
+    ExploreRecursiveEdge,
+    /// Applies all selectors to the same node
+    ExploreUnion(Vec<Selector>),
+}
+
+/// How often the edge of a recursive selector is followed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecursionLimit {
+    None,
+    Depth(u64),
+}
+
+impl Selector {
+    /// Returns true for the selectors, that explore the children of a node
+    fn explores(&self) -> bool {
+        !matches!(
+            self,
+            Selector::Matcher
+                | Selector::ExploreUnion(_)
+                | Selector::ExploreRecursive { .. }
+                | Selector::ExploreRecursiveEdge
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Selector::Matcher => "Matcher",
+            Selector::ExploreAll(_) => "ExploreAll",
+            Selector::ExploreFields(_) => "ExploreFields",
+            Selector::ExploreIndex(..) => "ExploreIndex",
+            Selector::ExploreRange { .. } => "ExploreRange",
+            Selector::ExploreRecursive { .. } => "ExploreRecursive",
+            Selector::ExploreRecursiveEdge => "ExploreRecursiveEdge",
+            Selector::ExploreUnion(_) => "ExploreUnion",
+        }
+    }
+}
+
+/// An error in a selector, found by checking it against a schema.
+///
+/// Paths are made of the schema field names and list indices the selector explores.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SelectorError {
+    #[error("Type \"{0}\" is not defined")]
+    UnknownType(String),
+    #[error("{ty} has no field \"{field}\" at {path}")]
+    UnknownField {
+        path: DataPath,
+        ty: String,
+        field: String,
+    },
+    #[error("{selector} can not explore {ty} at {path}")]
+    InvalidExplore {
+        path: DataPath,
+        ty: String,
+        selector: &'static str,
+    },
+    #[error("ExploreRecursive on {ty} at {path}, which is not a recursive type")]
+    NotRecursive { path: DataPath, ty: String },
+    #[error("ExploreRecursiveEdge outside of ExploreRecursive at {path}")]
+    EdgeOutsideRecursion { path: DataPath },
+    #[error("ExploreRange at {path} starts at {start} after its end {end}")]
+    InvalidRange {
+        path: DataPath,
+        start: usize,
+        end: usize,
+    },
+}
+
+struct CheckFrame<'s> {
+    sequence: &'s Selector,
+    /// The types, that the sequence was already checked against
+    checked: Vec<InlineIpldType>,
+}
+
+struct SelectFrame<'s> {
+    sequence: &'s Selector,
+    remaining: Option<u64>,
+}
+
+/// The state of running a selector over data
+struct Selection<'a, 's> {
+    schema: &'a IpldSchema,
+    adls: &'a AdlRegistry,
+    store: Option<&'a dyn BlockStore>,
+    frames: Vec<SelectFrame<'s>>,
+    matches: Vec<(DataPath, Ipld)>,
+}
+
+impl IpldSchema {
+    /// Checks that `selector` only explores fields and elements that exist in type `root`
+    pub fn check_selector(&self, root: &str, selector: &Selector) -> Result<(), SelectorError> {
+        if self.resolve(root).is_none() {
+            return Err(SelectorError::UnknownType(root.to_string()));
+        }
+
+        let root = InlineIpldType::Name(root.to_string());
+        self.check_selector_at(&root, selector, &mut DataPath::default(), &mut vec![])
+    }
+
+    /// Checks the selector against type `root` and returns all nodes it matches.
+    ///
+    /// Links are not followed, as the data behind them is not available, see
+    /// [`IpldSchema::select_with`].
+    pub fn select(
+        &self,
+        root: &str,
+        selector: &Selector,
+        data: &Ipld,
+    ) -> Result<Vec<(DataPath, Ipld)>, SelectorError> {
+        self.select_with(root, selector, data, builtin(), None)
+    }
+
+    /// Like [`IpldSchema::select`], but explores data in advanced layouts through `adls` and
+    /// follows links into the blocks of `store`.
+    ///
+    /// Explorations of links, whose block is missing or can not be decoded, match nothing.
+    pub fn select_with(
+        &self,
+        root: &str,
+        selector: &Selector,
+        data: &Ipld,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<Vec<(DataPath, Ipld)>, SelectorError> {
+        self.check_selector(root, selector)?;
+
+        let mut selection = Selection {
+            schema: self,
+            adls,
+            store,
+            frames: vec![],
+            matches: vec![],
+        };
+        selection.select_at(
+            &InlineIpldType::Name(root.to_string()),
+            selector,
+            data,
+            &mut DataPath::default(),
+        );
+        Ok(selection.matches)
+    }
+
+    fn check_selector_at<'s>(
+        &self,
+        ty: &InlineIpldType,
+        selector: &'s Selector,
+        path: &mut DataPath,
+        frames: &mut Vec<CheckFrame<'s>>,
+    ) -> Result<(), SelectorError> {
+        let resolved = self
+            .resolve_inline(ty)
+            .ok_or_else(|| SelectorError::UnknownType(ty.to_string()))?;
+        let invalid = || SelectorError::InvalidExplore {
+            path: path.clone(),
+            ty: ty.to_string(),
+            selector: selector.name(),
+        };
+
+        // Explorations continue at the linked type
+        if let (IpldType::Link(target), Selector::ExploreAll(_))
+        | (IpldType::Link(target), Selector::ExploreFields(_))
+        | (IpldType::Link(target), Selector::ExploreIndex(..))
+        | (IpldType::Link(target), Selector::ExploreRange { .. }) = (resolved.as_ref(), selector)
+        {
+            let target = InlineIpldType::Name(target.clone());
+            return self.check_selector_at(&target, selector, path, frames);
+        }
+
+        // Explorations of unions continue at their members
+        if let (IpldType::Union(union), true) = (resolved.as_ref(), selector.explores()) {
+            return self.check_union(ty, union, selector, path, frames);
+        }
+
+        match selector {
+            Selector::Matcher => Ok(()),
+            Selector::ExploreUnion(selectors) => selectors
+                .iter()
+                .try_for_each(|selector| self.check_selector_at(ty, selector, path, frames)),
+            Selector::ExploreRecursive { sequence, .. } => {
+                if !self.is_recursive(ty) {
+                    return Err(SelectorError::NotRecursive {
+                        path: path.clone(),
+                        ty: ty.to_string(),
+                    });
+                }
+
+                frames.push(CheckFrame {
+                    sequence,
+                    checked: vec![ty.clone()],
+                });
+                let result = self.check_selector_at(ty, sequence, path, frames);
+                frames.pop();
+                result
+            }
+            Selector::ExploreRecursiveEdge => {
+                let frame = frames
+                    .last_mut()
+                    .ok_or_else(|| SelectorError::EdgeOutsideRecursion { path: path.clone() })?;
+                if frame.checked.contains(ty) {
+                    return Ok(());
+                }
+
+                // The sequence starts again at a different type, which it has to fit as well
+                frame.checked.push(ty.clone());
+                let sequence = frame.sequence;
+                self.check_selector_at(ty, sequence, path, frames)
+            }
+            Selector::ExploreAll(next) => match resolved.as_ref() {
+                IpldType::List(list) => self.check_child(&list.ty, "*", next, path, frames),
+                IpldType::Map(map) => self.check_child(&map.value, "*", next, path, frames),
+                IpldType::Struct(stru) => stru.fields.iter().try_for_each(|field| {
+                    self.check_child(&field.value, &field.key, next, path, frames)
+                }),
+                IpldType::Any => self.check_child(&any(), "*", next, path, frames),
+                // There is nothing to explore in scalars
+                _ => Ok(()),
+            },
+            Selector::ExploreFields(fields) => match resolved.as_ref() {
+                IpldType::Struct(stru) => fields.iter().try_for_each(|(name, next)| {
+                    let field = stru
+                        .fields
+                        .iter()
+                        .find(|field| field.key == *name)
+                        .ok_or_else(|| SelectorError::UnknownField {
+                            path: path.clone(),
+                            ty: ty.to_string(),
+                            field: name.clone(),
+                        })?;
+                    self.check_child(&field.value, name, next, path, frames)
+                }),
+                IpldType::Map(map) => fields.iter().try_for_each(|(name, next)| {
+                    self.check_child(&map.value, name, next, path, frames)
+                }),
+                IpldType::Any => fields.iter().try_for_each(|(name, next)| {
+                    self.check_child(&any(), name, next, path, frames)
+                }),
+                _ => Err(invalid()),
+            },
+            Selector::ExploreIndex(idx, next) => match resolved.as_ref() {
+                IpldType::List(list) => self.check_child(&list.ty, idx, next, path, frames),
+                IpldType::Any => self.check_child(&any(), idx, next, path, frames),
+                _ => Err(invalid()),
+            },
+            Selector::ExploreRange { start, end, .. } if start > end => {
+                Err(SelectorError::InvalidRange {
+                    path: path.clone(),
+                    start: *start,
+                    end: *end,
+                })
+            }
+            Selector::ExploreRange { start, end, next } => {
+                let segment = format!("{}..{}", start, end);
+                match resolved.as_ref() {
+                    IpldType::List(list) => self.check_child(&list.ty, segment, next, path, frames),
+                    IpldType::Any => self.check_child(&any(), segment, next, path, frames),
+                    _ => Err(invalid()),
+                }
+            }
+        }
+    }
+
+    /// Checks an exploration of a union against its members, like they are selected.
+    ///
+    /// Members of kinded and inline unions are explored in place, the selector has to fit one of
+    /// them. Keyed and envelope unions hold their member under a key.
+    fn check_union<'s>(
+        &self,
+        ty: &InlineIpldType,
+        union: &UnionType,
+        selector: &'s Selector,
+        path: &mut DataPath,
+        frames: &mut Vec<CheckFrame<'s>>,
+    ) -> Result<(), SelectorError> {
+        let key = |discriminant: &str| match &union.repr {
+            UnionRepresentation::Envelope { content_key, .. } => content_key.clone(),
+            _ => discriminant.to_string(),
+        };
+
+        match (&union.repr, selector) {
+            (UnionRepresentation::Kinded | UnionRepresentation::Inline { .. }, selector) => {
+                let mut first = None;
+                for member in &union.members {
+                    // Types checked by a member, that does not fit, must be checked again
+                    let checked = frames
+                        .iter()
+                        .map(|frame| frame.checked.len())
+                        .collect::<Vec<_>>();
+                    match self.check_selector_at(&member.ty, selector, path, frames) {
+                        Ok(()) => return Ok(()),
+                        Err(err) => {
+                            first.get_or_insert(err);
+                        }
+                    }
+                    for (frame, len) in frames.iter_mut().zip(checked) {
+                        frame.checked.truncate(len);
+                    }
+                }
+                first.map_or(Ok(()), Err)
+            }
+            (_, Selector::ExploreAll(next)) => union.members.iter().try_for_each(|member| {
+                self.check_child(&member.ty, key(&member.discriminant), next, path, frames)
+            }),
+            (_, Selector::ExploreFields(fields)) => fields.iter().try_for_each(|(name, next)| {
+                let mut members = union
+                    .members
+                    .iter()
+                    .filter(|member| key(&member.discriminant) == *name)
+                    .peekable();
+                if members.peek().is_none() {
+                    return Err(SelectorError::UnknownField {
+                        path: path.clone(),
+                        ty: ty.to_string(),
+                        field: name.clone(),
+                    });
+                }
+                members
+                    .try_for_each(|member| self.check_child(&member.ty, name, next, path, frames))
+            }),
+            _ => Err(SelectorError::InvalidExplore {
+                path: path.clone(),
+                ty: ty.to_string(),
+                selector: selector.name(),
+            }),
+        }
+    }
+
+    fn check_child<'s>(
+        &self,
+        ty: &InlineIpldType,
+        segment: impl ToString,
+        selector: &'s Selector,
+        path: &mut DataPath,
+        frames: &mut Vec<CheckFrame<'s>>,
+    ) -> Result<(), SelectorError> {
+        path.push(segment);
+        let result = self.check_selector_at(ty, selector, path, frames);
+        path.pop();
+        result
+    }
+
+    /// Returns true, if a value of the type can contain another value of the same type
+    fn is_recursive(&self, ty: &InlineIpldType) -> bool {
+        let mut reachable = BTreeSet::new();
+        self.collect_references(ty, &mut reachable);
+
+        if let InlineIpldType::Name(name) = ty {
+            if reachable.contains(name) {
+                return true;
+            }
+        }
+
+        reachable.iter().any(|name| {
+            if name == "Any" {
+                return true;
+            }
+            let mut from_name = BTreeSet::new();
+            self.collect_references(&InlineIpldType::Name(name.clone()), &mut from_name);
+            from_name.contains(name)
+        })
+    }
+
+    /// Collects the names of all types, that are reachable from the definition of `ty`
+    pub(crate) fn collect_references(&self, ty: &InlineIpldType, names: &mut BTreeSet<String>) {
+        let mut visit = |name: &str, names: &mut BTreeSet<String>| {
+            if names.insert(name.to_string()) {
+                self.collect_references(&InlineIpldType::Name(name.to_string()), names);
+            }
+        };
+
+        let Some(resolved) = self.resolve_inline(ty) else {
+            return;
+        };
+        match resolved.as_ref() {
+            IpldType::List(list) => self.collect_inline(&list.ty, names, &mut visit),
+            IpldType::Map(map) => {
+                visit(&map.key, names);
+                self.collect_inline(&map.value, names, &mut visit);
+            }
+            IpldType::Link(target) | IpldType::Copy(target) => visit(target, names),
+            IpldType::Struct(stru) => {
+                for field in &stru.fields {
+                    self.collect_inline(&field.value, names, &mut visit);
+                }
+            }
+            IpldType::Union(union) => {
+                for member in &union.members {
+                    self.collect_inline(&member.ty, names, &mut visit);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn collect_inline(
+        &self,
+        ty: &InlineIpldType,
+        names: &mut BTreeSet<String>,
+        visit: &mut impl FnMut(&str, &mut BTreeSet<String>),
+    ) {
+        match ty {
+            InlineIpldType::Name(name) | InlineIpldType::Link(name) => visit(name, names),
+            inline => self.collect_references(inline, names),
+        }
+    }
+
+    /// Returns the children of a value by their schema name, with their type.
+    ///
+    /// Data that does not match the type has no children. Data in advanced layouts is read
+    /// through `adls` and `store`.
+    pub(crate) fn children<'d>(
+        &self,
+        ty: &InlineIpldType,
+        data: &'d Ipld,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Vec<(String, InlineIpldType, Cow<'d, Ipld>)> {
+        let Some(resolved) = self.resolve_inline(ty) else {
+            return vec![];
+        };
+        let path = &mut DataPath::default();
+
+        match (resolved.as_ref(), data) {
+            (IpldType::List(list), data) => {
+                let items = match (&list.repr, data) {
+                    (ListRepresentation::List, Ipld::List(items)) => {
+                        items.iter().map(Cow::Borrowed).collect()
+                    }
+                    (ListRepresentation::Advanced(adl), data) => {
+                        match read_advanced(adls, "", adl, Kind::List, data, store, path) {
+                            Ok(Ipld::List(items)) => items.into_iter().map(Cow::Owned).collect(),
+                            _ => vec![],
+                        }
+                    }
+                    _ => vec![],
+                };
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, item)| (idx.to_string(), list.ty.clone(), item))
+                    .collect()
+            }
+            (IpldType::Map(map), data) => {
+                let entries = match &map.repr {
+                    MapRepresentation::Map => match data {
+                        Ipld::Map(entries) => entries
+                            .iter()
+                            .map(|(key, value)| (key.clone(), Cow::Borrowed(value)))
+                            .collect(),
+                        _ => vec![],
+                    },
+                    MapRepresentation::ListPairs => list_pairs("", data, path)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(key, value)| (key.clone(), Cow::Borrowed(value)))
+                        .collect(),
+                    MapRepresentation::StringPairs(pairs) => string_pairs("", pairs, data, path)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(key, value)| (key, Cow::Owned(Ipld::String(value))))
+                        .collect(),
+                    MapRepresentation::Advanced(adl) => {
+                        match read_advanced(adls, "", adl, Kind::Map, data, store, path) {
+                            Ok(Ipld::Map(entries)) => entries
+                                .into_iter()
+                                .map(|(key, value)| (key, Cow::Owned(value)))
+                                .collect(),
+                            _ => vec![],
+                        }
+                    }
+                };
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, map.value.clone(), value))
+                    .collect()
+            }
+            (IpldType::Struct(stru), data) => {
+                let entries: BTreeMap<String, Cow<'d, Ipld>> = match &stru.repr {
+                    StructRepresentation::Map => match data {
+                        Ipld::Map(entries) => entries
+                            .iter()
+                            .map(|(key, value)| (key.clone(), Cow::Borrowed(value)))
+                            .collect(),
+                        _ => BTreeMap::new(),
+                    },
+                    StructRepresentation::ListPairs => list_pairs("", data, path)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(key, value)| (key.clone(), Cow::Borrowed(value)))
+                        .collect(),
+                    StructRepresentation::StringPairs(pairs) => string_pairs("", pairs, data, path)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(key, value)| (key, Cow::Owned(Ipld::String(value))))
+                        .collect(),
+                    StructRepresentation::Tuple => match data {
+                        Ipld::List(items) => stru
+                            .fields
+                            .iter()
+                            .zip(items)
+                            .map(|(field, item)| {
+                                let key = field.rename.as_deref().unwrap_or(&field.key);
+                                (key.to_string(), Cow::Borrowed(item))
+                            })
+                            .collect(),
+                        _ => BTreeMap::new(),
+                    },
+                };
+
+                stru.fields
+                    .iter()
+                    .filter_map(|field| {
+                        let key = field.rename.as_deref().unwrap_or(&field.key);
+                        let value = entries.get(key)?.clone();
+                        Some((field.key.clone(), field.value.clone(), value))
+                    })
+                    .collect()
+            }
+            // Unions are transparent, their members hold the children
+            (IpldType::Union(union), data) => match union.member_of(data) {
+                Some((member, Some(key), value)) => {
+                    vec![(key.to_string(), member.ty.clone(), Cow::Borrowed(value))]
+                }
+                Some((member, None, value)) => self.children(&member.ty, value, adls, store),
+                None => vec![],
+            },
+            (IpldType::Any, Ipld::List(items)) => items
+                .iter()
+                .enumerate()
+                .map(|(idx, item)| (idx.to_string(), any(), Cow::Borrowed(item)))
+                .collect(),
+            (IpldType::Any, Ipld::Map(entries)) => entries
+                .iter()
+                .map(|(key, value)| (key.clone(), any(), Cow::Borrowed(value)))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Looks up a single child of a value by its schema name, like [`IpldSchema::children`],
+    /// without reading the other children
+    pub(crate) fn child<'d>(
+        &self,
+        ty: &InlineIpldType,
+        data: &'d Ipld,
+        segment: &str,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Option<(InlineIpldType, Cow<'d, Ipld>)> {
+        let resolved = self.resolve_inline(ty)?;
+        let path = &mut DataPath::default();
+        let index = || segment.parse::<usize>().ok();
+
+        match (resolved.as_ref(), data) {
+            (IpldType::List(list), data) => {
+                let item = match (&list.repr, data) {
+                    (ListRepresentation::List, Ipld::List(items)) => {
+                        Cow::Borrowed(items.get(index()?)?)
+                    }
+                    (ListRepresentation::Advanced(adl), data) => {
+                        Cow::Owned(get_advanced(adls, adl, Kind::List, data, segment, store)?)
+                    }
+                    _ => return None,
+                };
+                Some((list.ty.clone(), item))
+            }
+            (IpldType::Map(map), data) => {
+                let value = match &map.repr {
+                    MapRepresentation::Map => match data {
+                        Ipld::Map(entries) => Cow::Borrowed(entries.get(segment)?),
+                        _ => return None,
+                    },
+                    MapRepresentation::ListPairs => {
+                        Cow::Borrowed(find_pair(list_pairs("", data, path).ok()?, segment)?)
+                    }
+                    MapRepresentation::StringPairs(pairs) => Cow::Owned(Ipld::String(find_pair(
+                        string_pairs("", pairs, data, path).ok()?,
+                        segment,
+                    )?)),
+                    MapRepresentation::Advanced(adl) => {
+                        Cow::Owned(get_advanced(adls, adl, Kind::Map, data, segment, store)?)
+                    }
+                };
+                Some((map.value.clone(), value))
+            }
+            (IpldType::Struct(stru), data) => {
+                let field = stru.fields.iter().find(|field| field.key == segment)?;
+                let key = field.rename.as_deref().unwrap_or(&field.key);
+                let value = match &stru.repr {
+                    StructRepresentation::Map => match data {
+                        Ipld::Map(entries) => Cow::Borrowed(entries.get(key)?),
+                        _ => return None,
+                    },
+                    StructRepresentation::ListPairs => {
+                        Cow::Borrowed(find_pair(list_pairs("", data, path).ok()?, key)?)
+                    }
+                    StructRepresentation::StringPairs(pairs) => Cow::Owned(Ipld::String(
+                        find_pair(string_pairs("", pairs, data, path).ok()?, key)?,
+                    )),
+                    StructRepresentation::Tuple => {
+                        let idx = stru.fields.iter().position(|other| other == field)?;
+                        match data {
+                            Ipld::List(items) => Cow::Borrowed(items.get(idx)?),
+                            _ => return None,
+                        }
+                    }
+                };
+                Some((field.value.clone(), value))
+            }
+            (IpldType::Union(union), data) => match union.member_of(data)? {
+                (member, Some(key), value) if key == segment => {
+                    Some((member.ty.clone(), Cow::Borrowed(value)))
+                }
+                (_, Some(_), _) => None,
+                (member, None, value) => self.child(&member.ty, value, segment, adls, store),
+            },
+            (IpldType::Any, Ipld::List(items)) => {
+                Some((any(), Cow::Borrowed(items.get(index()?)?)))
+            }
+            (IpldType::Any, Ipld::Map(entries)) => {
+                Some((any(), Cow::Borrowed(entries.get(segment)?)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'s> Selection<'_, 's> {
+    fn select_at(
+        &mut self,
+        ty: &InlineIpldType,
+        selector: &'s Selector,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) {
+        // Explorations continue at the linked block, like they are checked
+        if let (true, Some(store), Ipld::Link(cid)) = (selector.explores(), self.store, data) {
+            if let Some(IpldType::Link(target)) = self.schema.resolve_inline(ty).as_deref() {
+                if let Ok(block) = load_block(store, cid) {
+                    let target = InlineIpldType::Name(target.clone());
+                    self.select_at(&target, selector, &block, path);
+                }
+                return;
+            }
+        }
+
+        match selector {
+            Selector::Matcher => self.matches.push((path.clone(), data.clone())),
+            Selector::ExploreUnion(selectors) => {
+                for selector in selectors {
+                    self.select_at(ty, selector, data, path);
+                }
+            }
+            Selector::ExploreRecursive { limit, sequence } => {
+                self.frames.push(SelectFrame {
+                    sequence,
+                    remaining: match limit {
+                        RecursionLimit::None => None,
+                        RecursionLimit::Depth(depth) => Some(*depth),
+                    },
+                });
+                self.select_at(ty, sequence, data, path);
+                self.frames.pop();
+            }
+            Selector::ExploreRecursiveEdge => {
+                let Some(frame) = self.frames.last_mut() else {
+                    return;
+                };
+                let remaining = frame.remaining;
+                if remaining == Some(0) {
+                    return;
+                }
+
+                frame.remaining = remaining.map(|depth| depth - 1);
+                let sequence = frame.sequence;
+                self.select_at(ty, sequence, data, path);
+                if let Some(frame) = self.frames.last_mut() {
+                    frame.remaining = remaining;
+                }
+            }
+            Selector::ExploreAll(next) => {
+                for (segment, child_ty, child) in self.children(ty, data) {
+                    path.push(segment);
+                    self.select_at(&child_ty, next, &child, path);
+                    path.pop();
+                }
+            }
+            Selector::ExploreFields(fields) => {
+                for (segment, child_ty, child) in self.children(ty, data) {
+                    if let Some(next) = fields.get(&segment) {
+                        path.push(segment);
+                        self.select_at(&child_ty, next, &child, path);
+                        path.pop();
+                    }
+                }
+            }
+            Selector::ExploreIndex(idx, next) => {
+                self.select_range(ty, *idx..=*idx, next, data, path)
+            }
+            Selector::ExploreRange { start, end, next } => {
+                self.select_range(ty, *start..*end, next, data, path)
+            }
+        }
+    }
+
+    /// Selects the list elements in `range`, which are looked up one by one, until the first
+    /// missing element
+    fn select_range(
+        &mut self,
+        ty: &InlineIpldType,
+        range: impl Iterator<Item = usize>,
+        selector: &'s Selector,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) {
+        // Maps of any type have no indices
+        if let (Some(IpldType::Any), Ipld::Map(_)) =
+            (self.schema.resolve_inline(ty).as_deref(), data)
+        {
+            return;
+        }
+
+        for idx in range {
+            let segment = idx.to_string();
+            let Some((child_ty, child)) =
+                self.schema.child(ty, data, &segment, self.adls, self.store)
+            else {
+                break;
+            };
+            path.push(segment);
+            self.select_at(&child_ty, selector, &child, path);
+            path.pop();
+        }
+    }
+
+    fn children<'d>(
+        &self,
+        ty: &InlineIpldType,
+        data: &'d Ipld,
+    ) -> Vec<(String, InlineIpldType, Cow<'d, Ipld>)> {
+        self.schema.children(ty, data, self.adls, self.store)
+    }
+}
+
+/// The value of the first pair with the key `key`
+fn find_pair<K: AsRef<str>, V>(pairs: Vec<(K, V)>, key: &str) -> Option<V> {
+    pairs
+        .into_iter()
+        .find(|(entry, _)| entry.as_ref() == key)
+        .map(|(_, value)| value)
+}
+
+fn any() -> InlineIpldType {
+    InlineIpldType::Name("Any".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use ipld_core::{
+        cid::{multihash::Multihash, Cid},
+        ipld,
+    };
+
+    use super::*;
+    use crate::{dag::DAG_CBOR, store::MemoryStore};
+
+    const SCHEMA: &str = "\
+type Tree struct {
+  name String (rename \"n\")
+  children [Tree]
+  meta Meta
+}
+
+type Meta struct {
+  tags {String:Int}
+}
+";
+
+    fn fields<const N: usize>(fields: [(&str, Selector); N]) -> Selector {
+        Selector::ExploreFields(
+            fields
+                .into_iter()
+                .map(|(name, next)| (name.to_string(), next))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn check_selector() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+
+        let selector = fields([(
+            "meta",
+            fields([("tags", fields([("a", Selector::Matcher)]))]),
+        )]);
+        assert_eq!(schema.check_selector("Tree", &selector), Ok(()));
+
+        let selector = fields([("meta", fields([("missing", Selector::Matcher)]))]);
+        assert_eq!(
+            schema
+                .check_selector("Tree", &selector)
+                .unwrap_err()
+                .to_string(),
+            "Meta has no field \"missing\" at meta"
+        );
+
+        let selector = fields([(
+            "name",
+            Selector::ExploreIndex(0, Box::new(Selector::Matcher)),
+        )]);
+        assert_eq!(
+            schema
+                .check_selector("Tree", &selector)
+                .unwrap_err()
+                .to_string(),
+            "ExploreIndex can not explore String at name"
+        );
+
+        let selector = fields([(
+            "children",
+            Selector::ExploreRange {
+                start: 2,
+                end: 1,
+                next: Box::new(Selector::Matcher),
+            },
+        )]);
+        assert_eq!(
+            schema
+                .check_selector("Tree", &selector)
+                .unwrap_err()
+                .to_string(),
+            "ExploreRange at children starts at 2 after its end 1"
+        );
+
+        let recursive = |sequence| Selector::ExploreRecursive {
+            limit: RecursionLimit::None,
+            sequence: Box::new(sequence),
+        };
+        let selector = recursive(Selector::ExploreUnion(vec![
+            Selector::Matcher,
+            fields([(
+                "children",
+                Selector::ExploreAll(Box::new(Selector::ExploreRecursiveEdge)),
+            )]),
+        ]));
+        assert_eq!(schema.check_selector("Tree", &selector), Ok(()));
+        assert_eq!(
+            schema.check_selector("Meta", &selector).unwrap_err(),
+            SelectorError::NotRecursive {
+                path: DataPath::default(),
+                ty: "Meta".to_string()
+            }
+        );
+
+        let selector = recursive(fields([("meta", Selector::ExploreRecursiveEdge)]));
+        assert_eq!(
+            schema
+                .check_selector("Tree", &selector)
+                .unwrap_err()
+                .to_string(),
+            "Meta has no field \"meta\" at meta"
+        );
+        assert!(matches!(
+            schema.check_selector("Tree", &Selector::ExploreRecursiveEdge),
+            Err(SelectorError::EdgeOutsideRecursion { .. })
+        ));
+    }
+
+    #[test]
+    fn check_unions() {
+        let schema = IpldSchema::parse(
+            "\
+type Shape union {
+  | Circle \"circle\"
+  | Polygon \"polygon\"
+} representation keyed
+
+type Circle struct {
+  radius Int
+}
+
+type Polygon [Int]
+
+type Value union {
+  | String string
+  | Polygon list
+} representation kinded
+",
+        )
+        .unwrap();
+
+        let selector = fields([(
+            "polygon",
+            Selector::ExploreIndex(0, Box::new(Selector::Matcher)),
+        )]);
+        assert_eq!(schema.check_selector("Shape", &selector), Ok(()));
+        assert_eq!(
+            schema
+                .check_selector("Shape", &fields([("square", Selector::Matcher)]))
+                .unwrap_err()
+                .to_string(),
+            "Shape has no field \"square\" at (root)"
+        );
+        let selector = Selector::ExploreAll(Box::new(fields([("radius", Selector::Matcher)])));
+        assert_eq!(
+            schema
+                .check_selector("Shape", &selector)
+                .unwrap_err()
+                .to_string(),
+            "ExploreFields can not explore Polygon at polygon"
+        );
+
+        let range = Selector::ExploreRange {
+            start: 1,
+            end: 5,
+            next: Box::new(Selector::Matcher),
+        };
+        assert_eq!(schema.check_selector("Value", &range), Ok(()));
+        assert!(matches!(
+            schema.check_selector("Value", &fields([("a", Selector::Matcher)])),
+            Err(SelectorError::InvalidExplore { .. })
+        ));
+
+        let selected = schema.select("Value", &range, &ipld!([1, 2, 3])).unwrap();
+        assert_eq!(
+            selected
+                .iter()
+                .map(|(path, value)| (path.to_string(), value.clone()))
+                .collect::<Vec<_>>(),
+            vec![("1".to_string(), ipld!(2)), ("2".to_string(), ipld!(3)),]
+        );
+        assert_eq!(
+            schema.select("Value", &range, &ipld!("abc")).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn select() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+        let leaf = |name: &str| ipld!({"n": name, "children": [], "meta": {"tags": {}}});
+        let data = ipld!({
+            "n": "root",
+            "children": [leaf("a"), {"n": "b", "children": [leaf("c")], "meta": {"tags": {"x": 1}}}],
+            "meta": {"tags": {}},
+        });
+
+        let names = Selector::ExploreRecursive {
+            limit: RecursionLimit::None,
+            sequence: Box::new(Selector::ExploreUnion(vec![
+                fields([("name", Selector::Matcher)]),
+                fields([(
+                    "children",
+                    Selector::ExploreAll(Box::new(Selector::ExploreRecursiveEdge)),
+                )]),
+            ])),
+        };
+        let selected = schema.select("Tree", &names, &data).unwrap();
+        assert_eq!(
+            selected
+                .iter()
+                .map(|(path, value)| (path.to_string(), value.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("name".to_string(), ipld!("root")),
+                ("children/0/name".to_string(), ipld!("a")),
+                ("children/1/name".to_string(), ipld!("b")),
+                ("children/1/children/0/name".to_string(), ipld!("c")),
+            ]
+        );
+
+        let limited = Selector::ExploreRecursive {
+            limit: RecursionLimit::Depth(1),
+            sequence: match names {
+                Selector::ExploreRecursive { sequence, .. } => sequence,
+                _ => unreachable!(),
+            },
+        };
+        assert_eq!(schema.select("Tree", &limited, &data).unwrap().len(), 3);
+
+        let range = fields([(
+            "children",
+            Selector::ExploreRange {
+                start: 1,
+                end: 5,
+                next: Box::new(fields([(
+                    "meta",
+                    fields([("tags", fields([("x", Selector::Matcher)]))]),
+                )])),
+            },
+        )]);
+        let selected = schema.select("Tree", &range, &data).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].0.to_string(), "children/1/meta/tags/x");
+        assert_eq!(selected[0].1, ipld!(1));
+    }
+
+    #[test]
+    fn select_links() {
+        let schema =
+            IpldSchema::parse("type Node struct {\n  name String\n  next nullable &Node\n}\n")
+                .unwrap();
+        let cid = Cid::new_v1(DAG_CBOR, Multihash::wrap(0x12, &[1; 32]).unwrap());
+        let mut store = MemoryStore::new();
+        store
+            .put(
+                cid,
+                serde_ipld_dagcbor::to_vec(&ipld!({"name": "b", "next": null})).unwrap(),
+            )
+            .unwrap();
+        let data = ipld!({"name": "a", "next": cid});
+
+        let selector = fields([("next", fields([("name", Selector::Matcher)]))]);
+        assert_eq!(schema.select("Node", &selector, &data).unwrap(), vec![]);
+        let selected = schema
+            .select_with("Node", &selector, &data, builtin(), Some(&store))
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].0.to_string(), "next/name");
+        assert_eq!(selected[0].1, ipld!("b"));
+
+        let schema = IpldSchema::parse("type Items [Int]").unwrap();
+        let last = Selector::ExploreIndex(usize::MAX, Box::new(Selector::Matcher));
+        assert_eq!(
+            schema.select("Items", &last, &ipld!([1, 2])).unwrap(),
+            vec![]
+        );
+    }
+}
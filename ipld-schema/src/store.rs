@@ -0,0 +1,121 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use ipld_core::cid::Cid;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("Failed to access block {cid}: {source}")]
+    Io {
+        cid: Cid,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Stores blocks by their CID.
+///
+/// Stores do not verify that blocks match their CIDs.
+pub trait BlockStore {
+    /// Returns the block with the CID `cid`, or `None` if the store does not contain it
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, StoreError>;
+
+    /// Stores `block` under the CID `cid`, replacing a block stored under the same CID
+    fn put(&mut self, cid: Cid, block: Vec<u8>) -> Result<(), StoreError>;
+}
+
+/// A store, that keeps all blocks in memory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStore(BTreeMap<Cid, Vec<u8>>);
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn cids(&self) -> impl Iterator<Item = &Cid> {
+        self.0.keys()
+    }
+}
+
+impl BlockStore for MemoryStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.0.get(cid).cloned())
+    }
+
+    fn put(&mut self, cid: Cid, block: Vec<u8>) -> Result<(), StoreError> {
+        self.0.insert(cid, block);
+        Ok(())
+    }
+}
+
+/// A store, that keeps every block in a file of a directory, named by the CID of the block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryStore {
+    dir: PathBuf,
+}
+
+impl DirectoryStore {
+    /// Uses the blocks in `dir`, which is created on the first [`BlockStore::put`]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, cid: &Cid) -> PathBuf {
+        self.dir.join(cid.to_string())
+    }
+}
+
+impl BlockStore for DirectoryStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, StoreError> {
+        match fs::read(self.path(cid)) {
+            Ok(block) => Ok(Some(block)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(StoreError::Io { cid: *cid, source }),
+        }
+    }
+
+    fn put(&mut self, cid: Cid, block: Vec<u8>) -> Result<(), StoreError> {
+        fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(self.path(&cid), block))
+            .map_err(|source| StoreError::Io { cid, source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ipld_core::cid::multihash::Multihash;
+
+    use super::*;
+
+    #[test]
+    fn directory_store() {
+        let dir = std::env::temp_dir().join(format!("ipld-schema-store-{}", std::process::id()));
+        let cid = Cid::new_v1(0x55, Multihash::wrap(0x12, &[1; 32]).unwrap());
+        let other = Cid::new_v1(0x55, Multihash::wrap(0x12, &[2; 32]).unwrap());
+
+        let mut store = DirectoryStore::new(&dir);
+        assert_eq!(store.get(&cid).unwrap(), None);
+        store.put(cid, b"block".to_vec()).unwrap();
+        assert_eq!(store.get(&cid).unwrap(), Some(b"block".to_vec()));
+        assert_eq!(store.get(&other).unwrap(), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,835 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+
+use crate::{
+    adl::AdlRegistry,
+    enumerate::EnumType,
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::BytesRepresentation,
+    store::BlockStore,
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    validate::{string_pairs, validate_enum},
+    DataPath, InlineIpldType, IpldSchema, IpldType, Kind, ValidationError,
+};
+
+/// The number of lists and maps, that a value may be nested in.
+///
+/// Validating a value recurses into the types of its lists and maps, so deeper data is
+/// rejected as malformed, instead of overflowing the stack.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// A data model value, as far as it is needed for validation.
+///
+/// Scalars are read completely, the entries of lists and maps are read one at a time
+/// through [`TokenReader::next_entry`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Float,
+    String(String),
+    /// The bytes themselves are skipped, unless [`TokenReader::keep_bytes`] is set
+    Bytes(Vec<u8>),
+    Link(Cid),
+    List(Seq),
+    Map(Seq),
+}
+
+impl Token {
+    fn kind(&self) -> Kind {
+        match self {
+            Token::Null => Kind::Null,
+            Token::Bool(_) => Kind::Bool,
+            Token::Int(_) => Kind::Int,
+            Token::Float => Kind::Float,
+            Token::String(_) => Kind::String,
+            Token::Bytes(_) => Kind::Bytes,
+            Token::Link(_) => Kind::Link,
+            Token::List(_) => Kind::List,
+            Token::Map(_) => Kind::Map,
+        }
+    }
+}
+
+/// The state of reading the entries of a list or map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Seq {
+    /// The number of entries, if the encoding states it upfront
+    pub(crate) len: Option<usize>,
+    /// The number of entries started so far
+    pub(crate) read: usize,
+    pub(crate) is_map: bool,
+}
+
+impl Seq {
+    pub(crate) fn new(len: Option<usize>, is_map: bool) -> Self {
+        Self {
+            len,
+            read: 0,
+            is_map,
+        }
+    }
+}
+
+/// The data is not well-formed in its encoding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReadError {
+    pub(crate) span: Range<usize>,
+    pub(crate) reason: String,
+}
+
+/// Reads encoded data token by token
+pub(crate) trait TokenReader {
+    /// Reads the next value
+    fn token(&mut self) -> Result<Token, ReadError>;
+
+    /// Returns true, if the list or map has another entry. Otherwise its end is consumed.
+    fn next_entry(&mut self, seq: &mut Seq) -> Result<bool, ReadError>;
+
+    /// Reads the key of a map entry, after [`TokenReader::next_entry`] returned true
+    fn key(&mut self) -> Result<String, ReadError>;
+
+    /// Returns true, if the next value is null, without consuming it
+    fn peek_null(&mut self) -> bool;
+
+    /// Sets, whether the following bytes tokens hold their content
+    fn keep_bytes(&mut self, keep: bool);
+
+    /// The span of the last token or key that was read
+    fn last(&self) -> Range<usize>;
+
+    /// Fails, if there is data after the value
+    fn finish(&mut self) -> Result<(), ReadError>;
+}
+
+/// The data is not well-formed, or does not match the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum StreamError {
+    Invalid {
+        span: Range<usize>,
+        error: ValidationError,
+    },
+    Malformed {
+        span: Range<usize>,
+        path: DataPath,
+        reason: String,
+    },
+}
+
+impl IpldSchema {
+    /// Validates the value read by `reader` against the type `root`, stopping at the first error
+    pub(crate) fn validate_stream(
+        &self,
+        root: &str,
+        reader: &mut impl TokenReader,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<(), StreamError> {
+        let mut validator = StreamValidator {
+            schema: self,
+            adls,
+            store,
+            reader,
+            path: DataPath::default(),
+            pending: None,
+        };
+
+        validator.named(root)?;
+        validator
+            .reader
+            .finish()
+            .map_err(|err| validator.malformed(err))
+    }
+}
+
+struct StreamValidator<'s, 'r, R> {
+    schema: &'s IpldSchema,
+    /// The layouts of values, that are read completely
+    adls: &'s AdlRegistry,
+    store: Option<&'s dyn BlockStore>,
+    reader: &'r mut R,
+    path: DataPath,
+    /// A token, that was read ahead to select the member of a union
+    pending: Option<(Range<usize>, Token)>,
+}
+
+impl<'s, 'r, R: TokenReader> StreamValidator<'s, 'r, R> {
+    fn malformed(&self, err: ReadError) -> StreamError {
+        StreamError::Malformed {
+            span: err.span,
+            path: self.path.clone(),
+            reason: err.reason,
+        }
+    }
+
+    fn invalid(&self, span: Range<usize>, error: ValidationError) -> StreamError {
+        StreamError::Invalid { span, error }
+    }
+
+    /// Reads the next token, returning it with its span
+    fn token(&mut self) -> Result<(Range<usize>, Token), StreamError> {
+        if let Some(pending) = self.pending.take() {
+            return Ok(pending);
+        }
+        match self.reader.token() {
+            Ok(token) => {
+                let span = self.reader.last();
+                self.nest(&span, &token, self.path.segments().count())?;
+                Ok((span, token))
+            }
+            Err(err) => Err(self.malformed(err)),
+        }
+    }
+
+    /// Fails, if `token` starts a list or map inside of `depth` others and exceeds [`MAX_DEPTH`]
+    fn nest(&self, span: &Range<usize>, token: &Token, depth: usize) -> Result<(), StreamError> {
+        match token {
+            Token::List(_) | Token::Map(_) if depth >= MAX_DEPTH => {
+                Err(self.malformed(ReadError {
+                    span: span.clone(),
+                    reason: format!("lists and maps are nested deeper than {}", MAX_DEPTH),
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn next_entry(&mut self, seq: &mut Seq) -> Result<bool, StreamError> {
+        self.reader
+            .next_entry(seq)
+            .map_err(|err| self.malformed(err))
+    }
+
+    fn key(&mut self) -> Result<(Range<usize>, String), StreamError> {
+        match self.reader.key() {
+            Ok(key) => Ok((self.reader.last(), key)),
+            Err(err) => Err(self.malformed(err)),
+        }
+    }
+
+    /// Skips the rest of a value, that started with `token`, without recursion
+    fn skip(&mut self, token: Token) -> Result<(), StreamError> {
+        let mut open = vec![];
+        match token {
+            Token::List(seq) | Token::Map(seq) => open.push(seq),
+            _ => return Ok(()),
+        }
+
+        while let Some(mut seq) = open.pop() {
+            if !self.next_entry(&mut seq)? {
+                continue;
+            }
+            open.push(seq);
+
+            if seq.is_map {
+                self.key()?;
+            }
+            match self.token()?.1 {
+                Token::List(seq) | Token::Map(seq) => open.push(seq),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the rest of a value, that started with `token`, without recursion.
+    ///
+    /// Floats are read as zero and bytes are empty, unless they are kept by the reader.
+    fn read(&mut self, mut token: Token) -> Result<Ipld, StreamError> {
+        // The open lists and maps, with the key of the current entry of a map
+        let mut open: Vec<(Seq, Ipld, Option<String>)> = vec![];
+
+        loop {
+            let mut value = match token {
+                Token::List(seq) => {
+                    open.push((seq, Ipld::List(vec![]), None));
+                    None
+                }
+                Token::Map(seq) => {
+                    open.push((seq, Ipld::Map(BTreeMap::new()), None));
+                    None
+                }
+                token => Some(kind_placeholder(&token)),
+            };
+
+            // Closes lists and maps, until one of them has another entry
+            loop {
+                let Some((seq, container, key)) = open.last_mut() else {
+                    return Ok(value.expect("closed values are kept until they are added"));
+                };
+                match (value.take(), container) {
+                    (None, _) => (),
+                    (Some(value), Ipld::List(items)) => items.push(value),
+                    (Some(value), Ipld::Map(entries)) => {
+                        entries.insert(key.take().unwrap_or_default(), value);
+                    }
+                    _ => unreachable!("only lists and maps are open"),
+                }
+
+                if self.next_entry(seq)? {
+                    if seq.is_map {
+                        *key = Some(self.key()?.1);
+                    }
+                    break;
+                }
+                value = open.pop().map(|(_, container, _)| container);
+            }
+
+            let (span, next) = self.token()?;
+            self.nest(&span, &next, self.path.segments().count() + open.len())?;
+            token = next;
+        }
+    }
+
+    /// Skips the rest of a value, that started with `token` at `start`, and returns its span.
+    ///
+    /// If the rest is malformed, only the span of the token is returned.
+    fn value_span(&mut self, start: Range<usize>, token: Token) -> Range<usize> {
+        match self.skip(token) {
+            Ok(()) => start.start..self.reader.last().end.max(start.end),
+            Err(_) => start,
+        }
+    }
+
+    fn kind_error(
+        &mut self,
+        span: Range<usize>,
+        ty: &str,
+        expected: Kind,
+        token: Token,
+    ) -> StreamError {
+        let error = ValidationError::Kind {
+            path: self.path.clone(),
+            ty: ty.to_string(),
+            expected,
+            found: token.kind(),
+        };
+        let span = self.value_span(span, token);
+        self.invalid(span, error)
+    }
+
+    fn named(&mut self, name: &str) -> Result<(), StreamError> {
+        let ty = match self.schema.resolve_named(name) {
+            Ok(ty) => ty,
+            Err(err) => return Err(self.invalid(self.reader.last(), err)),
+        };
+        self.ty(name, &ty)
+    }
+
+    fn inline(&mut self, ty: &InlineIpldType) -> Result<(), StreamError> {
+        match ty {
+            InlineIpldType::Name(name) => self.named(name),
+            InlineIpldType::List(list) => self.list(&ty.to_string(), list),
+            InlineIpldType::Map(map) => self.map(&ty.to_string(), map),
+            InlineIpldType::Link(_) => self.scalar(&ty.to_string(), Kind::Link),
+        }
+    }
+
+    fn ty(&mut self, name: &str, ty: &IpldType) -> Result<(), StreamError> {
+        match ty {
+            IpldType::Bool => self.scalar(name, Kind::Bool),
+            IpldType::String => self.scalar(name, Kind::String),
+            IpldType::Bytes(BytesRepresentation::Bytes) => self.scalar(name, Kind::Bytes),
+            IpldType::Bytes(BytesRepresentation::Advanced(_)) => self.buffered(name, ty),
+            IpldType::Int => self.scalar(name, Kind::Int),
+            IpldType::Float => self.scalar(name, Kind::Float),
+            IpldType::Link(_) => self.scalar(name, Kind::Link),
+            IpldType::Any => {
+                let (_, token) = self.token()?;
+                self.skip(token)
+            }
+            IpldType::List(list) => self.list(name, list),
+            IpldType::Map(map) => self.map(name, map),
+            IpldType::Struct(stru) => self.structure(name, stru),
+            IpldType::Union(union) => match union.repr {
+                UnionRepresentation::Kinded => self.kinded(name, union),
+                UnionRepresentation::Keyed => self.keyed(name, union),
+                // The member is selected by data, that may come after it
+                UnionRepresentation::Inline { .. } | UnionRepresentation::Envelope { .. } => {
+                    self.buffered(name, ty)
+                }
+            },
+            IpldType::Enum(enu) => self.enumeration(name, enu),
+            IpldType::Unit(unit) => self.unit(name, unit),
+            IpldType::Copy(_) => unreachable!("copies are resolved by named"),
+        }
+    }
+
+    /// Validates a value of `ty` starting at `span`, that was read completely
+    fn validate_value(
+        &mut self,
+        span: Range<usize>,
+        name: &str,
+        ty: &IpldType,
+        data: &Ipld,
+    ) -> Result<(), StreamError> {
+        let span = span.start..self.reader.last().end.max(span.end);
+        let mut path = self.path.clone();
+        self.schema
+            .validate_value(name, ty, data, self.adls, self.store, &mut path)
+            .map_err(|err| self.invalid(span, err))
+    }
+
+    /// Reads a value completely to validate it as a whole, like the substrate of an advanced
+    /// layout. Bytes are kept for the layouts.
+    fn buffered(&mut self, name: &str, ty: &IpldType) -> Result<(), StreamError> {
+        self.reader.keep_bytes(true);
+        let data = self
+            .token()
+            .and_then(|(span, token)| Ok((span, self.read(token)?)));
+        self.reader.keep_bytes(false);
+
+        let (span, data) = data?;
+        self.validate_value(span, name, ty, &data)
+    }
+
+    /// Selects the member of a kinded union by the kind of the next token
+    fn kinded(&mut self, name: &str, union: &UnionType) -> Result<(), StreamError> {
+        // Layouts of members may be read from bytes
+        let keep = union.members.iter().any(|member| {
+            matches!(
+                self.schema.resolve_inline(&member.ty).as_deref(),
+                Some(
+                    IpldType::Bytes(BytesRepresentation::Advanced(_))
+                        | IpldType::List(ListType {
+                            repr: ListRepresentation::Advanced(_),
+                            ..
+                        })
+                        | IpldType::Map(MapType {
+                            repr: MapRepresentation::Advanced(_),
+                            ..
+                        })
+                )
+            )
+        });
+        self.reader.keep_bytes(keep);
+        let next = self.token();
+        self.reader.keep_bytes(false);
+        let (span, token) = next?;
+
+        let kind = token.kind();
+        let Some(member) = union
+            .members
+            .iter()
+            .find(|member| member.kind() == Some(kind))
+        else {
+            let error = self.invalid_union(name, format!("no member is of kind {}", kind));
+            let span = self.value_span(span, token);
+            return Err(self.invalid(span, error));
+        };
+
+        self.pending = Some((span, token));
+        self.inline(&member.ty)
+    }
+
+    /// Validates the single entry of a keyed union as its member
+    fn keyed(&mut self, name: &str, union: &UnionType) -> Result<(), StreamError> {
+        let (span, token) = self.token()?;
+        let Token::Map(mut seq) = token else {
+            return Err(self.kind_error(span, name, Kind::Map, token));
+        };
+        let single = |this: &Self| this.invalid_union(name, "must have a single entry".to_string());
+
+        if !self.next_entry(&mut seq)? {
+            return Err(self.invalid(span.start..self.reader.last().end, single(self)));
+        }
+        let (key_span, key) = self.key()?;
+        let Some(member) = union.member(&key) else {
+            let error = self.invalid_union(name, format!("unknown member \"{}\"", key));
+            return Err(self.invalid(key_span, error));
+        };
+
+        self.path.push(&key);
+        self.inline(&member.ty)?;
+        self.path.pop();
+
+        if self.next_entry(&mut seq)? {
+            let error = single(self);
+            let rest = self
+                .key()
+                .and_then(|_| self.token())
+                .and_then(|(_, token)| self.skip(token))
+                .and_then(|_| self.skip(Token::Map(seq)));
+            let end = match rest {
+                Ok(()) => self.reader.last().end,
+                Err(_) => span.end,
+            };
+            return Err(self.invalid(span.start..end, error));
+        }
+        Ok(())
+    }
+
+    fn invalid_union(&self, name: &str, reason: String) -> ValidationError {
+        ValidationError::InvalidValue {
+            path: self.path.clone(),
+            ty: name.to_string(),
+            reason,
+        }
+    }
+
+    fn scalar(&mut self, name: &str, expected: Kind) -> Result<(), StreamError> {
+        let (span, token) = self.token()?;
+        if token.kind() == expected {
+            Ok(())
+        } else {
+            Err(self.kind_error(span, name, expected, token))
+        }
+    }
+
+    fn nullable(&mut self, ty: &InlineIpldType, nullable: bool) -> Result<(), StreamError> {
+        if nullable && self.reader.peek_null() {
+            self.token()?;
+            Ok(())
+        } else {
+            self.inline(ty)
+        }
+    }
+
+    fn list(&mut self, name: &str, list: &ListType) -> Result<(), StreamError> {
+        if let ListRepresentation::Advanced(_) = list.repr {
+            return self.buffered(name, &IpldType::List(list.clone()));
+        }
+        let (span, token) = self.token()?;
+        let Token::List(mut seq) = token else {
+            return Err(self.kind_error(span, name, Kind::List, token));
+        };
+
+        let mut idx = 0;
+        while self.next_entry(&mut seq)? {
+            self.path.push(idx);
+            self.nullable(&list.ty, list.nullable)?;
+            self.path.pop();
+            idx += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the start of a `listpairs` entry and its key
+    fn list_pair(
+        &mut self,
+        name: &str,
+        idx: usize,
+    ) -> Result<(Range<usize>, String, Seq), StreamError> {
+        self.path.push(idx);
+        let (span, token) = self.token()?;
+
+        let mut seq = match token {
+            Token::List(seq) => seq,
+            token => {
+                let err = self.kind_error(span, name, Kind::List, token);
+                self.path.pop();
+                return Err(err);
+            }
+        };
+
+        let key = if self.next_entry(&mut seq)? {
+            match self.token()? {
+                (span, Token::String(key)) => Some((span, key)),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        let has_value = key.is_some() && self.next_entry(&mut seq)?;
+
+        let result = match key {
+            Some((span, key)) if has_value => Ok((span, key, seq)),
+            _ => Err(self.invalid_pair(span, name)),
+        };
+        self.path.pop();
+        result
+    }
+
+    /// Checks, that a `listpairs` entry ends after its value
+    fn list_pair_end(&mut self, name: &str, idx: usize, mut seq: Seq) -> Result<(), StreamError> {
+        let span = self.reader.last();
+        if self.next_entry(&mut seq)? {
+            self.path.push(idx);
+            let err = self.invalid_pair(span, name);
+            self.path.pop();
+            Err(err)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn invalid_pair(&self, span: Range<usize>, name: &str) -> StreamError {
+        self.invalid(
+            span,
+            ValidationError::InvalidValue {
+                path: self.path.clone(),
+                ty: name.to_string(),
+                reason: "listpairs entries must be a list of a string key and a value".to_string(),
+            },
+        )
+    }
+
+    fn map(&mut self, name: &str, map: &MapType) -> Result<(), StreamError> {
+        if let MapRepresentation::Advanced(_) = map.repr {
+            return self.buffered(name, &IpldType::Map(map.clone()));
+        }
+        let (span, token) = self.token()?;
+
+        match (&map.repr, token) {
+            (MapRepresentation::Map, Token::Map(mut seq)) => {
+                while self.next_entry(&mut seq)? {
+                    let (span, key) = self.key()?;
+                    self.path.push(&key);
+                    self.schema
+                        .validate_key(&map.key, &key, &self.path)
+                        .map_err(|err| self.invalid(span, err))?;
+                    self.nullable(&map.value, map.nullable)?;
+                    self.path.pop();
+                }
+                Ok(())
+            }
+            (MapRepresentation::ListPairs, Token::List(mut seq)) => {
+                let mut idx = 0;
+                while self.next_entry(&mut seq)? {
+                    let (span, key, pair) = self.list_pair(name, idx)?;
+                    self.path.push(&key);
+                    self.schema
+                        .validate_key(&map.key, &key, &self.path)
+                        .map_err(|err| self.invalid(span, err))?;
+                    self.nullable(&map.value, map.nullable)?;
+                    self.path.pop();
+                    self.list_pair_end(name, idx, pair)?;
+                    idx += 1;
+                }
+                Ok(())
+            }
+            (MapRepresentation::StringPairs(pairs), Token::String(data)) => {
+                let entries = string_pairs(name, pairs, &Ipld::String(data), &self.path)
+                    .map_err(|err| self.invalid(span.clone(), err))?;
+                for (key, value) in entries {
+                    self.path.push(&key);
+                    let result = self
+                        .schema
+                        .validate_key(&map.key, &key, &self.path)
+                        .and_then(|_| {
+                            self.schema
+                                .validate_string_value(&map.value, &value, &self.path)
+                        });
+                    result.map_err(|err| self.invalid(span.clone(), err))?;
+                    self.path.pop();
+                }
+                Ok(())
+            }
+            (repr, token) => {
+                let expected = match repr {
+                    MapRepresentation::Map | MapRepresentation::Advanced(_) => Kind::Map,
+                    MapRepresentation::ListPairs => Kind::List,
+                    MapRepresentation::StringPairs(_) => Kind::String,
+                };
+                Err(self.kind_error(span, name, expected, token))
+            }
+        }
+    }
+
+    fn structure(&mut self, name: &str, stru: &StructType) -> Result<(), StreamError> {
+        let (span, token) = self.token()?;
+        let mut seen = BTreeSet::new();
+
+        match (&stru.repr, token) {
+            (StructRepresentation::Map, Token::Map(mut seq)) => {
+                while self.next_entry(&mut seq)? {
+                    let (span, key) = self.key()?;
+                    self.field(name, stru, span, key, &mut seen)?;
+                }
+            }
+            (StructRepresentation::ListPairs, Token::List(mut seq)) => {
+                let mut idx = 0;
+                while self.next_entry(&mut seq)? {
+                    let (span, key, pair) = self.list_pair(name, idx)?;
+                    self.field(name, stru, span, key, &mut seen)?;
+                    self.list_pair_end(name, idx, pair)?;
+                    idx += 1;
+                }
+            }
+            (StructRepresentation::Tuple, Token::List(mut seq)) => {
+                let mut idx = 0;
+                while self.next_entry(&mut seq)? {
+                    let Some(field) = stru.fields.get(idx) else {
+                        return Err(self.extra_values(name, stru, span, seq));
+                    };
+                    seen.insert(field_key(field).to_string());
+                    self.path.push(idx);
+                    self.nullable(&field.value, field.nullable)?;
+                    self.path.pop();
+                    idx += 1;
+                }
+            }
+            (StructRepresentation::StringPairs(pairs), Token::String(data)) => {
+                let entries = string_pairs(name, pairs, &Ipld::String(data), &self.path)
+                    .map_err(|err| self.invalid(span.clone(), err))?;
+                return self
+                    .schema
+                    .validate_struct_string_pairs(name, stru, entries, &mut self.path)
+                    .map_err(|err| self.invalid(span, err));
+            }
+            (repr, token) => {
+                let expected = match repr {
+                    StructRepresentation::Map => Kind::Map,
+                    StructRepresentation::ListPairs | StructRepresentation::Tuple => Kind::List,
+                    StructRepresentation::StringPairs(_) => Kind::String,
+                };
+                return Err(self.kind_error(span, name, expected, token));
+            }
+        }
+
+        let missing = stru
+            .fields
+            .iter()
+            .find(|field| field.is_required() && !seen.contains(field_key(field)));
+        match missing {
+            Some(field) => Err(self.invalid(
+                span.start..self.reader.last().end,
+                ValidationError::MissingField {
+                    path: self.path.clone(),
+                    ty: name.to_string(),
+                    field: field_key(field).to_string(),
+                },
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Skips the values of a tuple after its last field, which started at `span`, and
+    /// reports how many there are
+    fn extra_values(
+        &mut self,
+        name: &str,
+        stru: &StructType,
+        span: Range<usize>,
+        mut seq: Seq,
+    ) -> StreamError {
+        let mut count = stru.fields.len();
+        let mut more = true;
+        while more {
+            count += 1;
+            let skipped = self
+                .token()
+                .and_then(|(_, token)| self.skip(token))
+                .and_then(|_| self.next_entry(&mut seq));
+            match skipped {
+                Ok(next) => more = next,
+                Err(err) => return err,
+            }
+        }
+
+        let error = ValidationError::InvalidValue {
+            path: self.path.clone(),
+            ty: name.to_string(),
+            reason: format!(
+                "tuple has {} values, but only {} fields",
+                count,
+                stru.fields.len()
+            ),
+        };
+        self.invalid(span.start..self.reader.last().end, error)
+    }
+
+    /// Validates the value of the struct field `key`, which is at `span`
+    fn field(
+        &mut self,
+        name: &str,
+        stru: &StructType,
+        span: Range<usize>,
+        key: String,
+        seen: &mut BTreeSet<String>,
+    ) -> Result<(), StreamError> {
+        let Some(field) = stru.fields.iter().find(|field| field_key(field) == key) else {
+            return Err(self.invalid(
+                span,
+                ValidationError::UnexpectedField {
+                    path: self.path.clone(),
+                    ty: name.to_string(),
+                    field: key,
+                },
+            ));
+        };
+
+        self.path.push(&key);
+        if !seen.insert(key) {
+            return Err(self.malformed(ReadError {
+                span,
+                reason: "duplicate map key".to_string(),
+            }));
+        }
+        self.nullable(&field.value, field.nullable)?;
+        self.path.pop();
+        Ok(())
+    }
+
+    fn enumeration(&mut self, name: &str, enu: &EnumType) -> Result<(), StreamError> {
+        let (span, token) = self.token()?;
+        let data = match &token {
+            Token::String(value) => Ipld::String(value.clone()),
+            Token::Int(value) => Ipld::Integer(*value),
+            // Only the kind of other values is reported
+            token => kind_placeholder(token),
+        };
+
+        let result = validate_enum(name, enu, &data, &self.path);
+        result.map_err(|err| {
+            let span = self.value_span(span, token);
+            self.invalid(span, err)
+        })
+    }
+
+    fn unit(&mut self, name: &str, unit: &UnitRepresentation) -> Result<(), StreamError> {
+        let (span, token) = self.token()?;
+        let (valid, expected) = match (unit, &token) {
+            (UnitRepresentation::Null, token) => (*token == Token::Null, Kind::Null),
+            (UnitRepresentation::True, token) => (*token == Token::Bool(true), Kind::Bool),
+            (UnitRepresentation::False, token) => (*token == Token::Bool(false), Kind::Bool),
+            (UnitRepresentation::EmptyMap, Token::Map(seq)) => {
+                let mut seq = *seq;
+                (!self.next_entry(&mut seq)?, Kind::Map)
+            }
+            (UnitRepresentation::EmptyMap, _) => (false, Kind::Map),
+        };
+
+        if valid {
+            Ok(())
+        } else if token.kind() != expected {
+            Err(self.kind_error(span, name, expected, token))
+        } else {
+            let error = ValidationError::InvalidValue {
+                path: self.path.clone(),
+                ty: name.to_string(),
+                reason: "value does not match the unit representation".to_string(),
+            };
+            Err(self.invalid(span, error))
+        }
+    }
+}
+
+fn field_key(field: &StructField) -> &str {
+    field.rename.as_deref().unwrap_or(&field.key)
+}
+
+/// The value of `token`, floats are read as zero and lists and maps as empty
+fn kind_placeholder(token: &Token) -> Ipld {
+    match token {
+        Token::Null => Ipld::Null,
+        Token::Bool(value) => Ipld::Bool(*value),
+        Token::Int(value) => Ipld::Integer(*value),
+        Token::Float => Ipld::Float(0.0),
+        Token::String(value) => Ipld::String(value.clone()),
+        Token::Bytes(bytes) => Ipld::Bytes(bytes.clone()),
+        Token::Link(cid) => Ipld::Link(*cid),
+        Token::List(_) => Ipld::List(vec![]),
+        Token::Map(_) => Ipld::Map(Default::default()),
+    }
+}
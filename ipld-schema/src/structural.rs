@@ -7,10 +7,11 @@ use crate::{
     InlineIpldType, Rule,
 };
 
+/// A struct type with its fields in the order of the source
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct StructType {
-    fields: Vec<StructField>,
-    repr: StructRepresentation,
+pub struct StructType {
+    pub fields: Vec<StructField>,
+    pub repr: StructRepresentation,
 }
 
 pub(crate) fn parse_struct(stru: Pairs<Rule>) -> Result<StructType, IpldSchemaParseError> {
@@ -31,15 +32,36 @@ pub(crate) fn parse_struct(stru: Pairs<Rule>) -> Result<StructType, IpldSchemaPa
     })
 }
 
+impl StructType {
+    /// The number of leading fields in a tuple value.
+    ///
+    /// Tuple values are positional, so only the fields after the last required one can be left
+    /// out.
+    pub(crate) fn required_tuple_fields(&self) -> usize {
+        self.fields
+            .iter()
+            .rposition(StructField::is_required)
+            .map_or(0, |idx| idx + 1)
+    }
+}
+
+/// A field of a struct with its modifiers
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct StructField {
-    doc: Option<String>,
-    key: String,
-    value: InlineIpldType,
-    optional: bool,
-    nullable: bool,
-    rename: Option<String>,
-    implicit: Option<String>,
+pub struct StructField {
+    pub doc: Option<String>,
+    pub key: String,
+    pub value: InlineIpldType,
+    pub optional: bool,
+    pub nullable: bool,
+    pub rename: Option<String>,
+    pub implicit: Option<String>,
+}
+
+impl StructField {
+    /// Returns true, if the field has to be present in the data
+    pub(crate) fn is_required(&self) -> bool {
+        !self.optional && self.implicit.is_none()
+    }
 }
 
 fn parse_struct_field(mut field: Pairs<Rule>) -> Result<StructField, IpldSchemaParseError> {
@@ -121,13 +143,27 @@ fn parse_struct_field_representation(mut repr: Pairs<Rule>) -> (Option<String>,
     (rename, implicit)
 }
 
+/// How a struct is represented in the data model
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum StructRepresentation {
+pub enum StructRepresentation {
     Map,
     ListPairs,
+    /// A list of the field values in the order of the fields
+    Tuple,
     StringPairs(StringPairs),
 }
 
+impl StructRepresentation {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            StructRepresentation::Map => "map",
+            StructRepresentation::ListPairs => "listpairs",
+            StructRepresentation::Tuple => "tuple",
+            StructRepresentation::StringPairs(_) => "stringpairs",
+        }
+    }
+}
+
 fn parse_struct_representation(mut repr: Pairs<Rule>) -> StructRepresentation {
     let inner = repr.next().unwrap();
     assert!(repr.next().is_none());
@@ -135,6 +171,7 @@ fn parse_struct_representation(mut repr: Pairs<Rule>) -> StructRepresentation {
     match inner.as_str() {
         "map" => return StructRepresentation::Map,
         "listpairs" => return StructRepresentation::ListPairs,
+        "tuple" => return StructRepresentation::Tuple,
         _ => (),
     }
 
@@ -150,9 +187,23 @@ fn parse_struct_representation(mut repr: Pairs<Rule>) -> StructRepresentation {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::IpldSchema;
+    use crate::{
+        list::{ListRepresentation, ListType},
+        map::MapRepresentation,
+        map::MapType,
+        Doc, IpldSchema, IpldType,
+    };
     use std::collections::BTreeMap;
 
+    fn map_of(value: InlineIpldType, nullable: bool) -> InlineIpldType {
+        InlineIpldType::Map(Box::new(MapType {
+            key: "String".to_string(),
+            value,
+            nullable,
+            repr: MapRepresentation::Map,
+        }))
+    }
+
     #[test]
     fn structural() {
         let file = include_str!("../test/struct.ipldsch");
@@ -160,6 +211,112 @@ mod tests {
         let parsed_schema = IpldSchema::parse(file).unwrap();
         let mut expected_schema = IpldSchema(BTreeMap::new());
 
+        expected_schema.0.insert(
+            "ExampleWithAnonDefns".to_string(),
+            Doc {
+                doc: None,
+                ty: IpldType::Struct(StructType {
+                    fields: vec![
+                        StructField {
+                            doc: None,
+                            key: "fooField".to_string(),
+                            value: map_of(InlineIpldType::Name("String".to_string()), false),
+                            optional: true,
+                            nullable: false,
+                            rename: Some("foo_field".to_string()),
+                            implicit: None,
+                        },
+                        StructField {
+                            doc: None,
+                            key: "barField".to_string(),
+                            value: map_of(InlineIpldType::Name("String".to_string()), false),
+                            optional: true,
+                            nullable: true,
+                            rename: None,
+                            implicit: None,
+                        },
+                        StructField {
+                            doc: None,
+                            key: "bazField".to_string(),
+                            value: map_of(InlineIpldType::Name("String".to_string()), true),
+                            optional: false,
+                            nullable: false,
+                            rename: None,
+                            implicit: None,
+                        },
+                        StructField {
+                            doc: None,
+                            key: "wozField".to_string(),
+                            value: map_of(
+                                InlineIpldType::List(Box::new(ListType {
+                                    ty: InlineIpldType::Name("String".to_string()),
+                                    nullable: true,
+                                    repr: ListRepresentation::List,
+                                })),
+                                false,
+                            ),
+                            optional: false,
+                            nullable: false,
+                            rename: None,
+                            implicit: None,
+                        },
+                        StructField {
+                            doc: None,
+                            key: "boomField".to_string(),
+                            value: InlineIpldType::Link("ExampleWithNullable".to_string()),
+                            optional: false,
+                            nullable: false,
+                            rename: None,
+                            implicit: None,
+                        },
+                    ],
+                    repr: StructRepresentation::Map,
+                }),
+            },
+        );
+
+        expected_schema.0.insert(
+            "Foo".to_string(),
+            Doc {
+                doc: None,
+                ty: IpldType::Struct(StructType {
+                    fields: vec![
+                        StructField {
+                            doc: None,
+                            key: "x".to_string(),
+                            value: InlineIpldType::Name("Int".to_string()),
+                            optional: true,
+                            nullable: false,
+                            rename: Some("a".to_string()),
+                            implicit: None,
+                        },
+                        StructField {
+                            doc: Some("This value will not be included if it is empty".to_string()),
+                            key: "y".to_string(),
+                            value: InlineIpldType::Name("Int".to_string()),
+                            optional: false,
+                            nullable: false,
+                            rename: Some("b".to_string()),
+                            implicit: Some("0".to_string()),
+                        },
+                        StructField {
+                            doc: None,
+                            key: "msg".to_string(),
+                            value: InlineIpldType::Name("String".to_string()),
+                            optional: false,
+                            nullable: false,
+                            rename: None,
+                            implicit: None,
+                        },
+                    ],
+                    repr: StructRepresentation::StringPairs(StringPairs {
+                        inner_delim: "=".to_string(),
+                        entry_delim: ",".to_string(),
+                    }),
+                }),
+            },
+        );
+
         assert_eq!(parsed_schema, expected_schema);
     }
 }
@@ -0,0 +1,396 @@
+use std::fmt::Write;
+
+use crate::{
+    enumerate::{EnumMemberTag, EnumType},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::BytesRepresentation,
+    structural::{StructField, StructRepresentation, StructType},
+    union::{UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    InlineIpldType, IpldSchema, IpldType,
+};
+
+/// The TypeScript types of the prelude
+const TS_PRELUDE: &[(&str, &str)] = &[
+    ("Bool", "boolean"),
+    ("String", "string"),
+    ("Bytes", "{ \"/\": { bytes: string } }"),
+    ("Int", "number"),
+    ("Float", "number"),
+    ("Map", "{ [key: string]: unknown | null }"),
+    ("List", "Array<unknown | null>"),
+    ("Link", "{ \"/\": string }"),
+    ("Any", "unknown"),
+];
+
+impl IpldSchema {
+    /// Generates TypeScript declarations for all types of this schema.
+    ///
+    /// Other than the Rust code, the declarations model the representation of the types
+    /// as it is decoded from DAG-JSON, i.e. links are `{ "/": string }` objects.
+    pub fn generate_typescript(&self) -> String {
+        let mut out = String::new();
+        out.push_str("// Generated by ipld-schema, do not edit.\n");
+
+        for (name, def) in &self.0 {
+            out.push('\n');
+            write_doc(&mut out, def.doc.as_deref(), "");
+
+            match &def.ty {
+                IpldType::Struct(stru) if stru.repr == StructRepresentation::Map => {
+                    self.generate_interface(&mut out, name, stru)
+                }
+                ty => {
+                    let _ = writeln!(out, "export type {} = {};", name, self.ts_type(ty));
+                }
+            }
+        }
+
+        out
+    }
+
+    fn ts_type(&self, ty: &IpldType) -> String {
+        match ty {
+            IpldType::Bool => "boolean".to_string(),
+            IpldType::String => "string".to_string(),
+            IpldType::Bytes(BytesRepresentation::Bytes) => {
+                "{ \"/\": { bytes: string } }".to_string()
+            }
+            IpldType::Bytes(BytesRepresentation::Advanced(_)) => "unknown".to_string(),
+            IpldType::Int | IpldType::Float => "number".to_string(),
+            IpldType::Any => "unknown".to_string(),
+            IpldType::Link(_) => "{ \"/\": string }".to_string(),
+            IpldType::List(list) => self.ts_list(list),
+            IpldType::Map(map) => self.ts_map(map),
+            IpldType::Struct(stru) => self.ts_struct(stru),
+            IpldType::Union(union) => self.ts_union(union),
+            IpldType::Enum(enu) => ts_enum(enu),
+            IpldType::Unit(unit) => match unit {
+                UnitRepresentation::Null => "null".to_string(),
+                UnitRepresentation::True => "true".to_string(),
+                UnitRepresentation::False => "false".to_string(),
+                UnitRepresentation::EmptyMap => "Record<string, never>".to_string(),
+            },
+            IpldType::Copy(from) => self.ts_name(from),
+        }
+    }
+
+    fn ts_name(&self, name: &str) -> String {
+        self.target_name(name, TS_PRELUDE)
+    }
+
+    fn ts_inline(&self, ty: &InlineIpldType) -> String {
+        match ty {
+            InlineIpldType::Name(name) => self.ts_name(name),
+            InlineIpldType::List(list) => self.ts_list(list),
+            InlineIpldType::Map(map) => self.ts_map(map),
+            InlineIpldType::Link(_) => "{ \"/\": string }".to_string(),
+        }
+    }
+
+    fn ts_list(&self, list: &ListType) -> String {
+        match &list.repr {
+            ListRepresentation::List => ts_array(self.ts_nullable(&list.ty, list.nullable)),
+            ListRepresentation::Advanced(_) => "unknown".to_string(),
+        }
+    }
+
+    fn ts_nullable(&self, ty: &InlineIpldType, nullable: bool) -> String {
+        if nullable {
+            format!("{} | null", self.ts_inline(ty))
+        } else {
+            self.ts_inline(ty)
+        }
+    }
+
+    fn ts_map(&self, map: &MapType) -> String {
+        let value = self.ts_nullable(&map.value, map.nullable);
+
+        match &map.repr {
+            MapRepresentation::Map => {
+                // Keys of string enums are restricted to their members
+                let is_string_enum = self
+                    .resolve_inline(&InlineIpldType::Name(map.key.clone()))
+                    .is_some_and(
+                        |key| matches!(key.as_ref(), IpldType::Enum(enu) if enu.is_string()),
+                    );
+
+                if is_string_enum {
+                    format!("{{ [key in {}]?: {} }}", map.key, value)
+                } else {
+                    format!("{{ [key: string]: {} }}", value)
+                }
+            }
+            MapRepresentation::ListPairs => {
+                format!("Array<[{}, {}]>", self.ts_name(&map.key), value)
+            }
+            MapRepresentation::StringPairs(_) => "string".to_string(),
+            MapRepresentation::Advanced(_) => "unknown".to_string(),
+        }
+    }
+
+    /// Structs, that are not represented as maps, are generated as type aliases
+    fn ts_struct(&self, stru: &StructType) -> String {
+        match &stru.repr {
+            StructRepresentation::Map => {
+                unreachable!("Structs represented as maps are generated as interfaces")
+            }
+            StructRepresentation::ListPairs if stru.fields.is_empty() => "[]".to_string(),
+            StructRepresentation::ListPairs => {
+                let entries = stru
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let key = field.rename.as_deref().unwrap_or(&field.key);
+                        format!(
+                            "[{:?}, {}]",
+                            key,
+                            self.ts_nullable(&field.value, field.nullable)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!("Array<{}>", entries.join(" | "))
+            }
+            StructRepresentation::StringPairs(_) => "string".to_string(),
+            StructRepresentation::Tuple => {
+                let required = stru.required_tuple_fields();
+                let fields = stru
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, field)| {
+                        let ty = self.ts_nullable(&field.value, field.nullable);
+                        match idx < required {
+                            true => ty,
+                            false if ty.contains(" | ") => format!("({})?", ty),
+                            false => format!("{}?", ty),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                format!("[{}]", fields.join(", "))
+            }
+        }
+    }
+
+    /// Unions are discriminated by a key, their kind, or a discriminant next to the member
+    fn ts_union(&self, union: &UnionType) -> String {
+        if union.members.is_empty() {
+            return "never".to_string();
+        }
+
+        union
+            .members
+            .iter()
+            .map(|member| {
+                let ty = self.ts_inline(&member.ty);
+                match &union.repr {
+                    UnionRepresentation::Kinded => ty,
+                    UnionRepresentation::Keyed => {
+                        format!("{{ {}: {} }}", ts_key(&member.discriminant), ty)
+                    }
+                    UnionRepresentation::Envelope {
+                        discriminant_key,
+                        content_key,
+                    } => format!(
+                        "{{ {}: {:?}; {}: {} }}",
+                        ts_key(discriminant_key),
+                        member.discriminant,
+                        ts_key(content_key),
+                        ty
+                    ),
+                    UnionRepresentation::Inline { discriminant_key } => format!(
+                        "{} & {{ {}: {:?} }}",
+                        ty,
+                        ts_key(discriminant_key),
+                        member.discriminant
+                    ),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    fn generate_interface(&self, out: &mut String, name: &str, stru: &StructType) {
+        let _ = writeln!(out, "export interface {} {{", name);
+        for field in &stru.fields {
+            write_doc(out, field_doc(field).as_deref(), "  ");
+
+            let key = field.rename.as_deref().unwrap_or(&field.key);
+            let optional = field.optional || field.implicit.is_some();
+            let _ = writeln!(
+                out,
+                "  {}{}: {};",
+                ts_key(key),
+                if optional { "?" } else { "" },
+                self.ts_nullable(&field.value, field.nullable)
+            );
+        }
+        out.push_str("}\n");
+    }
+}
+
+/// The documentation of a field, followed by its implicit value
+fn field_doc(field: &StructField) -> Option<String> {
+    let implicit = field
+        .implicit
+        .as_ref()
+        .map(|implicit| format!("Omitted if it is `{}`", implicit));
+    match (&field.doc, implicit) {
+        (Some(doc), Some(implicit)) => Some(format!("{}\n\n{}", doc, implicit)),
+        (doc, implicit) => doc.clone().or(implicit),
+    }
+}
+
+fn ts_enum(enu: &EnumType) -> String {
+    if enu.members.is_empty() {
+        return "never".to_string();
+    }
+
+    enu.members
+        .iter()
+        .map(|member| match &member.tag {
+            EnumMemberTag::String(tag) => format!("{:?}", tag),
+            EnumMemberTag::Int(tag) => tag.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Element types with unions need parentheses, which is easier to read as a generic
+fn ts_array(ty: String) -> String {
+    if ty.contains(" | ") {
+        format!("Array<{}>", ty)
+    } else {
+        format!("{}[]", ty)
+    }
+}
+
+/// Keys, that are not valid identifiers, are quoted
+fn ts_key(key: &str) -> String {
+    let is_identifier = key.chars().enumerate().all(|(idx, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || (idx != 0 && c.is_ascii_digit())
+    });
+
+    if is_identifier && !key.is_empty() {
+        key.to_string()
+    } else {
+        format!("{:?}", key)
+    }
+}
+
+/// Writes a JSDoc comment, that is not ended early by a `*/` in the documentation
+fn write_doc(out: &mut String, doc: Option<&str>, indent: &str) {
+    let Some(doc) = doc else {
+        return;
+    };
+    let doc = doc.replace("*/", "*\\/");
+
+    if !doc.contains('\n') {
+        let _ = writeln!(out, "{}/** {} */", indent, doc);
+        return;
+    }
+
+    let _ = writeln!(out, "{}/**", indent);
+    for line in doc.lines() {
+        let _ = writeln!(out, "{}", format!("{} * {}", indent, line).trim_end());
+    }
+    let _ = writeln!(out, "{} */", indent);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_typescript() {
+        let schema = IpldSchema::parse(include_str!("../test/message.ipldsch")).unwrap();
+
+        let expected = "\
+// Generated by ipld-schema, do not edit.
+
+export type Identity = string;
+
+/** A message */
+export interface Message {
+  /** Who sent it */
+  from: { \"/\": string };
+  to: Array<Identity | null> | null;
+  s: Status;
+  /** Omitted if it is `0` */
+  count?: number;
+  labels?: { [key in Status]?: number };
+  d: { \"/\": { bytes: string } };
+}
+
+export type Status = \"Act\" | \"Gone\";
+";
+
+        assert_eq!(schema.generate_typescript(), expected);
+    }
+
+    #[test]
+    fn unions_and_tuples() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+        let code = schema.generate_typescript();
+
+        for declaration in [
+            "export type Point = [number, number, string?];",
+            "export type Shape = { circle: Circle } | { square: { \"/\": string } };",
+            "export type Value = number | Shape | Point;",
+            "export type Tagged = Circle & { tag: \"c\" };",
+            "export type Wrapped = { tag: \"c\"; content: Circle } | { tag: \"s\"; content: Square };",
+        ] {
+            assert!(code.contains(declaration), "{}", code);
+        }
+    }
+
+    #[test]
+    fn representations_and_docs() {
+        let schema = IpldSchema::parse(
+            "\
+type Code enum {
+  | Ok (\"0\")
+  | Failed (\"1\")
+} representation int
+
+type Pairs struct {
+  name String
+  value optional Any
+  list List
+} representation listpairs
+
+type Nothing unit representation emptymap
+
+# Ends with */ here
+type Counter struct {
+  # The count
+  count Int (implicit \"0\")
+}
+",
+        )
+        .unwrap();
+
+        let expected = "\
+// Generated by ipld-schema, do not edit.
+
+export type Code = 0 | 1;
+
+/** Ends with *\\/ here */
+export interface Counter {
+  /**
+   * The count
+   *
+   * Omitted if it is `0`
+   */
+  count?: number;
+}
+
+export type Nothing = Record<string, never>;
+
+export type Pairs = Array<[\"name\", string] | [\"value\", unknown] | [\"list\", Array<unknown | null>]>;
+";
+
+        assert_eq!(schema.generate_typescript(), expected);
+    }
+}
@@ -0,0 +1,350 @@
+use ipld_core::ipld::Ipld;
+use pest::iterators::Pairs;
+use thiserror::Error;
+
+use crate::{
+    comment::parse_comment,
+    parse::{parse_type_reference, IpldSchemaParseError},
+    InlineIpldType, Kind, Rule,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidUnion {
+    #[error("Member \"{0}\" of a kinded union must be discriminated by a kind")]
+    ExpectedKind(String),
+    #[error("Member \"{0}\" of a {1} union must be discriminated by a quoted string")]
+    ExpectedKey(String, &'static str),
+}
+
+/// A union type with its members in the order of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnionType {
+    pub members: Vec<UnionMember>,
+    pub repr: UnionRepresentation,
+}
+
+impl UnionType {
+    /// Finds the member, that is selected by `discriminant` in the data
+    pub(crate) fn member(&self, discriminant: &str) -> Option<&UnionMember> {
+        self.members
+            .iter()
+            .find(|member| member.discriminant == discriminant)
+    }
+
+    /// Represents `value` of `member` as a value of this union
+    pub(crate) fn wrap(&self, member: &UnionMember, value: Ipld) -> Ipld {
+        let discriminant = Ipld::String(member.discriminant.clone());
+        match &self.repr {
+            UnionRepresentation::Kinded => value,
+            UnionRepresentation::Keyed => Ipld::Map([(member.discriminant.clone(), value)].into()),
+            UnionRepresentation::Envelope {
+                discriminant_key,
+                content_key,
+            } => Ipld::Map(
+                [
+                    (discriminant_key.clone(), discriminant),
+                    (content_key.clone(), value),
+                ]
+                .into(),
+            ),
+            UnionRepresentation::Inline { discriminant_key } => {
+                let mut entries = match value {
+                    Ipld::Map(entries) => entries,
+                    _ => Default::default(),
+                };
+                entries.insert(discriminant_key.clone(), discriminant);
+                Ipld::Map(entries)
+            }
+        }
+    }
+
+    /// Finds the member of a value and the data it holds, without validating the value.
+    ///
+    /// Keyed and envelope unions hold the member under a key, that is returned with it.
+    /// Kinded and inline unions hold the member in place.
+    pub(crate) fn member_of<'d>(
+        &self,
+        data: &'d Ipld,
+    ) -> Option<(&UnionMember, Option<&'d str>, &'d Ipld)> {
+        let entries = match (&self.repr, data) {
+            (UnionRepresentation::Kinded, data) => {
+                let kind = Kind::of(data);
+                let member = self
+                    .members
+                    .iter()
+                    .find(|member| member.kind() == Some(kind))?;
+                return Some((member, None, data));
+            }
+            (_, Ipld::Map(entries)) => entries,
+            _ => return None,
+        };
+
+        let discriminant = |key: &str| match entries.get(key) {
+            Some(Ipld::String(discriminant)) => self.member(discriminant),
+            _ => None,
+        };
+        match &self.repr {
+            UnionRepresentation::Kinded => unreachable!("kinded unions are matched above"),
+            UnionRepresentation::Keyed if entries.len() == 1 => {
+                let (key, value) = entries.iter().next()?;
+                Some((self.member(key)?, Some(key), value))
+            }
+            UnionRepresentation::Keyed => None,
+            UnionRepresentation::Envelope {
+                discriminant_key,
+                content_key,
+            } => {
+                let (key, value) = entries.get_key_value(content_key)?;
+                Some((discriminant(discriminant_key)?, Some(key), value))
+            }
+            UnionRepresentation::Inline { discriminant_key } => {
+                Some((discriminant(discriminant_key)?, None, data))
+            }
+        }
+    }
+}
+
+/// A member of a union
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnionMember {
+    pub doc: Option<String>,
+    /// The name of the member type or a link to it
+    pub ty: InlineIpldType,
+    /// The key, kind or discriminant value, that selects the member in the data
+    pub discriminant: String,
+}
+
+impl UnionMember {
+    /// The name of the member type, links are named by their target
+    pub(crate) fn name(&self) -> &str {
+        match &self.ty {
+            InlineIpldType::Name(name) | InlineIpldType::Link(name) => name,
+            InlineIpldType::List(_) | InlineIpldType::Map(_) => {
+                unreachable!("union members are type names or links")
+            }
+        }
+    }
+
+    /// The kind, that selects the member of a kinded union
+    pub(crate) fn kind(&self) -> Option<Kind> {
+        Kind::from_name(&self.discriminant)
+    }
+}
+
+/// How the member of a union is selected in the data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnionRepresentation {
+    /// A map with a single entry, whose key selects the member
+    Keyed,
+    /// The kind of the data selects the member
+    Kinded,
+    /// The member is a map, that contains the discriminant next to its fields
+    Inline { discriminant_key: String },
+    /// A map of the discriminant and the member under separate keys
+    Envelope {
+        discriminant_key: String,
+        content_key: String,
+    },
+}
+
+impl UnionRepresentation {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            UnionRepresentation::Keyed => "keyed",
+            UnionRepresentation::Kinded => "kinded",
+            UnionRepresentation::Inline { .. } => "inline",
+            UnionRepresentation::Envelope { .. } => "envelope",
+        }
+    }
+}
+
+pub(crate) fn parse_union(union: Pairs<Rule>) -> Result<UnionType, IpldSchemaParseError> {
+    let mut members = vec![];
+    let mut repr = None;
+
+    for pair in union {
+        match pair.as_rule() {
+            Rule::union_member => members.push(parse_union_member(pair.into_inner())?),
+            Rule::union_repr => repr = Some(parse_union_representation(pair.into_inner())),
+            _ => panic!("Expected union_member or union_repr"),
+        }
+    }
+
+    let repr = repr.unwrap();
+    for (member, is_kind) in &members {
+        match (&repr, is_kind) {
+            (UnionRepresentation::Kinded, false) => {
+                let name = member.name().to_string();
+                return Err(IpldSchemaParseError::Union(InvalidUnion::ExpectedKind(
+                    name,
+                )));
+            }
+            (UnionRepresentation::Kinded, true) | (_, false) => (),
+            (repr, true) => {
+                let name = member.name().to_string();
+                return Err(IpldSchemaParseError::Union(InvalidUnion::ExpectedKey(
+                    name,
+                    repr.name(),
+                )));
+            }
+        }
+    }
+
+    Ok(UnionType {
+        members: members.into_iter().map(|(member, _)| member).collect(),
+        repr,
+    })
+}
+
+/// Parses a member and whether it is discriminated by a kind instead of a quoted string
+fn parse_union_member(
+    mut member: Pairs<Rule>,
+) -> Result<(UnionMember, bool), IpldSchemaParseError> {
+    let doc = if member.peek().unwrap().as_rule() == Rule::comment {
+        Some(parse_comment(member.next().unwrap().into_inner()))
+    } else {
+        None
+    };
+
+    let ty = member.next().unwrap();
+    assert_eq!(ty.as_rule(), Rule::union_member_type);
+    let ty = ty.into_inner().next().unwrap();
+    let ty = match ty.as_rule() {
+        Rule::link_def => InlineIpldType::Link(parse_type_reference(ty.into_inner())?),
+        _ => InlineIpldType::Name(ty.as_str().to_string()),
+    };
+
+    let discriminant = member.next().unwrap();
+    assert!(member.next().is_none());
+    let is_kind = discriminant.as_rule() == Rule::union_kind;
+    let discriminant = match is_kind {
+        true => discriminant.as_str().to_string(),
+        false => parse_union_key(discriminant.into_inner()),
+    };
+
+    Ok((
+        UnionMember {
+            doc,
+            ty,
+            discriminant,
+        },
+        is_kind,
+    ))
+}
+
+fn parse_union_key(mut key: Pairs<Rule>) -> String {
+    let value = key.next().unwrap();
+    assert!(key.next().is_none());
+    assert_eq!(value.as_rule(), Rule::union_key_value);
+    value.as_str().to_string()
+}
+
+fn parse_union_representation(mut repr: Pairs<Rule>) -> UnionRepresentation {
+    let inner = repr.next().unwrap();
+    assert!(repr.next().is_none());
+
+    match inner.as_str() {
+        "keyed" => return UnionRepresentation::Keyed,
+        "kinded" => return UnionRepresentation::Kinded,
+        _ => (),
+    }
+
+    // Inline or envelope, which name their keys
+    let inner = inner.into_inner().next().unwrap();
+    let rule = inner.as_rule();
+    let mut keys = inner
+        .into_inner()
+        .map(|key| parse_union_key(key.into_inner().next().unwrap().into_inner()));
+    let discriminant_key = keys.next().unwrap();
+
+    match rule {
+        Rule::union_inline_repr => UnionRepresentation::Inline { discriminant_key },
+        Rule::union_envelope_repr => UnionRepresentation::Envelope {
+            discriminant_key,
+            content_key: keys.next().unwrap(),
+        },
+        _ => panic!("Expected union_inline_repr or union_envelope_repr"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IpldSchema, IpldType};
+
+    #[test]
+    fn union() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+
+        let IpldType::Union(shape) = &schema.0["Shape"].ty else {
+            panic!("Shape is a union");
+        };
+        assert_eq!(
+            shape.members,
+            vec![
+                UnionMember {
+                    doc: Some("A circle".to_string()),
+                    ty: InlineIpldType::Name("Circle".to_string()),
+                    discriminant: "circle".to_string(),
+                },
+                UnionMember {
+                    doc: None,
+                    ty: InlineIpldType::Link("Square".to_string()),
+                    discriminant: "square".to_string(),
+                },
+            ]
+        );
+        assert_eq!(shape.repr, UnionRepresentation::Keyed);
+
+        let IpldType::Union(value) = &schema.0["Value"].ty else {
+            panic!("Value is a union");
+        };
+        assert_eq!(value.member("map").unwrap().kind(), Some(Kind::Map));
+        let data = Ipld::List(vec![]);
+        assert_eq!(
+            value.member_of(&data),
+            Some((&value.members[2], None, &data))
+        );
+        let data = shape.wrap(&shape.members[1], Ipld::Null);
+        assert_eq!(data, Ipld::Map([("square".to_string(), Ipld::Null)].into()));
+        assert_eq!(
+            shape.member_of(&data),
+            Some((&shape.members[1], Some("square"), &Ipld::Null))
+        );
+        assert_eq!(
+            schema.0["Wrapped"].ty,
+            IpldType::Union(UnionType {
+                members: vec![
+                    UnionMember {
+                        doc: None,
+                        ty: InlineIpldType::Name("Circle".to_string()),
+                        discriminant: "c".to_string(),
+                    },
+                    UnionMember {
+                        doc: None,
+                        ty: InlineIpldType::Name("Square".to_string()),
+                        discriminant: "s".to_string(),
+                    },
+                ],
+                repr: UnionRepresentation::Envelope {
+                    discriminant_key: "tag".to_string(),
+                    content_key: "content".to_string(),
+                },
+            })
+        );
+
+        assert_eq!(
+            IpldSchema::parse("type Foo union {\n  | Int \"int\"\n} representation kinded"),
+            Err(IpldSchemaParseError::Union(InvalidUnion::ExpectedKind(
+                "Int".to_string()
+            )))
+        );
+        assert_eq!(
+            IpldSchema::parse("type Foo union {\n  | Int int\n} representation keyed"),
+            Err(IpldSchemaParseError::Union(InvalidUnion::ExpectedKey(
+                "Int".to_string(),
+                "keyed"
+            )))
+        );
+    }
+}
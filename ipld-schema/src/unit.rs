@@ -2,26 +2,34 @@ use pest::iterators::Pairs;
 
 use crate::{parse::IpldSchemaParseError, IpldType, Rule};
 
+/// The single value, that represents a unit type
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) enum UnitRepresentation {
+pub enum UnitRepresentation {
     Null,
     True,
     False,
     EmptyMap,
 }
 
+impl UnitRepresentation {
+    /// Reads the representation as it is written in the DSL, i.e. `emptymap`
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "null" => Some(UnitRepresentation::Null),
+            "false" => Some(UnitRepresentation::False),
+            "true" => Some(UnitRepresentation::True),
+            "emptymap" => Some(UnitRepresentation::EmptyMap),
+            _ => None,
+        }
+    }
+}
+
 pub(crate) fn parse_unit(mut unit: Pairs<Rule>) -> Result<IpldType, IpldSchemaParseError> {
     let inner = unit.next().unwrap();
     assert!(unit.next().is_none());
     assert_eq!(inner.as_rule(), Rule::unit_repr);
 
-    let repr = match inner.as_str() {
-        "null" => UnitRepresentation::Null,
-        "false" => UnitRepresentation::False,
-        "true" => UnitRepresentation::True,
-        "emptymap" => UnitRepresentation::EmptyMap,
-        _ => panic!(),
-    };
-
+    let repr =
+        UnitRepresentation::from_name(inner.as_str()).expect("unit_repr has no other values");
     Ok(IpldType::Unit(repr))
 }
@@ -0,0 +1,917 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use ipld_core::ipld::Ipld;
+use thiserror::Error;
+
+use crate::{
+    adl::{builtin, read_advanced, AdlRegistry},
+    enumerate::{EnumMemberTag, EnumRepresentation, EnumType},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::{BytesRepresentation, StringPairs},
+    store::BlockStore,
+    structural::{StructRepresentation, StructType},
+    union::{UnionMember, UnionRepresentation, UnionType},
+    unit::UnitRepresentation,
+    InlineIpldType, IpldSchema, IpldType, Kind,
+};
+
+/// The position of a value inside of a data model tree.
+///
+/// Segments are map keys or list indices as they appear in the data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataPath(Vec<String>);
+
+impl DataPath {
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    pub(crate) fn push(&mut self, segment: impl ToString) {
+        self.0.push(segment.to_string());
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.0.pop();
+    }
+}
+
+impl Display for DataPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            f.write_str("(root)")
+        } else {
+            f.write_str(&self.0.join("/"))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationError {
+    #[error("Type \"{0}\" is not defined")]
+    UnknownType(String),
+    #[error("Type \"{0}\" is a copy of itself")]
+    CopyCycle(String),
+    #[error("Expected {expected} for {ty} at {path}, found {found}")]
+    Kind {
+        path: DataPath,
+        ty: String,
+        expected: Kind,
+        found: Kind,
+    },
+    #[error("Missing field \"{field}\" of {ty} at {path}")]
+    MissingField {
+        path: DataPath,
+        ty: String,
+        field: String,
+    },
+    #[error("Unexpected field \"{field}\" in {ty} at {path}")]
+    UnexpectedField {
+        path: DataPath,
+        ty: String,
+        field: String,
+    },
+    #[error("Invalid value for {ty} at {path}: {reason}")]
+    InvalidValue {
+        path: DataPath,
+        ty: String,
+        reason: String,
+    },
+}
+
+impl IpldSchema {
+    /// Checks, whether `data` is a valid representation of the type `root` of this schema.
+    ///
+    /// Links are only checked to be links, the linked data is not loaded. Of data in advanced
+    /// layouts, only the part in `data` is checked. Only the layouts of this crate are known,
+    /// see [`IpldSchema::validate_with`].
+    pub fn validate(&self, root: &str, data: &Ipld) -> Result<(), ValidationError> {
+        self.validate_with(root, data, builtin(), None)
+    }
+
+    /// Like [`IpldSchema::validate`], but reads data in advanced layouts through `store`
+    pub fn validate_with_store(
+        &self,
+        root: &str,
+        data: &Ipld,
+        store: &dyn BlockStore,
+    ) -> Result<(), ValidationError> {
+        self.validate_with(root, data, builtin(), Some(store))
+    }
+
+    /// Like [`IpldSchema::validate`], but reads data in advanced layouts through the logical
+    /// view of the layouts in `adls`, which may load blocks from `store`
+    pub fn validate_with(
+        &self,
+        root: &str,
+        data: &Ipld,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+    ) -> Result<(), ValidationError> {
+        let validator = Validator {
+            schema: self,
+            adls,
+            store,
+        };
+        validator.validate_named(root, data, &mut DataPath::default())
+    }
+
+    /// Looks up the type `name` to validate data against, following copies
+    pub(crate) fn resolve_named(&self, name: &str) -> Result<Cow<'_, IpldType>, ValidationError> {
+        if let Some(ty) = self.resolve_inline(&InlineIpldType::Name(name.to_string())) {
+            return Ok(ty);
+        }
+
+        // Either one of the copies is not defined, or they form a cycle
+        let mut current = name;
+        for _ in 0..=self.0.len() {
+            match self.resolve(current) {
+                Some(Cow::Borrowed(IpldType::Copy(from))) => current = from,
+                Some(_) => break,
+                None => return Err(ValidationError::UnknownType(current.to_string())),
+            }
+        }
+        Err(ValidationError::CopyCycle(name.to_string()))
+    }
+
+    /// Map keys are always strings in the data model, so we check that the key type
+    /// can be parsed from the string
+    pub(crate) fn validate_key(
+        &self,
+        ty: &str,
+        key: &str,
+        path: &DataPath,
+    ) -> Result<(), ValidationError> {
+        self.validate_string_value(&InlineIpldType::Name(ty.to_string()), key, path)
+    }
+
+    /// Checks a value that is stored as a string, i.e. in map keys or `stringpairs` representations
+    pub(crate) fn validate_string_value(
+        &self,
+        ty: &InlineIpldType,
+        value: &str,
+        path: &DataPath,
+    ) -> Result<(), ValidationError> {
+        let invalid = |reason: &str| ValidationError::InvalidValue {
+            path: path.clone(),
+            ty: ty.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let InlineIpldType::Name(name) = ty else {
+            return Err(invalid("type can not be represented as a string"));
+        };
+        match self.resolve_named(name)?.as_ref() {
+            IpldType::String | IpldType::Any => Ok(()),
+            IpldType::Int => value
+                .parse::<i128>()
+                .map(|_| ())
+                .map_err(|_| invalid("not an integer")),
+            IpldType::Float => value
+                .parse::<f64>()
+                .map(|_| ())
+                .map_err(|_| invalid("not a float")),
+            IpldType::Bool => value
+                .parse::<bool>()
+                .map(|_| ())
+                .map_err(|_| invalid("not a boolean")),
+            IpldType::Enum(enu) if enu.repr == EnumRepresentation::String => {
+                validate_enum(name, enu, &Ipld::String(value.to_string()), path)
+            }
+            _ => Err(invalid("type can not be represented as a string")),
+        }
+    }
+
+    pub(crate) fn validate_struct_string_pairs(
+        &self,
+        name: &str,
+        stru: &StructType,
+        entries: Vec<(String, String)>,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        let entries: BTreeMap<String, String> = entries.into_iter().collect();
+
+        for field in &stru.fields {
+            let key = field.rename.as_deref().unwrap_or(&field.key);
+            match entries.get(key) {
+                Some(value) => {
+                    path.push(key);
+                    self.validate_string_value(&field.value, value, path)?;
+                    path.pop();
+                }
+                None if field.optional || field.implicit.is_some() => (),
+                None => {
+                    return Err(ValidationError::MissingField {
+                        path: path.clone(),
+                        ty: name.to_string(),
+                        field: key.to_string(),
+                    })
+                }
+            }
+        }
+
+        check_unexpected_fields(name, stru, entries.keys().map(String::as_str), path)
+    }
+
+    /// Validates a value of `ty`, that was read completely, through the layouts in `adls`
+    pub(crate) fn validate_value(
+        &self,
+        name: &str,
+        ty: &IpldType,
+        data: &Ipld,
+        adls: &AdlRegistry,
+        store: Option<&dyn BlockStore>,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        let validator = Validator {
+            schema: self,
+            adls,
+            store,
+        };
+        validator.validate_type(name, ty, data, path)
+    }
+}
+
+/// Validates data, reading advanced layouts through the registered layouts
+struct Validator<'a> {
+    schema: &'a IpldSchema,
+    adls: &'a AdlRegistry,
+    store: Option<&'a dyn BlockStore>,
+}
+
+impl Validator<'_> {
+    fn validate_named(
+        &self,
+        name: &str,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        let ty = self.schema.resolve_named(name)?;
+        self.validate_type(name, &ty, data, path)
+    }
+
+    fn validate_inline(
+        &self,
+        ty: &InlineIpldType,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        match ty {
+            InlineIpldType::Name(name) => self.validate_named(name, data, path),
+            InlineIpldType::List(list) => self.validate_list(&ty.to_string(), list, data, path),
+            InlineIpldType::Map(map) => self.validate_map(&ty.to_string(), map, data, path),
+            InlineIpldType::Link(_) => expect_kind(&ty.to_string(), Kind::Link, data, path),
+        }
+    }
+
+    fn validate_type(
+        &self,
+        name: &str,
+        ty: &IpldType,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        match ty {
+            IpldType::Bool => expect_kind(name, Kind::Bool, data, path),
+            IpldType::String => expect_kind(name, Kind::String, data, path),
+            IpldType::Bytes(BytesRepresentation::Bytes) => {
+                expect_kind(name, Kind::Bytes, data, path)
+            }
+            IpldType::Bytes(BytesRepresentation::Advanced(adl)) => {
+                self.read_advanced(name, adl, Kind::Bytes, data, path)?;
+                Ok(())
+            }
+            IpldType::Int => expect_kind(name, Kind::Int, data, path),
+            IpldType::Float => expect_kind(name, Kind::Float, data, path),
+            IpldType::Link(_) => expect_kind(name, Kind::Link, data, path),
+            IpldType::Any => Ok(()),
+            IpldType::List(list) => self.validate_list(name, list, data, path),
+            IpldType::Map(map) => self.validate_map(name, map, data, path),
+            IpldType::Struct(stru) => self.validate_struct(name, stru, data, path),
+            IpldType::Union(union) => self.validate_union(name, union, data, path),
+            IpldType::Enum(enu) => validate_enum(name, enu, data, path),
+            IpldType::Unit(unit) => validate_unit(name, unit, data, path),
+            IpldType::Copy(_) => unreachable!("copies are resolved by validate_named"),
+        }
+    }
+
+    fn validate_nullable(
+        &self,
+        ty: &InlineIpldType,
+        nullable: bool,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        if nullable && *data == Ipld::Null {
+            Ok(())
+        } else {
+            self.validate_inline(ty, data, path)
+        }
+    }
+
+    fn validate_list(
+        &self,
+        name: &str,
+        list: &ListType,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        let logical;
+        let data = match &list.repr {
+            ListRepresentation::List => data,
+            ListRepresentation::Advanced(adl) => {
+                logical = self.read_advanced(name, adl, Kind::List, data, path)?;
+                &logical
+            }
+        };
+        let Ipld::List(values) = data else {
+            return Err(kind_error(name, Kind::List, data, path));
+        };
+
+        for (idx, value) in values.iter().enumerate() {
+            path.push(idx);
+            self.validate_nullable(&list.ty, list.nullable, value, path)?;
+            path.pop();
+        }
+
+        Ok(())
+    }
+
+    fn validate_map(
+        &self,
+        name: &str,
+        map: &MapType,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        let logical;
+        let entries = match &map.repr {
+            MapRepresentation::Map => match data {
+                Ipld::Map(entries) => entries.iter().collect(),
+                _ => return Err(kind_error(name, Kind::Map, data, path)),
+            },
+            MapRepresentation::ListPairs => list_pairs(name, data, path)?,
+            MapRepresentation::Advanced(adl) => {
+                logical = self.read_advanced(name, adl, Kind::Map, data, path)?;
+                match &logical {
+                    Ipld::Map(entries) => entries.iter().collect(),
+                    _ => unreachable!("read_advanced checks the kind"),
+                }
+            }
+            MapRepresentation::StringPairs(pairs) => {
+                for (key, value) in string_pairs(name, pairs, data, path)? {
+                    path.push(&key);
+                    self.schema.validate_key(&map.key, &key, path)?;
+                    self.schema
+                        .validate_string_value(&map.value, &value, path)?;
+                    path.pop();
+                }
+                return Ok(());
+            }
+        };
+
+        for (key, value) in entries {
+            path.push(key);
+            self.schema.validate_key(&map.key, key, path)?;
+            self.validate_nullable(&map.value, map.nullable, value, path)?;
+            path.pop();
+        }
+
+        Ok(())
+    }
+
+    fn read_advanced(
+        &self,
+        name: &str,
+        adl: &str,
+        expected: Kind,
+        data: &Ipld,
+        path: &DataPath,
+    ) -> Result<Ipld, ValidationError> {
+        read_advanced(self.adls, name, adl, expected, data, self.store, path)
+    }
+
+    fn validate_struct(
+        &self,
+        name: &str,
+        stru: &StructType,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        let entries: BTreeMap<&str, &Ipld> = match &stru.repr {
+            StructRepresentation::Map => match data {
+                Ipld::Map(entries) => entries.iter().map(|(k, v)| (k.as_str(), v)).collect(),
+                _ => return Err(kind_error(name, Kind::Map, data, path)),
+            },
+            StructRepresentation::ListPairs => list_pairs(name, data, path)?
+                .into_iter()
+                .map(|(k, v)| (k.as_str(), v))
+                .collect(),
+            StructRepresentation::Tuple => return self.validate_tuple(name, stru, data, path),
+            StructRepresentation::StringPairs(pairs) => {
+                let entries = string_pairs(name, pairs, data, path)?;
+                return self
+                    .schema
+                    .validate_struct_string_pairs(name, stru, entries, path);
+            }
+        };
+
+        for field in &stru.fields {
+            let key = field.rename.as_deref().unwrap_or(&field.key);
+            match entries.get(key) {
+                Some(value) => {
+                    path.push(key);
+                    self.validate_nullable(&field.value, field.nullable, value, path)?;
+                    path.pop();
+                }
+                None if field.optional || field.implicit.is_some() => (),
+                None => {
+                    return Err(ValidationError::MissingField {
+                        path: path.clone(),
+                        ty: name.to_string(),
+                        field: key.to_string(),
+                    })
+                }
+            }
+        }
+
+        check_unexpected_fields(name, stru, entries.keys().copied(), path)
+    }
+
+    /// Validates a struct represented as a list of its field values, trailing optional fields
+    /// may be left out
+    fn validate_tuple(
+        &self,
+        name: &str,
+        stru: &StructType,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        let Ipld::List(values) = data else {
+            return Err(kind_error(name, Kind::List, data, path));
+        };
+        if values.len() > stru.fields.len() {
+            return Err(ValidationError::InvalidValue {
+                path: path.clone(),
+                ty: name.to_string(),
+                reason: format!(
+                    "tuple has {} values, but only {} fields",
+                    values.len(),
+                    stru.fields.len()
+                ),
+            });
+        }
+
+        for (idx, field) in stru.fields.iter().enumerate() {
+            match values.get(idx) {
+                Some(value) => {
+                    path.push(idx);
+                    self.validate_nullable(&field.value, field.nullable, value, path)?;
+                    path.pop();
+                }
+                None if field.optional || field.implicit.is_some() => (),
+                None => {
+                    return Err(ValidationError::MissingField {
+                        path: path.clone(),
+                        ty: name.to_string(),
+                        field: field.key.clone(),
+                    })
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_union(
+        &self,
+        name: &str,
+        union: &UnionType,
+        data: &Ipld,
+        path: &mut DataPath,
+    ) -> Result<(), ValidationError> {
+        let invalid = |reason: String| ValidationError::InvalidValue {
+            path: path.clone(),
+            ty: name.to_string(),
+            reason,
+        };
+        let unknown = |discriminant: &str| invalid(format!("unknown member \"{}\"", discriminant));
+
+        match &union.repr {
+            UnionRepresentation::Kinded => {
+                let kind = Kind::of(data);
+                let member = union
+                    .members
+                    .iter()
+                    .find(|member| member.kind() == Some(kind))
+                    .ok_or_else(|| invalid(format!("no member is of kind {}", kind)))?;
+                self.validate_inline(&member.ty, data, path)
+            }
+            UnionRepresentation::Keyed => {
+                let (key, value) = match data {
+                    Ipld::Map(entries) if entries.len() == 1 => entries.iter().next().unwrap(),
+                    Ipld::Map(_) => return Err(invalid("must have a single entry".to_string())),
+                    _ => return Err(kind_error(name, Kind::Map, data, path)),
+                };
+                let member = union.member(key).ok_or_else(|| unknown(key))?;
+
+                path.push(key);
+                self.validate_inline(&member.ty, value, path)?;
+                path.pop();
+                Ok(())
+            }
+            UnionRepresentation::Envelope {
+                discriminant_key,
+                content_key,
+            } => {
+                let Ipld::Map(entries) = data else {
+                    return Err(kind_error(name, Kind::Map, data, path));
+                };
+                let member = self.discriminated(name, union, entries, discriminant_key, path)?;
+                let content =
+                    entries
+                        .get(content_key)
+                        .ok_or_else(|| ValidationError::MissingField {
+                            path: path.clone(),
+                            ty: name.to_string(),
+                            field: content_key.clone(),
+                        })?;
+                if let Some(key) = entries
+                    .keys()
+                    .find(|key| *key != discriminant_key && *key != content_key)
+                {
+                    return Err(ValidationError::UnexpectedField {
+                        path: path.clone(),
+                        ty: name.to_string(),
+                        field: key.clone(),
+                    });
+                }
+
+                path.push(content_key);
+                self.validate_inline(&member.ty, content, path)?;
+                path.pop();
+                Ok(())
+            }
+            UnionRepresentation::Inline { discriminant_key } => {
+                let Ipld::Map(entries) = data else {
+                    return Err(kind_error(name, Kind::Map, data, path));
+                };
+                let member = self.discriminated(name, union, entries, discriminant_key, path)?;
+
+                // The member sees its own fields only
+                let mut fields = entries.clone();
+                fields.remove(discriminant_key);
+                self.validate_inline(&member.ty, &Ipld::Map(fields), path)
+            }
+        }
+    }
+
+    /// Looks up the member selected by the string at `discriminant_key`
+    fn discriminated<'u>(
+        &self,
+        name: &str,
+        union: &'u UnionType,
+        entries: &BTreeMap<String, Ipld>,
+        discriminant_key: &str,
+        path: &mut DataPath,
+    ) -> Result<&'u UnionMember, ValidationError> {
+        let discriminant = match entries.get(discriminant_key) {
+            Some(Ipld::String(discriminant)) => discriminant,
+            Some(data) => {
+                path.push(discriminant_key);
+                let err = kind_error(name, Kind::String, data, path);
+                path.pop();
+                return Err(err);
+            }
+            None => {
+                return Err(ValidationError::MissingField {
+                    path: path.clone(),
+                    ty: name.to_string(),
+                    field: discriminant_key.to_string(),
+                })
+            }
+        };
+
+        union
+            .member(discriminant)
+            .ok_or_else(|| ValidationError::InvalidValue {
+                path: path.clone(),
+                ty: name.to_string(),
+                reason: format!("unknown member \"{}\"", discriminant),
+            })
+    }
+}
+
+fn check_unexpected_fields<'a>(
+    name: &str,
+    stru: &StructType,
+    mut keys: impl Iterator<Item = &'a str>,
+    path: &DataPath,
+) -> Result<(), ValidationError> {
+    let unexpected = keys.find(|key| {
+        !stru
+            .fields
+            .iter()
+            .any(|field| field.rename.as_deref().unwrap_or(&field.key) == *key)
+    });
+
+    match unexpected {
+        Some(key) => Err(ValidationError::UnexpectedField {
+            path: path.clone(),
+            ty: name.to_string(),
+            field: key.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn validate_enum(
+    name: &str,
+    enu: &EnumType,
+    data: &Ipld,
+    path: &DataPath,
+) -> Result<(), ValidationError> {
+    let is_member = match (&enu.repr, data) {
+        (EnumRepresentation::String, Ipld::String(value)) => enu
+            .members
+            .iter()
+            .any(|member| member.tag == EnumMemberTag::String(value.clone())),
+        (EnumRepresentation::Int, Ipld::Integer(value)) => enu
+            .members
+            .iter()
+            .any(|member| member.tag == EnumMemberTag::Int(*value)),
+        (EnumRepresentation::String, _) => return Err(kind_error(name, Kind::String, data, path)),
+        (EnumRepresentation::Int, _) => return Err(kind_error(name, Kind::Int, data, path)),
+    };
+
+    if is_member {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidValue {
+            path: path.clone(),
+            ty: name.to_string(),
+            reason: "not a member of the enum".to_string(),
+        })
+    }
+}
+
+fn validate_unit(
+    name: &str,
+    unit: &UnitRepresentation,
+    data: &Ipld,
+    path: &DataPath,
+) -> Result<(), ValidationError> {
+    let (valid, expected) = match unit {
+        UnitRepresentation::Null => (*data == Ipld::Null, Kind::Null),
+        UnitRepresentation::True => (*data == Ipld::Bool(true), Kind::Bool),
+        UnitRepresentation::False => (*data == Ipld::Bool(false), Kind::Bool),
+        UnitRepresentation::EmptyMap => {
+            (matches!(data, Ipld::Map(map) if map.is_empty()), Kind::Map)
+        }
+    };
+
+    match valid {
+        true => Ok(()),
+        false if Kind::of(data) != expected => Err(kind_error(name, expected, data, path)),
+        false => Err(ValidationError::InvalidValue {
+            path: path.clone(),
+            ty: name.to_string(),
+            reason: "value does not match the unit representation".to_string(),
+        }),
+    }
+}
+
+fn expect_kind(
+    name: &str,
+    expected: Kind,
+    data: &Ipld,
+    path: &DataPath,
+) -> Result<(), ValidationError> {
+    if Kind::of(data) == expected {
+        Ok(())
+    } else {
+        Err(kind_error(name, expected, data, path))
+    }
+}
+
+fn kind_error(name: &str, expected: Kind, data: &Ipld, path: &DataPath) -> ValidationError {
+    ValidationError::Kind {
+        path: path.clone(),
+        ty: name.to_string(),
+        expected,
+        found: Kind::of(data),
+    }
+}
+
+/// Reads the entries of a `listpairs` representation, i.e. `[["key", value], ...]`
+pub(crate) fn list_pairs<'a>(
+    name: &str,
+    data: &'a Ipld,
+    path: &mut DataPath,
+) -> Result<Vec<(&'a String, &'a Ipld)>, ValidationError> {
+    let Ipld::List(pairs) = data else {
+        return Err(kind_error(name, Kind::List, data, path));
+    };
+
+    pairs
+        .iter()
+        .enumerate()
+        .map(|(idx, pair)| match pair {
+            Ipld::List(pair) => match pair.as_slice() {
+                [Ipld::String(key), value] => Ok((key, value)),
+                _ => {
+                    path.push(idx);
+                    let err = ValidationError::InvalidValue {
+                        path: path.clone(),
+                        ty: name.to_string(),
+                        reason: "listpairs entries must be a list of a string key and a value"
+                            .to_string(),
+                    };
+                    path.pop();
+                    Err(err)
+                }
+            },
+            _ => {
+                path.push(idx);
+                let err = kind_error(name, Kind::List, pair, path);
+                path.pop();
+                Err(err)
+            }
+        })
+        .collect()
+}
+
+/// Reads the entries of a `stringpairs` representation, i.e. `"key=value,key2=value2"`
+pub(crate) fn string_pairs(
+    name: &str,
+    pairs: &StringPairs,
+    data: &Ipld,
+    path: &DataPath,
+) -> Result<Vec<(String, String)>, ValidationError> {
+    let Ipld::String(data) = data else {
+        return Err(kind_error(name, Kind::String, data, path));
+    };
+
+    if data.is_empty() {
+        return Ok(vec![]);
+    }
+
+    data.split(pairs.entry_delim.as_str())
+        .map(|entry| match entry.split_once(pairs.inner_delim.as_str()) {
+            Some((key, value)) => Ok((key.to_string(), value.to_string())),
+            None => Err(ValidationError::InvalidValue {
+                path: path.clone(),
+                ty: name.to_string(),
+                reason: format!("stringpairs entry \"{}\" has no delimiter", entry),
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ipld_core::ipld;
+
+    #[test]
+    fn validate_struct() {
+        let file = include_str!("../test/struct.ipldsch");
+        let schema = IpldSchema::parse(file).unwrap();
+
+        let valid = ipld!({
+            "foo_field": {"a": "b"},
+            "barField": null,
+            "bazField": {"c": null},
+            "wozField": {"d": ["e", null]},
+            "boomField": ipld_core::cid::Cid::default(),
+        });
+        assert_eq!(schema.validate("ExampleWithAnonDefns", &valid), Ok(()));
+
+        let renamed = ipld!({
+            "fooField": {"a": "b"},
+            "bazField": {},
+            "wozField": {},
+            "boomField": ipld_core::cid::Cid::default(),
+        });
+        assert!(matches!(
+            schema.validate("ExampleWithAnonDefns", &renamed),
+            Err(ValidationError::UnexpectedField { field, .. }) if field == "fooField"
+        ));
+
+        let wrong_kind = ipld!({
+            "bazField": {},
+            "wozField": {"d": [1]},
+            "boomField": ipld_core::cid::Cid::default(),
+        });
+        assert_eq!(
+            schema
+                .validate("ExampleWithAnonDefns", &wrong_kind)
+                .unwrap_err()
+                .to_string(),
+            "Expected string for String at wozField/d/0, found int"
+        );
+
+        assert_eq!(schema.validate("Foo", &ipld!("a=1,msg=hello")), Ok(()));
+        assert!(matches!(
+            schema.validate("Foo", &ipld!("a=one,msg=hello")),
+            Err(ValidationError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            schema.validate("Foo", &ipld!("a=1")),
+            Err(ValidationError::MissingField { field, .. }) if field == "msg"
+        ));
+    }
+
+    #[test]
+    fn validate_enums_and_maps() {
+        let schema = IpldSchema::parse(include_str!("../test/enums.ipldsch")).unwrap();
+        assert_eq!(schema.validate("StatusString", &ipld!("Yay")), Ok(()));
+        assert!(schema.validate("StatusString", &ipld!("Yep")).is_err());
+        assert_eq!(schema.validate("StatusInt", &ipld!(100)), Ok(()));
+        assert!(schema.validate("StatusInt", &ipld!("100")).is_err());
+
+        let schema = IpldSchema::parse(include_str!("../test/maps.ipldsch")).unwrap();
+        assert_eq!(schema.validate("SimpleMap", &ipld!({"1": 1.5})), Ok(()));
+        assert!(schema.validate("SimpleMap", &ipld!({"one": 1.5})).is_err());
+        assert_eq!(
+            schema.validate("MapOfLists", &ipld!([["a", [true, null]]])),
+            Ok(())
+        );
+        assert_eq!(schema.validate("MountOptions", &ipld!("a=b,c=d")), Ok(()));
+        assert_eq!(
+            schema.validate("Unknown", &ipld!(null)),
+            Err(ValidationError::UnknownType("Unknown".to_string()))
+        );
+    }
+
+    #[test]
+    fn validate_unions_and_tuples() {
+        let schema = IpldSchema::parse(include_str!("../test/union.ipldsch")).unwrap();
+        assert_eq!(schema.validate("Point", &ipld!([1, 2])), Ok(()));
+        assert_eq!(schema.validate("Point", &ipld!([1, 2, "a"])), Ok(()));
+        assert_eq!(
+            schema.validate("Point", &ipld!([1])),
+            Err(ValidationError::MissingField {
+                path: DataPath::default(),
+                ty: "Point".to_string(),
+                field: "y".to_string(),
+            })
+        );
+        assert!(schema.validate("Point", &ipld!([1, 2, "a", 3])).is_err());
+
+        assert_eq!(
+            schema.validate("Shape", &ipld!({"circle": {"radius": 1}})),
+            Ok(())
+        );
+        assert!(schema
+            .validate("Shape", &ipld!({"circle": {"radius": 1}, "square": null}))
+            .is_err());
+        assert_eq!(
+            schema.validate("Value", &ipld!({"circle": {"radius": 1}})),
+            Ok(())
+        );
+        assert_eq!(schema.validate("Value", &ipld!([1, 2])), Ok(()));
+        assert!(schema.validate("Value", &ipld!("a")).is_err());
+        assert_eq!(
+            schema.validate("Tagged", &ipld!({"tag": "c", "radius": 1})),
+            Ok(())
+        );
+        assert_eq!(
+            schema.validate("Wrapped", &ipld!({"tag": "s", "content": {"side": 1}})),
+            Ok(())
+        );
+        assert_eq!(
+            schema.validate("Wrapped", &ipld!({"tag": "x", "content": {"side": 1}})),
+            Err(ValidationError::InvalidValue {
+                path: DataPath::default(),
+                ty: "Wrapped".to_string(),
+                reason: "unknown member \"x\"".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn validate_copies() {
+        let schema = IpldSchema::parse(
+            "type Aa = Bb\ntype Bb = Aa\ntype Cc = Missing\ntype Dd struct {\n  a Aa\n}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            schema.validate("Aa", &ipld!(1)),
+            Err(ValidationError::CopyCycle("Aa".to_string()))
+        );
+        assert_eq!(
+            schema.validate("Dd", &ipld!({"a": 1})),
+            Err(ValidationError::CopyCycle("Aa".to_string()))
+        );
+        assert_eq!(
+            schema.validate("Cc", &ipld!(1)),
+            Err(ValidationError::UnknownType("Missing".to_string()))
+        );
+    }
+}
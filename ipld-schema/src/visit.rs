@@ -0,0 +1,419 @@
+use crate::{
+    enumerate::{EnumField, EnumRepresentation},
+    list::{ListRepresentation, ListType},
+    map::{MapRepresentation, MapType},
+    representation::BytesRepresentation,
+    structural::{StructField, StructRepresentation},
+    union::{UnionMember, UnionRepresentation},
+    unit::UnitRepresentation,
+    Doc, InlineIpldType, IpldSchema, IpldType,
+};
+
+/// The representation of a type, i.e. `representation listpairs` of a map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Representation<'a> {
+    Bytes(&'a BytesRepresentation),
+    List(&'a ListRepresentation),
+    Map(&'a MapRepresentation),
+    Struct(&'a StructRepresentation),
+    Union(&'a UnionRepresentation),
+    Enum(&'a EnumRepresentation),
+    Unit(&'a UnitRepresentation),
+}
+
+/// Visits the nodes of a schema.
+///
+/// The schema is walked depth first, fields and members in the order of the source:
+///
+/// 1. Type definitions, sorted by name
+/// 2. For each definition, the parts of its type:
+///    - lists: the item type, then the representation
+///    - maps: the key type, the value type, then the representation
+///    - links and copies: the referenced type
+///    - structs: every field with its value type, then the representation
+///    - unions: every member with its type, then the representation
+///    - enums: every member, then the representation
+///    - bytes and units: the representation
+/// 3. Inline types are walked like the types above, names and link targets end in a reference
+///
+/// The default methods continue the traversal by calling the matching `walk_*` function,
+/// overriding methods call it to visit the children of a node. `parent` is the name of the
+/// type definition, that contains the node.
+pub trait Visitor {
+    fn visit_type(&mut self, name: &str, doc: Option<&str>, ty: &IpldType) {
+        walk_type(self, name, ty)
+    }
+
+    fn visit_struct_field(&mut self, parent: &str, field: &StructField) {
+        walk_struct_field(self, parent, field)
+    }
+
+    fn visit_enum_member(&mut self, parent: &str, member: &EnumField) {}
+
+    fn visit_union_member(&mut self, parent: &str, member: &UnionMember) {
+        walk_union_member(self, parent, member)
+    }
+
+    fn visit_inline_type(&mut self, parent: &str, ty: &InlineIpldType) {
+        walk_inline_type(self, parent, ty)
+    }
+
+    fn visit_representation(&mut self, parent: &str, repr: Representation<'_>) {}
+
+    /// Called for every name of another type, i.e. map keys, link targets or field types
+    fn visit_reference(&mut self, parent: &str, name: &str) {}
+}
+
+impl IpldSchema {
+    /// Walks the schema with `visitor`, see [`Visitor`] for the order of the nodes
+    pub fn visit<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        for (name, def) in &self.0 {
+            visitor.visit_type(name, def.doc.as_deref(), &def.ty);
+        }
+    }
+
+    /// Rebuilds the schema from the nodes transformed by `folder`.
+    ///
+    /// If two definitions are folded to the same name, the later one in the order of the
+    /// names before folding replaces the other.
+    pub fn fold<F: Fold + ?Sized>(self, folder: &mut F) -> IpldSchema {
+        IpldSchema(
+            self.0
+                .into_iter()
+                .map(|(name, def)| {
+                    let ty = folder.fold_type(&name, def.ty);
+                    (folder.fold_name(name), Doc { doc: def.doc, ty })
+                })
+                .collect(),
+        )
+    }
+}
+
+pub fn walk_type<V: Visitor + ?Sized>(visitor: &mut V, name: &str, ty: &IpldType) {
+    match ty {
+        IpldType::Bytes(repr) => visitor.visit_representation(name, Representation::Bytes(repr)),
+        IpldType::List(list) => walk_list(visitor, name, list),
+        IpldType::Map(map) => walk_map(visitor, name, map),
+        IpldType::Link(target) | IpldType::Copy(target) => visitor.visit_reference(name, target),
+        IpldType::Struct(stru) => {
+            for field in &stru.fields {
+                visitor.visit_struct_field(name, field);
+            }
+            visitor.visit_representation(name, Representation::Struct(&stru.repr));
+        }
+        IpldType::Union(union) => {
+            for member in &union.members {
+                visitor.visit_union_member(name, member);
+            }
+            visitor.visit_representation(name, Representation::Union(&union.repr));
+        }
+        IpldType::Enum(enu) => {
+            for member in &enu.members {
+                visitor.visit_enum_member(name, member);
+            }
+            visitor.visit_representation(name, Representation::Enum(&enu.repr));
+        }
+        IpldType::Unit(unit) => visitor.visit_representation(name, Representation::Unit(unit)),
+        IpldType::Bool | IpldType::String | IpldType::Int | IpldType::Float | IpldType::Any => (),
+    }
+}
+
+pub fn walk_struct_field<V: Visitor + ?Sized>(visitor: &mut V, parent: &str, field: &StructField) {
+    visitor.visit_inline_type(parent, &field.value)
+}
+
+pub fn walk_union_member<V: Visitor + ?Sized>(visitor: &mut V, parent: &str, member: &UnionMember) {
+    visitor.visit_inline_type(parent, &member.ty)
+}
+
+pub fn walk_inline_type<V: Visitor + ?Sized>(visitor: &mut V, parent: &str, ty: &InlineIpldType) {
+    match ty {
+        InlineIpldType::Name(name) | InlineIpldType::Link(name) => {
+            visitor.visit_reference(parent, name)
+        }
+        InlineIpldType::List(list) => walk_list(visitor, parent, list),
+        InlineIpldType::Map(map) => walk_map(visitor, parent, map),
+    }
+}
+
+fn walk_list<V: Visitor + ?Sized>(visitor: &mut V, parent: &str, list: &ListType) {
+    visitor.visit_inline_type(parent, &list.ty);
+    visitor.visit_representation(parent, Representation::List(&list.repr));
+}
+
+fn walk_map<V: Visitor + ?Sized>(visitor: &mut V, parent: &str, map: &MapType) {
+    visitor.visit_reference(parent, &map.key);
+    visitor.visit_inline_type(parent, &map.value);
+    visitor.visit_representation(parent, Representation::Map(&map.repr));
+}
+
+/// Rebuilds a schema from transformed nodes, in the order of a [`Visitor`].
+///
+/// The default methods rebuild the node from its folded children with the matching `fold_*`
+/// function. Representations are part of their types, they can be changed in
+/// [`Fold::fold_type`].
+pub trait Fold {
+    /// Called for the name of every type definition, after its type was folded. To rename a
+    /// type, the references to it are changed in [`Fold::fold_reference`].
+    fn fold_name(&mut self, name: String) -> String {
+        name
+    }
+
+    fn fold_type(&mut self, name: &str, ty: IpldType) -> IpldType {
+        fold_type(self, name, ty)
+    }
+
+    fn fold_struct_field(&mut self, parent: &str, field: StructField) -> StructField {
+        fold_struct_field(self, parent, field)
+    }
+
+    fn fold_enum_member(&mut self, parent: &str, member: EnumField) -> EnumField {
+        member
+    }
+
+    fn fold_union_member(&mut self, parent: &str, member: UnionMember) -> UnionMember {
+        fold_union_member(self, parent, member)
+    }
+
+    fn fold_inline_type(&mut self, parent: &str, ty: InlineIpldType) -> InlineIpldType {
+        fold_inline_type(self, parent, ty)
+    }
+
+    /// Called for every name of another type, i.e. map keys, link targets or field types
+    fn fold_reference(&mut self, parent: &str, name: String) -> String {
+        name
+    }
+}
+
+pub fn fold_type<F: Fold + ?Sized>(folder: &mut F, name: &str, ty: IpldType) -> IpldType {
+    match ty {
+        IpldType::List(list) => IpldType::List(fold_list(folder, name, list)),
+        IpldType::Map(map) => IpldType::Map(fold_map(folder, name, map)),
+        IpldType::Link(target) => IpldType::Link(folder.fold_reference(name, target)),
+        IpldType::Copy(from) => IpldType::Copy(folder.fold_reference(name, from)),
+        IpldType::Struct(mut stru) => {
+            stru.fields = stru
+                .fields
+                .into_iter()
+                .map(|field| folder.fold_struct_field(name, field))
+                .collect();
+            IpldType::Struct(stru)
+        }
+        IpldType::Union(mut union) => {
+            union.members = union
+                .members
+                .into_iter()
+                .map(|member| folder.fold_union_member(name, member))
+                .collect();
+            IpldType::Union(union)
+        }
+        IpldType::Enum(mut enu) => {
+            enu.members = enu
+                .members
+                .into_iter()
+                .map(|member| folder.fold_enum_member(name, member))
+                .collect();
+            IpldType::Enum(enu)
+        }
+        ty => ty,
+    }
+}
+
+pub fn fold_struct_field<F: Fold + ?Sized>(
+    folder: &mut F,
+    parent: &str,
+    mut field: StructField,
+) -> StructField {
+    field.value = folder.fold_inline_type(parent, field.value);
+    field
+}
+
+pub fn fold_union_member<F: Fold + ?Sized>(
+    folder: &mut F,
+    parent: &str,
+    mut member: UnionMember,
+) -> UnionMember {
+    member.ty = folder.fold_inline_type(parent, member.ty);
+    member
+}
+
+pub fn fold_inline_type<F: Fold + ?Sized>(
+    folder: &mut F,
+    parent: &str,
+    ty: InlineIpldType,
+) -> InlineIpldType {
+    match ty {
+        InlineIpldType::Name(name) => InlineIpldType::Name(folder.fold_reference(parent, name)),
+        InlineIpldType::Link(name) => InlineIpldType::Link(folder.fold_reference(parent, name)),
+        InlineIpldType::List(list) => {
+            InlineIpldType::List(Box::new(fold_list(folder, parent, *list)))
+        }
+        InlineIpldType::Map(map) => InlineIpldType::Map(Box::new(fold_map(folder, parent, *map))),
+    }
+}
+
+fn fold_list<F: Fold + ?Sized>(folder: &mut F, parent: &str, mut list: ListType) -> ListType {
+    list.ty = folder.fold_inline_type(parent, list.ty);
+    list
+}
+
+fn fold_map<F: Fold + ?Sized>(folder: &mut F, parent: &str, mut map: MapType) -> MapType {
+    map.key = folder.fold_reference(parent, map.key);
+    map.value = folder.fold_inline_type(parent, map.value);
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the visited nodes in order
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    impl Visitor for Log {
+        fn visit_type(&mut self, name: &str, doc: Option<&str>, ty: &IpldType) {
+            self.0.push(format!("type {}", name));
+            walk_type(self, name, ty);
+        }
+
+        fn visit_struct_field(&mut self, parent: &str, field: &StructField) {
+            self.0.push(format!("field {}.{}", parent, field.key));
+            walk_struct_field(self, parent, field);
+        }
+
+        fn visit_enum_member(&mut self, parent: &str, member: &EnumField) {
+            self.0.push(format!("member {}.{}", parent, member.name));
+        }
+
+        fn visit_union_member(&mut self, parent: &str, member: &UnionMember) {
+            self.0
+                .push(format!("member {}.{}", parent, member.discriminant));
+            walk_union_member(self, parent, member);
+        }
+
+        fn visit_inline_type(&mut self, parent: &str, ty: &InlineIpldType) {
+            self.0.push(format!("inline {}", ty));
+            walk_inline_type(self, parent, ty);
+        }
+
+        fn visit_representation(&mut self, parent: &str, repr: Representation<'_>) {
+            let name = match repr {
+                Representation::Bytes(repr) => repr.name(),
+                Representation::List(repr) => repr.name(),
+                Representation::Map(repr) => repr.name(),
+                Representation::Struct(repr) => repr.name(),
+                Representation::Union(repr) => repr.name(),
+                Representation::Enum(EnumRepresentation::String) => "string",
+                Representation::Enum(EnumRepresentation::Int) => "int",
+                Representation::Unit(_) => "unit",
+            };
+            self.0.push(format!("representation {}", name));
+        }
+
+        fn visit_reference(&mut self, parent: &str, name: &str) {
+            self.0.push(format!("reference {}", name));
+        }
+    }
+
+    /// Prefixes the names of all types defined in the schema
+    struct Prefix<'a>(&'a IpldSchema);
+
+    impl Fold for Prefix<'_> {
+        fn fold_name(&mut self, name: String) -> String {
+            format!("My{}", name)
+        }
+
+        fn fold_reference(&mut self, parent: &str, name: String) -> String {
+            match self.0.contains_type(&name) {
+                true => format!("My{}", name),
+                false => name,
+            }
+        }
+    }
+
+    const SCHEMA: &str = "\
+type Account union {
+  | User \"user\"
+  | &Status \"status\"
+} representation keyed
+
+type Status enum {
+  | Active
+  | Inactive
+}
+
+type User struct {
+  name String
+  friends [&User] (rename \"f\")
+  status Status
+} representation listpairs
+";
+
+    #[test]
+    fn visit_order() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+        let mut log = Log::default();
+        schema.visit(&mut log);
+
+        assert_eq!(
+            log.0,
+            [
+                "type Account",
+                "member Account.user",
+                "inline User",
+                "reference User",
+                "member Account.status",
+                "inline &Status",
+                "reference Status",
+                "representation keyed",
+                "type Status",
+                "member Status.Active",
+                "member Status.Inactive",
+                "representation string",
+                "type User",
+                "field User.name",
+                "inline String",
+                "reference String",
+                "field User.friends",
+                "inline [&User]",
+                "inline &User",
+                "reference User",
+                "representation list",
+                "field User.status",
+                "inline Status",
+                "reference Status",
+                "representation listpairs",
+            ]
+        );
+    }
+
+    #[test]
+    fn fold_references() {
+        let schema = IpldSchema::parse(SCHEMA).unwrap();
+        let mut prefix = Prefix(&schema);
+        let folded = schema.clone().fold(&mut prefix);
+
+        assert_eq!(
+            folded.to_string(),
+            "\
+type MyAccount union {
+  | MyUser \"user\"
+  | &MyStatus \"status\"
+} representation keyed
+
+type MyStatus enum {
+  | Active
+  | Inactive
+}
+
+type MyUser struct {
+  name String
+  friends [&MyUser] (rename \"f\")
+  status MyStatus
+} representation listpairs
+"
+        );
+        assert_eq!(folded.check(), vec![]);
+    }
+}
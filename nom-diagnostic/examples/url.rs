@@ -17,6 +17,7 @@ enum Protocol {
 }
 
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 struct Domain(Vec<String>);
 
 #[derive(Debug, Clone)]
@@ -41,7 +42,7 @@ enum UrlParseError {
 }
 
 impl Url {
-    fn parse(input: &str) -> Result<Self, ErrorDiagnose<UrlParseError>> {
+    fn parse(input: &str) -> Result<Self, ErrorDiagnose<'_, UrlParseError>> {
         let input = InStr::new(input);
         let (rest, url) = parse_url(input).finish()?;
         rest.finalize(
@@ -91,7 +92,7 @@ fn parse_domain(input: InStr) -> ParseResult<Domain, UrlParseError> {
                     segments
                 },
             ),
-            |vec| Domain(vec),
+            Domain,
         ),
         |error| {
             error
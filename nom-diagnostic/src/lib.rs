@@ -181,9 +181,9 @@ impl<'a> InStr<'a> {
     }
 }
 
-impl<'a> ToString for InStr<'a> {
-    fn to_string(&self) -> String {
-        self.inner().to_string()
+impl<'a> std::fmt::Display for InStr<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.inner())
     }
 }
 